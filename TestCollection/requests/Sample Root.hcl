@@ -20,7 +20,7 @@ body {
     files = [
       {
         "name" = "file"
-        "path" = "/Users/neeraj/projects/sanchaar/TestCollection/collection.hcl"
+        "path" = "collection.hcl"
       }
     ]
   }
@@ -0,0 +1,32 @@
+use core::{
+    codegen::{self, Language},
+    persistence::request::{read_request_file, RequestFile},
+};
+use std::{env, path::PathBuf};
+
+use crate::secrets::{load_environment, Secrets};
+
+pub struct CodegenArgs {
+    pub request: PathBuf,
+    pub lang: Language,
+    /// Without an environment the `{{vars}}` are kept
+    pub env: Option<String>,
+}
+
+pub async fn codegen(root: PathBuf, args: CodegenArgs, secrets: &Secrets) -> anyhow::Result<()> {
+    let current_dir = env::current_dir()?;
+    let root = current_dir.join(root);
+
+    let RequestFile::Http(req) = read_request_file(&root.join(args.request)).await? else {
+        anyhow::bail!("Code can only be generated for HTTP requests")
+    };
+
+    let env = match args.env {
+        Some(name) => load_environment(&root, Some(&name), secrets).await?,
+        None => None,
+    };
+
+    let code = codegen::generate(args.lang, req, &root, env.as_ref());
+    println!("{}", code.trim_end());
+    Ok(())
+}
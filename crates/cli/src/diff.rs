@@ -0,0 +1,167 @@
+use std::{env, path::Path, path::PathBuf};
+
+use core::client::{create_client, send_request, Response};
+use core::diff::{diff_responses, BodyDiff, Change, DiffOptions, LineTag, ValueChange};
+use core::http::collection::Collection;
+use core::http::environment::Environment;
+use core::persistence::collections::{open_collection, read_collection_variables};
+use core::persistence::globals::read_globals;
+use core::persistence::request::{read_request_file, RequestFile};
+use core::transformers::request::transform_request;
+
+use crate::color::{color, Color};
+use crate::secrets::Secrets;
+
+pub struct DiffArgs {
+    pub request: PathBuf,
+    pub other: Option<PathBuf>,
+    pub env: Option<String>,
+    pub other_env: Option<String>,
+    pub ignore: Vec<String>,
+}
+
+fn find_env(
+    collection: Option<&Collection>,
+    name: Option<&str>,
+) -> anyhow::Result<Option<Environment>> {
+    let (Some(collection), Some(name)) = (collection, name) else {
+        return Ok(None);
+    };
+    let key = collection
+        .environments
+        .find_by_name(name)
+        .ok_or_else(|| anyhow::anyhow!("Environment {} not found", name))?;
+    Ok(collection.environments.get(key).cloned())
+}
+
+async fn execute(root: &Path, path: &Path, env: Option<Environment>) -> anyhow::Result<Response> {
+    let RequestFile::Http(req) = read_request_file(path).await? else {
+        anyhow::bail!("{} is not an HTTP request", path.display());
+    };
+
+    let client = create_client();
+    let req = transform_request(client.clone(), req, env, root.to_path_buf()).await?;
+    send_request(client, req).await
+}
+
+fn print_change(change: &ValueChange, label: &str) {
+    let path = if change.path.is_empty() {
+        label
+    } else {
+        &change.path
+    };
+    match &change.change {
+        Change::Added(new) => {
+            println!(
+                "{} {}: {}",
+                color("+", Color::LIGHTGREEN),
+                path,
+                color(new, Color::LIGHTGREEN)
+            );
+        }
+        Change::Removed(old) => {
+            println!(
+                "{} {}: {}",
+                color("-", Color::RED),
+                path,
+                color(old, Color::RED)
+            );
+        }
+        Change::Changed(old, new) => {
+            println!(
+                "{} {}: {} -> {}",
+                color("~", Color::YELLOW),
+                path,
+                color(old, Color::RED),
+                color(new, Color::LIGHTGREEN)
+            );
+        }
+    }
+}
+
+/// Sends two requests, or the same request with two environments, and prints
+/// how the second response differs from the first
+pub async fn diff(root: PathBuf, args: DiffArgs, secrets: &Secrets) -> anyhow::Result<()> {
+    let current_dir = env::current_dir()?;
+    let root = current_dir.join(root);
+
+    // Environments are only looked up when asked for
+    let collection = match args.env.is_some() || args.other_env.is_some() {
+        true => Some(open_collection(root.clone()).await?),
+        false => None,
+    };
+    let mut env = find_env(collection.as_ref(), args.env.as_deref())?;
+    let mut other_env = match args.other_env.as_deref() {
+        Some(name) => find_env(collection.as_ref(), Some(name))?,
+        None => env.clone(),
+    };
+    for env in env.iter_mut().chain(other_env.iter_mut()) {
+        secrets.fill(&root, env).await?;
+    }
+    let globals = read_globals().await?;
+    let variables = read_collection_variables(&root).await?;
+    let env = Environment::scoped(env.as_ref(), &variables, &globals);
+    let other_env = Environment::scoped(other_env.as_ref(), &variables, &globals);
+
+    let left = root.join(&args.request);
+    let right = root.join(args.other.as_ref().unwrap_or(&args.request));
+
+    let old = execute(&root, &left, env).await?;
+    let new = execute(&root, &right, other_env).await?;
+
+    let options = DiffOptions {
+        ignore: args.ignore,
+    };
+    let diff = diff_responses(&old, &new, &options);
+    if diff.is_empty() {
+        println!("{}", color("Responses are the same", Color::LIGHTGREEN));
+        return Ok(());
+    }
+
+    if let Some((old, new)) = diff.status {
+        println!(
+            "{} {} -> {}",
+            color("Status:", Color::DARKGRAY),
+            color(&old.to_string(), Color::RED),
+            color(&new.to_string(), Color::LIGHTGREEN)
+        );
+    }
+
+    if !diff.headers.is_empty() {
+        println!("{}", color("Headers:", Color::DARKGRAY));
+        for change in &diff.headers {
+            print_change(change, "");
+        }
+    }
+
+    if !diff.body.is_empty() {
+        println!("{}", color("Body:", Color::DARKGRAY));
+    }
+    match &diff.body {
+        BodyDiff::Json(changes) => {
+            for change in changes {
+                print_change(change, "(root)");
+            }
+        }
+        BodyDiff::Lines(hunks) => {
+            for (idx, hunk) in hunks.iter().enumerate() {
+                if idx > 0 {
+                    println!("{}", color("...", Color::DARKGRAY));
+                }
+                for line in hunk {
+                    match line.tag {
+                        LineTag::Equal => println!("  {}", line.text),
+                        LineTag::Insert => {
+                            println!("{}", color(&format!("+ {}", line.text), Color::LIGHTGREEN))
+                        }
+                        LineTag::Delete => {
+                            println!("{}", color(&format!("- {}", line.text), Color::RED))
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    anyhow::bail!("Responses differ")
+}
@@ -0,0 +1,61 @@
+use std::{env, path::PathBuf};
+
+use core::persistence::collections::open_collection;
+use core::persistence::export::{openapi, ExportReport};
+use core::persistence::files::write_atomic;
+use core::persistence::history::read_history;
+
+use crate::color::{color, Color};
+
+pub async fn openapi(root: PathBuf, output: Option<PathBuf>) -> anyhow::Result<()> {
+    let current_dir = env::current_dir()?;
+    let root = current_dir.join(root);
+
+    let collection = open_collection(root.clone()).await?;
+    // Without a history responses are described from saved examples only
+    let history = read_history().await.unwrap_or_default();
+    let (document, report) = openapi::export(&collection, &history).await?;
+
+    let Some(output) = output else {
+        println!("{}", serde_json::to_string_pretty(&document)?);
+        print_report(&report);
+        return Ok(());
+    };
+
+    let yaml = output
+        .extension()
+        .is_some_and(|ext| ext == "yaml" || ext == "yml");
+    let data = if yaml {
+        serde_yaml::to_string(&document)?
+    } else {
+        serde_json::to_string_pretty(&document)?
+    };
+
+    let output = current_dir.join(output);
+    write_atomic(&output, data).await?;
+
+    println!(
+        "{} {}",
+        color("Exported", Color::LIGHTGREEN),
+        output.display()
+    );
+    print_report(&report);
+    Ok(())
+}
+
+/// Warnings go to stderr, the document may be piped from stdout
+fn print_report(report: &ExportReport) {
+    if report.is_empty() {
+        return;
+    }
+    eprintln!(
+        "{}",
+        color(
+            &format!("{} warnings:", report.warnings.len()),
+            Color::YELLOW
+        )
+    );
+    for warning in &report.warnings {
+        eprintln!("  - {}", warning);
+    }
+}
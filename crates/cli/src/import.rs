@@ -0,0 +1,141 @@
+use std::{env, path::PathBuf};
+
+use core::curl;
+use core::persistence::files::write_atomic;
+use core::persistence::import::har::{self, HarOptions};
+use core::persistence::import::openapi::{self, OpenApiOptions};
+use core::persistence::import::{
+    file_name, postman, write_collection, ImportReport, ImportedCollection,
+};
+use core::persistence::request::encode_request;
+use core::persistence::HCL_SUFFIX;
+use std::io::Read;
+use tokio::fs;
+
+use crate::color::{color, Color};
+
+pub async fn postman(
+    root: PathBuf,
+    collection: PathBuf,
+    environments: Vec<PathBuf>,
+    output: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    let current_dir = env::current_dir()?;
+    let root = current_dir.join(root);
+
+    let data = fs::read_to_string(&collection).await?;
+    let mut envs = Vec::new();
+    for path in environments {
+        envs.push(fs::read_to_string(path).await?);
+    }
+
+    let imported = postman::import(&data, &envs)?;
+    write(root, imported, output).await
+}
+
+pub async fn openapi(
+    root: PathBuf,
+    spec: PathBuf,
+    status_assertions: bool,
+    output: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    let current_dir = env::current_dir()?;
+    let root = current_dir.join(root);
+
+    let data = fs::read_to_string(&spec).await?;
+    let imported = openapi::import(&data, OpenApiOptions { status_assertions })?;
+    write(root, imported, output).await
+}
+
+pub async fn har(
+    root: PathBuf,
+    har: PathBuf,
+    deduplicate: bool,
+    output: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    let current_dir = env::current_dir()?;
+    let root = current_dir.join(root);
+
+    let data = fs::read_to_string(&har).await?;
+    let imported = har::import(&data, HarOptions { deduplicate })?;
+    write(root, imported, output).await
+}
+
+pub async fn curl(
+    root: PathBuf,
+    command: Option<PathBuf>,
+    output: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    let current_dir = env::current_dir()?;
+    let root = current_dir.join(root);
+
+    let command = match command {
+        Some(file) => fs::read_to_string(file).await?,
+        None => {
+            let mut command = String::new();
+            std::io::stdin().read_to_string(&mut command)?;
+            command
+        }
+    };
+
+    let request = encode_request(curl::parse(&command)?)?;
+    let Some(output) = output else {
+        print!("{}", hcl::to_string(&request)?);
+        return Ok(());
+    };
+
+    let mut output = root.join(output);
+    if output.extension().is_none() {
+        output.set_extension(HCL_SUFFIX);
+    }
+    if fs::try_exists(&output).await? {
+        anyhow::bail!("{} already exists", output.display());
+    }
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+    write_atomic(&output, hcl::to_string(&request)?).await?;
+
+    println!(
+        "{} request to {}",
+        color("Imported", Color::LIGHTGREEN),
+        output.display()
+    );
+    Ok(())
+}
+
+async fn write(
+    root: PathBuf,
+    imported: ImportedCollection,
+    output: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    let output = output.unwrap_or_else(|| root.join(file_name(&imported.name)));
+    let name = imported.name.clone();
+
+    let report = write_collection(output.clone(), imported).await?;
+
+    println!(
+        "{} {} to {}",
+        color("Imported", Color::LIGHTGREEN),
+        name,
+        output.display()
+    );
+    print_report(&report);
+    Ok(())
+}
+
+fn print_report(report: &ImportReport) {
+    if report.is_empty() {
+        return;
+    }
+    println!(
+        "{}",
+        color(
+            &format!("{} warnings:", report.warnings.len()),
+            Color::YELLOW
+        )
+    );
+    for warning in &report.warnings {
+        println!("  - {}", warning);
+    }
+}
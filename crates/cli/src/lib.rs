@@ -1,8 +1,16 @@
 pub mod run;
 pub mod test;
 pub mod color;
+pub mod import;
+pub mod codegen;
+pub mod export;
+pub mod mock;
+pub mod diff;
+pub mod secrets;
+pub mod lint;
 
 use clap::{Parser, Subcommand};
+use core::codegen::Language;
 use std::path::PathBuf;
 
 use clap::{arg, command};
@@ -15,6 +23,11 @@ struct Cli {
     #[arg(short, long, value_name = "PATH", default_value = ".")]
     path: PathBuf,
 
+    /// File of NAME=value lines with the values of secret variables
+    /// Secrets are also read from SANCHAAR_SECRET_<NAME> variables
+    #[arg(long, value_name = "FILE", global = true)]
+    secrets_file: Option<PathBuf>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -27,6 +40,10 @@ enum Commands {
         /// Path to request file
         request: PathBuf,
 
+        /// Environment used for variables
+        #[arg(short, long, value_name = "NAME")]
+        env: Option<String>,
+
         /// Run in verbose mode
         /// If not provided, only body is printed
         /// If provided, status, headers, duration, and size are also printed
@@ -39,15 +56,267 @@ enum Commands {
         /// If not provided, all tests are run
         #[arg(value_name = "PATH")]
         path: Option<PathBuf>,
+
+        /// Environment used for variables
+        #[arg(short, long, value_name = "NAME")]
+        env: Option<String>,
+
+        /// Write the executed requests and responses to a HAR file
+        #[arg(long, value_name = "FILE")]
+        har: Option<PathBuf>,
+
+        /// Replace snapshots which differ from the responses
+        #[arg(long)]
+        update_snapshots: bool,
+    },
+    /// Import a collection from another tool
+    Import {
+        #[command(subcommand)]
+        source: ImportSource,
+    },
+    /// Print a request file as code
+    #[command(arg_required_else_help = true)]
+    Codegen {
+        /// Language to generate
+        /// One of curl, rust, python, javascript, go, httpie, powershell
+        #[arg(short, long, default_value = "curl")]
+        lang: Language,
+
+        /// Path to request file
+        request: PathBuf,
+
+        /// Environment used for variables
+        /// If not provided, the {{variables}} are kept
+        #[arg(short, long, value_name = "NAME")]
+        env: Option<String>,
+
+        /// Keep the {{variables}} even when an environment is given
+        #[arg(long)]
+        keep_vars: bool,
+    },
+    /// Export the collection to another format
+    Export {
+        #[command(subcommand)]
+        target: ExportTarget,
+    },
+    /// Serve the saved examples of the requests from a local server
+    Mock {
+        /// Port to listen on, 0 picks a free port
+        #[arg(long, default_value_t = 4010)]
+        port: u16,
+
+        /// Environment used for variables in request paths
+        /// Defaults to the default environment of the collection
+        #[arg(short, long, value_name = "NAME")]
+        env: Option<String>,
+
+        /// Delay in milliseconds before every response
+        #[arg(long, value_name = "MS", default_value_t = 0)]
+        latency: u64,
+
+        /// Random extra delay in milliseconds, up to this value
+        #[arg(long, value_name = "MS", default_value_t = 0)]
+        jitter: u64,
+
+        /// Fraction of requests, between 0 and 1, answered with an error
+        #[arg(long, value_name = "RATE", default_value_t = 0.0)]
+        error_rate: f64,
+
+        /// Status code of the injected errors
+        #[arg(long, value_name = "STATUS", default_value_t = 500)]
+        error_status: u16,
+    },
+    /// Send requests and compare their responses
+    /// Compares two requests, or one request in two environments
+    #[command(arg_required_else_help = true)]
+    Diff {
+        /// Path to request file
+        request: PathBuf,
+
+        /// Request to compare with, defaults to the same request
+        other: Option<PathBuf>,
+
+        /// Environment of the first request
+        #[arg(short, long, value_name = "NAME")]
+        env: Option<String>,
+
+        /// Environment of the second request, defaults to the first one
+        #[arg(long, value_name = "NAME")]
+        other_env: Option<String>,
+
+        /// Path in the JSON body to ignore, like items.*.id, can be repeated
+        /// Headers are ignored with header.NAME
+        #[arg(short, long, value_name = "PATH")]
+        ignore: Vec<String>,
+    },
+    /// Check the collection files for parse errors
+    Lint,
+}
+
+#[derive(Debug, Subcommand)]
+enum ExportTarget {
+    /// Generate an OpenAPI 3.1 document from the HTTP requests
+    Openapi {
+        /// File to write, YAML when the extension is .yaml or .yml
+        /// If not provided, the JSON document is printed
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum ImportSource {
+    /// Import a Postman v2.1 collection export
+    #[command(arg_required_else_help = true)]
+    Postman {
+        /// Path to the exported collection JSON
+        collection: PathBuf,
+
+        /// Path to an exported Postman environment, can be repeated
+        #[arg(short, long, value_name = "FILE")]
+        environment: Vec<PathBuf>,
+
+        /// Directory to create the collection in
+        /// Defaults to the collection name inside the path
+        #[arg(short, long, value_name = "DIR")]
+        output: Option<PathBuf>,
+    },
+    /// Import an OpenAPI 3 or Swagger 2 specification
+    #[command(arg_required_else_help = true)]
+    Openapi {
+        /// Path to the specification, JSON or YAML
+        spec: PathBuf,
+
+        /// Add status assertions from the documented success responses
+        #[arg(long)]
+        status_assertions: bool,
+
+        /// Directory to create the collection in
+        /// Defaults to the API title inside the path
+        #[arg(short, long, value_name = "DIR")]
+        output: Option<PathBuf>,
+    },
+    /// Import a HAR 1.2 capture, each entry becomes a request
+    #[command(arg_required_else_help = true)]
+    Har {
+        /// Path to the HAR file
+        har: PathBuf,
+
+        /// Keep only the first request for each method and path
+        #[arg(long)]
+        deduplicate: bool,
+
+        /// Directory to create the collection in
+        /// Defaults to the host name inside the path
+        #[arg(short, long, value_name = "DIR")]
+        output: Option<PathBuf>,
+    },
+    /// Import a request from a curl command
+    Curl {
+        /// File containing the curl command, reads stdin if not provided
+        #[arg(value_name = "FILE")]
+        command: Option<PathBuf>,
+
+        /// Request file to create, relative to the path
+        /// If not provided, the request is printed
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
     },
 }
 
 #[tokio::main]
 pub async fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
+    let secrets = secrets::Secrets::load(cli.secrets_file).await?;
 
     match cli.command {
-        Commands::Run { request, verbose } => run::run(cli.path, request, verbose).await,
-        Commands::Test { path } => test::test(cli.path, path.unwrap_or_default()).await,
+        Commands::Run {
+            request,
+            env,
+            verbose,
+        } => run::run(cli.path, request, env, &secrets, verbose).await,
+        Commands::Test {
+            path,
+            env,
+            har,
+            update_snapshots,
+        } => {
+            let args = test::TestArgs {
+                path: path.unwrap_or_default(),
+                env,
+                har,
+                update_snapshots,
+            };
+            test::test(cli.path, args, &secrets).await
+        }
+        Commands::Import { source } => match source {
+            ImportSource::Postman {
+                collection,
+                environment,
+                output,
+            } => import::postman(cli.path, collection, environment, output).await,
+            ImportSource::Openapi {
+                spec,
+                status_assertions,
+                output,
+            } => import::openapi(cli.path, spec, status_assertions, output).await,
+            ImportSource::Har {
+                har,
+                deduplicate,
+                output,
+            } => import::har(cli.path, har, deduplicate, output).await,
+            ImportSource::Curl { command, output } => import::curl(cli.path, command, output).await,
+        },
+        Commands::Codegen {
+            lang,
+            request,
+            env,
+            keep_vars,
+        } => {
+            let args = codegen::CodegenArgs {
+                request,
+                lang,
+                env: env.filter(|_| !keep_vars),
+            };
+            codegen::codegen(cli.path, args, &secrets).await
+        }
+        Commands::Export { target } => match target {
+            ExportTarget::Openapi { output } => export::openapi(cli.path, output).await,
+        },
+        Commands::Mock {
+            port,
+            env,
+            latency,
+            jitter,
+            error_rate,
+            error_status,
+        } => {
+            let args = mock::MockArgs {
+                port,
+                env,
+                latency,
+                jitter,
+                error_rate,
+                error_status,
+            };
+            mock::mock(cli.path, args).await
+        }
+        Commands::Diff {
+            request,
+            other,
+            env,
+            other_env,
+            ignore,
+        } => {
+            let args = diff::DiffArgs {
+                request,
+                other,
+                env,
+                other_env,
+                ignore,
+            };
+            diff::diff(cli.path, args, &secrets).await
+        }
+        Commands::Lint => lint::lint(cli.path).await,
     }
 }
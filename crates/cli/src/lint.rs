@@ -0,0 +1,34 @@
+use std::env;
+use std::path::{Path, PathBuf};
+
+use core::persistence::diagnostics::{lint_collection, Diagnostic};
+
+use crate::color::{color, Color};
+
+fn print_diagnostic(root: &Path, diagnostic: &Diagnostic) {
+    let path = diagnostic
+        .path
+        .strip_prefix(root)
+        .unwrap_or(&diagnostic.path);
+    let location = match diagnostic.location {
+        Some((line, column)) => format!("{}:{}:{}", path.display(), line, column),
+        None => path.display().to_string(),
+    };
+    println!("{} {}", color(&location, Color::RED), diagnostic.message);
+}
+
+/// Checks the collection, environment and request files, failing when any
+/// of them cannot be parsed
+pub async fn lint(root: PathBuf) -> anyhow::Result<()> {
+    let root = env::current_dir()?.join(root);
+    let diagnostics = lint_collection(&root).await?;
+
+    if diagnostics.is_empty() {
+        println!("{}", color("No problems found", Color::LIGHTGREEN));
+        return Ok(());
+    }
+    for diagnostic in &diagnostics {
+        print_diagnostic(&root, diagnostic);
+    }
+    anyhow::bail!("{} files with problems", diagnostics.len())
+}
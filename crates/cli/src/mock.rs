@@ -0,0 +1,90 @@
+use std::{env, net::SocketAddr, path::PathBuf, time::Duration};
+
+use core::http::environment::Environment;
+use core::mock::{Mock, MockHit, MockOptions, EXAMPLE_HEADER};
+use core::persistence::collections::open_collection;
+use core::persistence::globals::read_globals;
+use tokio::net::TcpListener;
+
+use crate::color::{color, Color};
+
+pub struct MockArgs {
+    pub port: u16,
+    pub env: Option<String>,
+    pub latency: u64,
+    pub jitter: u64,
+    pub error_rate: f64,
+    pub error_status: u16,
+}
+
+fn print_hit(hit: MockHit) {
+    let status = if hit.status < 400 {
+        color(&hit.status.to_string(), Color::LIGHTGREEN)
+    } else {
+        color(&hit.status.to_string(), Color::RED)
+    };
+    let request = hit
+        .request
+        .map(|name| color(&format!("({})", name), Color::DARKGRAY))
+        .unwrap_or_default();
+    println!("{} {} {} {}", hit.method, hit.path, status, request);
+}
+
+pub async fn mock(root: PathBuf, args: MockArgs) -> anyhow::Result<()> {
+    let current_dir = env::current_dir()?;
+    let root = current_dir.join(root);
+
+    if !(0.0..=1.0).contains(&args.error_rate) {
+        anyhow::bail!("Error rate must be between 0 and 1");
+    }
+
+    let collection = open_collection(root).await?;
+    let env = match &args.env {
+        Some(name) => {
+            let key = collection
+                .environments
+                .find_by_name(name)
+                .ok_or_else(|| anyhow::anyhow!("Environment {} not found", name))?;
+            collection.environments.get(key)
+        }
+        None => collection.get_active_environment(),
+    };
+    let env = Environment::scoped(env, &collection.variables, &read_globals().await?);
+
+    let (mock, warnings) = Mock::load(&collection, env.as_ref()).await?;
+    for warning in warnings {
+        eprintln!("{} {}", color("Skipped", Color::YELLOW), warning);
+    }
+
+    let listener = TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], args.port))).await?;
+    let addr = listener.local_addr()?;
+
+    for (method, path, name, examples) in mock.routes() {
+        let examples = match examples {
+            0 => color("no examples", Color::YELLOW),
+            1 => "1 example".to_string(),
+            n => format!("{} examples", n),
+        };
+        println!(
+            "{:<7} {} {} {}",
+            method,
+            path,
+            color(name, Color::DARKGRAY),
+            examples
+        );
+    }
+    println!(
+        "\n{} http://{}, pick an example with the {} header\n",
+        color("Mock server listening on", Color::LIGHTGREEN),
+        addr,
+        EXAMPLE_HEADER
+    );
+
+    let options = MockOptions {
+        latency: Duration::from_millis(args.latency),
+        jitter: Duration::from_millis(args.jitter),
+        error_rate: args.error_rate,
+        error_status: args.error_status,
+    };
+    mock.serve(listener, options, print_hit).await
+}
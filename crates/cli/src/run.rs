@@ -1,76 +1,166 @@
 use colored_json::prelude::ToColoredJson;
 use core::{
-    client::{create_client, send_request, Response},
-    persistence::request::read_request,
-    transformers::request::transform_request,
+    client::{
+        create_client, send_request_streamed, ContentType, Response, ResponseBody, ResponseEvent,
+    },
+    grpc,
+    http::environment::Environment,
+    persistence::request::{read_request_file, RequestFile},
+    preview::{hex_dump, pdf_info},
+    sse::SseEvent,
+    transformers::{
+        grpc::{transform_grpc, transform_url},
+        request::transform_request,
+    },
     utils::fmt_duration,
 };
-use std::{env, path::PathBuf};
+use futures::{stream::BoxStream, StreamExt};
+use reqwest::{header::HeaderMap, StatusCode};
+use std::{env, path::PathBuf, time::Duration};
 
 use humansize::{format_size, BINARY};
 
 use crate::color::{color, Color};
+use crate::secrets::{load_environment, Secrets};
 
-pub async fn run(root: PathBuf, req: PathBuf, verbose: bool) -> anyhow::Result<()> {
+pub async fn run(
+    root: PathBuf,
+    req: PathBuf,
+    env: Option<String>,
+    secrets: &Secrets,
+    verbose: bool,
+) -> anyhow::Result<()> {
     let current_dir = env::current_dir()?;
     let root = current_dir.join(root);
 
     let path = root.join(req);
-    let req = read_request(&path).await?;
+    let dir = path.parent().unwrap_or(&root);
+    let env = load_environment(&root, env.as_deref(), secrets).await?;
 
-    let client = create_client();
-    let req = transform_request(client.clone(), req, None).await?;
-    let response = send_request(client, req).await?;
-
-    let Response {
-        status,
-        headers,
-        body,
-        duration,
-        size_bytes,
-    } = response;
-    if verbose {
-        println!("{}", color(&status.to_string(), Color::CYAN));
-        println!(
-            "{} {}",
-            color("Size:", Color::DARKGRAY),
-            color(&format_size(size_bytes, BINARY), Color::VIOLET)
-        );
-        println!(
-            "{} {}",
-            color("Time:", Color::DARKGRAY),
-            color(&fmt_duration(duration), Color::VIOLET)
-        );
+    let mut events: BoxStream<_> = match read_request_file(&path).await? {
+        RequestFile::Http(req) => {
+            let client = create_client();
+            let req = transform_request(client.clone(), req, env.clone(), root.clone()).await?;
+            send_request_streamed(client, req).boxed()
+        }
+        RequestFile::Grpc(req) => {
+            let url = transform_url(&req.url, env.as_ref())?;
+            let pool = grpc::load_descriptors(&url, &req.resolve_proto_files(dir)).await?;
+            grpc::call(transform_grpc(req, &pool, env.clone())?).boxed()
+        }
+        RequestFile::WebSocket(_) => {
+            anyhow::bail!("WebSocket requests can only be used from the app")
+        }
+    };
 
-        println!();
-        if !headers.is_empty() {
-            println!("{}", color("Headers:", Color::DARKGRAY));
-            for (k, v) in headers.iter() {
-                let value = v.to_str().unwrap_or("<Invalid UTF-8>");
-                println!(
-                    "  {}: {}",
-                    color(k.as_str(), Color::BLUE),
-                    color(value, Color::DARKGREEN)
-                );
+    while let Some(event) = events.next().await {
+        match event? {
+            ResponseEvent::Completed(response) => {
+                let Response {
+                    status,
+                    headers,
+                    body,
+                    duration,
+                    size_bytes,
+                } = response;
+                if verbose {
+                    print_head(status, duration);
+                    println!(
+                        "{} {}",
+                        color("Size:", Color::DARKGRAY),
+                        color(&format_size(size_bytes, BINARY), Color::VIOLET)
+                    );
+                    print_headers(&headers, env.as_ref());
+                }
+                println!();
+                print_body(body)?;
+            }
+            ResponseEvent::StreamOpened(head) => {
+                if verbose {
+                    print_head(head.status, head.duration);
+                    print_headers(&head.headers, env.as_ref());
+                }
+                println!();
+            }
+            ResponseEvent::Event(event) => print_event(&event),
+            ResponseEvent::StreamClosed => {
+                println!("{}", color("Stream closed", Color::DARKGRAY));
             }
         }
     }
 
+    Ok(())
+}
+
+fn print_head(status: StatusCode, duration: Duration) {
+    println!("{}", color(&status.to_string(), Color::CYAN));
+    println!(
+        "{} {}",
+        color("Time:", Color::DARKGRAY),
+        color(&fmt_duration(duration), Color::VIOLET)
+    );
+}
+
+/// Values of secret variables echoed in headers are redacted
+fn print_headers(headers: &HeaderMap, env: Option<&Environment>) {
     println!();
+    if !headers.is_empty() {
+        println!("{}", color("Headers:", Color::DARKGRAY));
+        for (k, v) in headers.iter() {
+            let value = v.to_str().unwrap_or("<Invalid UTF-8>");
+            let value = match env {
+                Some(env) => env.redact(value),
+                None => value.to_string(),
+            };
+            println!(
+                "  {}: {}",
+                color(k.as_str(), Color::BLUE),
+                color(&value, Color::DARKGREEN)
+            );
+        }
+    }
+}
+
+fn print_event(event: &SseEvent) {
+    let time = event.received.format("%H:%M:%S%.3f").to_string();
+    let id = event
+        .id
+        .as_deref()
+        .map(|id| format!(" #{}", id))
+        .unwrap_or_default();
+    println!(
+        "{} {}{}",
+        color(&time, Color::DARKGRAY),
+        color(&event.event, Color::BLUE),
+        color(&id, Color::VIOLET)
+    );
+    for line in event.data.lines() {
+        println!("  {}", line);
+    }
+}
+
+fn print_body(body: ResponseBody) -> anyhow::Result<()> {
     match body.content_type {
-        core::client::ContentType::Json => {
+        ContentType::Json => {
             let json = String::from_utf8(body.data)?;
             println!("{}", json.to_colored_json_auto()?);
         }
-        core::client::ContentType::Text => {
-            let text = String::from_utf8(body.data)?;
+        ContentType::Xml | ContentType::Html | ContentType::Text => {
+            let text = String::from_utf8_lossy(&body.data);
             println!("{}", text);
         }
-        core::client::ContentType::Buffer => {
-            let hex = hex::encode(body.data);
-            println!("Hex:\n{}", hex);
+        ContentType::Pdf => {
+            for (name, value) in pdf_info(&body.data).entries() {
+                println!(
+                    "{} {}",
+                    color(&format!("{}:", name), Color::DARKGRAY),
+                    color(&value, Color::VIOLET)
+                );
+            }
+        }
+        ContentType::Image(_) | ContentType::Buffer => {
+            print!("{}", hex_dump(&body.data, 0, body.data.len()));
         }
     }
-
     Ok(())
 }
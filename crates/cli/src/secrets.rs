@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use core::http::environment::Environment;
+use core::persistence::collections::{open_collection, read_collection_variables};
+use core::persistence::globals::read_globals;
+use core::persistence::secrets::SecretStore;
+
+/// Prefix of the process variables holding secret values, `api-token` is
+/// read from `SANCHAAR_SECRET_API_TOKEN`
+pub const SECRET_ENV_PREFIX: &str = "SANCHAAR_SECRET_";
+
+/// Passphrase unlocking the secret store of the app, secrets missing from
+/// the file and the process variables are read from it when set
+pub const PASSPHRASE_ENV: &str = "SANCHAAR_SECRETS_PASSPHRASE";
+
+/// Secret values given to the CLI
+#[derive(Debug, Default)]
+pub struct Secrets {
+    file: HashMap<String, String>,
+}
+
+fn env_var_name(name: &str) -> String {
+    let name = name
+        .chars()
+        .map(|c| match c.is_ascii_alphanumeric() {
+            true => c.to_ascii_uppercase(),
+            false => '_',
+        })
+        .collect::<String>();
+    format!("{}{}", SECRET_ENV_PREFIX, name)
+}
+
+impl Secrets {
+    /// Reads the `NAME=value` lines of the secrets file, empty lines and
+    /// lines starting with `#` are skipped
+    pub async fn load(file: Option<PathBuf>) -> anyhow::Result<Self> {
+        let Some(file) = file else {
+            return Ok(Self::default());
+        };
+
+        let content = tokio::fs::read_to_string(&file)
+            .await
+            .with_context(|| format!("Failed to read secrets file {}", file.display()))?;
+
+        let mut values = HashMap::new();
+        for (idx, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (name, value) = line.split_once('=').with_context(|| {
+                format!("{}:{} is not a NAME=value line", file.display(), idx + 1)
+            })?;
+            values.insert(name.trim().to_string(), value.trim().to_string());
+        }
+
+        Ok(Self { file: values })
+    }
+
+    /// Sets the values of the secret variables of the environment. The
+    /// secrets file wins over process variables, which win over the store.
+    pub async fn fill(&self, root: &Path, env: &mut Environment) -> anyhow::Result<()> {
+        let given = env
+            .secrets
+            .iter()
+            .filter_map(|name| {
+                let value = self
+                    .file
+                    .get(name)
+                    .cloned()
+                    .or_else(|| std::env::var(env_var_name(name)).ok())?;
+                Some((name.clone(), value))
+            })
+            .collect::<HashMap<_, _>>();
+
+        let missing = env.secrets.iter().any(|name| !given.contains_key(name));
+        if let (true, Ok(passphrase)) = (missing, std::env::var(PASSPHRASE_ENV)) {
+            // The store knows collections by their canonical path
+            let root = tokio::fs::canonicalize(root)
+                .await
+                .with_context(|| format!("Failed to find collection {}", root.display()))?;
+            let store = SecretStore::open(&passphrase).await?;
+            store.fill_environment(&root, env);
+        }
+
+        for kv in env.variables.iter_mut() {
+            if let Some(value) = given.get(&kv.name) {
+                kv.value = value.clone();
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Environment of the collection with its secret values, falling back to
+/// the collection variables and then the global variables
+pub async fn load_environment(
+    root: &Path,
+    name: Option<&str>,
+    secrets: &Secrets,
+) -> anyhow::Result<Option<Environment>> {
+    let globals = read_globals().await?;
+    let variables = read_collection_variables(root).await?;
+    let Some(name) = name else {
+        return Ok(Environment::scoped(None, &variables, &globals));
+    };
+
+    let collection = open_collection(root.to_path_buf()).await?;
+    let key = collection.environments.find_by_name(name).ok_or_else(|| {
+        // Broken environment files are skipped when opening the collection
+        let broken = collection
+            .diagnostics
+            .iter()
+            .find(|d| d.path.file_stem().is_some_and(|stem| stem == name));
+        match broken {
+            Some(diagnostic) => anyhow::anyhow!("Environment {} is invalid, {}", name, diagnostic),
+            None => anyhow::anyhow!("Environment {} not found", name),
+        }
+    })?;
+    let mut env = collection
+        .environments
+        .get(key)
+        .cloned()
+        .context("Environment not found")?;
+
+    secrets.fill(root, &mut env).await?;
+    Ok(Environment::scoped(Some(&env), &variables, &globals))
+}
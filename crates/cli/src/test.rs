@@ -1,67 +1,161 @@
 use core::{
-    assertions::{self, runner::MatcherResult},
-    client::{create_client, send_request},
-    persistence::request::read_request,
-    transformers::request::transform_request,
+    assertions::{
+        self,
+        runner::MatcherResult,
+        snapshot::{self, SnapshotResult},
+        Assertion,
+    },
+    client::{create_client, send_request, Response},
+    diff::LineTag,
+    grpc,
+    har::{save_har, Entry, Har, SentRequest},
+    http::environment::Environment,
+    persistence::{
+        examples::EXAMPLES_SUFFIX,
+        request::{read_request_file, RequestFile},
+        SNAPSHOTS,
+    },
+    transformers::{
+        grpc::{transform_grpc, transform_url},
+        request::transform_request,
+    },
 };
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::Context;
 use hcl::Value;
 
 use crate::color::{color, Color};
+use crate::secrets::{load_environment, Secrets};
 
-pub async fn test(root: PathBuf, path: PathBuf) -> anyhow::Result<()> {
+pub struct TestArgs {
+    pub path: PathBuf,
+    pub env: Option<String>,
+    pub har: Option<PathBuf>,
+    pub update_snapshots: bool,
+}
+
+/// Collection being tested, files and snapshots are relative to its root
+struct TestContext {
+    root: PathBuf,
+    env: Option<Environment>,
+    update_snapshots: bool,
+    /// Snapshots which are missing or differ from the responses
+    failed_snapshots: usize,
+}
+
+pub async fn test(root: PathBuf, args: TestArgs, secrets: &Secrets) -> anyhow::Result<()> {
     let current_dir = std::env::current_dir()?;
     let root = current_dir.join(root);
 
-    let path = root.join(path);
+    let path = root.join(args.path);
+    let env = load_environment(&root, args.env.as_deref(), secrets).await?;
+    let mut ctx = TestContext {
+        root,
+        env,
+        update_snapshots: args.update_snapshots,
+        failed_snapshots: 0,
+    };
 
     let client = create_client();
+    let mut entries = Vec::new();
 
     let file = tokio::fs::File::open(&path).await?;
     if file.metadata().await?.is_dir() {
-        walk_dir(client, &path).await?;
+        walk_dir(client, &path, &mut ctx, &mut entries).await?;
     } else {
-        test_file(client, &path).await?;
+        test_file(client, &path, &mut ctx, &mut entries).await?;
     }
 
+    if let Some(har) = args.har {
+        let har = current_dir.join(har);
+        save_har(har.clone(), &Har::new(entries)).await?;
+        println!("HAR written to {}", har.display());
+    }
+
+    if ctx.failed_snapshots > 0 {
+        anyhow::bail!(
+            "Snapshots missing or different: {}, run with --update-snapshots to accept the responses",
+            ctx.failed_snapshots
+        );
+    }
     Ok(())
 }
 
-async fn walk_dir(client: reqwest::Client, path: &PathBuf) -> anyhow::Result<()> {
+async fn walk_dir(
+    client: reqwest::Client,
+    path: &PathBuf,
+    ctx: &mut TestContext,
+    har: &mut Vec<Entry>,
+) -> anyhow::Result<()> {
     let mut entries = tokio::fs::read_dir(path).await?;
 
     while let Some(entry) = entries.next_entry().await? {
         let entry_path = entry.path();
         let file = tokio::fs::File::open(&entry_path).await?;
         if file.metadata().await?.is_dir() {
-            Box::pin(walk_dir(client.clone(), &entry_path)).await?;
+            // Saved examples and snapshots are not requests
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if name == SNAPSHOTS || name.ends_with(EXAMPLES_SUFFIX) {
+                continue;
+            }
+            Box::pin(walk_dir(client.clone(), &entry_path, ctx, har)).await?;
         } else {
-            test_file(client.clone(), &entry_path).await?;
+            test_file(client.clone(), &entry_path, ctx, har).await?;
         }
     }
 
     Ok(())
 }
 
-async fn test_file(client: reqwest::Client, path: &PathBuf) -> anyhow::Result<()> {
+async fn test_file(
+    client: reqwest::Client,
+    path: &Path,
+    ctx: &mut TestContext,
+    har: &mut Vec<Entry>,
+) -> anyhow::Result<()> {
     let file_name = path
         .file_name()
         .context("Invalid path")?
         .to_str()
         .context("Invalid file name")?;
 
-    let req = read_request(path).await?;
+    let (response, assertions) = match read_request_file(path).await? {
+        RequestFile::Http(req) => {
+            let assertions = req.assertions.clone();
+            let req = transform_request(client.clone(), req, ctx.env.clone(), ctx.root.clone());
+            let req = req.await?;
+            let sent = SentRequest::new(&req);
+            let response = send_request(client, req).await?;
 
-    let assertions = req.assertions.clone();
-
-    let req = transform_request(client.clone(), req, None).await?;
-    let response = send_request(client, req).await?;
+            let mut entry = sent.entry(&response);
+            if let Some(env) = &ctx.env {
+                entry.redact(env);
+            }
+            har.push(entry);
+            (response, assertions)
+        }
+        RequestFile::Grpc(req) => {
+            let assertions = req.assertions.clone();
+            let dir = path.parent().context("Invalid path")?;
+            let url = transform_url(&req.url, ctx.env.as_ref())?;
+            let pool = grpc::load_descriptors(&url, &req.resolve_proto_files(dir)).await?;
+            let call = transform_grpc(req, &pool, ctx.env.clone())?;
+            (grpc::execute(call).await?, assertions)
+        }
+        RequestFile::WebSocket(_) => {
+            println!(
+                "{} - skipped, WebSocket requests have no assertions",
+                file_name
+            );
+            return Ok(());
+        }
+    };
 
     let result = assertions::run(&response, &assertions);
 
-    println!("{} - {} assertions", file_name, result.len());
+    println!("{} - {} assertions", file_name, assertions.0.len());
 
     let indent = Indent::new();
     for assertion in result {
@@ -100,9 +194,79 @@ async fn test_file(client: reqwest::Client, path: &PathBuf) -> anyhow::Result<()
         }
     }
 
+    for assertion in &assertions.0 {
+        if let Assertion::Snapshot(options) = assertion {
+            let indent = indent.inc();
+            println!("{:id$}Assert {}", "", assertion.name(), id = indent.v);
+            if !check_snapshot(path, &response, options, ctx, indent.inc()).await? {
+                ctx.failed_snapshots += 1;
+            }
+        }
+    }
+
     Ok(())
 }
 
+/// Prints the result of the snapshot assertion, `false` when it failed
+async fn check_snapshot(
+    path: &Path,
+    response: &Response,
+    options: &assertions::SnapshotOptions,
+    ctx: &TestContext,
+    indent: Indent,
+) -> anyhow::Result<bool> {
+    let file = snapshot::snapshot_path(&ctx.root, path);
+    let value = snapshot::normalize(response, options);
+    let relative = file.strip_prefix(&ctx.root).unwrap_or(&file).display();
+
+    let (msg, clr) = match snapshot::check(&file, &value, ctx.update_snapshots).await? {
+        SnapshotResult::Matched => (format!("matches {}", relative), Color::LIGHTGREEN),
+        SnapshotResult::Missing => {
+            let msg = format!("{:id$}Snapshot {} is missing", "", relative, id = indent.v);
+            println!("{}", color(&msg, Color::RED));
+
+            let hint = "Run with --update-snapshots to write it";
+            let indent = indent.inc();
+            println!("{:id$}{}", "", color(hint, Color::YELLOW), id = indent.v);
+            return Ok(false);
+        }
+        SnapshotResult::Created => (format!("written to {}", relative), Color::YELLOW),
+        SnapshotResult::Updated => (format!("updated {}", relative), Color::YELLOW),
+        SnapshotResult::Mismatch(hunks) => {
+            let msg = format!(
+                "{:id$}Snapshot differs from {}",
+                "",
+                relative,
+                id = indent.v
+            );
+            println!("{}", color(&msg, Color::RED));
+
+            let indent = indent.inc();
+            for (idx, hunk) in hunks.iter().enumerate() {
+                if idx > 0 {
+                    println!("{:id$}{}", "", color("...", Color::DARKGRAY), id = indent.v);
+                }
+                for line in hunk {
+                    let (prefix, clr) = match line.tag {
+                        LineTag::Equal => (" ", Color::DARKGRAY),
+                        LineTag::Delete => ("-", Color::RED),
+                        LineTag::Insert => ("+", Color::LIGHTGREEN),
+                    };
+                    let line = format!("{} {}", prefix, line.text);
+                    println!("{:id$}{}", "", color(&line, clr), id = indent.v);
+                }
+            }
+            let hint = "Run with --update-snapshots to accept the changes";
+            println!("{:id$}{}", "", color(hint, Color::YELLOW), id = indent.v);
+            return Ok(false);
+        }
+    };
+
+    let msg = format!("{:id$}Snapshot {}", "", msg, id = indent.v);
+    println!("{}", color(&msg, clr));
+    Ok(true)
+}
+
 struct Indent {
     v: usize,
 }
@@ -3,12 +3,14 @@ use iced::widget::{component, Component};
 use iced::{highlighter, Element, Font, Length};
 use iced_core::text::Wrapping;
 
+use crate::text_editor::variables::{self, Highlight, VariableHighlighter, Variables};
 use crate::text_editor::{self, text_editor, ContentAction};
 
 pub enum ContentType {
     Json,
     Text,
     XML,
+    Html,
 }
 
 pub struct CodeEditor<'a, M> {
@@ -16,6 +18,7 @@ pub struct CodeEditor<'a, M> {
     pub content_type: ContentType,
     pub on_action: Option<Box<dyn Fn(CodeEditorMsg) -> M>>,
     pub editable: bool,
+    pub variables: Option<Variables>,
 }
 
 impl<'a, M: 'a> CodeEditor<'a, M> {
@@ -31,6 +34,12 @@ impl<'a, M: 'a> CodeEditor<'a, M> {
         self.editable = true;
         self
     }
+
+    /// Highlights the `{{name}}` references to the variables
+    pub fn variables(mut self, variables: &Variables) -> Self {
+        self.variables = Some(variables.clone());
+        self
+    }
 }
 
 impl ContentType {
@@ -39,6 +48,7 @@ impl ContentType {
             ContentType::Json => "json".to_string(),
             ContentType::Text => "txt".to_string(),
             ContentType::XML => "xml".to_string(),
+            ContentType::Html => "html".to_string(),
         }
     }
 }
@@ -66,6 +76,7 @@ pub fn code_editor<M>(code: &text_editor::Content, content_type: ContentType) ->
         content_type,
         on_action: None,
         editable: false,
+        variables: None,
     }
 }
 
@@ -78,19 +89,33 @@ impl<'a, M> Component<M> for CodeEditor<'a, M> {
     }
 
     fn view(&self, _state: &Self::State) -> Element<Self::Event> {
-        text_editor(self.code)
+        let editor = text_editor(self.code)
             .height(Length::Fill)
             .font(Font::MONOSPACE)
             .wrapping(Wrapping::WordOrGlyph)
-            .on_action(|ac| CodeEditorMsg::EditorAction(ac, self.editable))
-            .highlight::<Highlighter>(
-                highlighter::Settings {
-                    theme: highlighter::Theme::SolarizedDark,
-                    token: self.content_type.to_extension(),
-                },
-                |highlight, _theme| highlight.to_format(),
-            )
-            .into()
+            .on_action(|ac| CodeEditorMsg::EditorAction(ac, self.editable));
+        let settings = highlighter::Settings {
+            theme: highlighter::Theme::SolarizedDark,
+            token: self.content_type.to_extension(),
+        };
+
+        match &self.variables {
+            Some(variables) => editor
+                .highlight::<VariableHighlighter<Highlighter>>(
+                    variables::Settings {
+                        inner: settings,
+                        variables: variables.clone(),
+                    },
+                    |highlight, _theme| match highlight {
+                        Highlight::Inner(highlight) => highlight.to_format(),
+                        highlight => highlight.variable_format().unwrap_or_default(),
+                    },
+                )
+                .into(),
+            None => editor
+                .highlight::<Highlighter>(settings, |highlight, _theme| highlight.to_format())
+                .into(),
+        }
     }
 }
 
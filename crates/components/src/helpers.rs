@@ -10,21 +10,21 @@ pub fn expanded<'a, M>(base: impl Into<Element<'a, M>>) -> Container<'a, M> {
 pub fn tooltip<'a, M: 'a>(msg: &'a str, base: impl Into<Element<'a, M>>) -> Tooltip<'a, M> {
     iced::widget::tooltip(
         base,
-        container(text(msg))
-            .style(|theme: &Theme| {
-                let palette = theme.extended_palette();
-
-                container::Style {
-                    background: Some(palette.background.weak.color.into()),
-                    border: Border {
-                        width: 1.0,
-                        radius: 4.0.into(),
-                        color: palette.background.strong.color,
-                    },
-                    ..Default::default()
-                }
-            })
-            .padding([2, 4]),
+        container(text(msg)).style(tooltip_style).padding([2, 4]),
         Position::Bottom,
     )
 }
+
+pub fn tooltip_style(theme: &Theme) -> container::Style {
+    let palette = theme.extended_palette();
+
+    container::Style {
+        background: Some(palette.background.weak.color.into()),
+        border: Border {
+            width: 1.0,
+            radius: 4.0.into(),
+            color: palette.background.strong.color,
+        },
+        ..Default::default()
+    }
+}
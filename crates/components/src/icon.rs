@@ -55,4 +55,15 @@ pub mod icons {
     pub const API: NerdIcon = NerdIcon('󱂛');
     pub const Folder: NerdIcon = NerdIcon('󰉋');
     pub const FolderOpen: NerdIcon = NerdIcon('󰝰');
+    pub const LanConnect: NerdIcon = NerdIcon('󰌘');
+    pub const LanDisconnect: NerdIcon = NerdIcon('󰌙');
+    pub const ArrowUpBold: NerdIcon = NerdIcon('󰜷');
+    pub const ArrowDownBold: NerdIcon = NerdIcon('󰜮');
+    pub const Refresh: NerdIcon = NerdIcon('󰑐');
+    pub const Protocol: NerdIcon = NerdIcon('󰿘');
+    pub const CodeTags: NerdIcon = NerdIcon('󰅴');
+    pub const FileDocument: NerdIcon = NerdIcon('󰈙');
+    pub const Lock: NerdIcon = NerdIcon('󰌾');
+    pub const LockOpen: NerdIcon = NerdIcon('󰌿');
+    pub const History: NerdIcon = NerdIcon('󰋚');
 }
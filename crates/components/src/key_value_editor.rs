@@ -1,19 +1,22 @@
 use iced::widget::{column, text};
 use iced::{border, padding, Background, Length};
 use iced::{
-    widget::{button, checkbox, component, container, text_input, Component, Row},
+    widget::{button, checkbox, component, container, text_input, Column, Component, Row},
     Border, Element, Theme,
 };
 use std::ops::Not;
 
+use crate::text_editor::variables::Variables;
 use crate::text_editor::{self, line_editor, ContentAction};
-use crate::tooltip;
+use crate::{tooltip, variable_completions, variable_hover};
 
 use super::{icon, icons};
 
 #[derive(Debug, Default)]
 pub struct KeyValue {
     pub disabled: bool,
+    /// Value is masked, only used when the editor allows secrets
+    pub secret: bool,
     name: String,
     value: text_editor::Content,
 }
@@ -24,9 +27,15 @@ impl KeyValue {
             name: name.to_owned(),
             value: text_editor::Content::with_text(value),
             disabled,
+            secret: false,
         }
     }
 
+    pub fn with_secret(mut self, secret: bool) -> Self {
+        self.secret = secret;
+        self
+    }
+
     pub fn name(&self) -> &String {
         &self.name
     }
@@ -85,6 +94,10 @@ impl KeyValList {
             KeyValUpdateMsg::ValueChanged(idx, action) => {
                 self.list[idx].value.perform(action);
             }
+            KeyValUpdateMsg::SecretToggled(idx, secret) => self.list[idx].secret = secret,
+            KeyValUpdateMsg::SecretChanged(idx, value) => {
+                self.list[idx].value = text_editor::Content::with_text(&value);
+            }
             KeyValUpdateMsg::Remove(idx) => {
                 self.list.remove(idx);
             }
@@ -122,6 +135,7 @@ impl KeyValList {
         self.list.push(KeyValue {
             name: key,
             disabled: false,
+            secret: false,
             value: text_editor::Content::default(),
         });
     }
@@ -138,6 +152,8 @@ impl KeyValList {
 pub struct KeyValEditor<'a, M> {
     values: &'a KeyValList,
     on_change: Option<Box<dyn Fn(KeyValUpdateMsg) -> M + 'a>>,
+    secrets: Option<bool>,
+    variables: Option<Variables>,
 }
 
 impl<'a, M: Clone> KeyValEditor<'a, M> {
@@ -148,6 +164,20 @@ impl<'a, M: Clone> KeyValEditor<'a, M> {
         self.on_change = Some(Box::new(f));
         self
     }
+
+    /// Allows marking values secret, secret values are masked and can only
+    /// be edited when not locked
+    pub fn secrets(mut self, locked: bool) -> Self {
+        self.secrets = Some(locked);
+        self
+    }
+
+    /// Highlights the `{{name}}` references in the values and suggests the
+    /// variables while typing them
+    pub fn variables(mut self, variables: &Variables) -> Self {
+        self.variables = Some(variables.clone());
+        self
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -155,6 +185,8 @@ pub enum KeyValUpdateMsg {
     Toggled(usize, bool),
     NameChanged(usize, String),
     ValueChanged(usize, ContentAction),
+    SecretToggled(usize, bool),
+    SecretChanged(usize, String),
     Remove(usize),
 }
 
@@ -162,6 +194,8 @@ pub fn key_value_editor<M>(values: &KeyValList) -> KeyValEditor<'_, M> {
     KeyValEditor {
         values,
         on_change: None,
+        secrets: None,
+        variables: None,
     }
 }
 
@@ -196,10 +230,28 @@ impl<'a, M> Component<M> for KeyValEditor<'a, M> {
                     None
                 });
 
+            // Values can only move to the store while it is unlocked
+            let secret = self.secrets.map(|locked| {
+                let (label, icn) = match kv.secret {
+                    true => ("Secret", icons::Lock),
+                    false => ("Not Secret", icons::LockOpen),
+                };
+                let toggle = button(container(icon(icn).size(size + 4)))
+                    .padding(0)
+                    .style(button::text)
+                    .on_press_maybe(
+                        locked
+                            .not()
+                            .then_some(KeyValUpdateMsg::SecretToggled(idx, !kv.secret)),
+                    );
+                tooltip(label, toggle)
+            });
+
             let actions = self.values.fixed.not().then(|| {
                 container(
                     Row::new()
                         .push(tooltip("Enabled", enabled))
+                        .push_maybe(secret)
                         .push(tooltip("Delete", remove))
                         .align_y(iced::Alignment::Center)
                         .spacing(8),
@@ -220,22 +272,58 @@ impl<'a, M> Component<M> for KeyValEditor<'a, M> {
                 .size(size)
                 .width(Length::FillPortion(2));
 
-            let value = container(
-                line_editor(&kv.value)
-                    .style(move |t, s| text_editor::Style {
-                        border,
-                        ..text_editor::default(t, s)
-                    })
-                    .on_action(move |a| KeyValUpdateMsg::ValueChanged(idx, a))
-                    .size(size),
-            )
-            .width(Length::FillPortion(3));
+            let value: Element<_> = match self.secrets {
+                Some(locked) if kv.secret => {
+                    let placeholder = if locked { "Locked" } else { "" };
+                    text_input(placeholder, &kv.value())
+                        .secure(true)
+                        .style(input_style)
+                        .on_input_maybe(
+                            locked
+                                .not()
+                                .then_some(move |v| KeyValUpdateMsg::SecretChanged(idx, v)),
+                        )
+                        .size(size)
+                        .into()
+                }
+                _ => {
+                    let editor = line_editor(&kv.value)
+                        .style(move |t, s| text_editor::Style {
+                            border,
+                            ..text_editor::default(t, s)
+                        })
+                        .on_action(move |a| KeyValUpdateMsg::ValueChanged(idx, a))
+                        .size(size);
+                    match &self.variables {
+                        Some(variables) => {
+                            variable_hover(editor.variables(variables), &kv.value, variables)
+                        }
+                        None => editor.into(),
+                    }
+                }
+            };
+            let value = container(value).width(Length::FillPortion(3));
+
+            let masked = self.secrets.is_some() && kv.secret;
+            let completions = self
+                .variables
+                .as_ref()
+                .filter(|_| !masked)
+                .and_then(|variables| {
+                    let on_action = move |a| KeyValUpdateMsg::ValueChanged(idx, a);
+                    variable_completions(&kv.value, variables, on_action)
+                });
 
             container(
-                Row::new()
-                    .push(name)
-                    .push(value)
-                    .push_maybe(actions)
+                Column::new()
+                    .push(
+                        Row::new()
+                            .push(name)
+                            .push(value)
+                            .push_maybe(actions)
+                            .spacing(spacing),
+                    )
+                    .push_maybe(completions)
                     .spacing(spacing),
             )
             .style(move |t| {
@@ -11,6 +11,7 @@ mod lines;
 mod min_dimension;
 mod modal;
 mod multi_file_picker;
+mod variable_hints;
 
 pub mod colors;
 pub mod text_editor;
@@ -28,3 +29,5 @@ pub use lines::{horizontal_line, vertical_line};
 pub use min_dimension::{min_height, min_width, MinDimension};
 pub use modal::modal;
 pub use multi_file_picker::{multi_file_picker, FilePickerAction, KeyFile, KeyFileList};
+pub use text_editor::variables::Variables;
+pub use variable_hints::{variable_completions, variable_hover};
@@ -2,6 +2,7 @@
 
 mod content;
 mod undo_stack;
+pub mod variables;
 
 use std::cell::RefCell;
 use std::ops::DerefMut;
@@ -24,6 +25,7 @@ use iced_core::{
     Vector,
 };
 pub use text::editor::{Action, Edit, Motion};
+use variables::{Settings, VariableHighlighter, Variables};
 
 /// A multi-line text input.
 #[allow(missing_debug_implementations)]
@@ -93,6 +95,20 @@ where
             highlighter_format: |_highlight, _theme| highlighter::Format::default(),
         }
     }
+
+    /// Highlights the `{{name}}` references to the given [`Variables`].
+    pub fn variables(
+        self,
+        variables: &Variables,
+    ) -> TextEditor<'a, VariableHighlighter<highlighter::PlainText>, Message, Theme, Renderer> {
+        self.highlight::<VariableHighlighter<highlighter::PlainText>>(
+            Settings {
+                inner: (),
+                variables: variables.clone(),
+            },
+            |highlight, _theme| highlight.variable_format().unwrap_or_default(),
+        )
+    }
 }
 
 impl<'a, Highlighter, Message, Theme, Renderer>
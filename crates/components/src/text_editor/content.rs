@@ -192,6 +192,21 @@ where
     pub fn cursor_position(&self) -> (usize, usize) {
         self.0.borrow().editor.cursor_position()
     }
+
+    /// Returns the text of the [`Content`] up to the cursor.
+    pub fn text_before_cursor(&self) -> String {
+        let (line, col) = self.cursor_position();
+        let mut before = self.lines().take(line).fold(String::new(), |mut acc, l| {
+            acc.push_str(&l);
+            acc.push('\n');
+            acc
+        });
+
+        if let Some(current) = self.line(line) {
+            before.extend(current.chars().take(col));
+        }
+        before
+    }
 }
 
 impl<Renderer> Default for Content<Renderer>
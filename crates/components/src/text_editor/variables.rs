@@ -0,0 +1,176 @@
+//! Highlighting of `{{name}}` references to variables.
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::sync::Arc;
+
+use iced_core::text::highlighter::Format;
+use iced_core::text::Highlighter;
+
+use crate::colors;
+
+/// Variables which can be referenced with `{{name}}`, with the values shown
+/// for them
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Variables {
+    values: Arc<BTreeMap<String, String>>,
+    /// Names starting with one of these are defined whatever follows
+    prefixes: Arc<Vec<String>>,
+}
+
+impl Variables {
+    pub fn new(values: BTreeMap<String, String>) -> Self {
+        Self {
+            values: Arc::new(values),
+            prefixes: Arc::default(),
+        }
+    }
+
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        Arc::make_mut(&mut self.prefixes).push(prefix.to_owned());
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+
+    pub fn is_defined(&self, name: &str) -> bool {
+        self.values.contains_key(name) || self.prefixes.iter().any(|p| name.starts_with(p))
+    }
+
+    /// Variables and prefixes starting with the given text, with their values
+    pub fn completions<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = (&'a str, &'a str)> {
+        let prefixes = self.prefixes.iter().map(|p| (p.as_str(), ""));
+        self.values
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .chain(prefixes)
+            .filter(move |(name, _)| name.starts_with(prefix) && *name != prefix)
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-' | '$')
+}
+
+fn is_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(is_name_char)
+}
+
+/// Byte ranges of the `{{name}}` references in the line, with their names
+pub fn references(line: &str) -> Vec<(Range<usize>, &str)> {
+    let mut refs = Vec::new();
+    let mut from = 0;
+    while let Some(start) = line[from..].find("{{").map(|idx| from + idx) {
+        let Some(end) = line[start + 2..].find("}}").map(|idx| start + 2 + idx) else {
+            break;
+        };
+
+        let name = line[start + 2..end].trim();
+        if is_name(name) {
+            refs.push((start..end + 2, name));
+            from = end + 2;
+        } else {
+            from = start + 2;
+        }
+    }
+    refs
+}
+
+/// Part of the name typed after an unclosed `{{` at the end of the text
+pub fn completion_prefix(before_cursor: &str) -> Option<&str> {
+    let start = before_cursor.rfind("{{")?;
+    let typed = before_cursor[start + 2..].trim_start();
+    typed.chars().all(is_name_char).then_some(typed)
+}
+
+/// Settings of the [`VariableHighlighter`], the settings of the highlighter
+/// it wraps and the variables to resolve
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings<S> {
+    pub inner: S,
+    pub variables: Variables,
+}
+
+#[derive(Debug, Clone)]
+pub enum Highlight<T> {
+    Inner(T),
+    Resolved,
+    Undefined,
+}
+
+impl<T> Highlight<T> {
+    /// Format of the variable references, highlights of the wrapped
+    /// highlighter have none
+    pub fn variable_format<F>(&self) -> Option<Format<F>> {
+        let color = match self {
+            Self::Inner(_) => return None,
+            Self::Resolved => colors::LIME_GREEN,
+            Self::Undefined => colors::RED,
+        };
+        Some(Format {
+            color: Some(color),
+            font: None,
+        })
+    }
+}
+
+/// Highlights `{{name}}` references on top of the highlights of another
+/// highlighter, green when the variable is defined and red when it is not
+#[derive(Debug)]
+pub struct VariableHighlighter<H> {
+    inner: H,
+    variables: Variables,
+    current_line: usize,
+}
+
+impl<H: Highlighter> Highlighter for VariableHighlighter<H> {
+    type Settings = Settings<H::Settings>;
+    type Highlight = Highlight<H::Highlight>;
+    type Iterator<'a> = std::vec::IntoIter<(Range<usize>, Self::Highlight)>;
+
+    fn new(settings: &Self::Settings) -> Self {
+        Self {
+            inner: H::new(&settings.inner),
+            variables: settings.variables.clone(),
+            current_line: 0,
+        }
+    }
+
+    fn update(&mut self, new_settings: &Self::Settings) {
+        self.inner.update(&new_settings.inner);
+        self.variables = new_settings.variables.clone();
+        self.current_line = 0;
+    }
+
+    fn change_line(&mut self, line: usize) {
+        self.inner.change_line(line);
+        // The wrapped highlighter may restart from an earlier line
+        self.current_line = line.min(self.inner.current_line());
+    }
+
+    fn highlight_line(&mut self, line: &str) -> Self::Iterator<'_> {
+        let mut spans = self
+            .inner
+            .highlight_line(line)
+            .map(|(range, highlight)| (range, Highlight::Inner(highlight)))
+            .collect::<Vec<_>>();
+
+        // Later spans win, so references are drawn over the inner highlights
+        spans.extend(references(line).into_iter().map(|(range, name)| {
+            let highlight = match self.variables.is_defined(name) {
+                true => Highlight::Resolved,
+                false => Highlight::Undefined,
+            };
+            (range, highlight)
+        }));
+        self.current_line += 1;
+
+        spans.into_iter()
+    }
+
+    fn current_line(&self) -> usize {
+        self.current_line
+    }
+}
@@ -0,0 +1,104 @@
+use std::sync::Arc;
+
+use iced::widget::{button, container, scrollable, text, tooltip, Column, Row};
+use iced::{Alignment, Element, Length};
+
+use crate::colors;
+use crate::helpers::tooltip_style;
+use crate::text_editor::variables::{completion_prefix, references, Variables};
+use crate::text_editor::{self, Action, ContentAction, Edit};
+
+/// Maximum number of variables suggested below an editor
+const MAX_COMPLETIONS: usize = 20;
+
+/// Longest value shown for a variable before it is cut
+const MAX_VALUE_LEN: usize = 60;
+
+fn shorten(value: &str) -> String {
+    match value.char_indices().nth(MAX_VALUE_LEN) {
+        Some((idx, _)) => format!("{}...", &value[..idx]),
+        None => value.to_owned(),
+    }
+}
+
+/// Row of the variables completing the `{{` typed before the cursor, picking
+/// one pastes the rest of its name and the closing braces
+pub fn variable_completions<'a, M: Clone + 'a>(
+    content: &text_editor::Content,
+    variables: &Variables,
+    on_action: impl Fn(ContentAction) -> M,
+) -> Option<Element<'a, M>> {
+    let before = content.text_before_cursor();
+    let prefix = completion_prefix(&before)?;
+
+    let suggestions = variables
+        .completions(prefix)
+        .take(MAX_COMPLETIONS)
+        .map(|(name, value)| {
+            let suffix = &name[prefix.len()..];
+            // Prefixes like `$env.` are completed without closing them
+            let paste = match variables.get(name).is_some() {
+                true => format!("{}}}}}", suffix),
+                false => suffix.to_owned(),
+            };
+            let action = ContentAction::Action(Action::Edit(Edit::Paste(Arc::new(paste))));
+
+            button(
+                Row::new()
+                    .push(text(name.to_owned()).size(12))
+                    .push(text(shorten(value)).size(10).color(colors::DARK_GREY))
+                    .spacing(4)
+                    .align_y(Alignment::Center),
+            )
+            .padding([2, 6])
+            .style(button::secondary)
+            .on_press(on_action(action))
+        })
+        .collect::<Vec<_>>();
+    if suggestions.is_empty() {
+        return None;
+    }
+
+    Some(
+        scrollable(Row::with_children(suggestions.into_iter().map(Element::from)).spacing(4))
+            .direction(scrollable::Direction::Horizontal(Default::default()))
+            .width(Length::Fill)
+            .into(),
+    )
+}
+
+/// Shows the values of the variables referenced in the text when hovering
+/// the editor
+pub fn variable_hover<'a, M: 'a>(
+    base: impl Into<Element<'a, M>>,
+    content: &text_editor::Content,
+    variables: &Variables,
+) -> Element<'a, M> {
+    let text_content = content.text();
+    let mut names = text_content
+        .lines()
+        .flat_map(references)
+        .map(|(_, name)| name)
+        .collect::<Vec<_>>();
+    names.sort_unstable();
+    names.dedup();
+    if names.is_empty() {
+        return base.into();
+    }
+
+    let values = names.into_iter().fold(Column::new(), |col, name| {
+        let line = match variables.get(name) {
+            Some(value) => text(format!("{} = {}", name, shorten(value))),
+            None if variables.is_defined(name) => text(name.to_owned()),
+            None => text(format!("{} is not defined", name)).color(colors::RED),
+        };
+        col.push(line.size(12))
+    });
+
+    tooltip(
+        base,
+        container(values).style(tooltip_style).padding([2, 4]),
+        tooltip::Position::FollowCursor,
+    )
+    .into()
+}
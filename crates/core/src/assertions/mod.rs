@@ -1,4 +1,5 @@
 pub mod runner;
+pub mod snapshot;
 
 pub use runner::run;
 
@@ -6,7 +7,7 @@ use std::collections::HashMap;
 
 use anyhow::Context;
 use hcl::{
-    structure::{BlockBuilder, BodyBuilder},
+    structure::{Block, BlockBuilder, BodyBuilder},
     BlockLabel, Identifier, Value,
 };
 use serde::Deserialize;
@@ -54,6 +55,15 @@ pub struct Condition {
     matcher: Matcher,
 }
 
+impl Condition {
+    pub fn new(key: impl Into<String>, matcher: Matcher) -> Self {
+        Self {
+            key: key.into(),
+            matcher,
+        }
+    }
+}
+
 fn to_string_vec(val: &[Value]) -> String {
     let vals = val
         .iter()
@@ -113,14 +123,30 @@ pub enum Assertion {
     Duration(Vec<Condition>),
     Headers(Vec<Condition>),
     Body(Vec<Condition>),
+    GraphQL(Vec<Condition>),
+    /// Checked against a stored snapshot instead of conditions
+    Snapshot(SnapshotOptions),
+}
+
+/// Parts of the response kept in its snapshot
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SnapshotOptions {
+    /// Headers stored along with the status and body
+    pub headers: Vec<String>,
+    /// Dotted JSON paths of the body such as `items.*.id`, and headers such
+    /// as `header.date`, whose values are replaced by a placeholder
+    pub redact: Vec<String>,
 }
+
 impl Assertion {
-    fn name(&self) -> String {
+    pub fn name(&self) -> String {
         match self {
             Assertion::Status(_) => "Status".to_string(),
             Assertion::Duration(_) => "Response Duration".to_string(),
             Assertion::Headers(_) => "Headers".to_string(),
             Assertion::Body(_) => "Body".to_string(),
+            Assertion::GraphQL(_) => "GraphQL".to_string(),
+            Assertion::Snapshot(_) => "Snapshot".to_string(),
         }
     }
 }
@@ -161,7 +187,7 @@ fn parse_conditions(value: &Value) -> anyhow::Result<Vec<Condition>> {
                 "not_in" => Matcher::NotIn(as_array(value)?),
                 "is" => Matcher::Is(as_string(value)?.parse()?),
                 "is_not" => Matcher::IsNot(as_string(value)?.parse()?),
-                _ => continue, // Ignored
+                _ => anyhow::bail!("Unknown matcher `{}` for `{}`", op, key),
             };
             let key = key.to_owned();
             cons.push(Condition { key, matcher });
@@ -197,18 +223,39 @@ fn as_array(value: &Value) -> anyhow::Result<Vec<Value>> {
     Ok(value.as_array().context("Expected Array")?.to_owned())
 }
 
+fn parse_snapshot(value: &Value) -> anyhow::Result<SnapshotOptions> {
+    let object = value.as_object().context("Expected Object")?;
+    let strings = |key: &str| -> anyhow::Result<Vec<String>> {
+        match object.get(key) {
+            Some(value) => as_array(value)?.iter().map(as_string).collect(),
+            None => Ok(Vec::new()),
+        }
+    };
+
+    Ok(SnapshotOptions {
+        headers: strings("headers")?,
+        redact: strings("redact")?,
+    })
+}
+
 pub fn parse(body: Value) -> anyhow::Result<Assertions> {
     let mut assertions = Vec::new();
 
     let root = body.as_object().context("Expected Object")?;
     for (key, value) in root {
+        if key == "snapshot" {
+            assertions.push(Assertion::Snapshot(parse_snapshot(value)?));
+            continue;
+        }
+
         let matchers = parse_conditions(value)?;
         let condition = match key.as_str() {
             "status" => Assertion::Status(matchers),
             "duration" => Assertion::Duration(matchers),
             "header" => Assertion::Headers(matchers),
             "body" => Assertion::Body(matchers),
-            _ => continue, // Ignored
+            "graphql" => Assertion::GraphQL(matchers),
+            _ => anyhow::bail!("Unknown assertion `{}`", key),
         };
 
         assertions.push(condition);
@@ -268,6 +315,17 @@ fn encode_condition_block(
     root
 }
 
+fn encode_snapshot(options: SnapshotOptions) -> Block {
+    let mut block = BlockBuilder::new("snapshot");
+    if !options.headers.is_empty() {
+        block = block.add_attribute(("headers", options.headers));
+    }
+    if !options.redact.is_empty() {
+        block = block.add_attribute(("redact", options.redact));
+    }
+    block.build()
+}
+
 pub fn encode(builder: BodyBuilder, assertions: Assertions) -> BodyBuilder {
     if assertions.0.is_empty() {
         return builder;
@@ -281,6 +339,8 @@ pub fn encode(builder: BodyBuilder, assertions: Assertions) -> BodyBuilder {
             Assertion::Duration(duration) => encode_condition_block(root, "duration", duration),
             Assertion::Headers(headers) => encode_condition_block(root, "header", headers),
             Assertion::Body(body) => encode_condition_block(root, "body", body),
+            Assertion::GraphQL(graphql) => encode_condition_block(root, "graphql", graphql),
+            Assertion::Snapshot(options) => root.add_block(encode_snapshot(options)),
         };
     }
 
@@ -4,6 +4,7 @@ use hcl::Value;
 use regex::Regex;
 
 use crate::client::Response;
+use crate::grpc::GRPC_STATUS;
 
 use super::{Assertion, Assertions, Condition, MatchType, Matcher};
 
@@ -42,6 +43,11 @@ pub fn run(response: &Response, assertions: &Assertions) -> Vec<AssertionOutcome
                 match_conditions(conditions, |key| match key {
                     "code" => Some(status.as_u16().into()),
                     "text" => Some(status.to_string().into()),
+                    "grpc" => response
+                        .headers
+                        .get(GRPC_STATUS)
+                        .and_then(|v| v.to_str().ok()?.parse::<u64>().ok())
+                        .map(Value::from),
                     _ => None,
                 })
             }
@@ -71,6 +77,15 @@ pub fn run(response: &Response, assertions: &Assertions) -> Vec<AssertionOutcome
                 "string" => from_utf8(&response.body.data).ok().map(Value::from),
                 _ => None,
             }),
+            Assertion::GraphQL(conditions) => {
+                let body = serde_json::from_slice::<serde_json::Value>(&response.body.data).ok();
+                match_conditions(conditions, |key| {
+                    let value = json_path(body.as_ref()?, key)?;
+                    hcl::to_value(value).ok()
+                })
+            }
+            // Needs the stored snapshot, see `snapshot::check`
+            Assertion::Snapshot(_) => continue,
         };
 
         report.push(AssertionOutcome {
@@ -82,6 +97,21 @@ pub fn run(response: &Response, assertions: &Assertions) -> Vec<AssertionOutcome
     report
 }
 
+/// Looks up a dotted path such as `data.users.0.name` in a JSON value, only
+/// the `data` and `errors` members of a GraphQL response can be targeted.
+fn json_path<'a>(value: &'a serde_json::Value, path: &str) -> Option<&'a serde_json::Value> {
+    let mut segments = path.split('.');
+    let root = segments.next()?;
+    if root != "data" && root != "errors" {
+        return None;
+    }
+
+    segments.try_fold(value.get(root)?, |value, segment| match value {
+        serde_json::Value::Array(items) => items.get(segment.parse::<usize>().ok()?),
+        value => value.get(segment),
+    })
+}
+
 fn match_conditions(
     conditions: &[Condition],
     get_value: impl Fn(&str) -> Option<Value>,
@@ -0,0 +1,301 @@
+use std::path::{Path, PathBuf};
+
+use serde_json::{Map, Value};
+use tokio::fs;
+
+use crate::client::Response;
+use crate::diff::{diff_lines, header_values, path_matches, DiffLine, HEADER_PREFIX};
+use crate::persistence::files::write_atomic;
+use crate::persistence::{REQUESTS, SNAPSHOTS};
+
+use super::SnapshotOptions;
+
+/// Stored in place of redacted values
+pub const REDACTED: &str = "[redacted]";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SnapshotResult {
+    Matched,
+    /// No snapshot exists and none was written without `update`
+    Missing,
+    /// No snapshot existed, the response was stored as the first one
+    Created,
+    Updated,
+    /// Line diff between the stored snapshot and the response
+    Mismatch(Vec<Vec<DiffLine>>),
+}
+
+/// Snapshot file of a request, `requests/users/Get user.hcl` is stored as
+/// `__snapshots__/users/Get user.json` in the collection
+pub fn snapshot_path(collection: &Path, request: &Path) -> PathBuf {
+    let relative = request
+        .strip_prefix(collection.join(REQUESTS))
+        .or_else(|_| request.strip_prefix(collection))
+        .unwrap_or(request);
+
+    collection
+        .join(SNAPSHOTS)
+        .join(relative)
+        .with_extension("json")
+}
+
+fn redacted(options: &SnapshotOptions, path: &str) -> bool {
+    options
+        .redact
+        .iter()
+        .any(|pattern| path_matches(pattern, path))
+}
+
+fn redact(value: Value, path: String, options: &SnapshotOptions) -> Value {
+    if !path.is_empty() && redacted(options, &path) {
+        return Value::String(REDACTED.to_string());
+    }
+
+    let child = |key: &str| match path.is_empty() {
+        true => key.to_string(),
+        false => format!("{}.{}", path, key),
+    };
+    match value {
+        Value::Object(object) => Value::Object(
+            object
+                .into_iter()
+                .map(|(key, value)| {
+                    let value = redact(value, child(&key), options);
+                    (key, value)
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .enumerate()
+                .map(|(idx, value)| redact(value, child(&idx.to_string()), options))
+                .collect(),
+        ),
+        value => value,
+    }
+}
+
+/// The parts of a response kept in a snapshot, JSON bodies are stored as
+/// values and other bodies as text
+pub fn normalize(response: &Response, options: &SnapshotOptions) -> Value {
+    let all_headers = header_values(response);
+    let mut headers = Map::new();
+    for name in &options.headers {
+        let name = name.to_ascii_lowercase();
+        let Some(value) = all_headers.get(&name) else {
+            continue;
+        };
+        let value = match redacted(options, &format!("{}{}", HEADER_PREFIX, name)) {
+            true => REDACTED.to_string(),
+            false => value.clone(),
+        };
+        headers.insert(name, Value::String(value));
+    }
+
+    let body = match serde_json::from_slice::<Value>(&response.body.data) {
+        Ok(body) => redact(body, String::new(), options),
+        Err(_) => Value::String(String::from_utf8_lossy(&response.body.data).to_string()),
+    };
+
+    let mut snapshot = Map::new();
+    snapshot.insert("status".to_string(), response.status.as_u16().into());
+    snapshot.insert("headers".to_string(), Value::Object(headers));
+    snapshot.insert("body".to_string(), body);
+    Value::Object(snapshot)
+}
+
+async fn write_snapshot(path: &Path, snapshot: &str) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+    write_atomic(path, snapshot).await?;
+    Ok(())
+}
+
+/// Compares a normalized response with the snapshot stored at `path`. A
+/// missing snapshot is created and a different one is replaced only when
+/// `update` is set.
+pub async fn check(path: &Path, snapshot: &Value, update: bool) -> anyhow::Result<SnapshotResult> {
+    let new = format!("{}\n", serde_json::to_string_pretty(snapshot)?);
+
+    if !fs::try_exists(path).await? {
+        if !update {
+            return Ok(SnapshotResult::Missing);
+        }
+        write_snapshot(path, &new).await?;
+        return Ok(SnapshotResult::Created);
+    }
+
+    let stored = fs::read_to_string(path).await?;
+    // Formatting of the stored file does not matter, only its values
+    let old = match serde_json::from_str::<Value>(&stored) {
+        Ok(old) if &old == snapshot => return Ok(SnapshotResult::Matched),
+        Ok(old) => format!("{}\n", serde_json::to_string_pretty(&old)?),
+        Err(_) => stored,
+    };
+
+    if update {
+        write_snapshot(path, &new).await?;
+        return Ok(SnapshotResult::Updated);
+    }
+
+    Ok(SnapshotResult::Mismatch(diff_lines(&old, &new)))
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+    use reqwest::StatusCode;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::client::{ContentType, ResponseBody};
+    use crate::diff::LineTag;
+
+    fn response(status: u16, headers: &[(&str, &str)], body: &str) -> Response {
+        let mut map = HeaderMap::new();
+        for (name, value) in headers {
+            map.append(
+                HeaderName::from_bytes(name.as_bytes()).unwrap(),
+                HeaderValue::from_str(value).unwrap(),
+            );
+        }
+        Response {
+            status: StatusCode::from_u16(status).unwrap(),
+            headers: map,
+            body: ResponseBody {
+                content_type: ContentType::Text,
+                data: body.as_bytes().to_vec(),
+            },
+            duration: Default::default(),
+            size_bytes: body.len(),
+        }
+    }
+
+    fn options(headers: &[&str], redact: &[&str]) -> SnapshotOptions {
+        SnapshotOptions {
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            redact: redact.iter().map(|r| r.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn normalizes_responses() {
+        let json = response(
+            201,
+            &[
+                ("Content-Type", "application/json"),
+                ("Set-Cookie", "a=1"),
+                ("Set-Cookie", "b=2"),
+                ("Date", "Mon, 01 Jan 2024 00:00:00 GMT"),
+            ],
+            r#"{"id": "u-1", "items": [{"at": 1, "n": 1}, {"at": 2, "n": 2}]}"#,
+        );
+        let options = options(
+            &["content-type", "Set-Cookie", "Date", "X-Missing"],
+            &["id", "items.*.at", "header.date"],
+        );
+
+        assert_eq!(
+            normalize(&json, &options),
+            json!({
+                "status": 201,
+                "headers": {
+                    "content-type": "application/json",
+                    "set-cookie": "a=1, b=2",
+                    "date": REDACTED,
+                },
+                "body": {
+                    "id": REDACTED,
+                    "items": [{ "at": REDACTED, "n": 1 }, { "at": REDACTED, "n": 2 }],
+                },
+            })
+        );
+
+        // Bodies which are not JSON are kept as text
+        let text = response(200, &[], "plain text");
+        assert_eq!(
+            normalize(&text, &SnapshotOptions::default()),
+            json!({ "status": 200, "headers": {}, "body": "plain text" })
+        );
+    }
+
+    #[test]
+    fn stores_request_snapshots_in_the_collection() {
+        let collection = Path::new("/work/api");
+        assert_eq!(
+            snapshot_path(
+                collection,
+                &collection.join(REQUESTS).join("users/Get user.hcl")
+            ),
+            collection.join(SNAPSHOTS).join("users/Get user.json")
+        );
+    }
+
+    #[tokio::test]
+    async fn creates_matches_and_updates_snapshots() {
+        let dir = TempDir::new().unwrap();
+        let path = dir
+            .path()
+            .join(SNAPSHOTS)
+            .join("users")
+            .join("Get user.json");
+
+        let first = json!({ "status": 200, "headers": {}, "body": { "name": "a" } });
+        let second = json!({ "status": 200, "headers": {}, "body": { "name": "b" } });
+
+        // Snapshots are only written when updating
+        assert_eq!(
+            check(&path, &first, false).await.unwrap(),
+            SnapshotResult::Missing
+        );
+        assert!(!path.exists());
+        assert_eq!(
+            check(&path, &first, true).await.unwrap(),
+            SnapshotResult::Created
+        );
+        assert_eq!(
+            check(&path, &first, false).await.unwrap(),
+            SnapshotResult::Matched
+        );
+
+        // Only the values of the stored file are compared
+        std::fs::write(&path, first.to_string()).unwrap();
+        assert_eq!(
+            check(&path, &first, false).await.unwrap(),
+            SnapshotResult::Matched
+        );
+
+        let SnapshotResult::Mismatch(hunks) = check(&path, &second, false).await.unwrap() else {
+            panic!("expected a mismatch");
+        };
+        let changed = hunks
+            .iter()
+            .flatten()
+            .filter(|line| line.tag != LineTag::Equal)
+            .map(|line| (line.tag, line.text.trim()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changed,
+            [
+                (LineTag::Delete, r#""name": "a""#),
+                (LineTag::Insert, r#""name": "b""#),
+            ]
+        );
+        assert_eq!(
+            check(&path, &first, false).await.unwrap(),
+            SnapshotResult::Matched
+        );
+
+        assert_eq!(
+            check(&path, &second, true).await.unwrap(),
+            SnapshotResult::Updated
+        );
+        assert_eq!(
+            check(&path, &second, false).await.unwrap(),
+            SnapshotResult::Matched
+        );
+    }
+}
@@ -1,14 +1,114 @@
-use std::time::Duration;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
 
+use futures::stream::{self, BoxStream, Stream, StreamExt};
+use mime_guess::Mime;
 use reqwest::{header::HeaderMap, Client, Request, StatusCode};
 
-#[derive(Debug, Clone)]
+use crate::sse::{is_event_stream, SseEvent, SseParser};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Gif,
+    Webp,
+    Bmp,
+    Ico,
+    Svg,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ContentType {
     Json,
+    Xml,
+    Html,
     Text,
+    Image(ImageFormat),
+    Pdf,
     Buffer,
 }
 
+impl ContentType {
+    /// Picks a content type from the `Content-Type` header, falling back to
+    /// sniffing the body when the header is missing or too generic.
+    pub fn detect(header: Option<&str>, data: &[u8]) -> Self {
+        let mime = header.and_then(|h| h.trim().parse::<Mime>().ok());
+
+        match mime.as_ref().map(Self::from_mime) {
+            Some(ContentType::Buffer) | None => Self::sniff(data),
+            Some(ct) => ct,
+        }
+    }
+
+    pub fn from_mime(mime: &Mime) -> Self {
+        let type_ = mime.type_().as_str();
+        let subtype = mime.subtype().as_str();
+        let suffix = mime.suffix().map(|s| s.as_str());
+
+        match (type_, subtype, suffix) {
+            (_, "json", _) | (_, _, Some("json")) => ContentType::Json,
+            ("text", "html", _) | (_, "xhtml", Some("xml")) => ContentType::Html,
+            ("image", sub, _) => match sub {
+                "png" => ContentType::Image(ImageFormat::Png),
+                "jpeg" | "jpg" | "pjpeg" => ContentType::Image(ImageFormat::Jpeg),
+                "gif" => ContentType::Image(ImageFormat::Gif),
+                "webp" => ContentType::Image(ImageFormat::Webp),
+                "bmp" | "x-ms-bmp" => ContentType::Image(ImageFormat::Bmp),
+                "x-icon" | "vnd.microsoft.icon" => ContentType::Image(ImageFormat::Ico),
+                "svg" => ContentType::Image(ImageFormat::Svg),
+                _ => ContentType::Buffer,
+            },
+            (_, "xml", _) | (_, _, Some("xml")) => ContentType::Xml,
+            ("application", "pdf", _) => ContentType::Pdf,
+            ("text", _, _) => ContentType::Text,
+            ("application", "javascript" | "ecmascript" | "x-www-form-urlencoded", _)
+            | ("application", "graphql" | "yaml" | "x-yaml", _) => ContentType::Text,
+            _ => ContentType::Buffer,
+        }
+    }
+
+    /// Guesses the content type from well known magic bytes.
+    pub fn sniff(data: &[u8]) -> Self {
+        const SIGNATURES: &[(&[u8], ContentType)] = &[
+            (b"\x89PNG\r\n\x1a\n", ContentType::Image(ImageFormat::Png)),
+            (b"\xFF\xD8\xFF", ContentType::Image(ImageFormat::Jpeg)),
+            (b"GIF87a", ContentType::Image(ImageFormat::Gif)),
+            (b"GIF89a", ContentType::Image(ImageFormat::Gif)),
+            (b"%PDF-", ContentType::Pdf),
+        ];
+
+        for (magic, ct) in SIGNATURES {
+            if data.starts_with(magic) {
+                return *ct;
+            }
+        }
+        if data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+            return ContentType::Image(ImageFormat::Webp);
+        }
+
+        match std::str::from_utf8(data) {
+            Ok(text) if !text.contains('\0') => {
+                let trimmed = text.trim_start();
+                if (trimmed.starts_with('{') || trimmed.starts_with('['))
+                    && serde_json::from_str::<serde_json::Value>(text).is_ok()
+                {
+                    return ContentType::Json;
+                }
+                ContentType::Text
+            }
+            _ => ContentType::Buffer,
+        }
+    }
+
+    pub fn is_text(&self) -> bool {
+        matches!(
+            self,
+            ContentType::Json | ContentType::Xml | ContentType::Html | ContentType::Text
+        )
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ResponseBody {
     pub content_type: ContentType,
@@ -24,31 +124,20 @@ pub struct Response {
     pub size_bytes: usize,
 }
 
-pub async fn send_request(client: Client, req: Request) -> anyhow::Result<Response> {
-    let start = std::time::Instant::now();
-    let res = client.execute(req).await?;
-    let duration = start.elapsed();
+fn content_type_header(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+}
+
+async fn read_response(res: reqwest::Response, duration: Duration) -> anyhow::Result<Response> {
     let status = res.status();
     let headers = res.headers().clone();
 
-    let content_type = headers
-        .get(reqwest::header::CONTENT_TYPE)
-        .and_then(|v| v.to_str().ok())
-        .unwrap_or_default();
-
-    let body: ResponseBody = match content_type {
-        "application/json" => ResponseBody {
-            content_type: ContentType::Json,
-            data: res.bytes().await?.to_vec(),
-        },
-        "text/plain" => ResponseBody {
-            content_type: ContentType::Text,
-            data: res.bytes().await?.to_vec(),
-        },
-        _ => ResponseBody {
-            content_type: ContentType::Buffer,
-            data: res.bytes().await?.to_vec(),
-        },
+    let data = res.bytes().await?.to_vec();
+    let body = ResponseBody {
+        content_type: ContentType::detect(content_type_header(&headers), &data),
+        data,
     };
 
     let size_bytes = body.data.len();
@@ -62,6 +151,114 @@ pub async fn send_request(client: Client, req: Request) -> anyhow::Result<Respon
     })
 }
 
+pub async fn send_request(client: Client, req: Request) -> anyhow::Result<Response> {
+    let start = Instant::now();
+    let res = client.execute(req).await?;
+    read_response(res, start.elapsed()).await
+}
+
+#[derive(Debug, Clone)]
+pub struct StreamHead {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub duration: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub enum ResponseEvent {
+    /// A regular response, the body has been read completely.
+    Completed(Response),
+    /// The server answered with `text/event-stream`, events follow.
+    StreamOpened(StreamHead),
+    Event(SseEvent),
+    StreamClosed,
+}
+
+enum StreamState {
+    Start(Client, Request),
+    Streaming {
+        body: BoxStream<'static, reqwest::Result<Vec<u8>>>,
+        parser: SseParser,
+        pending: VecDeque<SseEvent>,
+    },
+    Done,
+}
+
+/// Same as [`send_request`] but keeps the connection open for server sent
+/// events, yielding each event as soon as it is received. Non streaming
+/// responses produce a single [`ResponseEvent::Completed`].
+pub fn send_request_streamed(
+    client: Client,
+    req: Request,
+) -> impl Stream<Item = anyhow::Result<ResponseEvent>> + Send + 'static {
+    stream::unfold(StreamState::Start(client, req), |state| async move {
+        match state {
+            StreamState::Start(client, req) => {
+                let start = Instant::now();
+                let res = match client.execute(req).await {
+                    Ok(res) => res,
+                    Err(err) => return Some((Err(err.into()), StreamState::Done)),
+                };
+                let duration = start.elapsed();
+
+                if !is_event_stream(content_type_header(res.headers())) {
+                    let res = read_response(res, duration)
+                        .await
+                        .map(ResponseEvent::Completed);
+                    return Some((res, StreamState::Done));
+                }
+
+                let head = StreamHead {
+                    status: res.status(),
+                    headers: res.headers().clone(),
+                    duration,
+                };
+                let next = StreamState::Streaming {
+                    body: res.bytes_stream().map(|c| c.map(|c| c.to_vec())).boxed(),
+                    parser: SseParser::new(),
+                    pending: Default::default(),
+                };
+                Some((Ok(ResponseEvent::StreamOpened(head)), next))
+            }
+            StreamState::Streaming {
+                mut body,
+                mut parser,
+                mut pending,
+            } => loop {
+                if let Some(event) = pending.pop_front() {
+                    let next = StreamState::Streaming {
+                        body,
+                        parser,
+                        pending,
+                    };
+                    return Some((Ok(ResponseEvent::Event(event)), next));
+                }
+
+                match body.next().await {
+                    Some(Ok(chunk)) => pending.extend(parser.feed(&chunk)),
+                    Some(Err(err)) => return Some((Err(err.into()), StreamState::Done)),
+                    None => {
+                        let event = match parser.finish() {
+                            Some(event) => ResponseEvent::Event(event),
+                            None => ResponseEvent::StreamClosed,
+                        };
+                        let next = match event {
+                            ResponseEvent::Event(_) => StreamState::Streaming {
+                                body: stream::empty().boxed(),
+                                parser,
+                                pending,
+                            },
+                            _ => StreamState::Done,
+                        };
+                        return Some((Ok(event), next));
+                    }
+                }
+            },
+            StreamState::Done => None,
+        }
+    })
+}
+
 pub fn create_client() -> reqwest::Client {
     reqwest::Client::builder()
         .build()
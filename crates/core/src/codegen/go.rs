@@ -0,0 +1,231 @@
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+use crate::http::request::Auth;
+
+use super::{path_str, quoted, Body, Snippet};
+
+/// Raw string literal when possible, it keeps JSON bodies readable. Raw
+/// strings can't hold backticks and drop carriage returns.
+fn string_lit(text: &str) -> String {
+    if text.contains(['`', '\r']) {
+        quoted(text)
+    } else {
+        format!("`{}`", text)
+    }
+}
+
+pub(super) fn render(s: &Snippet) -> String {
+    let mut imports = BTreeSet::from(["fmt", "io", "net/http"]);
+    let mut code = String::new();
+
+    let body = match &s.body {
+        Body::None => "nil",
+        Body::Raw { text, .. } => {
+            imports.insert("strings");
+            let _ = writeln!(code, "\tbody := strings.NewReader({})", string_lit(text));
+            "body"
+        }
+        Body::Form(params) => {
+            imports.insert("net/url");
+            imports.insert("strings");
+            code.push_str("\tform := url.Values{}\n");
+            for (name, value) in params {
+                let _ = writeln!(code, "\tform.Add({}, {})", quoted(name), quoted(value));
+            }
+            code.push_str("\tbody := strings.NewReader(form.Encode())\n");
+            "body"
+        }
+        Body::Multipart { params, files } => {
+            imports.extend(["bytes", "mime/multipart"]);
+            code.push_str("\tbody := &bytes.Buffer{}\n");
+            code.push_str("\twriter := multipart.NewWriter(body)\n");
+            for (name, value) in params {
+                let _ = writeln!(
+                    code,
+                    "\tcheck(writer.WriteField({}, {}))",
+                    quoted(name),
+                    quoted(value)
+                );
+            }
+            for (name, path) in files {
+                imports.extend(["os", "path/filepath"]);
+                let _ = writeln!(code, "\t{{");
+                let _ = writeln!(
+                    code,
+                    "\t\tfile, err := os.Open({})",
+                    quoted(&path_str(path))
+                );
+                code.push_str("\t\tcheck(err)\n");
+                let _ = writeln!(
+                    code,
+                    "\t\tpart, err := writer.CreateFormFile({}, filepath.Base(file.Name()))",
+                    quoted(name)
+                );
+                code.push_str("\t\tcheck(err)\n");
+                code.push_str("\t\t_, err = io.Copy(part, file)\n");
+                code.push_str("\t\tcheck(err)\n");
+                code.push_str("\t\tfile.Close()\n");
+                code.push_str("\t}\n");
+            }
+            code.push_str("\tcheck(writer.Close())\n");
+            "body"
+        }
+        Body::File { path, .. } => {
+            imports.insert("os");
+            let _ = writeln!(code, "\tbody, err := os.Open({})", quoted(&path_str(path)));
+            code.push_str("\tcheck(err)\n");
+            code.push_str("\tdefer body.Close()\n");
+            "body"
+        }
+    };
+    if !code.is_empty() {
+        code.push('\n');
+    }
+
+    let _ = writeln!(
+        code,
+        "\treq, err := http.NewRequest({}, {}, {})",
+        quoted(&s.method.to_string()),
+        quoted(&s.url),
+        body
+    );
+    code.push_str("\tcheck(err)\n");
+
+    for (name, value) in &s.headers {
+        let _ = writeln!(
+            code,
+            "\treq.Header.Add({}, {})",
+            quoted(name),
+            quoted(value)
+        );
+    }
+    match &s.auth {
+        Auth::None => (),
+        Auth::Basic { username, password } => {
+            let _ = writeln!(
+                code,
+                "\treq.SetBasicAuth({}, {})",
+                quoted(username),
+                quoted(password)
+            );
+        }
+        Auth::Bearer { token } => {
+            let value = quoted(&format!("Bearer {}", token));
+            let _ = writeln!(code, "\treq.Header.Set(\"Authorization\", {})", value);
+        }
+    }
+    let content_type = match &s.body {
+        Body::Raw { content_type, .. } => content_type.map(quoted),
+        Body::File { content_type, .. } => content_type.as_deref().map(quoted),
+        Body::Form(_) => Some(quoted("application/x-www-form-urlencoded")),
+        Body::Multipart { .. } => Some("writer.FormDataContentType()".to_string()),
+        Body::None => None,
+    };
+    if let Some(content_type) = content_type {
+        let _ = writeln!(code, "\treq.Header.Set(\"Content-Type\", {})", content_type);
+    }
+
+    code.push_str("\n\tres, err := http.DefaultClient.Do(req)\n");
+    code.push_str("\tcheck(err)\n");
+    code.push_str("\tdefer res.Body.Close()\n\n");
+    code.push_str("\tdata, err := io.ReadAll(res.Body)\n");
+    code.push_str("\tcheck(err)\n");
+    code.push_str("\tfmt.Println(string(data))\n");
+
+    let mut out = String::from("package main\n\nimport (\n");
+    for import in imports {
+        let _ = writeln!(out, "\t\"{}\"", import);
+    }
+    out.push_str(")\n\nfunc main() {\n");
+    out.push_str(&code);
+    out.push_str("}\n\nfunc check(err error) {\n\tif err != nil {\n\t\tpanic(err)\n\t}\n}\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::codegen::tests::*;
+    use crate::codegen::Language;
+    use crate::http::request::{Request, RequestBody};
+
+    fn render(request: Request) -> String {
+        generate(Language::Go, request)
+    }
+
+    #[test]
+    fn renders_json_body() {
+        let code = render(json());
+        assert_eq!(
+            code,
+            r#"package main
+
+import (
+	"fmt"
+	"io"
+	"net/http"
+	"strings"
+)
+
+func main() {
+	body := strings.NewReader(`{"name": "Ann"}`)
+
+	req, err := http.NewRequest("POST", "https://api.example.com/users", body)
+	check(err)
+	req.Header.Set("Content-Type", "application/json")
+
+	res, err := http.DefaultClient.Do(req)
+	check(err)
+	defer res.Body.Close()
+
+	data, err := io.ReadAll(res.Body)
+	check(err)
+	fmt.Println(string(data))
+}
+
+func check(err error) {
+	if err != nil {
+		panic(err)
+	}
+}
+"#
+        );
+    }
+
+    #[test]
+    fn renders_files() {
+        let code = render(multipart());
+        assert!(code.contains("\t\"mime/multipart\"\n"));
+        assert!(code.contains("\tcheck(writer.WriteField(\"title\", \"Profile\"))\n"));
+        assert!(code.contains("\t\tfile, err := os.Open(\"/collection/files/avatar.png\")\n"));
+        assert!(code.contains("writer.CreateFormFile(\"avatar\", filepath.Base(file.Name()))"));
+        assert!(code.contains("\treq.Header.Set(\"Content-Type\", writer.FormDataContentType())\n"));
+
+        let code = render(file());
+        assert!(code.contains("\tbody, err := os.Open(\"/collection/data/users.csv\")\n"));
+        assert!(code.contains("\treq.Header.Set(\"Content-Type\", \"text/csv\")\n"));
+    }
+
+    #[test]
+    fn renders_auth() {
+        assert!(render(basic()).contains("\treq.SetBasicAuth(\"ann\", \"pa55\")\n"));
+        assert!(
+            render(bearer()).contains("\treq.Header.Set(\"Authorization\", \"Bearer t0ken\")\n")
+        );
+        assert!(render(api_key_header()).contains("\treq.Header.Add(\"X-API-Key\", \"k3y\")\n"));
+        assert!(render(api_key_query()).contains(
+            "http.NewRequest(\"GET\", \"https://api.example.com/users?api_key=k3y\", nil)"
+        ));
+    }
+
+    #[test]
+    fn escapes_strings() {
+        let code = render(special());
+        assert!(code.contains(r#"req.Header.Add("X-Note", "it's \"$HOME\" `id`")"#));
+        // Backticks and carriage returns rule out a raw string
+        assert!(code.contains(r#"strings.NewReader("it's \"$HOME\" `id`\r\n")"#));
+
+        let body = RequestBody::Text("$HOME \"x\"\n".to_string());
+        assert!(render(request(body)).contains("strings.NewReader(`$HOME \"x\"\n`)"));
+    }
+}
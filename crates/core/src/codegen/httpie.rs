@@ -0,0 +1,123 @@
+use crate::curl::quote;
+use crate::http::request::Auth;
+
+use super::{path_str, Body, Snippet};
+
+pub(super) fn render(s: &Snippet) -> String {
+    let mut flags = Vec::new();
+    let mut items = Vec::new();
+
+    match &s.auth {
+        Auth::None => (),
+        Auth::Basic { username, password } => {
+            flags.push(format!(
+                "--auth {}",
+                quote(&format!("{}:{}", username, password))
+            ));
+        }
+        Auth::Bearer { token } => {
+            flags.push("--auth-type bearer".to_string());
+            flags.push(format!("--auth {}", quote(token)));
+        }
+    }
+
+    for (name, value) in &s.headers {
+        items.push(quote(&format!("{}:{}", name, value)));
+    }
+
+    let mut stdin = None;
+    match &s.body {
+        Body::None => (),
+        Body::Raw { content_type, text } => {
+            if let Some(content_type) = content_type {
+                items.push(quote(&format!("Content-Type:{}", content_type)));
+            }
+            flags.push(format!("--raw {}", quote(text)));
+        }
+        Body::Form(params) => {
+            flags.push("--form".to_string());
+            for (name, value) in params {
+                items.push(quote(&format!("{}={}", name, value)));
+            }
+        }
+        Body::Multipart { params, files } => {
+            flags.push("--multipart".to_string());
+            for (name, value) in params {
+                items.push(quote(&format!("{}={}", name, value)));
+            }
+            for (name, path) in files {
+                items.push(quote(&format!("{}@{}", name, path_str(path))));
+            }
+        }
+        Body::File { path, content_type } => {
+            if let Some(content_type) = content_type {
+                items.push(quote(&format!("Content-Type:{}", content_type)));
+            }
+            stdin = Some(format!("< {}", quote(&path_str(path))));
+        }
+    }
+
+    let mut command = vec![format!("http {}", flags.join(" ")).trim_end().to_string()];
+    command.push(format!("{} {}", s.method, quote(&s.url)));
+    command.extend(items);
+    command.extend(stdin);
+
+    command.join(" \\\n  ")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::codegen::tests::*;
+    use crate::codegen::Language;
+    use crate::http::request::Request;
+
+    fn render(request: Request) -> String {
+        generate(Language::HTTPie, request)
+    }
+
+    #[test]
+    fn renders_json_body() {
+        assert_eq!(
+            render(json()),
+            "http --raw '{\"name\": \"Ann\"}' \\\n  \
+             POST https://api.example.com/users \\\n  \
+             Content-Type:application/json"
+        );
+    }
+
+    #[test]
+    fn renders_files() {
+        assert_eq!(
+            render(multipart()),
+            "http --multipart \\\n  \
+             POST https://api.example.com/users \\\n  \
+             title=Profile \\\n  \
+             avatar@/collection/files/avatar.png"
+        );
+        assert_eq!(
+            render(file()),
+            "http \\\n  \
+             POST https://api.example.com/users \\\n  \
+             Content-Type:text/csv \\\n  \
+             < /collection/data/users.csv"
+        );
+    }
+
+    #[test]
+    fn renders_auth() {
+        assert!(render(basic()).starts_with("http --auth ann:pa55 \\\n"));
+        assert!(render(bearer()).starts_with("http --auth-type bearer --auth t0ken \\\n"));
+        assert!(render(api_key_header()).ends_with("\\\n  X-API-Key:k3y"));
+        assert!(
+            render(api_key_query()).ends_with("GET 'https://api.example.com/users?api_key=k3y'")
+        );
+    }
+
+    #[test]
+    fn escapes_strings() {
+        let code = render(special());
+        // Single quotes keep `$` and backticks literal
+        assert!(code.starts_with("http --raw 'it'\\''s \"$HOME\" `id`\r\n' \\\n"));
+        assert!(code.contains("'X-Note:it'\\''s \"$HOME\" `id`'"));
+    }
+}
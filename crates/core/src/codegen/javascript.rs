@@ -0,0 +1,170 @@
+use std::fmt::Write;
+
+use crate::http::request::{Auth, Method};
+
+use super::{file_name, path_str, quoted, Body, Snippet};
+
+pub(super) fn render(s: &Snippet) -> String {
+    let mut code = String::new();
+
+    let reads_files = match &s.body {
+        Body::Multipart { files, .. } => !files.is_empty(),
+        Body::File { .. } => true,
+        _ => false,
+    };
+    if reads_files {
+        code.push_str("import { openAsBlob } from \"node:fs\";\n\n");
+    }
+
+    let body = match &s.body {
+        Body::None => None,
+        Body::Raw { text, .. } => Some(quoted(text)),
+        Body::Form(params) => {
+            code.push_str("const body = new URLSearchParams();\n");
+            for (name, value) in params {
+                let _ = writeln!(code, "body.append({}, {});", quoted(name), quoted(value));
+            }
+            code.push('\n');
+            Some("body".to_string())
+        }
+        Body::Multipart { params, files } => {
+            code.push_str("const body = new FormData();\n");
+            for (name, value) in params {
+                let _ = writeln!(code, "body.append({}, {});", quoted(name), quoted(value));
+            }
+            for (name, path) in files {
+                let _ = writeln!(
+                    code,
+                    "body.append({}, await openAsBlob({}), {});",
+                    quoted(name),
+                    quoted(&path_str(path)),
+                    quoted(&file_name(path))
+                );
+            }
+            code.push('\n');
+            Some("body".to_string())
+        }
+        Body::File { path, .. } => Some(format!("await openAsBlob({})", quoted(&path_str(path)))),
+    };
+
+    // Header values are JavaScript expressions
+    let mut headers = s
+        .headers
+        .iter()
+        .map(|(name, value)| (name.clone(), quoted(value)))
+        .collect::<Vec<_>>();
+    match &s.auth {
+        Auth::None => (),
+        Auth::Basic { username, password } => {
+            let credentials = quoted(&format!("{}:{}", username, password));
+            headers.push((
+                "Authorization".to_string(),
+                format!("\"Basic \" + btoa({})", credentials),
+            ));
+        }
+        Auth::Bearer { token } => {
+            let value = quoted(&format!("Bearer {}", token));
+            headers.push(("Authorization".to_string(), value));
+        }
+    }
+    match &s.body {
+        Body::Raw {
+            content_type: Some(content_type),
+            ..
+        } => headers.push(("Content-Type".to_string(), quoted(content_type))),
+        Body::File {
+            content_type: Some(content_type),
+            ..
+        } => headers.push(("Content-Type".to_string(), quoted(content_type))),
+        _ => (),
+    }
+
+    let mut options = String::new();
+    if s.method != Method::GET {
+        let _ = writeln!(options, "  method: {},", quoted(&s.method.to_string()));
+    }
+    if !headers.is_empty() {
+        options.push_str("  headers: {\n");
+        for (name, value) in &headers {
+            let _ = writeln!(options, "    {}: {},", quoted(name), value);
+        }
+        options.push_str("  },\n");
+    }
+    if let Some(body) = body {
+        let _ = writeln!(options, "  body: {},", body);
+    }
+
+    let url = quoted(&s.url);
+    if options.is_empty() {
+        let _ = writeln!(code, "const response = await fetch({});\n", url);
+    } else {
+        let _ = writeln!(code, "const response = await fetch({}, {{", url);
+        code.push_str(&options);
+        code.push_str("});\n\n");
+    }
+    code.push_str("console.log(await response.text());\n");
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::codegen::tests::*;
+    use crate::codegen::Language;
+    use crate::http::request::Request;
+
+    fn render(request: Request) -> String {
+        generate(Language::JavaScript, request)
+    }
+
+    #[test]
+    fn renders_json_body() {
+        let code = render(json());
+        assert_eq!(
+            code,
+            r#"const response = await fetch("https://api.example.com/users", {
+  method: "POST",
+  headers: {
+    "Content-Type": "application/json",
+  },
+  body: "{\"name\": \"Ann\"}",
+});
+
+console.log(await response.text());
+"#
+        );
+    }
+
+    #[test]
+    fn renders_files() {
+        let code = render(multipart());
+        assert!(code.starts_with("import { openAsBlob } from \"node:fs\";\n"));
+        assert!(code.contains("body.append(\"title\", \"Profile\");"));
+        assert!(code.contains(
+            "body.append(\"avatar\", await openAsBlob(\"/collection/files/avatar.png\"), \"avatar.png\");"
+        ));
+
+        let code = render(file());
+        assert!(code.contains("\"Content-Type\": \"text/csv\","));
+        assert!(code.contains("body: await openAsBlob(\"/collection/data/users.csv\"),"));
+    }
+
+    #[test]
+    fn renders_auth() {
+        assert!(render(basic()).contains("\"Authorization\": \"Basic \" + btoa(\"ann:pa55\"),"));
+        assert!(render(bearer()).contains("\"Authorization\": \"Bearer t0ken\","));
+        assert!(render(api_key_header()).contains("\"X-API-Key\": \"k3y\","));
+        // Nothing to set besides the URL
+        assert_eq!(
+            render(api_key_query()),
+            "const response = await fetch(\"https://api.example.com/users?api_key=k3y\");\n\n\
+             console.log(await response.text());\n"
+        );
+    }
+
+    #[test]
+    fn escapes_strings() {
+        let code = render(special());
+        assert!(code.contains(r#""X-Note": "it's \"$HOME\" `id`","#));
+        assert!(code.contains(r#"body: "it's \"$HOME\" `id`\r\n","#));
+    }
+}
@@ -0,0 +1,375 @@
+use std::path::{Path, PathBuf};
+
+use strum::{Display, EnumString, VariantArray};
+
+use crate::curl;
+use crate::graphql;
+use crate::http::environment::Environment;
+use crate::http::request::{Auth, Method, Request, RequestBody};
+use crate::http::{KeyFile, KeyFileList, KeyValList};
+use crate::template::render_lenient;
+use crate::transformers::request::{full_url, param_enabled};
+
+mod go;
+mod httpie;
+mod javascript;
+mod powershell;
+mod python;
+mod rust;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, EnumString, Display, VariantArray)]
+#[strum(ascii_case_insensitive)]
+pub enum Language {
+    #[default]
+    #[strum(to_string = "cURL", serialize = "curl")]
+    Curl,
+    #[strum(to_string = "Rust reqwest", serialize = "rust")]
+    Rust,
+    #[strum(to_string = "Python requests", serialize = "python")]
+    Python,
+    #[strum(
+        to_string = "JavaScript fetch",
+        serialize = "javascript",
+        serialize = "js"
+    )]
+    JavaScript,
+    #[strum(to_string = "Go net/http", serialize = "go")]
+    Go,
+    #[strum(to_string = "HTTPie", serialize = "httpie")]
+    HTTPie,
+    #[strum(to_string = "PowerShell", serialize = "powershell", serialize = "pwsh")]
+    PowerShell,
+}
+
+/// Renders the request as code for the language. Without an environment
+/// the `{{vars}}` are kept as they are, and so are secret variables.
+/// Relative file paths are resolved against the collection `root`.
+pub fn generate(
+    language: Language,
+    mut request: Request,
+    root: &Path,
+    env: Option<&Environment>,
+) -> String {
+    resolve_files(&mut request.body, root, env);
+
+    let code = match language {
+        Language::Curl => curl::to_curl(request, env),
+        Language::Rust => rust::render(&Snippet::new(request, env)),
+        Language::Python => python::render(&Snippet::new(request, env)),
+        Language::JavaScript => javascript::render(&Snippet::new(request, env)),
+        Language::Go => go::render(&Snippet::new(request, env)),
+        Language::HTTPie => httpie::render(&Snippet::new(request, env)),
+        Language::PowerShell => powershell::render(&Snippet::new(request, env)),
+    };
+    match env {
+        Some(env) => env.redact(&code),
+        None => code,
+    }
+}
+
+/// Renders the `{{vars}}` of the file paths and joins them to the root the
+/// way requests are sent, so snippets work from any directory
+fn resolve_files(body: &mut RequestBody, root: &Path, env: Option<&Environment>) {
+    let env = env.map(Environment::without_secrets);
+    let resolve = |path: &Path| root.join(render_lenient(&path.to_string_lossy(), env.as_ref()));
+
+    match body {
+        RequestBody::Multipart { files, .. } => {
+            let resolved = std::mem::take(files)
+                .into_iter()
+                .map(|file| KeyFile {
+                    path: file.path.as_deref().map(resolve),
+                    ..file
+                })
+                .collect();
+            *files = KeyFileList::from(resolved);
+        }
+        RequestBody::File(Some(path)) => *path = resolve(path),
+        _ => (),
+    }
+}
+
+/// Request with params and variables resolved, the targets only deal with
+/// how to express it.
+struct Snippet {
+    method: Method,
+    url: String,
+    headers: Vec<(String, String)>,
+    auth: Auth,
+    body: Body,
+}
+
+enum Body {
+    None,
+    /// Content type is left out when a header already sets it
+    Raw {
+        content_type: Option<&'static str>,
+        text: String,
+    },
+    Form(Vec<(String, String)>),
+    Multipart {
+        params: Vec<(String, String)>,
+        files: Vec<(String, PathBuf)>,
+    },
+    File {
+        path: PathBuf,
+        content_type: Option<String>,
+    },
+}
+
+fn pairs(list: KeyValList, env: Option<&Environment>) -> Vec<(String, String)> {
+    list.into_iter()
+        .filter(param_enabled)
+        .map(|kv| (kv.name, render_lenient(&kv.value, env)))
+        .collect()
+}
+
+impl Snippet {
+    fn new(request: Request, env: Option<&Environment>) -> Self {
+        let Request {
+            method,
+            url,
+            headers,
+            body,
+            query_params,
+            path_params,
+            auth,
+            ..
+        } = request;
+        // Secrets stay as `{{vars}}` in snippets meant to be shared
+        let env = env.map(Environment::without_secrets);
+        let env = env.as_ref();
+
+        let url = full_url(url, path_params, query_params, env);
+        let headers = pairs(headers, env);
+        let has_content_type = headers
+            .iter()
+            .any(|(name, _)| name.eq_ignore_ascii_case("content-type"));
+
+        let raw = |content_type, text: &str| Body::Raw {
+            content_type: (!has_content_type).then_some(content_type),
+            text: render_lenient(text, env),
+        };
+        let body = match body {
+            RequestBody::Json(json) => raw("application/json", &json),
+            RequestBody::XML(xml) => raw("text/xml", &xml),
+            RequestBody::Text(text) => raw("text/plain", &text),
+            RequestBody::GraphQL {
+                query,
+                variables,
+                operation_name,
+            } => {
+                let variables = render_lenient(&variables, env);
+                let payload = graphql::payload(&query, &variables, operation_name.as_deref())
+                    .map(|p| p.to_string())
+                    .unwrap_or_default();
+                raw("application/json", &payload)
+            }
+            RequestBody::Form(form) => Body::Form(pairs(form, env)),
+            RequestBody::Multipart { params, files } => Body::Multipart {
+                params: pairs(params, env),
+                files: files
+                    .into_iter()
+                    .filter(|file| !file.disabled && !file.name.is_empty())
+                    .filter_map(|file| file.path.map(|path| (file.name, path)))
+                    .collect(),
+            },
+            RequestBody::File(Some(path)) => Body::File {
+                content_type: (!has_content_type).then(|| {
+                    mime_guess::from_path(&path)
+                        .first_or_octet_stream()
+                        .to_string()
+                }),
+                path,
+            },
+            RequestBody::File(None) | RequestBody::None => Body::None,
+        };
+
+        let auth = match auth {
+            Auth::None => Auth::None,
+            Auth::Basic { username, password } => Auth::Basic {
+                username: render_lenient(&username, env),
+                password: render_lenient(&password, env),
+            },
+            Auth::Bearer { token } => Auth::Bearer {
+                token: render_lenient(&token, env),
+            },
+        };
+
+        Self {
+            method,
+            url,
+            headers,
+            auth,
+            body,
+        }
+    }
+}
+
+/// Double quoted string literal, the JSON escapes are valid in most languages
+fn quoted(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+fn path_str(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::http::environment::REDACTED;
+    use crate::http::KeyValue;
+
+    use super::*;
+
+    pub(super) const ROOT: &str = "/collection";
+
+    pub(super) fn kv(name: &str, value: &str) -> KeyValue {
+        KeyValue {
+            disabled: false,
+            name: name.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    pub(super) fn request(body: RequestBody) -> Request {
+        Request {
+            method: Method::POST,
+            url: "https://api.example.com/users".to_string(),
+            body,
+            ..Default::default()
+        }
+    }
+
+    pub(super) fn json() -> Request {
+        request(RequestBody::Json(r#"{"name": "Ann"}"#.to_string()))
+    }
+
+    pub(super) fn multipart() -> Request {
+        request(RequestBody::Multipart {
+            params: KeyValList::from(vec![kv("title", "Profile")]),
+            files: KeyFileList::from(vec![KeyFile {
+                name: "avatar".to_string(),
+                path: Some(PathBuf::from("files/avatar.png")),
+                disabled: false,
+            }]),
+        })
+    }
+
+    pub(super) fn file() -> Request {
+        request(RequestBody::File(Some(PathBuf::from("data/users.csv"))))
+    }
+
+    pub(super) fn with_auth(auth: Auth) -> Request {
+        Request {
+            method: Method::GET,
+            auth,
+            ..request(RequestBody::None)
+        }
+    }
+
+    pub(super) fn basic() -> Request {
+        with_auth(Auth::Basic {
+            username: "ann".to_string(),
+            password: "pa55".to_string(),
+        })
+    }
+
+    pub(super) fn bearer() -> Request {
+        with_auth(Auth::Bearer {
+            token: "t0ken".to_string(),
+        })
+    }
+
+    /// API keys are sent as plain headers or query params
+    pub(super) fn api_key_header() -> Request {
+        Request {
+            headers: KeyValList::from(vec![kv("X-API-Key", "k3y")]),
+            ..with_auth(Auth::None)
+        }
+    }
+
+    pub(super) fn api_key_query() -> Request {
+        Request {
+            query_params: KeyValList::from(vec![kv("api_key", "k3y")]),
+            ..with_auth(Auth::None)
+        }
+    }
+
+    /// Text needing escapes in shells and string literals
+    pub(super) fn special() -> Request {
+        Request {
+            headers: KeyValList::from(vec![kv("X-Note", r#"it's "$HOME" `id`"#)]),
+            ..request(RequestBody::Text("it's \"$HOME\" `id`\r\n".to_string()))
+        }
+    }
+
+    pub(super) fn generate(language: Language, request: Request) -> String {
+        super::generate(language, request, Path::new(ROOT), None)
+    }
+
+    fn env() -> Environment {
+        let mut env = Environment::new("dev".to_string());
+        env.variables = KeyValList::from(vec![kv("dir", "uploads"), kv("token", "s3cret")]);
+        env.secrets.insert("token".to_string());
+        env
+    }
+
+    #[test]
+    fn resolves_file_paths_against_root() {
+        let mut req = multipart();
+        if let RequestBody::Multipart { files, .. } = &mut req.body {
+            *files = KeyFileList::from(vec![
+                KeyFile {
+                    name: "a".to_string(),
+                    path: Some(PathBuf::from("{{dir}}/a.png")),
+                    disabled: false,
+                },
+                KeyFile {
+                    name: "b".to_string(),
+                    path: Some(PathBuf::from("/tmp/b.png")),
+                    disabled: false,
+                },
+            ]);
+        }
+
+        let code = super::generate(Language::Curl, req.clone(), Path::new(ROOT), Some(&env()));
+        assert!(code.contains("-F a=@/collection/uploads/a.png"), "{}", code);
+        assert!(code.contains("-F b=@/tmp/b.png"), "{}", code);
+
+        // Variables are kept without an environment, the root is still added
+        let code = generate(Language::Curl, req);
+        assert!(
+            code.contains("-F 'a=@/collection/{{dir}}/a.png'"),
+            "{}",
+            code
+        );
+
+        let req = request(RequestBody::File(Some(PathBuf::from("{{dir}}/users.csv"))));
+        let code = super::generate(Language::Python, req, Path::new(ROOT), Some(&env()));
+        assert!(code.contains(r#"open("/collection/uploads/users.csv", "rb")"#));
+    }
+
+    #[test]
+    fn keeps_secrets_out_of_snippets() {
+        let req = Request {
+            headers: KeyValList::from(vec![kv("X-Token", "s3cret")]),
+            ..with_auth(Auth::Bearer {
+                token: "{{token}}".to_string(),
+            })
+        };
+
+        for language in Language::VARIANTS {
+            let code = super::generate(*language, req.clone(), Path::new(ROOT), Some(&env()));
+            assert!(code.contains("{{token}}"), "{}", code);
+            assert!(!code.contains("s3cret"), "{}", code);
+            assert!(code.contains(REDACTED), "{}", code);
+        }
+    }
+}
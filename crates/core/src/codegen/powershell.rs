@@ -0,0 +1,186 @@
+use std::fmt::Write;
+
+use crate::http::request::{Auth, Method};
+
+use super::{path_str, Body, Snippet};
+
+/// Single quoted strings are literal, quotes are escaped by doubling them.
+/// PowerShell takes the typographic single quotes as quotes too.
+fn quoted(value: &str) -> String {
+    let mut quoted = String::from("'");
+    for c in value.chars() {
+        if matches!(c, '\'' | '\u{2018}'..='\u{201B}') {
+            quoted.push(c);
+        }
+        quoted.push(c);
+    }
+    quoted.push('\'');
+    quoted
+}
+
+fn hashtable(code: &mut String, indent: &str, entries: &[(String, String)]) {
+    code.push_str("@{\n");
+    for (name, value) in entries {
+        let _ = writeln!(code, "{}    {} = {}", indent, quoted(name), value);
+    }
+    let _ = write!(code, "{}}}", indent);
+}
+
+pub(super) fn render(s: &Snippet) -> String {
+    let mut params = vec![("Uri".to_string(), quoted(&s.url))];
+
+    // Invoke-WebRequest only knows the common methods
+    match s.method {
+        Method::CONNECT => params.push(("CustomMethod".to_string(), quoted("CONNECT"))),
+        method => params.push(("Method".to_string(), quoted(&method.to_string()))),
+    }
+
+    let mut headers = s
+        .headers
+        .iter()
+        .map(|(name, value)| (name.clone(), quoted(value)))
+        .collect::<Vec<_>>();
+    match &s.auth {
+        Auth::None => (),
+        Auth::Basic { username, password } => {
+            let credentials = quoted(&format!("{}:{}", username, password));
+            headers.push((
+                "Authorization".to_string(),
+                format!(
+                    "'Basic ' + [Convert]::ToBase64String([Text.Encoding]::UTF8.GetBytes({}))",
+                    credentials
+                ),
+            ));
+        }
+        Auth::Bearer { token } => {
+            headers.push((
+                "Authorization".to_string(),
+                quoted(&format!("Bearer {}", token)),
+            ));
+        }
+    }
+
+    let mut code = String::new();
+    if !headers.is_empty() {
+        let mut table = String::new();
+        hashtable(&mut table, "    ", &headers);
+        params.push(("Headers".to_string(), table));
+    }
+
+    match &s.body {
+        Body::None => (),
+        Body::Raw { content_type, text } => {
+            if let Some(content_type) = content_type {
+                params.push(("ContentType".to_string(), quoted(content_type)));
+            }
+            params.push(("Body".to_string(), quoted(text)));
+        }
+        Body::Form(form) => {
+            let form = form
+                .iter()
+                .map(|(name, value)| (name.clone(), quoted(value)))
+                .collect::<Vec<_>>();
+            let mut table = String::new();
+            hashtable(&mut table, "    ", &form);
+            params.push((
+                "ContentType".to_string(),
+                quoted("application/x-www-form-urlencoded"),
+            ));
+            params.push(("Body".to_string(), table));
+        }
+        Body::Multipart {
+            params: fields,
+            files,
+        } => {
+            let mut form = fields
+                .iter()
+                .map(|(name, value)| (name.clone(), quoted(value)))
+                .collect::<Vec<_>>();
+            for (name, path) in files {
+                let item = format!("(Get-Item -Path {})", quoted(&path_str(path)));
+                form.push((name.clone(), item));
+            }
+            let mut table = String::new();
+            hashtable(&mut table, "    ", &form);
+            params.push(("Form".to_string(), table));
+        }
+        Body::File { path, content_type } => {
+            if let Some(content_type) = content_type {
+                params.push(("ContentType".to_string(), quoted(content_type)));
+            }
+            params.push(("InFile".to_string(), quoted(&path_str(path))));
+        }
+    }
+
+    code.push_str("$params = @{\n");
+    for (name, value) in &params {
+        let _ = writeln!(code, "    {} = {}", name, value);
+    }
+    code.push_str("}\n");
+    code.push_str("$response = Invoke-WebRequest @params\n");
+    code.push_str("$response.Content\n");
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::codegen::tests::*;
+    use crate::codegen::Language;
+    use crate::http::request::{Request, RequestBody};
+
+    fn render(request: Request) -> String {
+        generate(Language::PowerShell, request)
+    }
+
+    #[test]
+    fn renders_json_body() {
+        assert_eq!(
+            render(json()),
+            r#"$params = @{
+    Uri = 'https://api.example.com/users'
+    Method = 'POST'
+    ContentType = 'application/json'
+    Body = '{"name": "Ann"}'
+}
+$response = Invoke-WebRequest @params
+$response.Content
+"#
+        );
+    }
+
+    #[test]
+    fn renders_files() {
+        let code = render(multipart());
+        assert!(code.contains(
+            "    Form = @{\n        'title' = 'Profile'\n        \
+             'avatar' = (Get-Item -Path '/collection/files/avatar.png')\n    }\n"
+        ));
+
+        let code = render(file());
+        assert!(code.contains("    ContentType = 'text/csv'\n"));
+        assert!(code.contains("    InFile = '/collection/data/users.csv'\n"));
+    }
+
+    #[test]
+    fn renders_auth() {
+        assert!(render(basic()).contains(
+            "'Authorization' = 'Basic ' + [Convert]::ToBase64String([Text.Encoding]::UTF8.GetBytes('ann:pa55'))"
+        ));
+        assert!(render(bearer()).contains("'Authorization' = 'Bearer t0ken'"));
+        assert!(render(api_key_header()).contains("'X-API-Key' = 'k3y'"));
+        assert!(
+            render(api_key_query()).contains("Uri = 'https://api.example.com/users?api_key=k3y'")
+        );
+    }
+
+    #[test]
+    fn escapes_strings() {
+        let code = render(special());
+        // Single quoted strings keep `$` and backticks literal
+        assert!(code.contains("'X-Note' = 'it''s \"$HOME\" `id`'"));
+        assert!(code.contains("Body = 'it''s \"$HOME\" `id`\r\n'"));
+
+        let body = RequestBody::Text("it\u{2019}s".to_string());
+        assert!(render(request(body)).contains("Body = 'it\u{2019}\u{2019}s'"));
+    }
+}
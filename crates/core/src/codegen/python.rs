@@ -0,0 +1,145 @@
+use std::fmt::Write;
+
+use crate::http::request::Auth;
+
+use super::{path_str, quoted, Body, Snippet};
+
+fn tuples(code: &mut String, var: &str, params: &[(String, String)]) {
+    let _ = writeln!(code, "{} = [", var);
+    for (name, value) in params {
+        let _ = writeln!(code, "    ({}, {}),", quoted(name), quoted(value));
+    }
+    code.push_str("]\n");
+}
+
+pub(super) fn render(s: &Snippet) -> String {
+    let mut code = String::from("import requests\n\n");
+    let mut args = vec![quoted(&s.method.to_string()), "url".to_string()];
+
+    let _ = writeln!(code, "url = {}", quoted(&s.url));
+
+    let mut headers = s.headers.clone();
+    if let Auth::Bearer { token } = &s.auth {
+        headers.push(("Authorization".to_string(), format!("Bearer {}", token)));
+    }
+    match &s.body {
+        Body::Raw {
+            content_type: Some(content_type),
+            ..
+        } => headers.push(("Content-Type".to_string(), content_type.to_string())),
+        Body::File {
+            content_type: Some(content_type),
+            ..
+        } => headers.push(("Content-Type".to_string(), content_type.clone())),
+        _ => (),
+    }
+    if !headers.is_empty() {
+        code.push_str("headers = {\n");
+        for (name, value) in &headers {
+            let _ = writeln!(code, "    {}: {},", quoted(name), quoted(value));
+        }
+        code.push_str("}\n");
+        args.push("headers=headers".to_string());
+    }
+
+    match &s.body {
+        Body::None => (),
+        Body::Raw { text, .. } => {
+            let _ = writeln!(code, "data = {}", quoted(text));
+            args.push("data=data.encode()".to_string());
+        }
+        Body::Form(params) => {
+            tuples(&mut code, "data", params);
+            args.push("data=data".to_string());
+        }
+        Body::Multipart { params, files } => {
+            if !params.is_empty() {
+                tuples(&mut code, "data", params);
+                args.push("data=data".to_string());
+            }
+            code.push_str("files = [\n");
+            for (name, path) in files {
+                let path = quoted(&path_str(path));
+                let _ = writeln!(code, "    ({}, open({}, \"rb\")),", quoted(name), path);
+            }
+            code.push_str("]\n");
+            args.push("files=files".to_string());
+        }
+        Body::File { path, .. } => {
+            let _ = writeln!(code, "data = open({}, \"rb\")", quoted(&path_str(path)));
+            args.push("data=data".to_string());
+        }
+    }
+
+    if let Auth::Basic { username, password } = &s.auth {
+        args.push(format!("auth=({}, {})", quoted(username), quoted(password)));
+    }
+
+    let _ = writeln!(code, "\nresponse = requests.request({})\n", args.join(", "));
+    code.push_str("print(response.text)\n");
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::codegen::tests::*;
+    use crate::codegen::Language;
+    use crate::http::request::Request;
+
+    fn render(request: Request) -> String {
+        generate(Language::Python, request)
+    }
+
+    #[test]
+    fn renders_json_body() {
+        let code = render(json());
+        assert_eq!(
+            code,
+            r#"import requests
+
+url = "https://api.example.com/users"
+headers = {
+    "Content-Type": "application/json",
+}
+data = "{\"name\": \"Ann\"}"
+
+response = requests.request("POST", url, headers=headers, data=data.encode())
+
+print(response.text)
+"#
+        );
+    }
+
+    #[test]
+    fn renders_files() {
+        let code = render(multipart());
+        assert!(code.contains("data = [\n    (\"title\", \"Profile\"),\n]"));
+        assert!(code.contains(
+            "files = [\n    (\"avatar\", open(\"/collection/files/avatar.png\", \"rb\")),\n]"
+        ));
+        assert!(code.contains("requests.request(\"POST\", url, data=data, files=files)"));
+
+        let code = render(file());
+        assert!(code.contains("\"Content-Type\": \"text/csv\","));
+        assert!(code.contains("data = open(\"/collection/data/users.csv\", \"rb\")"));
+    }
+
+    #[test]
+    fn renders_auth() {
+        assert!(
+            render(basic()).contains("requests.request(\"GET\", url, auth=(\"ann\", \"pa55\"))")
+        );
+        assert!(render(bearer()).contains("\"Authorization\": \"Bearer t0ken\","));
+        assert!(render(api_key_header()).contains("\"X-API-Key\": \"k3y\","));
+        assert!(
+            render(api_key_query()).contains("url = \"https://api.example.com/users?api_key=k3y\"")
+        );
+    }
+
+    #[test]
+    fn escapes_strings() {
+        let code = render(special());
+        assert!(code.contains(r#""X-Note": "it's \"$HOME\" `id`","#));
+        assert!(code.contains(r#"data = "it's \"$HOME\" `id`\r\n""#));
+    }
+}
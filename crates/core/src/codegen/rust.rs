@@ -0,0 +1,181 @@
+use std::fmt::Write;
+
+use crate::http::request::Auth;
+
+use super::{file_name, path_str, Body, Snippet};
+
+/// Raw string literal with enough `#` to contain the text. Raw strings
+/// can't hold a bare carriage return.
+fn raw_str(text: &str) -> String {
+    if text.contains('\r') {
+        return format!("{:?}", text);
+    }
+    let mut hashes = 1;
+    while text.contains(&format!("\"{}", "#".repeat(hashes))) {
+        hashes += 1;
+    }
+    let hashes = "#".repeat(hashes);
+    format!("r{}\"{}\"{}", hashes, text, hashes)
+}
+
+pub(super) fn render(s: &Snippet) -> String {
+    let mut code = String::new();
+
+    if let Body::Multipart { .. } = s.body {
+        code.push_str("use reqwest::multipart;\n\n");
+    }
+    code.push_str("#[tokio::main]\n");
+    code.push_str("async fn main() -> Result<(), Box<dyn std::error::Error>> {\n");
+    code.push_str("    let client = reqwest::Client::new();\n\n");
+
+    if let Body::Multipart { params, files } = &s.body {
+        code.push_str("    let form = multipart::Form::new()");
+        for (name, value) in params {
+            let _ = write!(code, "\n        .text({:?}, {:?})", name, value);
+        }
+        for (name, path) in files {
+            let _ = write!(
+                code,
+                "\n        .part(\n            {:?},\n            multipart::Part::bytes(std::fs::read({:?})?).file_name({:?}),\n        )",
+                name,
+                path_str(path),
+                file_name(path)
+            );
+        }
+        code.push_str(";\n\n");
+    }
+
+    let _ = write!(
+        code,
+        "    let response = client\n        .request(reqwest::Method::{}, {:?})",
+        s.method, s.url
+    );
+    for (name, value) in &s.headers {
+        let _ = write!(code, "\n        .header({:?}, {:?})", name, value);
+    }
+
+    match &s.auth {
+        Auth::None => (),
+        Auth::Basic { username, password } => {
+            let _ = write!(
+                code,
+                "\n        .basic_auth({:?}, Some({:?}))",
+                username, password
+            );
+        }
+        Auth::Bearer { token } => {
+            let _ = write!(code, "\n        .bearer_auth({:?})", token);
+        }
+    }
+
+    match &s.body {
+        Body::None => (),
+        Body::Raw { content_type, text } => {
+            if let Some(content_type) = content_type {
+                let _ = write!(
+                    code,
+                    "\n        .header(\"Content-Type\", {:?})",
+                    content_type
+                );
+            }
+            let _ = write!(code, "\n        .body({})", raw_str(text));
+        }
+        Body::Form(params) => {
+            code.push_str("\n        .form(&[");
+            for (name, value) in params {
+                let _ = write!(code, "\n            ({:?}, {:?}),", name, value);
+            }
+            code.push_str("\n        ])");
+        }
+        Body::Multipart { .. } => code.push_str("\n        .multipart(form)"),
+        Body::File { path, content_type } => {
+            if let Some(content_type) = content_type {
+                let _ = write!(
+                    code,
+                    "\n        .header(\"Content-Type\", {:?})",
+                    content_type
+                );
+            }
+            let _ = write!(
+                code,
+                "\n        .body(std::fs::read({:?})?)",
+                path_str(path)
+            );
+        }
+    }
+
+    code.push_str("\n        .send()\n        .await?;\n\n");
+    code.push_str("    println!(\"{}\", response.text().await?);\n");
+    code.push_str("    Ok(())\n}\n");
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::codegen::tests::*;
+    use crate::codegen::Language;
+    use crate::http::request::{Request, RequestBody};
+
+    fn render(request: Request) -> String {
+        generate(Language::Rust, request)
+    }
+
+    #[test]
+    fn renders_json_body() {
+        let code = render(json());
+        assert_eq!(
+            code,
+            r##"#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let client = reqwest::Client::new();
+
+    let response = client
+        .request(reqwest::Method::POST, "https://api.example.com/users")
+        .header("Content-Type", "application/json")
+        .body(r#"{"name": "Ann"}"#)
+        .send()
+        .await?;
+
+    println!("{}", response.text().await?);
+    Ok(())
+}
+"##
+        );
+    }
+
+    #[test]
+    fn renders_files() {
+        let code = render(multipart());
+        assert!(code.starts_with("use reqwest::multipart;\n"));
+        assert!(code.contains(".text(\"title\", \"Profile\")"));
+        assert!(code.contains(
+            "multipart::Part::bytes(std::fs::read(\"/collection/files/avatar.png\")?).file_name(\"avatar.png\")"
+        ));
+        assert!(code.contains(".multipart(form)"));
+
+        let code = render(file());
+        assert!(code.contains(".header(\"Content-Type\", \"text/csv\")"));
+        assert!(code.contains(".body(std::fs::read(\"/collection/data/users.csv\")?)"));
+    }
+
+    #[test]
+    fn renders_auth() {
+        assert!(render(basic()).contains(".basic_auth(\"ann\", Some(\"pa55\"))"));
+        assert!(render(bearer()).contains(".bearer_auth(\"t0ken\")"));
+        assert!(render(api_key_header()).contains(".header(\"X-API-Key\", \"k3y\")"));
+        assert!(render(api_key_query()).contains(
+            ".request(reqwest::Method::GET, \"https://api.example.com/users?api_key=k3y\")"
+        ));
+    }
+
+    #[test]
+    fn escapes_strings() {
+        let code = render(special());
+        assert!(code.contains(r#".header("X-Note", "it's \"$HOME\" `id`")"#));
+        // Raw strings can't hold the carriage return
+        assert!(code.contains(r#".body("it's \"$HOME\" `id`\r\n")"#));
+
+        let body = RequestBody::Text(r##"a "# b"##.to_string());
+        assert!(render(request(body)).contains(r###".body(r##"a "# b"##)"###));
+    }
+}
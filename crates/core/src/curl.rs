@@ -0,0 +1,793 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use anyhow::Context;
+
+use crate::http::environment::Environment;
+use crate::http::request::{Auth, Method, Request, RequestBody};
+use crate::http::{KeyFile, KeyFileList, KeyValList, KeyValue};
+use crate::template::render_lenient;
+use crate::transformers::request::{full_url, param_enabled};
+use crate::utils::{percent_decode, percent_encode};
+
+// Options which are accepted but have no equivalent in a request, their
+// value must not be mistaken for the URL
+const IGNORED_VALUE_OPTIONS: &[&str] = &[
+    "-o",
+    "--output",
+    "--output-dir",
+    "-D",
+    "--dump-header",
+    "-K",
+    "--config",
+    "-c",
+    "--cookie-jar",
+    "-w",
+    "--write-out",
+    "--stderr",
+    "--trace",
+    "--trace-ascii",
+    "-m",
+    "--max-time",
+    "--connect-timeout",
+    "--expect100-timeout",
+    "--keepalive-time",
+    "--retry",
+    "--retry-delay",
+    "--retry-max-time",
+    "-Y",
+    "--speed-limit",
+    "-y",
+    "--speed-time",
+    "--limit-rate",
+    "--max-filesize",
+    "--max-redirs",
+    "-z",
+    "--time-cond",
+    "-C",
+    "--continue-at",
+    "-r",
+    "--range",
+    "-x",
+    "--proxy",
+    "-U",
+    "--proxy-user",
+    "--proxy-header",
+    "--preproxy",
+    "--noproxy",
+    "--socks4",
+    "--socks4a",
+    "--socks5",
+    "--socks5-hostname",
+    "--cacert",
+    "--capath",
+    "--cert",
+    "-E",
+    "--cert-type",
+    "--key",
+    "--key-type",
+    "--pass",
+    "--ciphers",
+    "--pinnedpubkey",
+    "--crlfile",
+    "--tls-max",
+    "--resolve",
+    "--connect-to",
+    "--dns-servers",
+    "--interface",
+    "--local-port",
+    "--unix-socket",
+    "--abstract-unix-socket",
+    "--proto",
+    "--proto-redir",
+    "--netrc-file",
+    "--aws-sigv4",
+    "-Q",
+    "--quote",
+    "-P",
+    "--ftp-port",
+    "-t",
+    "--telnet-option",
+];
+
+#[derive(Debug)]
+enum Data {
+    Raw(String),
+    /// `@file` read by curl, only supported as the whole body
+    File(PathBuf),
+    UrlEncode(String),
+}
+
+#[derive(Debug)]
+enum FormPart {
+    Text(String, String),
+    File(String, PathBuf),
+}
+
+/// Quick check to tell a pasted curl command apart from a URL
+pub fn is_curl(text: &str) -> bool {
+    let text = text.trim_start();
+    let text = text.strip_prefix("$ ").unwrap_or(text).trim_start();
+    text.strip_prefix("curl")
+        .is_some_and(|rest| rest.starts_with(char::is_whitespace))
+}
+
+/// Parses a curl command line into a request. Options without an equivalent,
+/// like `--compressed` or `-k`, are accepted and ignored.
+pub fn parse(command: &str) -> anyhow::Result<Request> {
+    let args = split_args(command)?;
+    let mut args = args.into_iter().skip_while(|arg| arg == "$").peekable();
+    if args.next().as_deref() != Some("curl") {
+        anyhow::bail!("Not a curl command");
+    }
+
+    let mut method = None;
+    let mut url = None;
+    let mut headers = Vec::new();
+    let mut data = Vec::new();
+    let mut form = Vec::new();
+    let mut auth = Auth::None;
+    let mut get = false;
+    let mut head = false;
+
+    let mut pending = Vec::new();
+    while let Some(arg) = pending.pop().or_else(|| args.next()) {
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => {
+                (flag.to_string(), Some(value.to_string()))
+            }
+            _ if arg.starts_with("--") || arg == "-" || !arg.starts_with('-') => (arg, None),
+            // Short flags can be combined `-sSL` or carry their value `-XPOST`
+            _ => {
+                let mut chars = arg[1..].chars();
+                let flag = format!("-{}", chars.next().unwrap_or_default());
+                let rest = chars.as_str();
+                if rest.is_empty() {
+                    (flag, None)
+                } else if takes_value(&flag) {
+                    (flag, Some(rest.to_string()))
+                } else {
+                    pending.push(format!("-{}", rest));
+                    (flag, None)
+                }
+            }
+        };
+
+        let mut value = |flag: &str| {
+            inline
+                .clone()
+                .or_else(|| args.next())
+                .with_context(|| format!("Missing value for {}", flag))
+        };
+
+        match flag.as_str() {
+            "-X" | "--request" => method = Some(value(&flag)?),
+            "-H" | "--header" => {
+                let header = value(&flag)?;
+                if let Some((name, value)) = header.split_once(':') {
+                    headers.push(KeyValue {
+                        disabled: false,
+                        name: name.trim().to_string(),
+                        value: value.trim().to_string(),
+                    });
+                }
+            }
+            "-d" | "--data" | "--data-ascii" | "--data-binary" => {
+                let value = value(&flag)?;
+                match value.strip_prefix('@') {
+                    Some(file) => data.push(Data::File(PathBuf::from(file))),
+                    None => data.push(Data::Raw(value)),
+                }
+            }
+            "--data-raw" => data.push(Data::Raw(value(&flag)?)),
+            "--data-urlencode" => data.push(Data::UrlEncode(value(&flag)?)),
+            "--json" => {
+                headers.push(KeyValue {
+                    disabled: false,
+                    name: "Content-Type".to_string(),
+                    value: "application/json".to_string(),
+                });
+                data.push(Data::Raw(value(&flag)?));
+            }
+            "-F" | "--form" => {
+                let value = value(&flag)?;
+                let (name, content) = value.split_once('=').unwrap_or((&value, ""));
+                if content.starts_with('<') {
+                    anyhow::bail!("Form parts read from a file with `<` are not supported");
+                }
+                // `;type=` and `;filename=` modifiers are dropped
+                let part = match content.strip_prefix('@') {
+                    Some(file) => {
+                        let file = file.split(';').next().unwrap_or_default();
+                        FormPart::File(name.to_string(), PathBuf::from(file))
+                    }
+                    None => FormPart::Text(name.to_string(), content.to_string()),
+                };
+                form.push(part);
+            }
+            "--form-string" => {
+                let value = value(&flag)?;
+                let (name, content) = value.split_once('=').unwrap_or((&value, ""));
+                form.push(FormPart::Text(name.to_string(), content.to_string()));
+            }
+            "-u" | "--user" => {
+                let value = value(&flag)?;
+                let (username, password) = value.split_once(':').unwrap_or((&value, ""));
+                auth = Auth::Basic {
+                    username: username.to_string(),
+                    password: password.to_string(),
+                };
+            }
+            "-A" | "--user-agent" => headers.push(KeyValue {
+                disabled: false,
+                name: "User-Agent".to_string(),
+                value: value(&flag)?,
+            }),
+            "-e" | "--referer" => headers.push(KeyValue {
+                disabled: false,
+                name: "Referer".to_string(),
+                value: value(&flag)?,
+            }),
+            "-b" | "--cookie" => {
+                let value = value(&flag)?;
+                // Without `=` the value names a cookie file
+                if value.contains('=') {
+                    headers.push(KeyValue {
+                        disabled: false,
+                        name: "Cookie".to_string(),
+                        value,
+                    });
+                }
+            }
+            "-T" | "--upload-file" => {
+                data.push(Data::File(PathBuf::from(value(&flag)?)));
+                method.get_or_insert_with(|| "PUT".to_string());
+            }
+            "--url" => url = Some(value(&flag)?),
+            "-G" | "--get" => get = true,
+            "-I" | "--head" => head = true,
+            flag if IGNORED_VALUE_OPTIONS.contains(&flag) => {
+                value(flag)?;
+            }
+            flag if flag.starts_with('-') && flag != "-" => (),
+            _ => {
+                if url.is_none() {
+                    url = Some(flag.to_string());
+                }
+            }
+        }
+    }
+
+    let url = url.context("No URL found in curl command")?;
+    let (url, mut query_params) = split_query(&url);
+
+    let content_type = headers
+        .iter()
+        .find(|h| h.name.eq_ignore_ascii_case("content-type"))
+        .map(|h| h.value.to_lowercase());
+
+    let body = if get {
+        query_params.extend(data_pairs(&data));
+        RequestBody::None
+    } else if !form.is_empty() {
+        multipart_body(form)
+    } else {
+        data_body(data, content_type.as_deref())
+    };
+
+    // Content type of these bodies is set when sending
+    if !matches!(body, RequestBody::None | RequestBody::Text(_)) {
+        headers.retain(|h| !h.name.eq_ignore_ascii_case("content-type"));
+    }
+
+    let bearer = headers.iter().position(|h| {
+        h.name.eq_ignore_ascii_case("authorization") && h.value.starts_with("Bearer ")
+    });
+    if let (Some(idx), Auth::None) = (bearer, &auth) {
+        let header = headers.remove(idx);
+        auth = Auth::Bearer {
+            token: header.value["Bearer ".len()..].trim().to_string(),
+        };
+    }
+
+    let method = match method {
+        Some(method) => Method::from_str(&method.to_uppercase())
+            .map_err(|_| anyhow::anyhow!("Unsupported method {}", method))?,
+        None if head => Method::HEAD,
+        None if get || matches!(body, RequestBody::None) => Method::GET,
+        None => Method::POST,
+    };
+
+    Ok(Request {
+        method,
+        url,
+        headers: KeyValList::from(headers),
+        body,
+        query_params: KeyValList::from(query_params),
+        auth,
+        ..Request::default()
+    })
+}
+
+fn takes_value(flag: &str) -> bool {
+    matches!(
+        flag,
+        "-X" | "-H" | "-d" | "-F" | "-u" | "-A" | "-e" | "-b" | "-T"
+    ) || IGNORED_VALUE_OPTIONS.contains(&flag)
+}
+
+/// Splits a command line using POSIX shell quoting rules, including
+/// `$'...'` strings and backslash line continuations.
+fn split_args(command: &str) -> anyhow::Result<Vec<String>> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut chars = command.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_arg = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => anyhow::bail!("Unterminated single quote"),
+                    }
+                }
+            }
+            '$' if chars.peek() == Some(&'\'') => {
+                chars.next();
+                in_arg = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => current.push('\n'),
+                            Some('t') => current.push('\t'),
+                            Some('r') => current.push('\r'),
+                            Some(c) => current.push(c),
+                            None => anyhow::bail!("Unterminated quote"),
+                        },
+                        Some(c) => current.push(c),
+                        None => anyhow::bail!("Unterminated quote"),
+                    }
+                }
+            }
+            '"' => {
+                in_arg = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => current.push(c),
+                            Some('\n') => (),
+                            Some(c) => {
+                                current.push('\\');
+                                current.push(c);
+                            }
+                            None => anyhow::bail!("Unterminated double quote"),
+                        },
+                        Some(c) => current.push(c),
+                        None => anyhow::bail!("Unterminated double quote"),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                // Line continuation
+                Some('\n') => (),
+                Some('\r') if chars.peek() == Some(&'\n') => {
+                    chars.next();
+                }
+                Some(c) => {
+                    in_arg = true;
+                    current.push(c);
+                }
+                None => (),
+            },
+            c if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            c => {
+                in_arg = true;
+                current.push(c);
+            }
+        }
+    }
+    if in_arg {
+        args.push(current);
+    }
+
+    Ok(args)
+}
+
+fn split_query(url: &str) -> (String, Vec<KeyValue>) {
+    let (url, query) = url.split_once('?').unwrap_or((url, ""));
+    (url.to_string(), parse_pairs(query))
+}
+
+fn parse_pairs(query: &str) -> Vec<KeyValue> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            KeyValue {
+                disabled: false,
+                name: percent_decode(name),
+                value: percent_decode(value),
+            }
+        })
+        .collect()
+}
+
+fn data_pairs(data: &[Data]) -> Vec<KeyValue> {
+    data.iter()
+        .flat_map(|data| match data {
+            Data::Raw(raw) => parse_pairs(raw),
+            Data::UrlEncode(value) => vec![urlencode_pair(value)],
+            Data::File(_) => Vec::new(),
+        })
+        .collect()
+}
+
+/// `--data-urlencode` values are `content`, `=content` or `name=content`
+fn urlencode_pair(value: &str) -> KeyValue {
+    let (name, value) = value.split_once('=').unwrap_or(("", value));
+    KeyValue {
+        disabled: false,
+        name: name.to_string(),
+        value: value.to_string(),
+    }
+}
+
+fn data_body(data: Vec<Data>, content_type: Option<&str>) -> RequestBody {
+    if let [Data::File(file)] = data.as_slice() {
+        return RequestBody::File(Some(file.clone()));
+    }
+    if data.is_empty() {
+        return RequestBody::None;
+    }
+
+    let is_form = match content_type {
+        Some(ct) => ct.contains("x-www-form-urlencoded"),
+        // curl sends data as a form unless told otherwise, JSON with `=`
+        // in its values is still JSON
+        None => data.iter().all(|d| match d {
+            Data::Raw(raw) => {
+                !raw.trim_start().starts_with(['{', '[']) && raw.split('&').all(|p| p.contains('='))
+            }
+            Data::UrlEncode(_) => true,
+            Data::File(_) => false,
+        }),
+    };
+    if is_form {
+        return RequestBody::Form(KeyValList::from(data_pairs(&data)));
+    }
+
+    let raw = data
+        .iter()
+        .filter_map(|d| match d {
+            Data::Raw(raw) => Some(raw.clone()),
+            Data::UrlEncode(value) => {
+                let pair = urlencode_pair(value);
+                Some(match pair.name.is_empty() {
+                    true => percent_encode(&pair.value),
+                    false => format!("{}={}", pair.name, percent_encode(&pair.value)),
+                })
+            }
+            Data::File(_) => None,
+        })
+        .collect::<Vec<_>>()
+        .join("&");
+
+    match content_type {
+        Some(ct) if ct.contains("json") => RequestBody::Json(raw),
+        Some(ct) if ct.contains("xml") => RequestBody::XML(raw),
+        None if raw.trim_start().starts_with(['{', '[']) => RequestBody::Json(raw),
+        _ => RequestBody::Text(raw),
+    }
+}
+
+fn multipart_body(form: Vec<FormPart>) -> RequestBody {
+    let mut params = Vec::new();
+    let mut files = Vec::new();
+    for part in form {
+        match part {
+            FormPart::Text(name, value) => params.push(KeyValue {
+                disabled: false,
+                name,
+                value,
+            }),
+            FormPart::File(name, path) => files.push(KeyFile {
+                name,
+                path: Some(path),
+                disabled: false,
+            }),
+        }
+    }
+    RequestBody::Multipart {
+        params: KeyValList::from(params),
+        files: KeyFileList::from(files),
+    }
+}
+
+/// Quotes an argument for POSIX shells
+pub(crate) fn quote(value: &str) -> String {
+    let safe = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=@,%+".contains(c));
+    if safe {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', r"'\''"))
+    }
+}
+
+/// Builds a curl command for the request with environment variables and path
+/// params replaced, one option per line. Secret variables are left as
+/// `{{vars}}`.
+pub fn to_curl(request: Request, env: Option<&Environment>) -> String {
+    let Request {
+        method,
+        url,
+        headers,
+        body,
+        query_params,
+        path_params,
+        auth,
+        ..
+    } = request;
+    let env = env.map(Environment::without_secrets);
+    let env = env.as_ref();
+
+    let url = full_url(url, path_params, query_params, env);
+
+    let has_content_type = headers
+        .iter()
+        .any(|h| param_enabled(h) && h.name.eq_ignore_ascii_case("content-type"));
+
+    let mut args = vec![format!("curl {}", quote(&url))];
+    match method {
+        Method::GET => (),
+        Method::HEAD => args.push("-I".to_string()),
+        method => args.push(format!("-X {}", method)),
+    }
+
+    for header in headers.into_iter().filter(param_enabled) {
+        let value = render_lenient(&header.value, env);
+        args.push(format!(
+            "-H {}",
+            quote(&format!("{}: {}", header.name, value))
+        ));
+    }
+
+    match auth {
+        Auth::None => (),
+        Auth::Basic { username, password } => {
+            let user = format!(
+                "{}:{}",
+                render_lenient(&username, env),
+                render_lenient(&password, env)
+            );
+            args.push(format!("-u {}", quote(&user)));
+        }
+        Auth::Bearer { token } => {
+            let header = format!("Authorization: Bearer {}", render_lenient(&token, env));
+            args.push(format!("-H {}", quote(&header)));
+        }
+    }
+
+    let mut raw_body = |content_type: &str, data: String| {
+        if !has_content_type {
+            args.push(format!(
+                "-H {}",
+                quote(&format!("Content-Type: {}", content_type))
+            ));
+        }
+        args.push(format!("--data-raw {}", quote(&data)));
+    };
+    match body {
+        RequestBody::Json(json) => raw_body("application/json", render_lenient(&json, env)),
+        RequestBody::XML(xml) => raw_body("text/xml", render_lenient(&xml, env)),
+        RequestBody::Text(text) => raw_body("text/plain", render_lenient(&text, env)),
+        RequestBody::GraphQL {
+            query,
+            variables,
+            operation_name,
+        } => {
+            let variables = render_lenient(&variables, env);
+            let payload = crate::graphql::payload(&query, &variables, operation_name.as_deref())
+                .map(|p| p.to_string())
+                .unwrap_or_default();
+            raw_body("application/json", payload);
+        }
+        RequestBody::Form(form) => {
+            for param in form.into_iter().filter(param_enabled) {
+                let value = render_lenient(&param.value, env);
+                let pair = format!("{}={}", param.name, value);
+                args.push(format!("--data-urlencode {}", quote(&pair)));
+            }
+        }
+        RequestBody::Multipart { params, files } => {
+            for param in params.into_iter().filter(param_enabled) {
+                let value = render_lenient(&param.value, env);
+                let part = format!("{}={}", param.name, value);
+                args.push(format!("--form-string {}", quote(&part)));
+            }
+            for file in files
+                .into_iter()
+                .filter(|f| !f.disabled && !f.name.is_empty())
+            {
+                if let Some(path) = file.path {
+                    let part = format!("{}=@{}", file.name, path.display());
+                    args.push(format!("-F {}", quote(&part)));
+                }
+            }
+        }
+        RequestBody::File(Some(path)) => {
+            args.push(format!(
+                "--data-binary {}",
+                quote(&format!("@{}", path.display()))
+            ));
+        }
+        RequestBody::File(None) | RequestBody::None => (),
+    }
+
+    args.join(" \\\n  ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header<'a>(req: &'a Request, name: &str) -> Option<&'a str> {
+        req.headers
+            .iter()
+            .find(|h| h.name.eq_ignore_ascii_case(name))
+            .map(|h| h.value.as_str())
+    }
+
+    #[test]
+    fn splits_quoted_args() {
+        let args = split_args(
+            r#"curl 'a b' "c \"d\" \$e" $'f\ng' h\ i \
+  j"#,
+        )
+        .unwrap();
+        assert_eq!(args, ["curl", "a b", r#"c "d" $e"#, "f\ng", "h i", "j"]);
+
+        assert!(split_args("curl 'open").is_err());
+        assert!(split_args("curl \"open").is_err());
+    }
+
+    #[test]
+    fn parses_headers_and_data() {
+        let req = parse(
+            r#"curl -X PUT 'https://example.com/users?page=2&q=a%20b' \
+  -H 'Content-Type: application/json' -H "X-Id:  7" \
+  -d '{"name":"a"}'"#,
+        )
+        .unwrap();
+
+        assert_eq!(req.method, Method::PUT);
+        assert_eq!(req.url, "https://example.com/users");
+        let query = req
+            .query_params
+            .iter()
+            .map(|q| (q.name.as_str(), q.value.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(query, [("page", "2"), ("q", "a b")]);
+        assert_eq!(header(&req, "x-id"), Some("7"));
+        assert_eq!(req.body, RequestBody::Json(r#"{"name":"a"}"#.to_string()));
+    }
+
+    #[test]
+    fn data_defaults_to_post_form() {
+        let req = parse("curl https://example.com -d a=1 -d b=2").unwrap();
+        assert_eq!(req.method, Method::POST);
+        let RequestBody::Form(form) = req.body else {
+            panic!("Expected a form body, got {:?}", req.body);
+        };
+        let pairs = form
+            .iter()
+            .map(|p| (p.name.as_str(), p.value.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(pairs, [("a", "1"), ("b", "2")]);
+
+        let req = parse("curl -G https://example.com -d a=1").unwrap();
+        assert_eq!(req.method, Method::GET);
+        assert_eq!(req.body, RequestBody::None);
+        assert_eq!(req.query_params.iter().count(), 1);
+
+        let req = parse(r#"curl https://example.com -d '{"q":"a=b"}'"#).unwrap();
+        assert_eq!(req.body, RequestBody::Json(r#"{"q":"a=b"}"#.to_string()));
+    }
+
+    #[test]
+    fn parses_multipart_form() {
+        let req = parse("curl https://example.com -F name=a -F 'file=@/tmp/a.png;type=image/png'")
+            .unwrap();
+        let RequestBody::Multipart { params, files } = req.body else {
+            panic!("Expected a multipart body, got {:?}", req.body);
+        };
+        let param = params.iter().next().unwrap();
+        assert_eq!((param.name.as_str(), param.value.as_str()), ("name", "a"));
+        let file = files.iter().next().unwrap();
+        assert_eq!(file.name, "file");
+        assert_eq!(file.path, Some(PathBuf::from("/tmp/a.png")));
+
+        assert!(parse("curl https://example.com -F 'text=<notes.txt'").is_err());
+    }
+
+    #[test]
+    fn parses_auth() {
+        let req = parse("curl -u user:p4ss https://example.com").unwrap();
+        assert_eq!(
+            req.auth,
+            Auth::Basic {
+                username: "user".to_string(),
+                password: "p4ss".to_string(),
+            }
+        );
+
+        let req = parse("curl https://example.com -H 'Authorization: Bearer abc'").unwrap();
+        assert_eq!(
+            req.auth,
+            Auth::Bearer {
+                token: "abc".to_string()
+            }
+        );
+        assert_eq!(header(&req, "authorization"), None);
+    }
+
+    #[test]
+    fn parses_combined_short_flags() {
+        let req = parse("curl -sSLXPOST https://example.com -HAccept:text/plain").unwrap();
+        assert_eq!(req.method, Method::POST);
+        assert_eq!(req.url, "https://example.com");
+        assert_eq!(header(&req, "accept"), Some("text/plain"));
+
+        let req = parse("curl -sI https://example.com").unwrap();
+        assert_eq!(req.method, Method::HEAD);
+    }
+
+    #[test]
+    fn skips_values_of_ignored_options() {
+        let commands = [
+            "curl -D headers.txt https://example.com",
+            "curl -K config.txt https://example.com",
+            "curl -c jar.txt -b jar.txt https://example.com",
+            "curl -w '%{http_code}' --connect-timeout 5 https://example.com",
+            "curl -o out.json -m 10 --retry 3 https://example.com",
+            "curl --dump-header=headers.txt -x proxy:8080 https://example.com",
+            "curl -Dheaders.txt --compressed -k https://example.com",
+        ];
+        for command in commands {
+            let req = parse(command).unwrap();
+            assert_eq!(req.url, "https://example.com", "{}", command);
+            assert_eq!(req.method, Method::GET, "{}", command);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_commands() {
+        assert!(parse("wget https://example.com").is_err());
+        assert!(parse("curl -s").is_err());
+        assert!(parse("curl https://example.com -H").is_err());
+    }
+
+    #[test]
+    fn exports_head_as_flag() {
+        let req = Request {
+            method: Method::HEAD,
+            url: "https://example.com".to_string(),
+            ..Request::default()
+        };
+        assert_eq!(to_curl(req, None), "curl https://example.com \\\n  -I");
+    }
+}
@@ -0,0 +1,365 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::Value;
+use similar::{ChangeTag, TextDiff};
+
+use crate::client::Response;
+
+/// Prefix of the ignored paths naming a header, `header.date`
+pub const HEADER_PREFIX: &str = "header.";
+
+/// Lines of unchanged text kept around each change of a line diff
+const CONTEXT_LINES: usize = 3;
+
+#[derive(Debug, Clone, Default)]
+pub struct DiffOptions {
+    /// Dotted JSON paths such as `data.items.*.id` and headers such as
+    /// `header.date`. `*` matches any key or index, and everything below an
+    /// ignored path is ignored as well.
+    pub ignore: Vec<String>,
+}
+
+impl DiffOptions {
+    /// Options from a comma separated list of ignored paths
+    pub fn from_ignore_list(list: &str) -> Self {
+        Self {
+            ignore: list
+                .split(',')
+                .map(str::trim)
+                .filter(|path| !path.is_empty())
+                .map(str::to_string)
+                .collect(),
+        }
+    }
+
+    fn ignores(&self, path: &str) -> bool {
+        self.ignore
+            .iter()
+            .any(|pattern| path_matches(pattern, path))
+    }
+
+    fn ignores_header(&self, name: &str) -> bool {
+        self.ignore.iter().any(|pattern| {
+            pattern
+                .strip_prefix(HEADER_PREFIX)
+                .is_some_and(|header| header.eq_ignore_ascii_case(name))
+        })
+    }
+}
+
+pub(crate) fn path_matches(pattern: &str, path: &str) -> bool {
+    let mut pattern = pattern.split('.');
+    let mut path = path.split('.');
+    loop {
+        match (pattern.next(), path.next()) {
+            (None, _) => return true,
+            (Some(expected), Some(segment)) if expected == "*" || expected == segment => (),
+            _ => return false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Added(String),
+    Removed(String),
+    Changed(String, String),
+}
+
+/// Change of a header or of a value inside a JSON body, the path of the
+/// root value is empty
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueChange {
+    pub path: String,
+    pub change: Change,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineTag {
+    Equal,
+    Insert,
+    Delete,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffLine {
+    pub tag: LineTag,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BodyDiff {
+    /// Both bodies are JSON, objects are compared ignoring key order
+    Json(Vec<ValueChange>),
+    /// Changed lines with some context around them, grouped in hunks
+    Lines(Vec<Vec<DiffLine>>),
+}
+
+impl BodyDiff {
+    pub fn is_empty(&self) -> bool {
+        match self {
+            BodyDiff::Json(changes) => changes.is_empty(),
+            BodyDiff::Lines(hunks) => hunks.is_empty(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResponseDiff {
+    pub status: Option<(u16, u16)>,
+    pub headers: Vec<ValueChange>,
+    pub body: BodyDiff,
+}
+
+impl ResponseDiff {
+    pub fn is_empty(&self) -> bool {
+        self.status.is_none() && self.headers.is_empty() && self.body.is_empty()
+    }
+}
+
+/// Compares two responses, bodies are diffed structurally when both are
+/// JSON and line by line otherwise
+pub fn diff_responses(old: &Response, new: &Response, options: &DiffOptions) -> ResponseDiff {
+    let status = (old.status != new.status).then(|| (old.status.as_u16(), new.status.as_u16()));
+
+    let old_json = serde_json::from_slice::<Value>(&old.body.data);
+    let new_json = serde_json::from_slice::<Value>(&new.body.data);
+    let body = match (old_json, new_json) {
+        (Ok(old), Ok(new)) => BodyDiff::Json(diff_json(&old, &new, options)),
+        _ => BodyDiff::Lines(diff_lines(
+            &String::from_utf8_lossy(&old.body.data),
+            &String::from_utf8_lossy(&new.body.data),
+        )),
+    };
+
+    ResponseDiff {
+        status,
+        headers: diff_headers(old, new, options),
+        body,
+    }
+}
+
+pub(crate) fn header_values(response: &Response) -> BTreeMap<String, String> {
+    let mut headers = BTreeMap::<String, String>::new();
+    for (name, value) in response.headers.iter() {
+        let value = String::from_utf8_lossy(value.as_bytes());
+        headers
+            .entry(name.as_str().to_ascii_lowercase())
+            .and_modify(|values| {
+                values.push_str(", ");
+                values.push_str(&value);
+            })
+            .or_insert_with(|| value.to_string());
+    }
+    headers
+}
+
+fn diff_headers(old: &Response, new: &Response, options: &DiffOptions) -> Vec<ValueChange> {
+    let old = header_values(old);
+    let new = header_values(new);
+    let names = old.keys().chain(new.keys()).collect::<BTreeSet<_>>();
+
+    names
+        .into_iter()
+        .filter(|name| !options.ignores_header(name))
+        .filter_map(|name| {
+            let change = match (old.get(name), new.get(name)) {
+                (Some(old), Some(new)) if old != new => Change::Changed(old.clone(), new.clone()),
+                (Some(old), None) => Change::Removed(old.clone()),
+                (None, Some(new)) => Change::Added(new.clone()),
+                _ => return None,
+            };
+            Some(ValueChange {
+                path: name.clone(),
+                change,
+            })
+        })
+        .collect()
+}
+
+/// Structural diff of two JSON values, array items are compared by index
+pub fn diff_json(old: &Value, new: &Value, options: &DiffOptions) -> Vec<ValueChange> {
+    let mut changes = Vec::new();
+    walk(String::new(), Some(old), Some(new), options, &mut changes);
+    changes
+}
+
+fn child_path(parent: &str, key: &str) -> String {
+    if parent.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", parent, key)
+    }
+}
+
+fn walk(
+    path: String,
+    old: Option<&Value>,
+    new: Option<&Value>,
+    options: &DiffOptions,
+    changes: &mut Vec<ValueChange>,
+) {
+    if !path.is_empty() && options.ignores(&path) {
+        return;
+    }
+
+    let change = match (old, new) {
+        (Some(Value::Object(old)), Some(Value::Object(new))) => {
+            let keys = old.keys().chain(new.keys()).collect::<BTreeSet<_>>();
+            for key in keys {
+                let child = child_path(&path, key);
+                walk(child, old.get(key), new.get(key), options, changes);
+            }
+            return;
+        }
+        (Some(Value::Array(old)), Some(Value::Array(new))) => {
+            for idx in 0..old.len().max(new.len()) {
+                let child = child_path(&path, &idx.to_string());
+                walk(child, old.get(idx), new.get(idx), options, changes);
+            }
+            return;
+        }
+        (Some(old), Some(new)) if old != new => Change::Changed(old.to_string(), new.to_string()),
+        (Some(old), None) => Change::Removed(old.to_string()),
+        (None, Some(new)) => Change::Added(new.to_string()),
+        _ => return,
+    };
+
+    changes.push(ValueChange { path, change });
+}
+
+/// Line diff of two texts, empty when they are the same
+pub fn diff_lines(old: &str, new: &str) -> Vec<Vec<DiffLine>> {
+    let diff = TextDiff::from_lines(old, new);
+
+    diff.grouped_ops(CONTEXT_LINES)
+        .iter()
+        .map(|group| {
+            group
+                .iter()
+                .flat_map(|op| diff.iter_changes(op))
+                .map(|change| DiffLine {
+                    tag: match change.tag() {
+                        ChangeTag::Equal => LineTag::Equal,
+                        ChangeTag::Insert => LineTag::Insert,
+                        ChangeTag::Delete => LineTag::Delete,
+                    },
+                    text: change.value().trim_end_matches(['\r', '\n']).to_string(),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn change(path: &str, change: Change) -> ValueChange {
+        ValueChange {
+            path: path.to_string(),
+            change,
+        }
+    }
+
+    #[test]
+    fn matches_paths_with_wildcards() {
+        assert!(path_matches("data.items.*.id", "data.items.3.id"));
+        assert!(path_matches("*", "data"));
+        // Everything below a matched path matches
+        assert!(path_matches("data.meta", "data.meta.created"));
+        assert!(!path_matches("data.items.*.id", "data.items"));
+        assert!(!path_matches("data.items.*.id", "data.items.3.name"));
+        assert!(!path_matches("data.id", "data.identifier"));
+    }
+
+    #[test]
+    fn diffs_json_ignoring_key_order() {
+        let old = json!({ "id": 1, "tags": ["a", "b"], "name": "old", "gone": null });
+        let new = json!({ "name": "new", "tags": ["a"], "id": 1, "added": { "x": true } });
+
+        let changes = diff_json(&old, &new, &DiffOptions::default());
+        assert_eq!(
+            changes,
+            [
+                change("added", Change::Added(r#"{"x":true}"#.to_string())),
+                change("gone", Change::Removed("null".to_string())),
+                change(
+                    "name",
+                    Change::Changed(r#""old""#.to_string(), r#""new""#.to_string())
+                ),
+                change("tags.1", Change::Removed(r#""b""#.to_string())),
+            ]
+        );
+
+        // Different root values are a change of the root path
+        let changes = diff_json(&json!([1]), &json!({}), &DiffOptions::default());
+        assert_eq!(
+            changes,
+            [change(
+                "",
+                Change::Changed("[1]".to_string(), "{}".to_string())
+            )]
+        );
+    }
+
+    #[test]
+    fn skips_ignored_subtrees() {
+        let old = json!({
+            "items": [{ "id": 1, "at": "09:00" }, { "id": 2, "at": "09:01" }],
+            "meta": { "request": "a", "took": 3 },
+            "total": 2,
+        });
+        let new = json!({
+            "items": [{ "id": 1, "at": "10:00" }, { "id": 3, "at": "10:01" }],
+            "meta": { "request": "b" },
+            "total": 2,
+        });
+
+        let options = DiffOptions::from_ignore_list(" items.*.at, meta ,,header.date");
+        assert_eq!(options.ignore, ["items.*.at", "meta", "header.date"]);
+        assert!(options.ignores_header("Date"));
+        assert!(!options.ignores_header("meta"));
+
+        let changes = diff_json(&old, &new, &options);
+        assert_eq!(
+            changes,
+            [change(
+                "items.1.id",
+                Change::Changed("2".to_string(), "3".to_string())
+            )]
+        );
+    }
+
+    #[test]
+    fn groups_changed_lines_in_hunks() {
+        let old = (1..=10)
+            .map(|n| format!("line {}\n", n))
+            .collect::<String>();
+        let new = old.replace("line 2\n", "line two\n");
+
+        assert!(diff_lines(&old, &old).is_empty());
+
+        let hunks = diff_lines(&old, &new);
+        let [hunk] = hunks.as_slice() else {
+            panic!("expected one hunk, got {:?}", hunks);
+        };
+        let lines = hunk
+            .iter()
+            .map(|line| (line.tag, line.text.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            [
+                (LineTag::Equal, "line 1"),
+                (LineTag::Delete, "line 2"),
+                (LineTag::Insert, "line two"),
+                (LineTag::Equal, "line 3"),
+                (LineTag::Equal, "line 4"),
+                (LineTag::Equal, "line 5"),
+            ]
+        );
+    }
+}
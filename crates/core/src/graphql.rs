@@ -0,0 +1,516 @@
+use std::collections::HashMap;
+
+use anyhow::Context;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// Standard introspection query, trimmed down to what field completion needs.
+pub const INTROSPECTION_QUERY: &str = r#"query IntrospectionQuery {
+  __schema {
+    queryType { name }
+    mutationType { name }
+    subscriptionType { name }
+    types {
+      name
+      fields(includeDeprecated: true) {
+        name
+        description
+        type { ...TypeRef }
+      }
+    }
+  }
+}
+
+fragment TypeRef on __Type {
+  kind
+  name
+  ofType {
+    kind
+    name
+    ofType {
+      kind
+      name
+      ofType {
+        kind
+        name
+        ofType { kind name }
+      }
+    }
+  }
+}
+"#;
+
+/// Builds the JSON payload sent for a GraphQL request. Blank variables are
+/// left out, anything else has to be a valid JSON object.
+pub fn payload(
+    query: &str,
+    variables: &str,
+    operation_name: Option<&str>,
+) -> anyhow::Result<Value> {
+    let mut payload = json!({ "query": query });
+
+    if !variables.trim().is_empty() {
+        let variables: Value =
+            serde_json::from_str(variables).context("Invalid GraphQL variables")?;
+        anyhow::ensure!(
+            variables.is_object(),
+            "GraphQL variables must be a JSON object"
+        );
+        payload["variables"] = variables;
+    }
+
+    if let Some(name) = operation_name.filter(|n| !n.is_empty()) {
+        payload["operationName"] = name.into();
+    }
+
+    Ok(payload)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphQLError {
+    pub message: String,
+    pub path: Option<String>,
+}
+
+/// Extracts the `errors` array of a GraphQL response, if the body has one.
+pub fn response_errors(body: &[u8]) -> Option<Vec<GraphQLError>> {
+    let body: Value = serde_json::from_slice(body).ok()?;
+    let errors = body.get("errors")?.as_array()?;
+
+    let errors = errors
+        .iter()
+        .map(|err| {
+            let message = err
+                .get("message")
+                .and_then(Value::as_str)
+                .map(str::to_owned)
+                .unwrap_or_else(|| err.to_string());
+            let path = err.get("path").and_then(Value::as_array).map(|path| {
+                path.iter()
+                    .map(|p| match p {
+                        Value::String(s) => s.clone(),
+                        other => other.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(".")
+            });
+            GraphQLError { message, path }
+        })
+        .collect();
+
+    Some(errors)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub name: String,
+    pub type_name: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Schema {
+    pub query_type: Option<String>,
+    pub mutation_type: Option<String>,
+    pub subscription_type: Option<String>,
+    pub types: HashMap<String, Vec<Field>>,
+}
+
+#[derive(Deserialize)]
+struct IntrospectionResponse {
+    data: IntrospectionData,
+}
+
+#[derive(Deserialize)]
+struct IntrospectionData {
+    #[serde(rename = "__schema")]
+    schema: IntrospectionSchema,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IntrospectionSchema {
+    query_type: Option<NamedType>,
+    mutation_type: Option<NamedType>,
+    subscription_type: Option<NamedType>,
+    types: Vec<IntrospectionType>,
+}
+
+#[derive(Deserialize)]
+struct NamedType {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct IntrospectionType {
+    name: Option<String>,
+    fields: Option<Vec<IntrospectionField>>,
+}
+
+#[derive(Deserialize)]
+struct IntrospectionField {
+    name: String,
+    description: Option<String>,
+    #[serde(rename = "type")]
+    field_type: TypeRef,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TypeRef {
+    name: Option<String>,
+    of_type: Option<Box<TypeRef>>,
+}
+
+impl TypeRef {
+    // Lists and non-null wrappers only carry a name on the innermost type
+    fn named(&self) -> Option<&str> {
+        match (&self.name, &self.of_type) {
+            (Some(name), _) => Some(name),
+            (None, Some(inner)) => inner.named(),
+            (None, None) => None,
+        }
+    }
+}
+
+/// Field suggestions for the position of the cursor in a query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Completions<'a> {
+    /// Partially typed field name right before the cursor.
+    pub prefix: String,
+    pub fields: Vec<&'a Field>,
+}
+
+impl Schema {
+    /// Parses the response of [`INTROSPECTION_QUERY`].
+    pub fn from_introspection(body: &[u8]) -> anyhow::Result<Schema> {
+        let res: IntrospectionResponse =
+            serde_json::from_slice(body).context("Invalid introspection response")?;
+        let schema = res.data.schema;
+
+        let types = schema
+            .types
+            .into_iter()
+            .filter_map(|ty| Some((ty.name?, ty.fields?)))
+            .filter(|(name, _)| !name.starts_with("__"))
+            .map(|(name, fields)| {
+                let fields = fields
+                    .into_iter()
+                    .map(|f| Field {
+                        type_name: f.field_type.named().unwrap_or_default().to_string(),
+                        name: f.name,
+                        description: f.description,
+                    })
+                    .collect();
+                (name, fields)
+            })
+            .collect();
+
+        Ok(Schema {
+            query_type: schema.query_type.map(|t| t.name),
+            mutation_type: schema.mutation_type.map(|t| t.name),
+            subscription_type: schema.subscription_type.map(|t| t.name),
+            types,
+        })
+    }
+
+    fn field_type(&self, type_name: &str, field: &str) -> Option<&str> {
+        self.types
+            .get(type_name)?
+            .iter()
+            .find(|f| f.name == field)
+            .map(|f| f.type_name.as_str())
+    }
+
+    /// Suggests fields for the selection set the end of `before_cursor` is in.
+    pub fn completions(&self, before_cursor: &str) -> Completions<'_> {
+        let prefix = before_cursor
+            .chars()
+            .rev()
+            .take_while(|c| c.is_alphanumeric() || *c == '_')
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+            .collect::<String>();
+
+        let fields = self
+            .selection_type(&before_cursor[..before_cursor.len() - prefix.len()])
+            .and_then(|ty| self.types.get(&ty))
+            .map(|fields| {
+                fields
+                    .iter()
+                    .filter(|f| f.name.starts_with(&prefix) && f.name != prefix)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Completions { prefix, fields }
+    }
+
+    /// Walks the query keeping a stack of the types of the open selection sets.
+    /// `None` entries are selection sets of fields unknown to the schema.
+    fn selection_type(&self, query: &str) -> Option<String> {
+        let mut stack: Vec<Option<String>> = Vec::new();
+        let mut operation = self.query_type.clone();
+        let mut last_word: Option<String> = None;
+        let mut after_on = false;
+        let mut fragment_type: Option<String> = None;
+
+        let mut chars = query.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '#' => {
+                    for c in chars.by_ref() {
+                        if c == '\n' {
+                            break;
+                        }
+                    }
+                }
+                '"' => {
+                    let mut escaped = false;
+                    for c in chars.by_ref() {
+                        match c {
+                            '\\' if !escaped => escaped = true,
+                            '"' if !escaped => break,
+                            _ => escaped = false,
+                        }
+                    }
+                }
+                '(' => {
+                    let mut depth = 1;
+                    for c in chars.by_ref() {
+                        match c {
+                            '(' => depth += 1,
+                            ')' => depth -= 1,
+                            _ => (),
+                        }
+                        if depth == 0 {
+                            break;
+                        }
+                    }
+                }
+                '{' => {
+                    let ty = match (stack.last(), fragment_type.take()) {
+                        (_, Some(ty)) => Some(ty),
+                        (None, None) => operation.clone(),
+                        (Some(parent), None) => parent.as_deref().and_then(|parent| {
+                            let field = last_word.as_deref()?;
+                            self.field_type(parent, field).map(str::to_owned)
+                        }),
+                    };
+                    stack.push(ty);
+                    last_word = None;
+                }
+                '}' => {
+                    stack.pop();
+                    last_word = None;
+                }
+                c if c.is_alphabetic() || c == '_' => {
+                    let mut word = c.to_string();
+                    while let Some(&next) = chars.peek() {
+                        if !(next.is_alphanumeric() || next == '_') {
+                            break;
+                        }
+                        word.push(next);
+                        chars.next();
+                    }
+
+                    if after_on {
+                        fragment_type = Some(word);
+                        after_on = false;
+                        continue;
+                    }
+
+                    match word.as_str() {
+                        "on" => after_on = true,
+                        "query" if stack.is_empty() => operation = self.query_type.clone(),
+                        "mutation" if stack.is_empty() => operation = self.mutation_type.clone(),
+                        "subscription" if stack.is_empty() => {
+                            operation = self.subscription_type.clone()
+                        }
+                        _ => last_word = Some(word),
+                    }
+                }
+                // Aliases, `alias: field`, resolve to the field after the colon
+                ':' => last_word = None,
+                _ => (),
+            }
+        }
+
+        stack.pop()?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(name: &str, ty: Value) -> Value {
+        json!({ "name": name, "description": null, "type": ty })
+    }
+
+    fn named(name: &str) -> Value {
+        json!({ "kind": "OBJECT", "name": name, "ofType": null })
+    }
+
+    fn list_of(ty: Value) -> Value {
+        json!({ "kind": "NON_NULL", "name": null, "ofType": {
+            "kind": "LIST", "name": null, "ofType": ty,
+        }})
+    }
+
+    fn schema() -> Schema {
+        let introspection = json!({ "data": { "__schema": {
+            "queryType": { "name": "Query" },
+            "mutationType": { "name": "Mutation" },
+            "subscriptionType": null,
+            "types": [
+                { "name": "Query", "fields": [
+                    field("user", named("User")),
+                    field("users", list_of(named("User"))),
+                ]},
+                { "name": "Mutation", "fields": [field("createUser", named("User"))] },
+                { "name": "User", "fields": [
+                    field("id", named("ID")),
+                    field("name", named("String")),
+                    field("nickname", named("String")),
+                    field("friends", list_of(named("User"))),
+                    field("address", named("Address")),
+                ]},
+                { "name": "Address", "fields": [
+                    field("city", named("String")),
+                    field("street", named("String")),
+                ]},
+                { "name": "ID", "fields": null },
+                { "name": "__Type", "fields": [field("kind", named("String"))] },
+            ],
+        }}});
+        Schema::from_introspection(introspection.to_string().as_bytes()).unwrap()
+    }
+
+    fn names(schema: &Schema, before_cursor: &str) -> (String, Vec<String>) {
+        let completions = schema.completions(before_cursor);
+        let names = completions.fields.iter().map(|f| f.name.clone()).collect();
+        (completions.prefix, names)
+    }
+
+    #[test]
+    fn reads_introspection_results() {
+        let schema = schema();
+        assert_eq!(schema.query_type.as_deref(), Some("Query"));
+        assert_eq!(schema.subscription_type, None);
+        // Scalars without fields and introspection types are left out
+        let mut types = schema.types.keys().cloned().collect::<Vec<_>>();
+        types.sort();
+        assert_eq!(types, ["Address", "Mutation", "Query", "User"]);
+        assert_eq!(schema.field_type("Query", "users"), Some("User"));
+    }
+
+    #[test]
+    fn resolves_nested_selection_types() {
+        let schema = schema();
+        let query = r#"query Friends($id: ID!) {
+  user(id: $id, filter: "{ not a selection }") {
+    # address {
+    friends {
+      home: address {
+"#;
+        assert_eq!(schema.selection_type(query).as_deref(), Some("Address"));
+        assert_eq!(
+            names(&schema, query),
+            (String::new(), vec!["city".into(), "street".into()])
+        );
+
+        let query = "{ users { id } user { friends { n";
+        assert_eq!(
+            names(&schema, query),
+            ("n".into(), vec!["name".into(), "nickname".into()])
+        );
+
+        assert_eq!(
+            schema.selection_type("mutation { createUser { ").as_deref(),
+            Some("User")
+        );
+        // Back in the query after closing the nested selection sets
+        assert_eq!(
+            schema
+                .selection_type("{ user { address { city } } ")
+                .as_deref(),
+            Some("Query")
+        );
+    }
+
+    #[test]
+    fn completes_fields_of_fragments() {
+        let schema = schema();
+        assert_eq!(
+            names(&schema, "fragment Place on Address { ci"),
+            ("ci".into(), vec!["city".into()])
+        );
+        assert_eq!(
+            names(&schema, "{ user { ... on User { add"),
+            ("add".into(), vec!["address".into()])
+        );
+    }
+
+    #[test]
+    fn completes_nothing_for_unknown_types() {
+        let schema = schema();
+        for query in [
+            "fragment Missing on Unknown { ",
+            "{ unknown { ",
+            "{ unknown { user { ",
+            "subscription { ",
+            "{ user { name } } ",
+        ] {
+            assert_eq!(names(&schema, query), (String::new(), vec![]), "{}", query);
+        }
+        // A complete field name is not suggested again
+        assert_eq!(names(&schema, "{ user { name"), ("name".into(), vec![]));
+    }
+
+    #[test]
+    fn builds_payloads() {
+        let query = "query Me { me { id } }";
+        assert_eq!(payload(query, "", None).unwrap(), json!({ "query": query }));
+        assert_eq!(
+            payload(query, "  \n", Some("")).unwrap(),
+            json!({ "query": query })
+        );
+        assert_eq!(
+            payload(query, r#"{"id": 1}"#, Some("Me")).unwrap(),
+            json!({ "query": query, "variables": { "id": 1 }, "operationName": "Me" })
+        );
+
+        let err = payload(query, "{ id: 1 }", None).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid GraphQL variables");
+        let err = payload(query, "[1]", None).unwrap_err();
+        assert_eq!(err.to_string(), "GraphQL variables must be a JSON object");
+    }
+
+    #[test]
+    fn extracts_response_errors() {
+        assert_eq!(response_errors(br#"{"data": {}, "errors": null}"#), None);
+        assert_eq!(response_errors(br#"{"data": {}}"#), None);
+        assert_eq!(response_errors(b"not json"), None);
+        assert_eq!(response_errors(br#"{"errors": []}"#), Some(vec![]));
+
+        let body = br#"{"errors": [
+            {"message": "Not found", "path": ["users", 0, "name"]},
+            {"extensions": {"code": "INTERNAL"}}
+        ]}"#;
+        assert_eq!(
+            response_errors(body),
+            Some(vec![
+                GraphQLError {
+                    message: "Not found".to_string(),
+                    path: Some("users.0.name".to_string()),
+                },
+                GraphQLError {
+                    message: r#"{"extensions":{"code":"INTERNAL"}}"#.to_string(),
+                    path: None,
+                },
+            ])
+        );
+    }
+}
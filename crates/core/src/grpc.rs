@@ -0,0 +1,497 @@
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use chrono::Local;
+use futures::stream::{self, BoxStream, Stream};
+use futures::StreamExt;
+use prost::Message;
+use prost_reflect::{
+    DescriptorPool, DynamicMessage, MessageDescriptor, MethodDescriptor, SerializeOptions,
+};
+use protobuf::Message as _;
+use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+use reqwest::StatusCode;
+use tonic::codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder};
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::metadata::MetadataMap;
+use tonic::transport::{Channel, ClientTlsConfig, Endpoint};
+use tonic::Status;
+use tonic_reflection::pb::v1::server_reflection_client::ServerReflectionClient;
+use tonic_reflection::pb::v1::server_reflection_request::MessageRequest;
+use tonic_reflection::pb::v1::server_reflection_response::MessageResponse;
+use tonic_reflection::pb::v1::ServerReflectionRequest;
+
+use crate::client::{ContentType, Response, ResponseBody, ResponseEvent, StreamHead};
+use crate::har::{HarRequest, NameValue, PostData, SentRequest};
+use crate::sse::{SseEvent, DEFAULT_EVENT};
+
+pub const GRPC_STATUS: &str = "grpc-status";
+pub const GRPC_MESSAGE: &str = "grpc-message";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrpcMethod {
+    pub service: String,
+    pub method: String,
+    pub client_streaming: bool,
+    pub server_streaming: bool,
+}
+
+impl GrpcMethod {
+    /// Client streaming calls can't be composed from a single message.
+    pub fn is_supported(&self) -> bool {
+        !self.client_streaming
+    }
+}
+
+impl fmt::Display for GrpcMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.service, self.method)
+    }
+}
+
+/// Parses `.proto` files without needing `protoc`, imports are resolved
+/// relative to the directories of the given files.
+pub fn parse_proto_files(files: &[PathBuf]) -> anyhow::Result<DescriptorPool> {
+    let includes = files
+        .iter()
+        .filter_map(|f| f.parent())
+        .collect::<HashSet<_>>();
+
+    let set = protobuf_parse::Parser::new()
+        .pure()
+        .includes(includes)
+        .inputs(files)
+        .file_descriptor_set()
+        .context("Failed to parse proto files")?;
+
+    let pool = DescriptorPool::decode(set.write_to_bytes()?.as_slice())?;
+    Ok(pool)
+}
+
+pub async fn connect(url: &str) -> anyhow::Result<Channel> {
+    let mut endpoint = Endpoint::from_shared(url.to_string()).context("Invalid gRPC URL")?;
+    if url.starts_with("https://") {
+        endpoint = endpoint.tls_config(ClientTlsConfig::new().with_native_roots())?;
+    }
+    let channel = endpoint.connect().await?;
+    Ok(channel)
+}
+
+async fn reflection_request(
+    client: &mut ServerReflectionClient<Channel>,
+    request: MessageRequest,
+) -> anyhow::Result<MessageResponse> {
+    let request = ServerReflectionRequest {
+        host: String::new(),
+        message_request: Some(request),
+    };
+
+    let mut responses = client
+        .server_reflection_info(stream::iter([request]))
+        .await?
+        .into_inner();
+
+    let response = responses
+        .message()
+        .await?
+        .and_then(|r| r.message_response)
+        .context("Empty reflection response")?;
+
+    match response {
+        MessageResponse::ErrorResponse(e) => {
+            anyhow::bail!("Reflection failed: {}", e.error_message)
+        }
+        response => Ok(response),
+    }
+}
+
+/// Builds the descriptors of every service the server exposes through the
+/// reflection service, fetching imported files as well.
+pub async fn reflect_descriptors(channel: Channel) -> anyhow::Result<DescriptorPool> {
+    let mut client = ServerReflectionClient::new(channel);
+
+    let services =
+        match reflection_request(&mut client, MessageRequest::ListServices(String::new())).await? {
+            MessageResponse::ListServicesResponse(list) => list.service,
+            _ => anyhow::bail!("Unexpected reflection response"),
+        };
+
+    let mut files = Vec::new();
+    let mut seen = HashSet::new();
+    let mut pending = services
+        .into_iter()
+        .filter(|s| !s.name.starts_with("grpc.reflection."))
+        .map(|s| MessageRequest::FileContainingSymbol(s.name))
+        .collect::<VecDeque<_>>();
+
+    while let Some(request) = pending.pop_front() {
+        let MessageResponse::FileDescriptorResponse(res) =
+            reflection_request(&mut client, request).await?
+        else {
+            anyhow::bail!("Unexpected reflection response");
+        };
+
+        for encoded in res.file_descriptor_proto {
+            let file = prost_reflect::prost_types::FileDescriptorProto::decode(encoded.as_slice())?;
+            if !seen.insert(file.name().to_string()) {
+                continue;
+            }
+            for dep in file.dependency.iter() {
+                if !seen.contains(dep) {
+                    pending.push_back(MessageRequest::FileByFilename(dep.clone()));
+                }
+            }
+            files.push(file);
+        }
+    }
+
+    let mut pool = DescriptorPool::new();
+    pool.add_file_descriptor_protos(files)?;
+    Ok(pool)
+}
+
+pub async fn load_descriptors(
+    url: &str,
+    proto_files: &[PathBuf],
+) -> anyhow::Result<DescriptorPool> {
+    if proto_files.is_empty() {
+        reflect_descriptors(connect(url).await?).await
+    } else {
+        parse_proto_files(proto_files)
+    }
+}
+
+pub fn list_methods(pool: &DescriptorPool) -> Vec<GrpcMethod> {
+    pool.services()
+        .flat_map(|service| {
+            service
+                .methods()
+                .map(|method| GrpcMethod {
+                    service: service.full_name().to_string(),
+                    method: method.name().to_string(),
+                    client_streaming: method.is_client_streaming(),
+                    server_streaming: method.is_server_streaming(),
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// JSON of the method's input message with every field set to its default,
+/// used as a starting point when editing the request message.
+pub fn message_template(pool: &DescriptorPool, method: &GrpcMethod) -> Option<String> {
+    let input = pool
+        .get_service_by_name(&method.service)?
+        .methods()
+        .find(|m| m.name() == method.method)?
+        .input();
+
+    let options = SerializeOptions::new().skip_default_fields(false);
+    let mut serializer = serde_json::Serializer::pretty(Vec::new());
+    DynamicMessage::new(input)
+        .serialize_with_options(&mut serializer, &options)
+        .ok()?;
+    String::from_utf8(serializer.into_inner()).ok()
+}
+
+/// Encodes any [`DynamicMessage`] and decodes messages of a known type.
+#[derive(Debug, Clone)]
+pub struct DynamicCodec {
+    decode: MessageDescriptor,
+}
+
+impl DynamicCodec {
+    pub fn new(decode: MessageDescriptor) -> Self {
+        Self { decode }
+    }
+}
+
+pub struct DynamicEncoder;
+
+pub struct DynamicDecoder(MessageDescriptor);
+
+impl Codec for DynamicCodec {
+    type Encode = DynamicMessage;
+    type Decode = DynamicMessage;
+    type Encoder = DynamicEncoder;
+    type Decoder = DynamicDecoder;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        DynamicEncoder
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        DynamicDecoder(self.decode.clone())
+    }
+}
+
+impl Encoder for DynamicEncoder {
+    type Item = DynamicMessage;
+    type Error = Status;
+
+    fn encode(&mut self, item: Self::Item, dst: &mut EncodeBuf<'_>) -> Result<(), Self::Error> {
+        item.encode(dst)
+            .map_err(|e| Status::internal(format!("Failed to encode message: {}", e)))
+    }
+}
+
+impl Decoder for DynamicDecoder {
+    type Item = DynamicMessage;
+    type Error = Status;
+
+    fn decode(&mut self, src: &mut DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error> {
+        DynamicMessage::decode(self.0.clone(), src)
+            .map(Some)
+            .map_err(|e| Status::internal(format!("Failed to decode message: {}", e)))
+    }
+}
+
+/// A call ready to be sent, see [`crate::transformers::grpc::transform_grpc`].
+#[derive(Debug, Clone)]
+pub struct GrpcCall {
+    pub url: String,
+    pub method: MethodDescriptor,
+    pub message: DynamicMessage,
+    pub metadata: MetadataMap,
+}
+
+impl GrpcCall {
+    /// The call as a HAR request posting the JSON message to the path of the
+    /// method, kept in the history
+    pub fn sent(&self) -> SentRequest {
+        let text = to_json(&self.message).unwrap_or_default();
+        let headers = self
+            .metadata
+            .clone()
+            .into_headers()
+            .iter()
+            .map(|(name, value)| NameValue {
+                name: name.to_string(),
+                value: String::from_utf8_lossy(value.as_bytes()).to_string(),
+            })
+            .collect();
+
+        let request = HarRequest {
+            method: "POST".to_string(),
+            url: format!(
+                "{}/{}/{}",
+                self.url.trim_end_matches('/'),
+                self.method.parent_service().full_name(),
+                self.method.name()
+            ),
+            http_version: "HTTP/2.0".to_string(),
+            cookies: Vec::new(),
+            headers,
+            query_string: Vec::new(),
+            body_size: text.len() as i64,
+            post_data: Some(PostData {
+                mime_type: "application/json".to_string(),
+                params: Vec::new(),
+                text,
+                comment: None,
+            }),
+            headers_size: -1,
+        };
+        SentRequest {
+            started: Local::now(),
+            request,
+        }
+    }
+}
+
+fn to_json(message: &DynamicMessage) -> anyhow::Result<String> {
+    Ok(serde_json::to_string_pretty(message)?)
+}
+
+fn status_headers(mut headers: HeaderMap, status: &Status) -> HeaderMap {
+    headers.insert(GRPC_STATUS, HeaderValue::from(status.code() as i32));
+    if let Ok(message) = HeaderValue::from_str(status.message()) {
+        headers.insert(GRPC_MESSAGE, message);
+    }
+    headers
+}
+
+fn status_body(status: &Status) -> String {
+    let body = serde_json::json!({
+        "code": format!("{:?}", status.code()),
+        "message": status.message(),
+    });
+    serde_json::to_string_pretty(&body).unwrap_or_default()
+}
+
+fn json_response(headers: HeaderMap, body: String, duration: Duration) -> Response {
+    let mut headers = headers;
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+    let data = body.into_bytes();
+    Response {
+        // gRPC always answers with HTTP 200, the call status is in `grpc-status`
+        status: StatusCode::OK,
+        headers,
+        size_bytes: data.len(),
+        body: ResponseBody {
+            content_type: ContentType::Json,
+            data,
+        },
+        duration,
+    }
+}
+
+fn status_response(status: &Status, duration: Duration) -> Response {
+    let headers = status_headers(status.metadata().clone().into_headers(), status);
+    json_response(headers, status_body(status), duration)
+}
+
+fn ok_headers(metadata: MetadataMap) -> HeaderMap {
+    status_headers(metadata.into_headers(), &Status::ok(""))
+}
+
+fn path(method: &MethodDescriptor) -> anyhow::Result<PathAndQuery> {
+    let path = format!("/{}/{}", method.parent_service().full_name(), method.name());
+    Ok(PathAndQuery::from_str(&path)?)
+}
+
+enum CallState {
+    Start(GrpcCall),
+    Streaming {
+        messages: tonic::Streaming<DynamicMessage>,
+        index: usize,
+    },
+    Done,
+}
+
+fn message_event(index: usize, data: String) -> SseEvent {
+    SseEvent {
+        event: DEFAULT_EVENT.to_string(),
+        id: Some(index.to_string()),
+        data,
+        retry: None,
+        received: Local::now(),
+    }
+}
+
+async fn start_call(call: GrpcCall) -> anyhow::Result<(ResponseEvent, CallState)> {
+    let start = Instant::now();
+    let GrpcCall {
+        url,
+        method,
+        message,
+        metadata,
+    } = call;
+
+    let mut grpc = tonic::client::Grpc::new(connect(&url).await?);
+    grpc.ready().await?;
+
+    let codec = DynamicCodec::new(method.output());
+    let mut request = tonic::Request::new(message);
+    *request.metadata_mut() = metadata;
+    let path = path(&method)?;
+
+    if method.is_server_streaming() {
+        match grpc.server_streaming(request, path, codec).await {
+            Ok(response) => {
+                let (metadata, messages, _) = response.into_parts();
+                let head = StreamHead {
+                    status: StatusCode::OK,
+                    headers: ok_headers(metadata),
+                    duration: start.elapsed(),
+                };
+                let next = CallState::Streaming { messages, index: 0 };
+                Ok((ResponseEvent::StreamOpened(head), next))
+            }
+            Err(status) => {
+                let response = status_response(&status, start.elapsed());
+                Ok((ResponseEvent::Completed(response), CallState::Done))
+            }
+        }
+    } else {
+        let response = match grpc.unary(request, path, codec).await {
+            Ok(response) => {
+                let (metadata, message, _) = response.into_parts();
+                json_response(ok_headers(metadata), to_json(&message)?, start.elapsed())
+            }
+            Err(status) => status_response(&status, start.elapsed()),
+        };
+        Ok((ResponseEvent::Completed(response), CallState::Done))
+    }
+}
+
+/// Sends a unary or server streaming call. Unary calls and failed calls
+/// produce a single [`ResponseEvent::Completed`] while streamed messages are
+/// reported as events with their JSON mapping as data.
+pub fn call(call: GrpcCall) -> impl Stream<Item = anyhow::Result<ResponseEvent>> + Send + 'static {
+    stream::unfold(CallState::Start(call), |state| async move {
+        match state {
+            CallState::Start(call) => match start_call(call).await {
+                Ok((event, next)) => Some((Ok(event), next)),
+                Err(e) => Some((Err(e), CallState::Done)),
+            },
+            CallState::Streaming {
+                mut messages,
+                index,
+            } => match messages.message().await {
+                Ok(Some(message)) => {
+                    let event = to_json(&message).map(|data| message_event(index, data));
+                    let next = CallState::Streaming {
+                        messages,
+                        index: index + 1,
+                    };
+                    Some((event.map(ResponseEvent::Event), next))
+                }
+                Ok(None) => Some((Ok(ResponseEvent::StreamClosed), CallState::Done)),
+                Err(status) => {
+                    let event = SseEvent {
+                        event: "error".to_string(),
+                        ..message_event(index, status_body(&status))
+                    };
+                    Some((Ok(ResponseEvent::Event(event)), CallState::Done))
+                }
+            },
+            CallState::Done => None,
+        }
+    })
+}
+
+/// Runs a call to completion. Messages of a server stream are collected into
+/// a JSON array so the response can be checked with assertions.
+pub async fn execute(call: GrpcCall) -> anyhow::Result<Response> {
+    let start = Instant::now();
+    let mut events: BoxStream<_> = self::call(call).boxed();
+
+    let mut head = None;
+    let mut messages = Vec::new();
+    let mut failed = None;
+    while let Some(event) = events.next().await {
+        match event? {
+            ResponseEvent::Completed(response) => return Ok(response),
+            ResponseEvent::StreamOpened(stream_head) => head = Some(stream_head),
+            ResponseEvent::Event(event) if event.event == DEFAULT_EVENT => {
+                messages.push(serde_json::from_str::<serde_json::Value>(&event.data)?);
+            }
+            ResponseEvent::Event(event) => failed = Some(event.data),
+            ResponseEvent::StreamClosed => (),
+        }
+    }
+
+    let mut headers = head.map(|h| h.headers).unwrap_or_default();
+    if let Some(error) = failed {
+        let error: serde_json::Value = serde_json::from_str(&error)?;
+        let code = error["code"].as_str().unwrap_or_default();
+        let code = status_code(code);
+        headers.insert(GRPC_STATUS, HeaderValue::from(code));
+    }
+
+    let body = serde_json::to_string_pretty(&messages)?;
+    Ok(json_response(headers, body, start.elapsed()))
+}
+
+fn status_code(name: &str) -> i32 {
+    (0..=16)
+        .map(tonic::Code::from_i32)
+        .find(|code| format!("{:?}", code) == name)
+        .unwrap_or(tonic::Code::Unknown) as i32
+}
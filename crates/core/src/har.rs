@@ -0,0 +1,471 @@
+use std::path::PathBuf;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, Local};
+use reqwest::header::{HeaderMap, CONTENT_TYPE};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::client::Response;
+use crate::http::environment::{Environment, REDACTED};
+use crate::persistence::files::write_atomic;
+
+pub const HAR_VERSION: &str = "1.2";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Har {
+    pub log: Log,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Log {
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub creator: Creator,
+    #[serde(default)]
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Creator {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub version: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Entry {
+    #[serde(default)]
+    pub started_date_time: String,
+    #[serde(default)]
+    pub time: f64,
+    pub request: HarRequest,
+    pub response: Option<HarResponse>,
+    #[serde(default)]
+    pub cache: Cache,
+    #[serde(default)]
+    pub timings: Timings,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarRequest {
+    pub method: String,
+    pub url: String,
+    #[serde(default)]
+    pub http_version: String,
+    #[serde(default)]
+    pub cookies: Vec<NameValue>,
+    #[serde(default)]
+    pub headers: Vec<NameValue>,
+    #[serde(default)]
+    pub query_string: Vec<NameValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_data: Option<PostData>,
+    #[serde(default = "unknown")]
+    pub headers_size: i64,
+    #[serde(default = "unknown")]
+    pub body_size: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PostData {
+    #[serde(default)]
+    pub mime_type: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub params: Vec<Param>,
+    #[serde(default)]
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Param {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NameValue {
+    pub name: String,
+    #[serde(default)]
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarResponse {
+    pub status: u16,
+    #[serde(default)]
+    pub status_text: String,
+    #[serde(default)]
+    pub http_version: String,
+    #[serde(default)]
+    pub cookies: Vec<NameValue>,
+    #[serde(default)]
+    pub headers: Vec<NameValue>,
+    #[serde(default)]
+    pub content: Content,
+    #[serde(default, rename = "redirectURL")]
+    pub redirect_url: String,
+    #[serde(default = "unknown")]
+    pub headers_size: i64,
+    #[serde(default = "unknown")]
+    pub body_size: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Content {
+    #[serde(default)]
+    pub size: i64,
+    #[serde(default)]
+    pub mime_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Cache {}
+
+/// Only the total duration is measured, it is reported as waiting time and
+/// the phases that are not measured are `-1` as the spec requires
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Timings {
+    pub blocked: f64,
+    pub dns: f64,
+    pub connect: f64,
+    pub send: f64,
+    pub wait: f64,
+    pub receive: f64,
+    pub ssl: f64,
+}
+
+impl Default for Timings {
+    fn default() -> Self {
+        Self {
+            blocked: -1.0,
+            dns: -1.0,
+            connect: -1.0,
+            send: 0.0,
+            wait: 0.0,
+            receive: 0.0,
+            ssl: -1.0,
+        }
+    }
+}
+
+fn unknown() -> i64 {
+    -1
+}
+
+fn headers(headers: &HeaderMap) -> Vec<NameValue> {
+    headers
+        .iter()
+        .map(|(name, value)| NameValue {
+            name: name.to_string(),
+            value: String::from_utf8_lossy(value.as_bytes()).to_string(),
+        })
+        .collect()
+}
+
+fn header_str(headers: &HeaderMap, name: impl reqwest::header::AsHeaderName) -> String {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Snapshot of a request taken right before it is sent, combined with the
+/// response into a HAR entry
+#[derive(Debug, Clone)]
+pub struct SentRequest {
+    pub started: DateTime<Local>,
+    pub request: HarRequest,
+}
+
+impl SentRequest {
+    pub fn new(req: &reqwest::Request) -> Self {
+        let mime_type = header_str(req.headers(), CONTENT_TYPE);
+        // Multipart and file bodies are streamed and not available here
+        let post_data = req.body().map(|body| match body.as_bytes() {
+            Some(bytes) => PostData {
+                mime_type,
+                params: Vec::new(),
+                text: String::from_utf8_lossy(bytes).to_string(),
+                comment: None,
+            },
+            None => PostData {
+                mime_type,
+                params: Vec::new(),
+                text: String::new(),
+                comment: Some("Streamed body is not recorded".to_string()),
+            },
+        });
+        let body_size = req
+            .body()
+            .and_then(|b| b.as_bytes())
+            .map(|b| b.len() as i64)
+            .unwrap_or(if post_data.is_some() { -1 } else { 0 });
+
+        let request = HarRequest {
+            method: req.method().to_string(),
+            url: req.url().to_string(),
+            http_version: format!("{:?}", req.version()),
+            cookies: Vec::new(),
+            headers: headers(req.headers()),
+            query_string: req
+                .url()
+                .query_pairs()
+                .map(|(name, value)| NameValue {
+                    name: name.to_string(),
+                    value: value.to_string(),
+                })
+                .collect(),
+            post_data,
+            headers_size: -1,
+            body_size,
+        };
+
+        Self {
+            started: Local::now(),
+            request,
+        }
+    }
+
+    pub fn entry(self, response: &Response) -> Entry {
+        let body = &response.body;
+        let (text, encoding) = if body.content_type.is_text() {
+            (String::from_utf8_lossy(&body.data).to_string(), None)
+        } else {
+            (STANDARD.encode(&body.data), Some("base64".to_string()))
+        };
+
+        let time = response.duration.as_secs_f64() * 1000.0;
+        let http_version = self.request.http_version.clone();
+
+        Entry {
+            started_date_time: self.started.to_rfc3339(),
+            time,
+            request: self.request,
+            response: Some(HarResponse {
+                status: response.status.as_u16(),
+                status_text: response
+                    .status
+                    .canonical_reason()
+                    .unwrap_or_default()
+                    .to_string(),
+                http_version,
+                cookies: Vec::new(),
+                headers: headers(&response.headers),
+                content: Content {
+                    size: body.data.len() as i64,
+                    mime_type: header_str(&response.headers, CONTENT_TYPE),
+                    text: Some(text),
+                    encoding,
+                },
+                redirect_url: header_str(&response.headers, reqwest::header::LOCATION),
+                headers_size: -1,
+                body_size: response.size_bytes as i64,
+            }),
+            cache: Cache::default(),
+            timings: Timings {
+                wait: time,
+                ..Timings::default()
+            },
+        }
+    }
+}
+
+fn is_auth_header(name: &str) -> bool {
+    name.eq_ignore_ascii_case("authorization") || name.eq_ignore_ascii_case("proxy-authorization")
+}
+
+/// The credentials of an auth header hold a secret, `Basic` credentials are
+/// decoded as the secret can be anywhere in them
+fn has_secret_credentials(value: &str, env: &Environment) -> bool {
+    let basic = value
+        .split_once(' ')
+        .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("basic"))
+        .and_then(|(_, credentials)| STANDARD.decode(credentials.trim()).ok())
+        .map(|credentials| String::from_utf8_lossy(&credentials).to_string());
+    env.contains_secret(value) || basic.is_some_and(|credentials| env.contains_secret(&credentials))
+}
+
+impl Entry {
+    /// Replaces the values of the secret variables of the environment
+    /// wherever they show up in the entry
+    pub fn redact(&mut self, env: &Environment) {
+        let request = &mut self.request;
+        request.url = env.redact(&request.url);
+        for header in request.headers.iter_mut() {
+            header.value = match is_auth_header(&header.name) {
+                true if has_secret_credentials(&header.value, env) => REDACTED.to_string(),
+                _ => env.redact(&header.value),
+            };
+        }
+        let pairs = request
+            .query_string
+            .iter_mut()
+            .chain(request.cookies.iter_mut());
+        for pair in pairs {
+            pair.value = env.redact(&pair.value);
+        }
+        if let Some(post_data) = &mut request.post_data {
+            post_data.text = env.redact(&post_data.text);
+        }
+
+        if let Some(response) = &mut self.response {
+            for pair in response.headers.iter_mut() {
+                pair.value = env.redact(&pair.value);
+            }
+            if let Some(text) = &mut response.content.text {
+                *text = env.redact(text);
+            }
+        }
+    }
+}
+
+impl Har {
+    pub fn new(entries: Vec<Entry>) -> Self {
+        Self {
+            log: Log {
+                version: HAR_VERSION.to_string(),
+                creator: Creator {
+                    name: "Sanchaar".to_string(),
+                    version: env!("CARGO_PKG_VERSION").to_string(),
+                },
+                entries,
+            },
+        }
+    }
+}
+
+pub async fn save_har(path: PathBuf, har: &Har) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+    let encoded = serde_json::to_string_pretty(har)?;
+    write_atomic(&path, encoded).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+    use crate::http::{KeyValList, KeyValue};
+
+    fn env() -> Environment {
+        let var = |name: &str, value: &str| KeyValue {
+            disabled: false,
+            name: name.to_string(),
+            value: value.to_string(),
+        };
+        let mut env = Environment::new("dev".to_string());
+        env.variables = KeyValList::from(vec![
+            var("user", "alice"),
+            var("password", "p@ss word/1"),
+            var("token", "t0k3n"),
+        ]);
+        env.secrets = BTreeSet::from(["password".to_string(), "token".to_string()]);
+        env
+    }
+
+    fn sent(builder: reqwest::RequestBuilder) -> Entry {
+        let sent = SentRequest::new(&builder.build().unwrap());
+        Entry {
+            started_date_time: sent.started.to_rfc3339(),
+            time: 0.0,
+            request: sent.request,
+            response: None,
+            cache: Cache::default(),
+            timings: Timings::default(),
+        }
+    }
+
+    fn header<'a>(entry: &'a Entry, name: &str) -> &'a str {
+        let header = entry.request.headers.iter().find(|h| h.name == name);
+        &header.unwrap().value
+    }
+
+    #[test]
+    fn redacts_basic_auth_credentials() {
+        let client = reqwest::Client::new();
+        let mut entry = sent(
+            client
+                .get("https://example.com")
+                .basic_auth("alice", Some("p@ss word/1")),
+        );
+        entry.redact(&env());
+        assert_eq!(header(&entry, "authorization"), REDACTED);
+
+        // Credentials without secrets are kept
+        let mut entry = sent(
+            client
+                .get("https://example.com")
+                .basic_auth("alice", Some("open")),
+        );
+        entry.redact(&env());
+        assert!(header(&entry, "authorization").starts_with("Basic "));
+    }
+
+    #[test]
+    fn redacts_bearer_and_proxy_auth() {
+        let client = reqwest::Client::new();
+        let mut entry = sent(
+            client
+                .get("https://example.com")
+                .bearer_auth("t0k3n")
+                .header("proxy-authorization", "Basic YWxpY2U6dDBrM24="),
+        );
+        entry.redact(&env());
+        assert_eq!(header(&entry, "authorization"), REDACTED);
+        assert_eq!(header(&entry, "proxy-authorization"), REDACTED);
+    }
+
+    #[test]
+    fn redacts_encoded_secrets_in_urls() {
+        let client = reqwest::Client::new();
+        let mut entry = sent(
+            client
+                .get("https://example.com/p%40ss%20word%2F1/items")
+                .query(&[("key", "p@ss word/1"), ("user", "alice")]),
+        );
+        entry.redact(&env());
+
+        let request = &entry.request;
+        assert!(!request.url.contains("p%40ss"), "{}", request.url);
+        assert!(request.url.contains("user=alice"), "{}", request.url);
+        let key = request.query_string.iter().find(|q| q.name == "key");
+        assert_eq!(key.unwrap().value, REDACTED);
+    }
+
+    #[test]
+    fn redacts_base64_secrets_in_bodies() {
+        let client = reqwest::Client::new();
+        let body = format!(r#"{{"token": "{}"}}"#, STANDARD.encode("t0k3n"));
+        let mut entry = sent(client.post("https://example.com").body(body));
+        entry.redact(&env());
+
+        let text = &entry.request.post_data.as_ref().unwrap().text;
+        assert_eq!(text, &format!(r#"{{"token": "{}"}}"#, REDACTED));
+    }
+}
@@ -1,10 +1,16 @@
 use super::environment::{Environment, EnvironmentKey};
+use super::KeyValList;
 use crate::new_id_type;
 use crate::{
     http::environment::Environments,
+    persistence::diagnostics::Diagnostic,
     persistence::{HCL_EXTENSION, REQUESTS, SCRIPTS, TS_EXTENSION},
 };
-use std::{ops::Not, path::PathBuf};
+use std::{
+    collections::HashMap,
+    ops::Not,
+    path::{Path, PathBuf},
+};
 
 new_id_type! {
     pub struct RequestId;
@@ -25,6 +31,8 @@ pub struct RequestRef {
     pub id: RequestId,
     pub name: String,
     pub path: PathBuf,
+    /// Names of the saved response examples, sorted
+    pub examples: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -55,6 +63,14 @@ pub struct Collection {
     pub scripts: Vec<Script>,
     pub active_environment: Option<EnvironmentKey>,
     pub default_env: Option<EnvironmentKey>,
+    /// Variables shared by every environment of the collection, saved with
+    /// the collection
+    pub variables: KeyValList,
+    /// Files of the collection which failed to parse
+    pub diagnostics: Vec<Diagnostic>,
+    /// The collection file failed to parse and defaults are used in its
+    /// place, it is not saved over until it is fixed
+    pub fallback: bool,
 }
 
 impl Collection {
@@ -75,6 +91,9 @@ impl Collection {
             expanded: false,
             active_environment: default_env,
             default_env,
+            variables: KeyValList::new(),
+            diagnostics: Vec::new(),
+            fallback: false,
         }
     }
 
@@ -138,11 +157,121 @@ impl Collection {
         recurse(self.entries.iter(), id)
     }
 
+    /// Paths of the expanded folders, at any depth
+    pub fn expanded_folders(&self) -> Vec<PathBuf> {
+        self.iter()
+            .filter_map(|entry| match entry {
+                Entry::Folder(folder) if folder.expanded => Some(folder.path.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn expand_folders(&mut self, paths: &[PathBuf]) {
+        fn recurse(entries: &mut [Entry], paths: &[PathBuf]) {
+            for entry in entries {
+                if let Entry::Folder(folder) = entry {
+                    folder.expanded = paths.contains(&folder.path);
+                    recurse(&mut folder.entries, paths);
+                }
+            }
+        }
+        recurse(&mut self.entries, paths)
+    }
+
+    /// Replaces the entries with the ones read from disk, entries at the
+    /// same path keep their id and expanded state so open tabs stay linked
+    pub fn merge_entries(&mut self, mut entries: Vec<Entry>) {
+        let mut requests = HashMap::new();
+        let mut folders = HashMap::new();
+        for entry in self.iter() {
+            match entry {
+                Entry::Item(item) => {
+                    requests.insert(item.path.clone(), item.id);
+                }
+                Entry::Folder(folder) => {
+                    folders.insert(folder.path.clone(), (folder.id, folder.expanded));
+                }
+            }
+        }
+
+        fn recurse(
+            entries: &mut [Entry],
+            requests: &HashMap<PathBuf, RequestId>,
+            folders: &HashMap<PathBuf, (FolderId, bool)>,
+        ) {
+            for entry in entries {
+                match entry {
+                    Entry::Item(item) => {
+                        if let Some(id) = requests.get(&item.path) {
+                            item.id = *id;
+                        }
+                    }
+                    Entry::Folder(folder) => {
+                        if let Some((id, expanded)) = folders.get(&folder.path) {
+                            folder.id = *id;
+                            folder.expanded = *expanded;
+                        }
+                        recurse(&mut folder.entries, requests, folders);
+                    }
+                }
+            }
+        }
+        recurse(&mut entries, &requests, &folders);
+        self.entries = entries;
+    }
+
+    /// Replaces the environments with the ones read from disk, environments
+    /// with the same name keep their key
+    pub fn merge_environments(&mut self, environments: Environments) {
+        let mut kept = Vec::new();
+        for (_, env) in environments.entries() {
+            let key = match self.environments.find_by_name(&env.name) {
+                Some(key) => {
+                    self.environments.update(key, env.clone());
+                    key
+                }
+                None => self.environments.insert(env.clone()),
+            };
+            kept.push(key);
+        }
+
+        let removed = self
+            .environments
+            .entries()
+            .map(|(key, _)| *key)
+            .filter(|key| !kept.contains(key))
+            .collect::<Vec<_>>();
+        for key in removed {
+            self.environments.remove(key);
+        }
+        self.default_env = self.default_env.filter(|key| kept.contains(key));
+        if self
+            .active_environment
+            .is_some_and(|key| !kept.contains(&key))
+        {
+            self.active_environment = self.default_env;
+        }
+    }
+
+    pub fn find_request(&self, path: &Path) -> Option<RequestId> {
+        self.iter().find_map(|entry| match entry {
+            Entry::Item(item) if item.path == path => Some(item.id),
+            _ => None,
+        })
+    }
+
     pub fn get_active_environment(&self) -> Option<&Environment> {
         self.active_environment
             .and_then(|key| self.environments.get(key))
     }
 
+    /// Variables requests of the collection are resolved with, see
+    /// [`Environment::scoped`]
+    pub fn scoped_environment(&self, globals: &Environment) -> Option<Environment> {
+        Environment::scoped(self.get_active_environment(), &self.variables, globals)
+    }
+
     pub fn rename_request(&mut self, id: RequestId, name: &str) -> Option<(PathBuf, PathBuf)> {
         for entry in self.iter_mut() {
             if let Entry::Item(item) = entry {
@@ -158,6 +287,20 @@ impl Collection {
         None
     }
 
+    pub fn add_example(&mut self, id: RequestId, name: &str) {
+        if let Some(item) = self.get_ref_mut(id) {
+            if let Err(idx) = item.examples.binary_search_by(|e| e.as_str().cmp(name)) {
+                item.examples.insert(idx, name.to_string());
+            }
+        }
+    }
+
+    pub fn remove_example(&mut self, id: RequestId, name: &str) {
+        if let Some(item) = self.get_ref_mut(id) {
+            item.examples.retain(|e| e != name);
+        }
+    }
+
     pub fn rename_folder(&mut self, id: FolderId, name: &str) -> Option<(PathBuf, PathBuf)> {
         for entry in self.iter_mut() {
             if let Entry::Folder(item) = entry {
@@ -184,6 +327,17 @@ impl Collection {
         None
     }
 
+    fn get_ref_mut(&mut self, id: RequestId) -> Option<&mut RequestRef> {
+        for entry in self.iter_mut() {
+            if let Entry::Item(item) = entry {
+                if item.id == id {
+                    return Some(item);
+                }
+            }
+        }
+        None
+    }
+
     pub fn delete_folder(&mut self, folder_id: FolderId) -> Option<PathBuf> {
         fn recurse(entries: &mut Vec<Entry>, id: FolderId) -> Option<PathBuf> {
             let mut path = None;
@@ -322,6 +476,9 @@ impl Default for Collection {
             scripts: Vec::new(),
             active_environment: None,
             default_env: None,
+            variables: KeyValList::new(),
+            diagnostics: Vec::new(),
+            fallback: false,
         }
     }
 }
@@ -1,6 +1,12 @@
-use std::collections::HashMap;
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use reqwest::Url;
 
 use crate::new_id_type;
+use crate::utils::percent_encode;
 
 use super::KeyValList;
 
@@ -48,6 +54,10 @@ impl Environments {
         self.envs.iter()
     }
 
+    pub fn entries_mut(&mut self) -> impl Iterator<Item = (&EnvironmentKey, &mut Environment)> {
+        self.envs.iter_mut()
+    }
+
     pub fn is_empty(&self) -> bool {
         self.envs.is_empty()
     }
@@ -69,10 +79,43 @@ impl Environments {
     }
 }
 
+/// Shown in place of secret values
+pub const REDACTED: &str = "********";
+
+/// The value along with its forms in URL paths, URL queries and base64
+fn encoded_forms(value: &str) -> Vec<String> {
+    let mut forms = vec![
+        value.to_string(),
+        percent_encode(value),
+        STANDARD.encode(value),
+    ];
+
+    let mut url = Url::parse("http://localhost/").expect("valid URL");
+    if let Ok(mut segments) = url.path_segments_mut() {
+        segments.clear().push(value);
+    }
+    url.query_pairs_mut().append_pair("", value);
+    forms.push(url.path()[1..].to_string());
+    forms.extend(
+        url.query()
+            .and_then(|q| q.strip_prefix('='))
+            .map(str::to_string),
+    );
+
+    forms.retain(|form| !form.is_empty());
+    forms
+}
+
+/// Name of the environment holding the global variables
+pub const GLOBAL_ENV: &str = "Global";
+
 #[derive(Debug, Clone)]
 pub struct Environment {
     pub name: String,
     pub variables: KeyValList,
+    /// Variables whose values live in the local secret store and are never
+    /// written to the environment file
+    pub secrets: BTreeSet<String>,
 }
 
 impl Environment {
@@ -80,13 +123,217 @@ impl Environment {
         Self {
             name,
             variables: KeyValList::new(),
+            secrets: BTreeSet::new(),
+        }
+    }
+
+    pub fn is_secret(&self, name: &str) -> bool {
+        self.secrets.contains(name)
+    }
+
+    /// Copy without the secret variables, rendering text with it keeps the
+    /// `{{secret}}` references
+    pub fn without_secrets(&self) -> Self {
+        let variables = self
+            .variables
+            .iter()
+            .filter(|kv| !self.is_secret(&kv.name))
+            .cloned()
+            .collect();
+        Self {
+            name: self.name.clone(),
+            variables: KeyValList::from(variables),
+            secrets: BTreeSet::new(),
         }
     }
 
+    /// Replaces the values of the secret variables found in the text, as
+    /// they are or encoded the way URLs and base64 carry them
+    pub fn redact(&self, text: &str) -> String {
+        let forms = self
+            .variables
+            .iter()
+            .filter(|kv| self.is_secret(&kv.name) && !kv.value.is_empty())
+            .flat_map(|kv| encoded_forms(&kv.value))
+            .collect::<BTreeSet<_>>();
+        let mut forms = forms.into_iter().collect::<Vec<_>>();
+        // A secret containing another one is replaced whole
+        forms.sort_by_key(|form| Reverse(form.len()));
+
+        forms
+            .iter()
+            .fold(text.to_string(), |text, form| text.replace(form, REDACTED))
+    }
+
+    /// Whether the text holds a secret value in any form [`Self::redact`]
+    /// replaces
+    pub fn contains_secret(&self, text: &str) -> bool {
+        self.redact(text) != text
+    }
+
     pub fn get(&self, name: &str) -> Option<&str> {
         self.variables
             .iter()
             .find(|kv| kv.name == name)
             .map(|kv| kv.value.as_str())
     }
+
+    /// Environment used to resolve variables. The environment wins over the
+    /// variables of the collection, which win over the global variables
+    /// shared by all collections. Without an environment the other scopes
+    /// are used on their own.
+    pub fn scoped(
+        env: Option<&Environment>,
+        collection: &KeyValList,
+        globals: &Environment,
+    ) -> Option<Environment> {
+        let plain = BTreeSet::new();
+        let scopes = [
+            env.map(|env| (&env.variables, &env.secrets)),
+            Some((collection, &plain)),
+            Some((&globals.variables, &globals.secrets)),
+        ];
+
+        let mut variables = Vec::new();
+        let mut secrets = BTreeSet::new();
+        let mut seen = HashSet::new();
+        for (vars, scope_secrets) in scopes.into_iter().flatten() {
+            for kv in vars.iter().filter(|kv| seen.insert(kv.name.clone())) {
+                // Secret only when the value used comes from a secret
+                if scope_secrets.contains(&kv.name) {
+                    secrets.insert(kv.name.clone());
+                }
+                variables.push(kv.clone());
+            }
+        }
+
+        if env.is_none() && variables.is_empty() {
+            return None;
+        }
+        Some(Self {
+            name: env.unwrap_or(globals).name.clone(),
+            variables: KeyValList::from(variables),
+            secrets,
+        })
+    }
+
+    /// Values set while running a request, they win over every scope of
+    /// [`Environment::scoped`] and are never secret
+    pub fn with_runtime(mut self, runtime: &KeyValList) -> Self {
+        for value in runtime.iter() {
+            self.secrets.remove(&value.name);
+            if let Some(kv) = self.variables.iter_mut().find(|kv| kv.name == value.name) {
+                kv.value = value.value.clone();
+                continue;
+            }
+            self.variables.push(value.clone());
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::KeyValue;
+
+    fn kv(name: &str, value: &str) -> KeyValue {
+        KeyValue {
+            disabled: false,
+            name: name.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    fn vars(pairs: &[(&str, &str)]) -> KeyValList {
+        KeyValList::from(pairs.iter().map(|(name, value)| kv(name, value)).collect())
+    }
+
+    fn env(name: &str, pairs: &[(&str, &str)], secrets: &[&str]) -> Environment {
+        Environment {
+            name: name.to_string(),
+            variables: vars(pairs),
+            secrets: secrets.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    fn globals(pairs: &[(&str, &str)]) -> Environment {
+        env(GLOBAL_ENV, pairs, &[])
+    }
+
+    #[test]
+    fn each_scope_overrides_the_next() {
+        let runtime = vars(&[("a", "runtime")]);
+        let dev = env("dev", &[("a", "env"), ("b", "env")], &[]);
+        let collection = vars(&[("a", "col"), ("b", "col"), ("c", "col")]);
+        let globals = globals(&[("a", "g"), ("b", "g"), ("c", "g"), ("d", "g")]);
+
+        let scoped = Environment::scoped(Some(&dev), &collection, &globals)
+            .unwrap()
+            .with_runtime(&runtime);
+
+        assert_eq!(scoped.name, "dev");
+        assert_eq!(scoped.get("a"), Some("runtime"));
+        assert_eq!(scoped.get("b"), Some("env"));
+        assert_eq!(scoped.get("c"), Some("col"));
+        assert_eq!(scoped.get("d"), Some("g"));
+        assert_eq!(scoped.variables.iter().count(), 4);
+    }
+
+    #[test]
+    fn collection_overrides_globals_without_environment() {
+        let collection = vars(&[("a", "col"), ("b", "col")]);
+        let globals = globals(&[("b", "g"), ("c", "g")]);
+
+        let scoped = Environment::scoped(None, &collection, &globals).unwrap();
+
+        assert_eq!(scoped.name, GLOBAL_ENV);
+        assert_eq!(scoped.get("a"), Some("col"));
+        assert_eq!(scoped.get("b"), Some("col"));
+        assert_eq!(scoped.get("c"), Some("g"));
+    }
+
+    #[test]
+    fn environment_overrides_globals() {
+        let dev = env("dev", &[("host", "dev.local")], &[]);
+        let globals = globals(&[("host", "example.com"), ("port", "80")]);
+
+        let scoped = Environment::scoped(Some(&dev), &KeyValList::new(), &globals).unwrap();
+
+        assert_eq!(scoped.get("host"), Some("dev.local"));
+        assert_eq!(scoped.get("port"), Some("80"));
+    }
+
+    #[test]
+    fn secrets_follow_the_value_used() {
+        let dev = env("dev", &[("token", "t0ken"), ("user", "ann")], &["token"]);
+        let globals = env(
+            GLOBAL_ENV,
+            &[("user", "bob"), ("key", "k3y"), ("token", "global")],
+            &["user", "key", "token"],
+        );
+
+        let scoped = Environment::scoped(Some(&dev), &KeyValList::new(), &globals).unwrap();
+        // The environment value of `user` is not secret, the global key is
+        assert!(scoped.is_secret("token"));
+        assert!(!scoped.is_secret("user"));
+        assert!(scoped.is_secret("key"));
+        assert_eq!(scoped.redact("t0ken ann k3y"), "******** ann ********");
+
+        let scoped = scoped.with_runtime(&vars(&[("token", "runtime")]));
+        assert_eq!(scoped.get("token"), Some("runtime"));
+        assert!(!scoped.is_secret("token"));
+    }
+
+    #[test]
+    fn nothing_to_resolve_without_variables() {
+        let none = KeyValList::new();
+        assert!(Environment::scoped(None, &none, &globals(&[])).is_none());
+
+        // An empty environment is still the one variables are reported for
+        let dev = env("dev", &[], &[]);
+        let scoped = Environment::scoped(Some(&dev), &none, &globals(&[])).unwrap();
+        assert_eq!(scoped.name, "dev");
+        assert!(scoped.variables.is_empty());
+    }
 }
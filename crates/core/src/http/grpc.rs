@@ -0,0 +1,46 @@
+use std::path::{Path, PathBuf};
+
+use crate::assertions::Assertions;
+
+use super::KeyValList;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GrpcRequest {
+    pub description: String,
+    pub url: String,
+    /// Files describing the service, server reflection is used when empty.
+    pub proto_files: Vec<PathBuf>,
+    pub service: String,
+    pub method: String,
+    /// Request message in its JSON mapping.
+    pub message: String,
+    pub metadata: KeyValList,
+    pub assertions: Assertions,
+}
+
+impl GrpcRequest {
+    pub fn uses_reflection(&self) -> bool {
+        self.proto_files.is_empty()
+    }
+
+    /// Proto files with relative paths resolved against `dir`, usually the
+    /// directory of the request file.
+    pub fn resolve_proto_files(&self, dir: &Path) -> Vec<PathBuf> {
+        self.proto_files.iter().map(|p| dir.join(p)).collect()
+    }
+}
+
+impl Default for GrpcRequest {
+    fn default() -> Self {
+        Self {
+            description: "gRPC request".to_string(),
+            url: "http://localhost:50051".to_string(),
+            proto_files: Vec::new(),
+            service: String::new(),
+            method: String::new(),
+            message: "{}".to_string(),
+            metadata: KeyValList::new(),
+            assertions: Assertions::default(),
+        }
+    }
+}
@@ -10,7 +10,9 @@ use self::environment::EnvironmentKey;
 
 pub mod collection;
 pub mod environment;
+pub mod grpc;
 pub mod request;
+pub mod websocket;
 
 crate::new_id_type! {
     pub struct CollectionKey;
@@ -39,9 +41,17 @@ impl KeyValList {
         self.0.iter()
     }
 
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut KeyValue> {
+        self.0.iter_mut()
+    }
+
     pub fn is_empty(&self) -> bool {
         self.0.len() == 0
     }
+
+    pub fn push(&mut self, kv: KeyValue) {
+        self.0.push(kv);
+    }
 }
 
 impl IntoIterator for KeyValList {
@@ -225,6 +235,18 @@ impl Collections {
         self.get_mut(col)?.delete_request(req)
     }
 
+    pub fn add_example(&mut self, req: CollectionRequest, name: &str) {
+        if let Some(collection) = self.get_mut(req.0) {
+            collection.add_example(req.1, name);
+        }
+    }
+
+    pub fn remove_example(&mut self, req: CollectionRequest, name: &str) {
+        if let Some(collection) = self.get_mut(req.0) {
+            collection.remove_example(req.1, name);
+        }
+    }
+
     pub fn create_script_in(&mut self, col: CollectionKey, name: String) -> Option<PathBuf> {
         self.get_mut(col)?.create_script(name)
     }
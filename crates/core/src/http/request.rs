@@ -13,6 +13,11 @@ pub enum RequestBody {
     },
     Form(KeyValList),
     Json(String),
+    GraphQL {
+        query: String,
+        variables: String,
+        operation_name: Option<String>,
+    },
     XML(String),
     Text(String),
     File(Option<PathBuf>),
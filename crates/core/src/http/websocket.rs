@@ -0,0 +1,44 @@
+use strum::{Display, EnumString, VariantArray};
+
+use super::request::Auth;
+use super::KeyValList;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, EnumString, VariantArray, Display)]
+pub enum MessageKind {
+    #[default]
+    Text,
+    #[strum(serialize = "JSON")]
+    Json,
+    Binary,
+}
+
+/// A message saved with the request so it can be sent again later.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageTemplate {
+    pub name: String,
+    pub kind: MessageKind,
+    pub body: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebSocketRequest {
+    pub description: String,
+    pub url: String,
+    pub headers: KeyValList,
+    pub query_params: KeyValList,
+    pub auth: Auth,
+    pub messages: Vec<MessageTemplate>,
+}
+
+impl Default for WebSocketRequest {
+    fn default() -> Self {
+        Self {
+            description: "WebSocket request".to_string(),
+            url: "wss://echo.websocket.org".to_string(),
+            headers: KeyValList::new(),
+            query_params: KeyValList::new(),
+            auth: Auth::None,
+            messages: Vec::new(),
+        }
+    }
+}
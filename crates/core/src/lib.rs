@@ -1,7 +1,18 @@
 pub mod client;
+pub mod codegen;
+pub mod curl;
+pub mod diff;
+pub mod graphql;
+pub mod grpc;
+pub mod har;
 pub mod http;
 pub mod ids;
+pub mod mock;
 pub mod persistence;
+pub mod preview;
+pub mod sse;
+pub mod template;
 pub mod transformers;
 pub mod assertions;
 pub mod utils;
+pub mod websocket;
@@ -0,0 +1,340 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+
+use http_body_util::Full;
+use hyper::body::{Bytes, Incoming};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{HeaderMap, StatusCode};
+use hyper_util::rt::TokioIo;
+use regex::{Captures, Regex};
+use tokio::net::TcpListener;
+
+use crate::http::collection::Collection;
+use crate::http::environment::Environment;
+use crate::http::request::Method;
+use crate::persistence::collections::read_http_requests;
+use crate::persistence::examples::{read_examples, ResponseExample};
+use crate::template::{render_variables, VARIABLE_PATTERN};
+use crate::utils::{is_framing_header, percent_decode, percent_decode_path, split_url};
+
+/// Request header picking the example to respond with by name, without it
+/// the first successful example is used
+pub const EXAMPLE_HEADER: &str = "x-mock-example";
+
+#[derive(Debug, Clone, Copy)]
+pub struct MockOptions {
+    /// Delay before every response
+    pub latency: Duration,
+    /// Upper bound of a random delay added to the latency
+    pub jitter: Duration,
+    /// Fraction of requests, between 0 and 1, failing with `error_status`
+    pub error_rate: f64,
+    pub error_status: u16,
+}
+
+impl Default for MockOptions {
+    fn default() -> Self {
+        Self {
+            latency: Duration::ZERO,
+            jitter: Duration::ZERO,
+            error_rate: 0.0,
+            error_status: 500,
+        }
+    }
+}
+
+/// A request handled by the mock server
+#[derive(Debug, Clone)]
+pub struct MockHit {
+    pub method: String,
+    pub path: String,
+    pub status: u16,
+    /// Name of the matched request
+    pub request: Option<String>,
+}
+
+#[derive(Debug)]
+struct Route {
+    name: String,
+    method: Method,
+    /// Request path as written in the collection
+    path: String,
+    pattern: Regex,
+    params: Vec<String>,
+    /// Routes with more literal characters win when several match
+    literal_len: usize,
+    examples: Vec<ResponseExample>,
+}
+
+/// Routes built from the requests of a collection
+#[derive(Debug, Clone)]
+pub struct Mock {
+    routes: Arc<Vec<Route>>,
+}
+
+static PLACEHOLDERS: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(&format!(r":([a-zA-Z0-9]+)|{}", VARIABLE_PATTERN)).expect("placeholder pattern")
+});
+
+static TEMPLATE_VALUES: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\{\{\s*(path|query|header)\.([^{}\s]+)\s*\}\}").expect("template pattern")
+});
+
+/// Turns the path of a request into a pattern, `:param` matches a single
+/// segment and so do `{{vars}}` missing from the environment. Built-ins
+/// are matched as they are written.
+fn route_pattern(path: &str) -> (Regex, Vec<String>, usize) {
+    let mut pattern = String::from("^");
+    let mut params = Vec::new();
+    let mut literal_len = 0;
+    let mut last = 0;
+    for cap in PLACEHOLDERS.captures_iter(path) {
+        if cap.get(2).is_some_and(|name| name.as_str().starts_with('$')) {
+            continue;
+        }
+        let matched = cap.get(0).expect("whole match");
+        let literal = &path[last..matched.start()];
+        pattern.push_str(&regex::escape(literal));
+        literal_len += literal.len();
+
+        pattern.push_str("([^/]+)");
+        params.push(
+            cap.get(1)
+                .map(|p| p.as_str())
+                .unwrap_or_default()
+                .to_string(),
+        );
+        last = matched.end();
+    }
+    let literal = path[last..].trim_end_matches('/');
+    pattern.push_str(&regex::escape(literal));
+    literal_len += literal.len();
+    pattern.push_str("/?$");
+
+    let pattern = Regex::new(&pattern).expect("escaped route pattern");
+    (pattern, params, literal_len)
+}
+
+fn query_values(query: Option<&str>) -> HashMap<String, String> {
+    query
+        .unwrap_or_default()
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| pair.split_once('=').unwrap_or((pair, "")))
+        .map(|(name, value)| (percent_decode(name), percent_decode(value)))
+        .collect()
+}
+
+struct Values<'a> {
+    path: HashMap<&'a str, String>,
+    query: HashMap<String, String>,
+    headers: &'a HeaderMap,
+}
+
+/// Fills `{{path.id}}`, `{{query.page}}` and `{{header.X-Name}}` from the
+/// incoming request, unknown values are left empty
+fn render(template: &str, values: &Values) -> String {
+    TEMPLATE_VALUES
+        .replace_all(template, |cap: &Captures| {
+            let name = &cap[2];
+            let value = match &cap[1] {
+                "path" => values.path.get(name).cloned(),
+                "query" => values.query.get(name).cloned(),
+                _ => values
+                    .headers
+                    .get(name)
+                    .and_then(|v| v.to_str().ok())
+                    .map(str::to_string),
+            };
+            value.unwrap_or_default()
+        })
+        .to_string()
+}
+
+fn text_response(status: u16, body: String) -> hyper::Response<Full<Bytes>> {
+    let mut response = hyper::Response::new(Full::new(Bytes::from(body)));
+    *response.status_mut() =
+        StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    response
+        .headers_mut()
+        .insert("content-type", "text/plain; charset=utf-8".parse().unwrap());
+    response
+}
+
+impl Mock {
+    /// Builds a route for every HTTP request of the collection. The server
+    /// part of the URLs is dropped, `{{baseUrl}}/users/:id` is served at
+    /// `/users/:id`. Returns the files that could not be used.
+    pub async fn load(
+        collection: &Collection,
+        env: Option<&Environment>,
+    ) -> anyhow::Result<(Self, Vec<String>)> {
+        let (requests, mut warnings) = read_http_requests(collection).await;
+
+        let mut routes = Vec::new();
+        for entry in requests {
+            let url = entry.request.url;
+            let (_, path) = split_url(&url);
+            // Built-ins would give the route a different path every time
+            let path = render_variables(path, env);
+            let path = if path.starts_with('/') {
+                path
+            } else {
+                format!("/{}", path)
+            };
+
+            let examples = match read_examples(&entry.path).await {
+                Ok(examples) => examples,
+                Err(e) => {
+                    warnings.push(format!("{}: Failed to read examples, {}", entry.name, e));
+                    Vec::new()
+                }
+            };
+
+            let (pattern, params, literal_len) = route_pattern(&path);
+            routes.push(Route {
+                name: entry.name,
+                method: entry.request.method,
+                path,
+                pattern,
+                params,
+                literal_len,
+                examples,
+            });
+        }
+
+        let mock = Self {
+            routes: Arc::new(routes),
+        };
+        Ok((mock, warnings))
+    }
+
+    /// Method, path, request name and number of examples of every route
+    pub fn routes(&self) -> impl Iterator<Item = (Method, &str, &str, usize)> {
+        self.routes
+            .iter()
+            .map(|r| (r.method, r.path.as_str(), r.name.as_str(), r.examples.len()))
+    }
+
+    /// Serves requests until the returned future is dropped
+    pub async fn serve(
+        self,
+        listener: TcpListener,
+        options: MockOptions,
+        on_hit: impl Fn(MockHit) + Clone + Send + Sync + 'static,
+    ) -> anyhow::Result<()> {
+        loop {
+            let (stream, _) = listener.accept().await?;
+
+            let mock = self.clone();
+            let on_hit = on_hit.clone();
+            let service = service_fn(move |req| {
+                let mock = mock.clone();
+                let on_hit = on_hit.clone();
+                async move {
+                    let method = req.method().to_string();
+                    let path = req.uri().path().to_string();
+                    let (request, response) = mock.respond(req, options).await;
+                    on_hit(MockHit {
+                        method,
+                        path,
+                        status: response.status().as_u16(),
+                        request,
+                    });
+                    Ok::<_, Infallible>(response)
+                }
+            });
+
+            tokio::spawn(async move {
+                let _ = http1::Builder::new()
+                    .serve_connection(TokioIo::new(stream), service)
+                    .await;
+            });
+        }
+    }
+
+    async fn respond(
+        &self,
+        req: hyper::Request<Incoming>,
+        options: MockOptions,
+    ) -> (Option<String>, hyper::Response<Full<Bytes>>) {
+        let path = req.uri().path();
+        let method = req.method().as_str();
+
+        let matched = self
+            .routes
+            .iter()
+            .filter(|route| route.method.to_string() == method)
+            .filter_map(|route| route.pattern.captures(path).map(|cap| (route, cap)))
+            .max_by_key(|(route, _)| route.literal_len);
+        let Some((route, captures)) = matched else {
+            let body = format!("No request matches {} {}", method, path);
+            return (None, text_response(404, body));
+        };
+        let name = Some(route.name.clone());
+
+        let jitter = options.jitter.as_millis() as u64;
+        let delay = options.latency + Duration::from_millis(fastrand::u64(0..=jitter));
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+
+        if options.error_rate > 0.0 && fastrand::f64() < options.error_rate {
+            let body = format!("Injected error for {}", route.name);
+            return (name, text_response(options.error_status, body));
+        }
+
+        let selected = req
+            .headers()
+            .get(EXAMPLE_HEADER)
+            .and_then(|v| v.to_str().ok());
+        let example = match selected {
+            Some(selected) => route.examples.iter().find(|e| e.name == selected),
+            None => route
+                .examples
+                .iter()
+                .find(|e| (200..300).contains(&e.status))
+                .or(route.examples.first()),
+        };
+        let Some(example) = example else {
+            let body = match selected {
+                Some(selected) => format!("{} has no example named {}", route.name, selected),
+                None => format!("{} has no saved examples", route.name),
+            };
+            return (name, text_response(501, body));
+        };
+
+        let values = Values {
+            path: route
+                .params
+                .iter()
+                .zip(captures.iter().skip(1))
+                .filter_map(|(name, value)| {
+                    Some((name.as_str(), percent_decode_path(value?.as_str())))
+                })
+                .collect(),
+            query: query_values(req.uri().query()),
+            headers: req.headers(),
+        };
+
+        // The length is set from the rendered body
+        let mut builder = hyper::Response::builder().status(example.status);
+        for header in example.headers.iter() {
+            if !header.disabled && !is_framing_header(&header.name) {
+                builder = builder.header(&header.name, render(&header.value, &values));
+            }
+        }
+        let body = render(&example.body, &values);
+
+        match builder.body(Full::new(Bytes::from(body))) {
+            Ok(response) => (name, response),
+            Err(e) => {
+                let body = format!("Invalid example {}, {}", example.name, e);
+                (name, text_response(500, body))
+            }
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use std::future::Future;
 use std::path::{Path, PathBuf};
 
 use anyhow::Context;
@@ -6,9 +7,15 @@ use serde::{Deserialize, Serialize};
 use tokio::fs;
 
 use crate::http::collection::{Collection, Entry, Folder, FolderId, RequestId, RequestRef, Script};
-use crate::persistence::Version;
+use crate::http::request::Request;
+use crate::http::KeyValList;
+use crate::persistence::{EncodedKeyValue, Version};
 
+use super::diagnostics::lint_collection;
 use super::environment::read_environments;
+use super::examples::{example_names, EXAMPLES_SUFFIX};
+use super::files::write_atomic;
+use super::request::{read_request_file, RequestFile};
 use super::{COLLECTION_ROOT_FILE, HCL_EXTENSION, JS_EXTENSION, REQUESTS, SCRIPTS, TS_EXTENSION};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,6 +23,8 @@ pub struct EncodedCollection {
     pub name: String,
     pub version: Version,
     pub default_environment: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variables: Vec<EncodedKeyValue>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,7 +37,7 @@ pub struct CollectionsState {
     pub open: Vec<CollectionConfig>,
 }
 
-fn project_dirs() -> Option<ProjectDirs> {
+pub(crate) fn project_dirs() -> Option<ProjectDirs> {
     ProjectDirs::from("com", "nrjais", "sanchaar")
 }
 
@@ -51,6 +60,7 @@ async fn create_collections_state(collections_file: PathBuf) -> anyhow::Result<C
             name: "Sanchaar".to_string(),
             version: Version::V1,
             default_environment: None,
+            variables: Vec::new(),
         },
     )
     .await?;
@@ -60,7 +70,7 @@ async fn create_collections_state(collections_file: PathBuf) -> anyhow::Result<C
     };
 
     let data = hcl::to_string(&state)?;
-    fs::write(collections_file, data).await?;
+    write_atomic(&collections_file, data).await?;
 
     Ok(state)
 }
@@ -75,7 +85,7 @@ async fn open_collections_list() -> Option<Vec<CollectionConfig>> {
                     create_collections_state(collections_path)
                         .await
                         .inspect_err(|e| {
-                            println!("Error creating collections state: {:?}", e);
+                            log::error!("Error creating collections state: {:?}", e);
                         })
                         .ok()?
                         .open,
@@ -89,6 +99,8 @@ async fn open_collections_list() -> Option<Vec<CollectionConfig>> {
     Some(collections.open)
 }
 
+/// Opens the collections of the last run, broken files are kept as
+/// diagnostics of their collection rather than dropping it
 pub async fn load() -> anyhow::Result<Vec<Collection>> {
     let collections = match open_collections_list().await {
         Some(collections) => collections,
@@ -104,7 +116,7 @@ pub async fn load() -> anyhow::Result<Vec<Collection>> {
                 match col {
                     Ok(col) => result.push(col),
                     Err(e) => {
-                        println!("Error opening collection: {:?}", e);
+                        log::error!("Error opening collection: {:?}", e);
                     }
                 }
             }
@@ -124,31 +136,80 @@ pub async fn save(collection: Vec<Collection>) -> anyhow::Result<()> {
     };
 
     let data = hcl::to_string(&state)?;
-    fs::write(collections_file, data).await?;
+    write_atomic(&collections_file, data).await?;
 
     Ok(())
 }
 
+/// Opens the collection at `path`, files which fail to parse are listed in
+/// its diagnostics. The path is made canonical, a collection has the same
+/// path however it is opened.
 pub async fn open_collection(path: PathBuf) -> Result<Collection, anyhow::Error> {
+    let path = fs::canonicalize(&path)
+        .await
+        .with_context(|| format!("Failed to open collection {}", path.display()))?;
     let data = fs::read_to_string(path.join(COLLECTION_ROOT_FILE)).await?;
 
-    let collection: EncodedCollection = hcl::from_str(&data)?;
+    let parsed = hcl::from_str::<EncodedCollection>(&data);
+    let fallback = parsed.is_err();
+    let collection = match parsed {
+        Ok(collection) => collection,
+        // Named after its directory until the file is fixed
+        Err(_) => EncodedCollection {
+            name: path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            version: Version::V1,
+            default_environment: None,
+            variables: Vec::new(),
+        },
+    };
     let environments = read_environments(&path).await?;
     let entries = find_all_requests(&path).await?;
     let scripts = find_all_scripts(&path).await?;
+    let diagnostics = lint_collection(&path).await?;
 
     let default_env = collection
         .default_environment
         .as_deref()
         .and_then(|n| environments.find_by_name(n));
 
-    Ok(Collection::new(
+    let variables = KeyValList::from(collection.variables.into_iter().map(Into::into).collect());
+    let mut collection = Collection::new(
         collection.name,
         entries,
         scripts,
         path,
         environments,
         default_env,
+    );
+    collection.variables = variables;
+    collection.diagnostics = diagnostics;
+    collection.fallback = fallback;
+    Ok(collection)
+}
+
+/// Collection file of the collection at `path`, none when it is missing or
+/// fails to parse
+pub async fn read_collection_file(path: &Path) -> anyhow::Result<Option<EncodedCollection>> {
+    let file = path.join(COLLECTION_ROOT_FILE);
+    if !fs::try_exists(&file).await? {
+        return Ok(None);
+    }
+    let data = fs::read_to_string(&file).await?;
+    Ok(hcl::from_str(&data).ok())
+}
+
+/// Variables saved with the collection at `path`, none when it has no
+/// collection file or the file fails to parse
+pub async fn read_collection_variables(path: &Path) -> anyhow::Result<KeyValList> {
+    let variables = read_collection_file(path)
+        .await?
+        .map(|col| col.variables)
+        .unwrap_or_default();
+    Ok(KeyValList::from(
+        variables.into_iter().map(Into::into).collect(),
     ))
 }
 
@@ -187,7 +248,7 @@ pub async fn find_all_scripts(col: &Path) -> anyhow::Result<Vec<Script>> {
     Ok(scripts)
 }
 
-async fn find_all_requests(path: &Path) -> anyhow::Result<Vec<Entry>> {
+pub async fn find_all_requests(path: &Path) -> anyhow::Result<Vec<Entry>> {
     let requests = path.join(REQUESTS);
     let exists = fs::try_exists(&requests).await?;
     if !exists {
@@ -203,6 +264,14 @@ async fn walk_entries(dir_path: &Path) -> anyhow::Result<Vec<Entry>> {
 
     while let Some(entry) = dir.next_entry().await? {
         if entry.file_type().await?.is_dir() {
+            // Saved responses of a request, not a folder
+            if entry
+                .file_name()
+                .to_string_lossy()
+                .ends_with(EXAMPLES_SUFFIX)
+            {
+                continue;
+            }
             let entries = Box::pin(walk_entries(&entry.path())).await?;
             all_entries.push(Entry::Folder(Folder {
                 id: FolderId::new(),
@@ -220,9 +289,11 @@ async fn walk_entries(dir_path: &Path) -> anyhow::Result<Vec<Entry>> {
                 continue;
             }
 
+            let path = entry.path();
             all_entries.push(Entry::Item(RequestRef {
                 name: without_ext.to_string(),
-                path: entry.path(),
+                examples: example_names(&path).await.unwrap_or_default(),
+                path,
                 id: RequestId::new(),
             }));
         }
@@ -230,6 +301,63 @@ async fn walk_entries(dir_path: &Path) -> anyhow::Result<Vec<Entry>> {
     Ok(all_entries)
 }
 
+/// HTTP request of a collection along with the folders it is nested in.
+#[derive(Debug, Clone)]
+pub struct RequestEntry {
+    pub folders: Vec<String>,
+    pub name: String,
+    pub path: PathBuf,
+    pub request: Request,
+}
+
+/// Reads every HTTP request of the collection, sorted by their path so the
+/// result does not depend on the directory listing order. Files that were
+/// skipped are returned with the reason, relative to the collection.
+pub async fn read_http_requests(collection: &Collection) -> (Vec<RequestEntry>, Vec<String>) {
+    let mut refs = Vec::new();
+    let mut stack = collection
+        .entries
+        .iter()
+        .map(|entry| (Vec::new(), entry))
+        .collect::<Vec<_>>();
+
+    while let Some((folders, entry)) = stack.pop() {
+        match entry {
+            Entry::Item(item) => refs.push((folders, item)),
+            Entry::Folder(folder) => {
+                let mut path = folders.clone();
+                path.push(folder.name.clone());
+                stack.extend(folder.entries.iter().map(|e| (path.clone(), e)));
+            }
+        }
+    }
+    refs.sort_by(|(_, a), (_, b)| a.path.cmp(&b.path));
+
+    let mut requests = Vec::new();
+    let mut skipped = Vec::new();
+    for (folders, item) in refs {
+        let relative = item
+            .path
+            .strip_prefix(&collection.path)
+            .unwrap_or(&item.path);
+        match read_request_file(&item.path).await {
+            Ok(RequestFile::Http(request)) => requests.push(RequestEntry {
+                folders,
+                name: item.name.clone(),
+                path: item.path.clone(),
+                request,
+            }),
+            Ok(_) => skipped.push(format!("{}: Not an HTTP request", relative.display())),
+            Err(e) => skipped.push(format!(
+                "{}: Failed to read request, {}",
+                relative.display(),
+                e
+            )),
+        }
+    }
+    (requests, skipped)
+}
+
 pub fn encode_collection(collection: &Collection) -> EncodedCollection {
     EncodedCollection {
         name: collection.name.clone(),
@@ -238,6 +366,13 @@ pub fn encode_collection(collection: &Collection) -> EncodedCollection {
             .default_env
             .and_then(|env| collection.environments.get(env))
             .map(|env| env.name.clone()),
+        variables: collection
+            .variables
+            .iter()
+            .filter(|kv| !kv.name.is_empty())
+            .cloned()
+            .map(Into::into)
+            .collect(),
     }
 }
 
@@ -245,14 +380,92 @@ pub async fn save_collection(path: PathBuf, collection: EncodedCollection) -> an
     let data = hcl::to_string(&collection).expect("Failed to encode collection");
 
     fs::create_dir_all(&path).await?;
-    fs::write(path.join(COLLECTION_ROOT_FILE), data).await?;
+    write_atomic(&path.join(COLLECTION_ROOT_FILE), data).await?;
 
     Ok(())
 }
 
+/// Writes the collection file of an open collection, refused while the
+/// file on disk fails to parse so the defaults used in its place do not
+/// replace it
+pub fn save_collection_file(
+    collection: &Collection,
+) -> impl Future<Output = anyhow::Result<()>> + Send + 'static {
+    let fallback = collection.fallback;
+    let path = collection.path.clone();
+    let encoded = encode_collection(collection);
+    async move {
+        if fallback {
+            anyhow::bail!(
+                "{} failed to parse, fix it before saving the collection",
+                path.join(COLLECTION_ROOT_FILE).display()
+            );
+        }
+        save_collection(path, encoded).await
+    }
+}
+
 pub async fn save_script(path: PathBuf, name: &str, content: &str) -> anyhow::Result<()> {
-    let path = path.join(SCRIPTS).join(name);
-    fs::create_dir_all(&path).await?;
-    fs::write(path, content).await?;
+    let dir = path.join(SCRIPTS);
+    fs::create_dir_all(&dir).await?;
+    write_atomic(&dir.join(name), content).await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::http::KeyValue;
+
+    #[tokio::test]
+    async fn keeps_collection_variables() {
+        let dir = TempDir::new().unwrap();
+        let mut collection = Collection {
+            name: "col".to_string(),
+            path: dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        collection.variables = KeyValList::from(vec![KeyValue {
+            disabled: false,
+            name: "host".to_string(),
+            value: "example.com".to_string(),
+        }]);
+
+        save_collection(collection.path.clone(), encode_collection(&collection))
+            .await
+            .unwrap();
+        let opened = open_collection(dir.path().to_path_buf()).await.unwrap();
+
+        assert_eq!(opened.variables, collection.variables);
+        assert_eq!(
+            read_collection_variables(dir.path()).await.unwrap(),
+            collection.variables
+        );
+    }
+
+    #[tokio::test]
+    async fn keeps_broken_collection_files() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join(COLLECTION_ROOT_FILE);
+        let broken = "name = \"col\"\nvariables = [\n";
+        std::fs::write(&file, broken).unwrap();
+
+        let mut collection = open_collection(dir.path().to_path_buf()).await.unwrap();
+        assert!(collection.fallback);
+
+        collection.variables = KeyValList::from(vec![KeyValue {
+            disabled: false,
+            name: "host".to_string(),
+            value: "example.com".to_string(),
+        }]);
+        assert!(save_collection_file(&collection).await.is_err());
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), broken);
+
+        std::fs::write(&file, "name = \"col\"\nversion = \"V1\"\n").unwrap();
+        let collection = open_collection(dir.path().to_path_buf()).await.unwrap();
+        assert!(!collection.fallback);
+        save_collection_file(&collection).await.unwrap();
+    }
+}
@@ -0,0 +1,240 @@
+//! Readable problems of collection files which fail to parse, with the file
+//! and, when known, the position of the problem.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use tokio::fs;
+
+use super::collections::EncodedCollection;
+use super::environment::EncodedEnvironment;
+use super::examples::{is_example, EXAMPLES_SUFFIX};
+use super::request::parse_request_file;
+use super::{COLLECTION_ROOT_FILE, ENVIRONMENTS, HCL_EXTENSION, REQUESTS};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub path: PathBuf,
+    /// Line and column of the problem, starting at 1
+    pub location: Option<(usize, usize)>,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.location {
+            Some((line, column)) => write!(
+                f,
+                "{}:{}:{}: {}",
+                self.path.display(),
+                line,
+                column,
+                self.message
+            ),
+            None => write!(f, "{}: {}", self.path.display(), self.message),
+        }
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+/// Best effort position of the attribute, block or value named by a schema
+/// error, errors of the HCL syntax have their own
+fn locate(content: &str, message: &str) -> Option<(usize, usize)> {
+    let name = message.split('`').nth(1).filter(|name| !name.is_empty())?;
+
+    let key = content.lines().enumerate().find_map(|(idx, line)| {
+        let trimmed = line.trim_start();
+        let rest = trimmed.strip_prefix(name)?.trim_start();
+        let column = line.len() - trimmed.len() + 1;
+        // Attribute, or block with or without labels
+        let found = rest.starts_with(['=', '"']) || rest.ends_with('{');
+        found.then_some((idx + 1, column))
+    });
+    // Values, like `unknown variant `GTE``
+    let quoted = format!("\"{}\"", name);
+    key.or_else(|| {
+        content
+            .lines()
+            .enumerate()
+            .find_map(|(idx, line)| Some((idx + 1, line.find(&quoted)? + 1)))
+    })
+}
+
+impl Diagnostic {
+    /// Problem of a file whose content failed to parse
+    pub fn new(path: &Path, content: &str, error: &anyhow::Error) -> Self {
+        let hcl_error = error.chain().find_map(|e| e.downcast_ref::<hcl::Error>());
+        let (location, message) = match hcl_error {
+            Some(hcl::Error::Parse(e)) => (
+                Some((e.location().line(), e.location().column())),
+                e.message().to_string(),
+            ),
+            _ => {
+                let message = error.to_string();
+                (locate(content, &message), message)
+            }
+        };
+
+        Self {
+            path: path.to_path_buf(),
+            location,
+            message,
+        }
+    }
+}
+
+async fn check_file<T>(
+    path: &Path,
+    parse: impl Fn(&str) -> anyhow::Result<T>,
+) -> anyhow::Result<Option<Diagnostic>> {
+    let content = fs::read_to_string(path).await?;
+    Ok(parse(&content)
+        .err()
+        .map(|e| Diagnostic::new(path, &content, &e)))
+}
+
+fn is_hcl_file(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    name.len() > HCL_EXTENSION.len() && name.ends_with(HCL_EXTENSION)
+}
+
+async fn hcl_files(dir: &Path, recursive: bool) -> anyhow::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+
+    while let Some(dir) = dirs.pop() {
+        let mut entries = match fs::read_dir(&dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if entry.file_type().await?.is_dir() {
+                // Saved responses of a request are checked with the request
+                let examples = path.to_string_lossy().ends_with(EXAMPLES_SUFFIX);
+                if recursive && !examples {
+                    dirs.push(path);
+                }
+            } else if is_hcl_file(&path) {
+                files.push(path);
+            }
+        }
+    }
+    Ok(files)
+}
+
+fn parse_collection(content: &str) -> anyhow::Result<EncodedCollection> {
+    Ok(hcl::from_str(content)?)
+}
+
+fn parse_environment(content: &str) -> anyhow::Result<EncodedEnvironment> {
+    Ok(hcl::from_str(content)?)
+}
+
+/// Problem of a file of the collection at `root`, removed files and files
+/// the collection does not read have none
+pub async fn lint_file(root: &Path, path: &Path) -> anyhow::Result<Option<Diagnostic>> {
+    if !fs::try_exists(path).await? {
+        return Ok(None);
+    }
+
+    if path == root.join(COLLECTION_ROOT_FILE) {
+        return check_file(path, parse_collection).await;
+    }
+    if !is_hcl_file(path) {
+        return Ok(None);
+    }
+    if path.parent() == Some(&root.join(ENVIRONMENTS)) {
+        return check_file(path, parse_environment).await;
+    }
+    if path.starts_with(root.join(REQUESTS)) && !is_example(path) {
+        return check_file(path, parse_request_file).await;
+    }
+    Ok(None)
+}
+
+/// Problems of the collection, environment and request files of the
+/// collection at `root`, sorted by file
+pub async fn lint_collection(root: &Path) -> anyhow::Result<Vec<Diagnostic>> {
+    let mut diagnostics = Vec::new();
+
+    let root_file = root.join(COLLECTION_ROOT_FILE);
+    diagnostics.extend(check_file(&root_file, parse_collection).await?);
+
+    let mut paths = hcl_files(&root.join(ENVIRONMENTS), false).await?;
+    paths.extend(hcl_files(&root.join(REQUESTS), true).await?);
+    for path in paths {
+        diagnostics.extend(lint_file(root, &path).await?);
+    }
+    diagnostics.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(diagnostics)
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn locates_unknown_assertions() {
+        let content = r#"
+version = "V1"
+method  = "GET"
+url     = "https://example.com"
+
+assertions {
+  status code {
+    eq = 200
+  }
+
+  cookie "session" {
+    eq = "abc"
+  }
+}
+"#;
+        let path = Path::new("Get.hcl");
+        let error = parse_request_file(content).unwrap_err();
+        let diagnostic = Diagnostic::new(path, content, &error);
+
+        assert!(
+            diagnostic.message.contains("Unknown assertion `cookie`"),
+            "{}",
+            diagnostic.message
+        );
+        assert_eq!(diagnostic.location, Some((11, 3)));
+    }
+
+    #[tokio::test]
+    async fn lints_files_of_the_collection() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().to_path_buf();
+        let requests = root.join(REQUESTS);
+        std::fs::create_dir_all(requests.join("Get.examples")).unwrap();
+        std::fs::create_dir_all(root.join(ENVIRONMENTS)).unwrap();
+
+        let broken = "method = \"GET\"\nurl = [\n";
+        let request = requests.join("Get.hcl");
+        let example = requests.join("Get.examples").join("ok.hcl");
+        let environment = root.join(ENVIRONMENTS).join("dev.hcl");
+        let notes = requests.join("notes.txt");
+        for path in [&request, &example, &environment, &notes] {
+            std::fs::write(path, broken).unwrap();
+        }
+
+        let lint = |path: PathBuf| {
+            let root = root.clone();
+            async move { lint_file(&root, &path).await.unwrap() }
+        };
+        assert_eq!(lint(request.clone()).await.unwrap().path, request);
+        assert_eq!(lint(environment.clone()).await.unwrap().path, environment);
+        assert_eq!(lint(example).await, None);
+        assert_eq!(lint(notes).await, None);
+        assert_eq!(lint(requests.join("Removed.hcl")).await, None);
+    }
+}
@@ -6,6 +6,7 @@ use tokio::fs;
 use crate::http::environment::{Environment, Environments};
 use crate::http::KeyValList;
 
+use super::files::write_atomic;
 use super::ENVIRONMENTS;
 use super::{EncodedKeyValue, Version, HCL_EXTENSION};
 
@@ -18,15 +19,24 @@ pub struct EncodedEnvironment {
 
 impl From<EncodedEnvironment> for Environment {
     fn from(val: EncodedEnvironment) -> Self {
+        let secrets = val
+            .variables
+            .iter()
+            .filter(|kv| kv.secret)
+            .map(|kv| kv.name.clone())
+            .collect();
+
         Environment {
             name: val.name,
             variables: KeyValList::from(val.variables.into_iter().map(Into::into).collect()),
+            secrets,
         }
     }
 }
 
 impl From<Environment> for EncodedEnvironment {
     fn from(environment: Environment) -> Self {
+        let secrets = environment.secrets;
         EncodedEnvironment {
             name: environment.name,
             version: Version::V1,
@@ -34,12 +44,25 @@ impl From<Environment> for EncodedEnvironment {
                 .variables
                 .into_iter()
                 .filter(|kv| !kv.name.is_empty())
-                .map(Into::into)
+                .map(|kv| match secrets.contains(&kv.name) {
+                    // Secret values are saved to the secret store only
+                    true => EncodedKeyValue {
+                        name: kv.name,
+                        value: String::new(),
+                        disabled: kv.disabled,
+                        secret: true,
+                    },
+                    false => kv.into(),
+                })
                 .collect(),
         }
     }
 }
 
+/// Reads the environments of the collection, files which fail to parse are
+/// skipped and reported by [`lint_collection`]
+///
+/// [`lint_collection`]: super::diagnostics::lint_collection
 pub async fn read_environments(col: &Path) -> anyhow::Result<Environments> {
     let env_path = col.join(ENVIRONMENTS);
     let exists = fs::try_exists(&env_path).await?;
@@ -66,9 +89,9 @@ pub async fn read_environments(col: &Path) -> anyhow::Result<Environments> {
 
         let content = fs::read_to_string(&file.path()).await?;
 
-        let environment: EncodedEnvironment = hcl::from_str(&content)?;
-
-        environments.insert(environment.into());
+        if let Ok(environment) = hcl::from_str::<EncodedEnvironment>(&content) {
+            environments.insert(environment.into());
+        }
     }
 
     Ok(environments)
@@ -93,7 +116,7 @@ pub async fn save_environments(
         let path = env_path.join(format!("{}{}", &environment.name, HCL_EXTENSION));
         let content = hcl::to_string(environment)?;
 
-        fs::write(&path, content).await?;
+        write_atomic(&path, content).await?;
     }
 
     Ok(())
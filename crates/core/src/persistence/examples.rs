@@ -0,0 +1,239 @@
+use std::path::{Path, PathBuf};
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::client::{ContentType, Response, ResponseBody};
+use crate::http::{KeyValList, KeyValue};
+use crate::persistence::Version;
+use crate::utils::is_framing_header;
+
+use super::files::write_atomic;
+use super::import::file_name;
+use super::{EncodedKeyValue, HCL_EXTENSION};
+
+/// Directory next to the request file holding its saved responses,
+/// `Get user.hcl` keeps them in `Get user.examples`
+pub const EXAMPLES_SUFFIX: &str = ".examples";
+
+/// A saved response of a request
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResponseExample {
+    pub name: String,
+    pub status: u16,
+    pub headers: KeyValList,
+    pub body: String,
+}
+
+impl ResponseExample {
+    /// Keeps the status, headers and body of a response, bodies which are
+    /// not text are stored lossily. Headers framing the body, like its
+    /// length, are left out as they depend on how it is sent.
+    pub fn from_response(name: String, response: &Response) -> Self {
+        let headers = response
+            .headers
+            .iter()
+            .filter(|(name, _)| !is_framing_header(name.as_str()))
+            .map(|(name, value)| KeyValue {
+                disabled: false,
+                name: name.to_string(),
+                value: String::from_utf8_lossy(value.as_bytes()).to_string(),
+            })
+            .collect::<Vec<_>>();
+
+        Self {
+            name,
+            status: response.status.as_u16(),
+            headers: KeyValList::from(headers),
+            body: String::from_utf8_lossy(&response.body.data).to_string(),
+        }
+    }
+
+    /// The example as a response received instantly, invalid status codes
+    /// and headers are dropped
+    pub fn to_response(&self) -> Response {
+        let mut headers = HeaderMap::new();
+        for header in self.headers.iter().filter(|h| !h.disabled) {
+            let name = HeaderName::from_bytes(header.name.as_bytes());
+            let value = HeaderValue::from_str(&header.value);
+            if let (Ok(name), Ok(value)) = (name, value) {
+                headers.append(name, value);
+            }
+        }
+
+        let data = self.body.as_bytes().to_vec();
+        let content_type = headers
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok());
+        let body = ResponseBody {
+            content_type: ContentType::detect(content_type, &data),
+            data,
+        };
+
+        Response {
+            status: StatusCode::from_u16(self.status).unwrap_or(StatusCode::OK),
+            size_bytes: body.data.len(),
+            headers,
+            body,
+            duration: Default::default(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EncodedExample {
+    #[serde(default)]
+    pub version: Version,
+    pub status: u16,
+    #[serde(default)]
+    pub headers: Vec<EncodedKeyValue>,
+    #[serde(default)]
+    pub body: String,
+}
+
+pub fn examples_dir(request: &Path) -> PathBuf {
+    let stem = request
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    request.with_file_name(format!("{}{}", stem, EXAMPLES_SUFFIX))
+}
+
+/// Whether the path is a directory of examples or inside one
+pub(super) fn is_example(path: &Path) -> bool {
+    path.ancestors()
+        .any(|p| p.to_string_lossy().ends_with(EXAMPLES_SUFFIX))
+}
+
+/// File of the example, names holding path separators or `..` stay in the
+/// examples directory of the request
+pub fn example_path(request: &Path, name: &str) -> PathBuf {
+    examples_dir(request).join(format!("{}{}", file_name(name), HCL_EXTENSION))
+}
+
+pub fn encode_example(example: ResponseExample) -> EncodedExample {
+    EncodedExample {
+        version: Version::V1,
+        status: example.status,
+        headers: example
+            .headers
+            .into_iter()
+            .map(EncodedKeyValue::from)
+            .collect(),
+        body: example.body,
+    }
+}
+
+fn decode_example(name: String, example: EncodedExample) -> ResponseExample {
+    ResponseExample {
+        name,
+        status: example.status,
+        headers: KeyValList::from(
+            example
+                .headers
+                .into_iter()
+                .map(KeyValue::from)
+                .collect::<Vec<_>>(),
+        ),
+        body: example.body,
+    }
+}
+
+/// Reads the saved responses of the request sorted by name, a request without
+/// any has no examples directory
+pub async fn read_examples(request: &Path) -> anyhow::Result<Vec<ResponseExample>> {
+    let mut examples = Vec::new();
+    for name in example_names(request).await? {
+        examples.push(read_example(request, &name).await?);
+    }
+    Ok(examples)
+}
+
+/// Names of the saved responses of the request, sorted
+pub async fn example_names(request: &Path) -> anyhow::Result<Vec<String>> {
+    let dir = examples_dir(request);
+    if !fs::try_exists(&dir).await? {
+        return Ok(Vec::new());
+    }
+
+    let mut names = Vec::new();
+    let mut files = fs::read_dir(&dir).await?;
+    while let Some(file) = files.next_entry().await? {
+        let file_name = file.file_name();
+        if let Some(name) = file_name.to_string_lossy().strip_suffix(HCL_EXTENSION) {
+            names.push(name.to_string());
+        }
+    }
+    names.sort();
+
+    Ok(names)
+}
+
+pub async fn read_example(request: &Path, name: &str) -> anyhow::Result<ResponseExample> {
+    let data = fs::read_to_string(example_path(request, name)).await?;
+    let example: EncodedExample = hcl::from_str(&data)?;
+    Ok(decode_example(name.to_string(), example))
+}
+
+/// Writes the example next to the request, replacing one with the same name
+pub async fn save_example(request: &Path, example: ResponseExample) -> anyhow::Result<()> {
+    fs::create_dir_all(examples_dir(request)).await?;
+
+    let path = example_path(request, &example.name);
+    let content = hcl::to_string(&encode_example(example))?;
+    write_atomic(&path, content).await?;
+    Ok(())
+}
+
+pub async fn delete_example(request: &Path, name: &str) -> anyhow::Result<()> {
+    fs::remove_file(example_path(request, name)).await?;
+
+    // Leave no empty directory behind
+    let dir = examples_dir(request);
+    if example_names(request).await?.is_empty() {
+        fs::remove_dir(dir).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn keeps_example_files_in_the_examples_dir() {
+        let request = Path::new("/col/requests/Get user.hcl");
+        let dir = Path::new("/col/requests/Get user.examples");
+
+        assert_eq!(example_path(request, "ok"), dir.join("ok.hcl"));
+        assert_eq!(
+            example_path(request, "../../secrets"),
+            dir.join("-..-secrets.hcl")
+        );
+        assert_eq!(example_path(request, "a/b\\c"), dir.join("a-b-c.hcl"));
+        assert_eq!(example_path(request, ".."), dir.join("Untitled.hcl"));
+    }
+
+    #[tokio::test]
+    async fn saves_examples_under_safe_names() {
+        let dir = TempDir::new().unwrap();
+        let request = dir.path().join("requests").join("Get user.hcl");
+        let example = ResponseExample {
+            name: "../not found".to_string(),
+            status: 404,
+            headers: KeyValList::new(),
+            body: "missing".to_string(),
+        };
+
+        save_example(&request, example).await.unwrap();
+
+        assert_eq!(example_names(&request).await.unwrap(), ["-not found"]);
+        let saved = read_example(&request, "-not found").await.unwrap();
+        assert_eq!(saved.status, 404);
+        assert!(!dir.path().join("not found.hcl").exists());
+    }
+}
@@ -0,0 +1,17 @@
+pub mod openapi;
+
+/// Things that were left out of an export, one line each.
+#[derive(Debug, Clone, Default)]
+pub struct ExportReport {
+    pub warnings: Vec<String>,
+}
+
+impl ExportReport {
+    pub fn is_empty(&self) -> bool {
+        self.warnings.is_empty()
+    }
+
+    pub(crate) fn warn(&mut self, context: &str, msg: impl std::fmt::Display) {
+        self.warnings.push(format!("{}: {}", context, msg));
+    }
+}
@@ -0,0 +1,711 @@
+use std::collections::{BTreeMap, HashSet};
+
+use regex::{Captures, Regex};
+use serde_json::{json, Map, Value};
+
+use crate::graphql;
+use crate::http::collection::Collection;
+use crate::http::environment::Environment;
+use crate::http::request::{Auth, Method, Request, RequestBody};
+use crate::http::KeyValList;
+use crate::persistence::examples::{read_examples, ResponseExample};
+use crate::persistence::history::HistoryEntry;
+use crate::persistence::import::param_name;
+use crate::template::render_lenient;
+use crate::utils::split_url;
+
+use crate::persistence::collections::{read_http_requests, RequestEntry};
+
+use super::ExportReport;
+
+pub const OPENAPI_VERSION: &str = "3.1.0";
+
+/// Headers OpenAPI describes elsewhere, they are not allowed as parameters
+const RESERVED_HEADERS: [&str; 3] = ["accept", "content-type", "authorization"];
+
+const BASIC_AUTH: &str = "basicAuth";
+const BEARER_AUTH: &str = "bearerAuth";
+
+/// Generates an OpenAPI 3.1 document describing the HTTP requests of the
+/// collection. Requests sharing a method and path are merged into the first.
+/// Responses are described from the saved examples of a request, or else
+/// from its latest response in `history`, ordered from the newest entry.
+pub async fn export(
+    collection: &Collection,
+    history: &[HistoryEntry],
+) -> anyhow::Result<(Value, ExportReport)> {
+    let (requests, skipped) = read_http_requests(collection).await;
+    let report = ExportReport { warnings: skipped };
+
+    let env = collection
+        .default_env
+        .and_then(|key| collection.environments.get(key))
+        .or_else(|| collection.environments.entries().next().map(|(_, env)| env));
+
+    let mut exporter = Exporter {
+        env,
+        history,
+        report,
+        servers: Vec::new(),
+        paths: BTreeMap::new(),
+        operation_ids: HashSet::new(),
+        security: BTreeMap::new(),
+    };
+    for request in requests {
+        let examples = match read_examples(&request.path).await {
+            Ok(examples) => examples,
+            Err(e) => {
+                let context = request.path.display().to_string();
+                exporter
+                    .report
+                    .warn(&context, format!("Failed to read examples, {}", e));
+                Vec::new()
+            }
+        };
+        exporter.operation(request, examples);
+    }
+
+    let Exporter {
+        report,
+        servers,
+        paths,
+        security,
+        ..
+    } = exporter;
+
+    let mut document = json!({
+        "openapi": OPENAPI_VERSION,
+        "info": {
+            "title": collection.name,
+            "version": "1.0.0",
+        },
+    });
+    if !servers.is_empty() {
+        document["servers"] = Value::Array(servers.into_iter().map(|(_, s)| s).collect());
+    }
+    document["paths"] = Value::Object(
+        paths
+            .into_iter()
+            .map(|(path, item)| (path, Value::Object(item)))
+            .collect(),
+    );
+    if !security.is_empty() {
+        document["components"] = json!({
+            "securitySchemes": Map::from_iter(security),
+        });
+    }
+
+    Ok((document, report))
+}
+
+struct Exporter<'a> {
+    env: Option<&'a Environment>,
+    history: &'a [HistoryEntry],
+    report: ExportReport,
+    /// Server URL templates in the order they were first used
+    servers: Vec<(String, Value)>,
+    paths: BTreeMap<String, Map<String, Value>>,
+    operation_ids: HashSet<String>,
+    security: BTreeMap<String, Value>,
+}
+
+/// Converts `{{var}}` and `:param` references to `{name}` and returns the
+/// names in the order they appear
+fn template(text: &str, path_params: bool) -> (String, Vec<String>) {
+    let regex = if path_params {
+        Regex::new(r"\{\{\s*([a-zA-Z0-9_.-]+)\s*\}\}|/:([a-zA-Z0-9_-]+)").unwrap()
+    } else {
+        Regex::new(r"\{\{\s*([a-zA-Z0-9_.-]+)\s*\}\}").unwrap()
+    };
+
+    let mut names = Vec::new();
+    let text = regex
+        .replace_all(text, |cap: &Captures| match (cap.get(1), cap.get(2)) {
+            (Some(var), _) => {
+                names.push(var.as_str().to_string());
+                format!("{{{}}}", var.as_str())
+            }
+            (_, Some(param)) => {
+                names.push(param.as_str().to_string());
+                format!("/{{{}}}", param.as_str())
+            }
+            _ => cap[0].to_string(),
+        })
+        .to_string();
+    (text, names)
+}
+
+fn is_templated(value: &str) -> bool {
+    value.contains("{{")
+}
+
+/// Schema describing the shape of a JSON example
+pub fn infer_schema(value: &Value) -> Value {
+    match value {
+        Value::Null => json!({ "type": "null" }),
+        Value::Bool(_) => json!({ "type": "boolean" }),
+        Value::Number(n) if n.is_f64() => json!({ "type": "number" }),
+        Value::Number(_) => json!({ "type": "integer" }),
+        Value::String(_) => json!({ "type": "string" }),
+        Value::Array(items) => match items.first() {
+            Some(item) => json!({ "type": "array", "items": infer_schema(item) }),
+            None => json!({ "type": "array" }),
+        },
+        Value::Object(fields) => {
+            let properties = fields
+                .iter()
+                .map(|(name, value)| (name.clone(), infer_schema(value)))
+                .collect::<Map<_, _>>();
+            json!({ "type": "object", "properties": properties })
+        }
+    }
+}
+
+fn string_properties(params: &KeyValList) -> Map<String, Value> {
+    params
+        .iter()
+        .filter(|p| !p.disabled && !p.name.is_empty())
+        .map(|p| (p.name.clone(), json!({ "type": "string" })))
+        .collect()
+}
+
+fn method_key(method: Method) -> Option<&'static str> {
+    match method {
+        Method::GET => Some("get"),
+        Method::POST => Some("post"),
+        Method::PUT => Some("put"),
+        Method::DELETE => Some("delete"),
+        Method::PATCH => Some("patch"),
+        Method::HEAD => Some("head"),
+        Method::OPTIONS => Some("options"),
+        Method::TRACE => Some("trace"),
+        Method::CONNECT => None,
+    }
+}
+
+impl Exporter<'_> {
+    fn operation(&mut self, entry: RequestEntry, examples: Vec<ResponseExample>) {
+        let RequestEntry {
+            folders,
+            name,
+            request,
+            ..
+        } = entry;
+        let context = folders
+            .iter()
+            .chain([&name])
+            .cloned()
+            .collect::<Vec<_>>()
+            .join("/");
+
+        let Some(method) = method_key(request.method) else {
+            self.report.warn(
+                &context,
+                format!("{} is not supported by OpenAPI", request.method),
+            );
+            return;
+        };
+
+        let (server, path) = split_url(&request.url);
+        let server = self.server(server);
+        let (mut path, names) = template(path, true);
+        if !path.starts_with('/') {
+            path.insert(0, '/');
+        }
+
+        if self
+            .paths
+            .get(&path)
+            .is_some_and(|item| item.contains_key(method))
+        {
+            self.report.warn(
+                &context,
+                format!(
+                    "{} {} is already described by another request",
+                    method, path
+                ),
+            );
+            return;
+        }
+
+        let mut operation = Map::new();
+        if let Some(tag) = folders.first() {
+            operation.insert("tags".to_string(), json!([tag]));
+        }
+        operation.insert("summary".to_string(), json!(name));
+        // Leaves out the placeholder description of new requests
+        let description = request.description.trim();
+        if !description.is_empty()
+            && description != name
+            && description != Request::default().description
+        {
+            operation.insert("description".to_string(), json!(description));
+        }
+        operation.insert("operationId".to_string(), json!(self.operation_id(&name)));
+
+        let parameters = parameters(&request, &names);
+        if !parameters.is_empty() {
+            operation.insert("parameters".to_string(), Value::Array(parameters));
+        }
+        if let Some(body) = self.request_body(&request.body, &context) {
+            operation.insert("requestBody".to_string(), body);
+        }
+        let recorded = match examples.is_empty() {
+            true => latest_response(self.history, &request, self.env)
+                .into_iter()
+                .collect(),
+            false => examples.iter().map(Recorded::from_example).collect(),
+        };
+        operation.insert("responses".to_string(), responses(recorded));
+        if let Some(scheme) = self.security(&request.auth) {
+            operation.insert("security".to_string(), json!([{ scheme: [] }]));
+        }
+
+        // Operations served from another server than the document default
+        if let Some(idx) = server.filter(|idx| *idx > 0) {
+            let server = self.servers[idx].1.clone();
+            operation.insert("servers".to_string(), json!([server]));
+        }
+
+        let item = self.paths.entry(path).or_default();
+        item.insert(method.to_string(), Value::Object(operation));
+    }
+
+    /// Index of the server for the URL prefix, `{{vars}}` become server
+    /// variables defaulting to the environment value
+    fn server(&mut self, prefix: &str) -> Option<usize> {
+        if prefix.is_empty() {
+            return None;
+        }
+        let (url, names) = template(prefix, false);
+        if let Some(idx) = self.servers.iter().position(|(u, _)| *u == url) {
+            return Some(idx);
+        }
+
+        let mut server = json!({ "url": url });
+        if !names.is_empty() {
+            let variables = names
+                .iter()
+                .map(|name| {
+                    let value = self.env.and_then(|env| env.get(name)).unwrap_or_default();
+                    (name.clone(), json!({ "default": value }))
+                })
+                .collect::<Map<_, _>>();
+            server["variables"] = Value::Object(variables);
+        }
+        self.servers.push((url, server));
+        Some(self.servers.len() - 1)
+    }
+
+    fn operation_id(&mut self, name: &str) -> String {
+        let mut base = param_name(name);
+        if let Some(first) = base.get_mut(..1) {
+            first.make_ascii_lowercase();
+        }
+        let mut id = base.clone();
+        let mut counter = 2;
+        while !self.operation_ids.insert(id.clone()) {
+            id = format!("{}{}", base, counter);
+            counter += 1;
+        }
+        id
+    }
+
+    fn security(&mut self, auth: &Auth) -> Option<&'static str> {
+        let (name, scheme) = match auth {
+            Auth::None => return None,
+            Auth::Basic { .. } => (BASIC_AUTH, "basic"),
+            Auth::Bearer { .. } => (BEARER_AUTH, "bearer"),
+        };
+        self.security
+            .entry(name.to_string())
+            .or_insert_with(|| json!({ "type": "http", "scheme": scheme }));
+        Some(name)
+    }
+
+    fn request_body(&mut self, body: &RequestBody, context: &str) -> Option<Value> {
+        let (content_type, media) = match body {
+            RequestBody::Json(json) => match serde_json::from_str::<Value>(json) {
+                Ok(example) => (
+                    "application/json",
+                    json!({ "schema": infer_schema(&example), "example": example }),
+                ),
+                Err(_) => {
+                    self.report.warn(
+                        context,
+                        "JSON body is not valid JSON, only its example was exported",
+                    );
+                    ("application/json", json!({ "example": json }))
+                }
+            },
+            RequestBody::GraphQL {
+                query,
+                variables,
+                operation_name,
+            } => {
+                let example = graphql::payload(query, variables, operation_name.as_deref()).ok()?;
+                (
+                    "application/json",
+                    json!({ "schema": infer_schema(&example), "example": example }),
+                )
+            }
+            RequestBody::XML(xml) => (
+                "application/xml",
+                json!({ "schema": { "type": "string" }, "example": xml }),
+            ),
+            RequestBody::Text(text) => (
+                "text/plain",
+                json!({ "schema": { "type": "string" }, "example": text }),
+            ),
+            RequestBody::Form(params) => (
+                "application/x-www-form-urlencoded",
+                json!({ "schema": { "type": "object", "properties": string_properties(params) } }),
+            ),
+            RequestBody::Multipart { params, files } => {
+                let mut properties = string_properties(params);
+                for file in files.iter().filter(|f| !f.disabled && !f.name.is_empty()) {
+                    properties.insert(
+                        file.name.clone(),
+                        json!({ "type": "string", "format": "binary" }),
+                    );
+                }
+                (
+                    "multipart/form-data",
+                    json!({ "schema": { "type": "object", "properties": properties } }),
+                )
+            }
+            RequestBody::File(path) => {
+                let content_type = path
+                    .as_ref()
+                    .and_then(|p| mime_guess::from_path(p).first())
+                    .map(|mime| mime.to_string())
+                    .unwrap_or("application/octet-stream".to_string());
+                return Some(json!({
+                    "content": { content_type: { "schema": { "type": "string", "format": "binary" } } },
+                }));
+            }
+            RequestBody::None => return None,
+        };
+
+        Some(json!({ "content": { content_type: media } }))
+    }
+}
+
+/// Status, content type and body of a response seen for a request
+#[derive(Debug)]
+struct Recorded {
+    status: u16,
+    content_type: Option<String>,
+    body: String,
+}
+
+impl Recorded {
+    fn from_example(example: &ResponseExample) -> Self {
+        let content_type = example
+            .headers
+            .iter()
+            .find(|h| !h.disabled && h.name.eq_ignore_ascii_case("content-type"))
+            .map(|h| h.value.clone());
+        Self {
+            status: example.status,
+            content_type,
+            body: example.body.clone(),
+        }
+    }
+}
+
+/// Matches the URLs requests were sent to, `:params` and unresolved
+/// `{{vars}}` match any path segment, and any server when they are part of it
+fn sent_url_pattern(url: &str, env: Option<&Environment>) -> Option<Regex> {
+    let url = render_lenient(url, env);
+    let (server, path) = split_url(&url);
+
+    let server = match is_templated(server) {
+        true => ".*".to_string(),
+        false => regex::escape(server.trim_end_matches('/')),
+    };
+    let path = path
+        .split('/')
+        .map(
+            |segment| match segment.starts_with(':') || is_templated(segment) {
+                true => "[^/]+".to_string(),
+                false => regex::escape(segment),
+            },
+        )
+        .collect::<Vec<_>>()
+        .join("/");
+    Regex::new(&format!("^{}{}/?$", server, path.trim_end_matches('/'))).ok()
+}
+
+/// Latest response received for the request, `history` is ordered from the
+/// newest entry
+fn latest_response(
+    history: &[HistoryEntry],
+    request: &Request,
+    env: Option<&Environment>,
+) -> Option<Recorded> {
+    let pattern = sent_url_pattern(&request.url, env)?;
+    let method = request.method.to_string();
+
+    history.iter().find_map(|entry| {
+        let response = entry.entry.response.as_ref()?;
+        let url = entry.url().split(['?', '#']).next().unwrap_or_default();
+        if !entry.method().eq_ignore_ascii_case(&method) || !pattern.is_match(url) {
+            return None;
+        }
+
+        let content = &response.content;
+        // Binary bodies are kept base64 encoded
+        let body = match content.encoding {
+            Some(_) => String::new(),
+            None => content.text.clone().unwrap_or_default(),
+        };
+        Some(Recorded {
+            status: response.status,
+            content_type: Some(content.mime_type.clone()).filter(|mime| !mime.is_empty()),
+            body,
+        })
+    })
+}
+
+fn response_object(recorded: Recorded) -> Value {
+    let description = reqwest::StatusCode::from_u16(recorded.status)
+        .ok()
+        .and_then(|status| status.canonical_reason())
+        .unwrap_or("Response");
+    let mut response = json!({ "description": description });
+    if recorded.body.trim().is_empty() {
+        return response;
+    }
+
+    // Parameters like the charset are left out
+    let content_type = recorded
+        .content_type
+        .as_deref()
+        .and_then(|ct| ct.split(';').next())
+        .map(|ct| ct.trim().to_lowercase())
+        .filter(|ct| !ct.is_empty());
+    let json = serde_json::from_str::<Value>(&recorded.body).ok();
+    let (content_type, media) = match (content_type, json) {
+        (Some(ct), Some(json)) if ct.contains("json") => (
+            ct,
+            json!({ "schema": infer_schema(&json), "example": json }),
+        ),
+        (None, Some(json)) => (
+            "application/json".to_string(),
+            json!({ "schema": infer_schema(&json), "example": json }),
+        ),
+        (content_type, _) => (
+            content_type.unwrap_or("text/plain".to_string()),
+            json!({ "schema": { "type": "string" } }),
+        ),
+    };
+    response["content"] = json!({ content_type: media });
+    response
+}
+
+/// Responses by status code, the first response of each status is used.
+/// Without any the operation only gets a default response.
+fn responses(recorded: Vec<Recorded>) -> Value {
+    let mut responses = Map::new();
+    for recorded in recorded {
+        let status = recorded.status.to_string();
+        if !responses.contains_key(&status) {
+            responses.insert(status, response_object(recorded));
+        }
+    }
+    if responses.is_empty() {
+        return json!({ "default": { "description": "Response" } });
+    }
+    Value::Object(responses)
+}
+
+/// Disabled query params and headers are documented too, they are only left
+/// out when sending
+fn parameters(request: &Request, path_names: &[String]) -> Vec<Value> {
+    let mut parameters = Vec::new();
+
+    let mut seen = HashSet::new();
+    for name in path_names.iter().filter(|n| seen.insert(n.as_str())) {
+        let mut param = json!({
+            "name": name,
+            "in": "path",
+            "required": true,
+            "schema": { "type": "string" },
+        });
+        let example = request
+            .path_params
+            .iter()
+            .find(|p| p.name == *name)
+            .map(|p| p.value.as_str())
+            .filter(|value| !value.is_empty() && !is_templated(value));
+        if let Some(example) = example {
+            param["example"] = json!(example);
+        }
+        parameters.push(param);
+    }
+
+    let (_, query) = request.url.split_once('?').unwrap_or_default();
+    let url_query = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| pair.split_once('=').unwrap_or((pair, "")))
+        .map(|(name, value)| (name.to_string(), value.to_string()));
+    let query = request
+        .query_params
+        .iter()
+        .filter(|p| !p.name.is_empty())
+        .map(|p| (p.name.clone(), p.value.clone()));
+    let headers = request
+        .headers
+        .iter()
+        .filter(|h| !h.name.is_empty())
+        .filter(|h| !RESERVED_HEADERS.contains(&h.name.to_lowercase().as_str()))
+        .map(|h| (h.name.clone(), h.value.clone()));
+
+    let params = url_query
+        .chain(query)
+        .map(|p| ("query", p))
+        .chain(headers.map(|h| ("header", h)));
+
+    let mut seen = HashSet::new();
+    for (location, (name, value)) in params {
+        if !seen.insert((location, name.to_lowercase())) {
+            continue;
+        }
+        let mut param = json!({
+            "name": name,
+            "in": location,
+            "schema": { "type": "string" },
+        });
+        if !value.is_empty() && !is_templated(&value) {
+            param["example"] = json!(value);
+        }
+        parameters.push(param);
+    }
+
+    parameters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::KeyValue;
+
+    fn sent(method: &str, url: &str, status: u16, mime: &str, body: &str) -> HistoryEntry {
+        serde_json::from_value(json!({
+            "entry": {
+                "request": { "method": method, "url": url },
+                "response": {
+                    "status": status,
+                    "content": { "mimeType": mime, "text": body },
+                },
+            },
+        }))
+        .unwrap()
+    }
+
+    fn env() -> Environment {
+        let mut env = Environment::new("dev".to_string());
+        env.variables = KeyValList::from(vec![KeyValue {
+            disabled: false,
+            name: "baseUrl".to_string(),
+            value: "https://api.example.com/v1".to_string(),
+        }]);
+        env
+    }
+
+    #[test]
+    fn matches_sent_urls() {
+        let env = env();
+        let pattern = sent_url_pattern("{{baseUrl}}/users/:id", Some(&env)).unwrap();
+        assert!(pattern.is_match("https://api.example.com/v1/users/42"));
+        assert!(!pattern.is_match("https://api.example.com/v1/users/42/posts"));
+        assert!(!pattern.is_match("https://other.com/v1/users/42"));
+
+        // Unknown servers match any server
+        let pattern = sent_url_pattern("{{host}}/users/{{id}}", None).unwrap();
+        assert!(pattern.is_match("http://localhost:8080/users/42"));
+    }
+
+    #[test]
+    fn describes_latest_sent_response() {
+        let request = Request {
+            method: Method::GET,
+            url: "{{baseUrl}}/users/:id".to_string(),
+            ..Request::default()
+        };
+        let history = [
+            sent("POST", "https://api.example.com/v1/users/1", 201, "", ""),
+            sent(
+                "GET",
+                "https://api.example.com/v1/users/2?full=true",
+                200,
+                "application/json; charset=utf-8",
+                r#"{"id": 2, "name": "a"}"#,
+            ),
+            sent("GET", "https://api.example.com/v1/users/3", 404, "", ""),
+        ];
+
+        let env = env();
+        let recorded = latest_response(&history, &request, Some(&env));
+        let responses = responses(recorded.into_iter().collect());
+        assert_eq!(
+            responses,
+            json!({
+                "200": {
+                    "description": "OK",
+                    "content": {
+                        "application/json": {
+                            "schema": {
+                                "type": "object",
+                                "properties": {
+                                    "id": { "type": "integer" },
+                                    "name": { "type": "string" },
+                                },
+                            },
+                            "example": { "id": 2, "name": "a" },
+                        },
+                    },
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn describes_examples_by_status() {
+        let example = |name: &str, status, body: &str| ResponseExample {
+            name: name.to_string(),
+            status,
+            headers: KeyValList::new(),
+            body: body.to_string(),
+        };
+        let examples = [
+            example("missing", 404, "Not found"),
+            example("ok", 200, "[1, 2]"),
+            example("other", 200, "{}"),
+        ];
+
+        let recorded = examples.iter().map(Recorded::from_example).collect();
+        let responses = responses(recorded);
+        assert_eq!(
+            responses["200"]["content"]["application/json"]["schema"],
+            json!({ "type": "array", "items": { "type": "integer" } })
+        );
+        assert_eq!(
+            responses["404"],
+            json!({
+                "description": "Not Found",
+                "content": { "text/plain": { "schema": { "type": "string" } } },
+            })
+        );
+    }
+
+    #[test]
+    fn defaults_without_recorded_responses() {
+        assert_eq!(
+            responses(Vec::new()),
+            json!({ "default": { "description": "Response" } })
+        );
+    }
+}
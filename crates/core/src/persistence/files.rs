@@ -0,0 +1,256 @@
+//! Crash-safe writes and detection of files changed on disk since the app
+//! last read them.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use std::time::SystemTime;
+use std::{fmt, io};
+
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+
+/// Saving would overwrite changes made outside of the app
+#[derive(Debug, Clone)]
+pub struct FileConflict {
+    pub path: PathBuf,
+}
+
+impl fmt::Display for FileConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} was changed on disk since it was loaded, refusing to overwrite it",
+            self.path.display()
+        )
+    }
+}
+
+impl std::error::Error for FileConflict {}
+
+/// Content of a file at the time it was read, see [`track`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FileVersion {
+    modified: Option<SystemTime>,
+    len: u64,
+    hash: u64,
+}
+
+/// Last version read or written by the app of each tracked file
+static VERSIONS: LazyLock<Mutex<HashMap<PathBuf, FileVersion>>> = LazyLock::new(Default::default);
+
+fn content_hash(content: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+async fn version_of(path: &Path, content: &[u8]) -> FileVersion {
+    let modified = match fs::metadata(path).await {
+        Ok(meta) => meta.modified().ok(),
+        Err(_) => None,
+    };
+    FileVersion {
+        modified,
+        len: content.len() as u64,
+        hash: content_hash(content),
+    }
+}
+
+/// Remembers the version of the file the app now holds, saving it fails
+/// once the file no longer has this content
+pub fn track(path: &Path, version: FileVersion) {
+    if let Ok(mut versions) = VERSIONS.lock() {
+        versions.insert(path.to_path_buf(), version);
+    }
+}
+
+fn known_version(path: &Path) -> Option<FileVersion> {
+    VERSIONS.lock().ok()?.get(path).copied()
+}
+
+/// Fails when the file no longer has the content it had when it was last
+/// read, files which were never read or were removed since are not checked
+async fn check_unchanged(path: &Path) -> anyhow::Result<()> {
+    let Some(known) = known_version(path) else {
+        return Ok(());
+    };
+    let meta = match fs::metadata(path).await {
+        Ok(meta) => meta,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+    if meta.modified().ok() == known.modified && meta.len() == known.len {
+        return Ok(());
+    }
+
+    // Touched without being changed
+    let content = fs::read(path).await?;
+    if content_hash(&content) == known.hash {
+        return Ok(());
+    }
+    Err(FileConflict {
+        path: path.to_path_buf(),
+    }
+    .into())
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    // Hidden so the collection watcher skips it
+    path.with_file_name(format!(".{}.{:08x}.tmp", name, fastrand::u32(..)))
+}
+
+async fn write_temp(temp: &Path, path: &Path, content: &[u8]) -> io::Result<()> {
+    let mut file = fs::File::create(temp).await?;
+    file.write_all(content).await?;
+    file.sync_all().await?;
+
+    if let Ok(meta) = fs::metadata(path).await {
+        fs::set_permissions(temp, meta.permissions()).await?;
+    }
+    fs::rename(temp, path).await
+}
+
+/// Writes the whole content to a temporary file next to `path` and renames
+/// it over the file, a crash leaves either the old or the new content
+pub async fn write_atomic(path: &Path, content: impl AsRef<[u8]>) -> anyhow::Result<()> {
+    let temp = temp_path(path);
+    if let Err(e) = write_temp(&temp, path, content.as_ref()).await {
+        let _ = fs::remove_file(&temp).await;
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+/// Reads the file with its version, which is only tracked once passed to
+/// [`track`]
+pub async fn read_versioned(path: &Path) -> anyhow::Result<(String, FileVersion)> {
+    let content = fs::read_to_string(path).await?;
+    let version = version_of(path, content.as_bytes()).await;
+    Ok((content, version))
+}
+
+/// Reads the file and remembers its version to detect later changes made
+/// outside of the app
+pub async fn read_tracked(path: &Path) -> anyhow::Result<String> {
+    let (content, version) = read_versioned(path).await?;
+    track(path, version);
+    Ok(content)
+}
+
+/// Atomically writes a file read with [`read_tracked`], failing with a
+/// [`FileConflict`] if it changed on disk since
+pub async fn write_tracked(path: &Path, content: impl AsRef<[u8]>) -> anyhow::Result<()> {
+    check_unchanged(path).await?;
+    write_atomic(path, content.as_ref()).await?;
+    track(path, version_of(path, content.as_ref()).await);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn names(dir: &Path) -> Vec<String> {
+        let entries = std::fs::read_dir(dir).unwrap();
+        let mut names: Vec<_> = entries
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    fn is_conflict(result: anyhow::Result<()>, path: &Path) -> bool {
+        let error = result.unwrap_err();
+        error
+            .downcast_ref::<FileConflict>()
+            .is_some_and(|conflict| conflict.path == path)
+    }
+
+    #[tokio::test]
+    async fn write_atomic_replaces_the_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("request.hcl");
+
+        write_atomic(&path, "first").await.unwrap();
+        write_atomic(&path, "second").await.unwrap();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "second");
+        // No temporary file is left behind
+        assert_eq!(names(dir.path()), ["request.hcl"]);
+    }
+
+    #[tokio::test]
+    async fn write_atomic_fails_without_leftovers() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("missing").join("request.hcl");
+
+        assert!(write_atomic(&path, "content").await.is_err());
+        assert!(names(dir.path()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn write_tracked_refuses_files_changed_on_disk() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("request.hcl");
+        std::fs::write(&path, "mine").unwrap();
+
+        assert_eq!(read_tracked(&path).await.unwrap(), "mine");
+        std::fs::write(&path, "theirs, changed").unwrap();
+
+        assert!(is_conflict(write_tracked(&path, "new").await, &path));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "theirs, changed");
+    }
+
+    #[tokio::test]
+    async fn write_tracked_allows_own_and_unchanged_files() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("request.hcl");
+        std::fs::write(&path, "mine").unwrap();
+
+        read_tracked(&path).await.unwrap();
+        // Rewritten with the same content
+        std::fs::write(&path, "mine").unwrap();
+        write_tracked(&path, "first save").await.unwrap();
+        write_tracked(&path, "second save").await.unwrap();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "second save");
+    }
+
+    #[tokio::test]
+    async fn versions_are_tracked_only_when_asked() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("request.hcl");
+        std::fs::write(&path, "mine").unwrap();
+
+        let (content, version) = read_versioned(&path).await.unwrap();
+        assert_eq!(content, "mine");
+        std::fs::write(&path, "theirs, changed").unwrap();
+        // Never tracked, nothing to conflict with
+        write_tracked(&path, "saved").await.unwrap();
+
+        track(&path, version);
+        assert!(is_conflict(write_tracked(&path, "again").await, &path));
+    }
+
+    #[tokio::test]
+    async fn removed_files_are_written_again() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("request.hcl");
+        std::fs::write(&path, "mine").unwrap();
+
+        read_tracked(&path).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        write_tracked(&path, "saved").await.unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "saved");
+    }
+}
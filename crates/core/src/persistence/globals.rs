@@ -0,0 +1,81 @@
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use tokio::fs;
+
+use crate::http::environment::{Environment, GLOBAL_ENV};
+
+use super::collections::project_dirs;
+use super::environment::EncodedEnvironment;
+use super::files::write_atomic;
+
+pub const GLOBALS_FILE: &str = "globals.hcl";
+
+/// Global variables are kept in the data directory next to the list of
+/// open collections. They are not secret, values are saved in plain text.
+pub fn globals_file() -> Option<PathBuf> {
+    let dirs = project_dirs()?;
+    Some(dirs.data_dir().join(GLOBALS_FILE))
+}
+
+/// Reads the global variables, none are defined until they are first saved
+pub async fn read_globals() -> anyhow::Result<Environment> {
+    let path = globals_file().context("Failed to find the data directory")?;
+    let content = match fs::read_to_string(&path).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(Environment::new(GLOBAL_ENV.to_string()));
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let encoded: EncodedEnvironment = hcl::from_str(&content)
+        .with_context(|| format!("Failed to parse global variables {}", path.display()))?;
+    let mut globals = Environment::from(encoded);
+    // Nothing fills secret values of globals, a `secret` flag is ignored
+    globals.secrets.clear();
+    Ok(globals)
+}
+
+pub async fn save_globals(globals: Environment) -> anyhow::Result<()> {
+    let path = globals_file().context("Failed to find the data directory")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+
+    write_atomic(&path, hcl::to_string(&encode_globals(globals))?).await?;
+    Ok(())
+}
+
+fn encode_globals(globals: Environment) -> EncodedEnvironment {
+    EncodedEnvironment::from(Environment {
+        name: GLOBAL_ENV.to_string(),
+        secrets: BTreeSet::new(),
+        ..globals
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::{KeyValList, KeyValue};
+
+    #[test]
+    fn saves_globals_in_plain_text() {
+        let mut globals = Environment::new("any".to_string());
+        globals.variables = KeyValList::from(vec![KeyValue {
+            disabled: false,
+            name: "token".to_string(),
+            value: "t0ken".to_string(),
+        }]);
+        globals.secrets.insert("token".to_string());
+
+        let encoded = encode_globals(globals);
+
+        assert_eq!(encoded.name, GLOBAL_ENV);
+        let token = &encoded.variables[0];
+        assert_eq!(token.value, "t0ken");
+        assert!(!token.secret);
+    }
+}
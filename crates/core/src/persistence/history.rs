@@ -0,0 +1,621 @@
+use std::ops::Not;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::Context;
+use chrono::{DateTime, FixedOffset, Utc};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use strum::{Display, VariantArray};
+use tokio::fs;
+
+use crate::client::Response;
+use crate::har::{Cache, Entry, SentRequest, Timings};
+use crate::http::environment::Environment;
+use crate::http::grpc::GrpcRequest;
+use crate::http::request::{Method, Request};
+use crate::http::{KeyValList, KeyValue};
+
+use super::collections::project_dirs;
+use super::files::write_atomic;
+use super::import::har::to_request;
+
+/// Directory in the data directory holding a file per sent request
+pub const HISTORY_DIR: &str = "history";
+const HISTORY_EXTENSION: &str = ".json";
+
+/// Oldest entries are deleted past this many
+pub const MAX_ENTRIES: usize = 500;
+
+/// Request and response bodies are cut to this many bytes
+pub const MAX_BODY_SIZE: usize = 64 * 1024;
+
+/// A sent request with its response, as a HAR entry. Secret values of the
+/// environment are redacted before it is written.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Name of the file, sorts from the oldest to the newest entry
+    #[serde(skip)]
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<String>,
+    /// Why no response was received
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// A body was cut to [`MAX_BODY_SIZE`]
+    #[serde(default, skip_serializing_if = "Not::not")]
+    pub truncated: bool,
+    /// Method called by a gRPC entry, whose message is the request body
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grpc: Option<GrpcTarget>,
+    pub entry: Entry,
+}
+
+/// Server and method of a gRPC call, the entry is replayed as a gRPC request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GrpcTarget {
+    pub url: String,
+    pub service: String,
+    pub method: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, VariantArray)]
+pub enum StatusFilter {
+    #[strum(to_string = "2xx")]
+    Success,
+    #[strum(to_string = "3xx")]
+    Redirect,
+    #[strum(to_string = "4xx")]
+    ClientError,
+    #[strum(to_string = "5xx")]
+    ServerError,
+    Failed,
+}
+
+impl StatusFilter {
+    fn matches(self, status: Option<u16>) -> bool {
+        match (self, status) {
+            (Self::Success, Some(status)) => (200..300).contains(&status),
+            (Self::Redirect, Some(status)) => (300..400).contains(&status),
+            (Self::ClientError, Some(status)) => (400..500).contains(&status),
+            (Self::ServerError, Some(status)) => status >= 500,
+            (Self::Failed, None) => true,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HistoryFilter {
+    /// Searched in the URL, headers, bodies and environment, ignoring case
+    pub text: String,
+    pub method: Option<Method>,
+    pub status: Option<StatusFilter>,
+    pub host: Option<String>,
+}
+
+/// Cuts the text to the size cap on a character boundary
+fn truncate(text: &mut String) -> bool {
+    if text.len() <= MAX_BODY_SIZE {
+        return false;
+    }
+    let mut end = MAX_BODY_SIZE;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text.truncate(end);
+    true
+}
+
+fn new_id() -> String {
+    format!(
+        "{:013}-{:04x}",
+        Utc::now().timestamp_millis(),
+        fastrand::u16(..)
+    )
+}
+
+impl HistoryEntry {
+    /// Entry of a request sent with the environment, `Err` when no response
+    /// was received
+    pub fn new(
+        sent: SentRequest,
+        response: Result<&Response, &anyhow::Error>,
+        env: Option<&Environment>,
+    ) -> Self {
+        let (mut entry, error) = match response {
+            Ok(response) => (sent.entry(response), None),
+            Err(e) => {
+                let entry = Entry {
+                    started_date_time: sent.started.to_rfc3339(),
+                    time: 0.0,
+                    request: sent.request,
+                    response: None,
+                    cache: Cache::default(),
+                    timings: Timings::default(),
+                };
+                (entry, Some(format!("{:#}", e)))
+            }
+        };
+        if let Some(env) = env {
+            entry.redact(env);
+        }
+
+        let mut truncated = false;
+        if let Some(post_data) = &mut entry.request.post_data {
+            truncated |= truncate(&mut post_data.text);
+        }
+        if let Some(content) = entry.response.as_mut().map(|r| &mut r.content) {
+            // Cutting base64 would leave a broken body
+            if content.encoding.is_some()
+                && content
+                    .text
+                    .as_ref()
+                    .is_some_and(|t| t.len() > MAX_BODY_SIZE)
+            {
+                content.text = None;
+                truncated = true;
+            } else if let Some(text) = &mut content.text {
+                truncated |= truncate(text);
+            }
+        }
+
+        Self {
+            id: new_id(),
+            environment: env.map(|env| env.name.clone()),
+            error,
+            truncated,
+            grpc: None,
+            entry,
+        }
+    }
+
+    /// Entry of a gRPC call, see [`crate::grpc::GrpcCall::sent`]
+    pub fn grpc(
+        target: GrpcTarget,
+        sent: SentRequest,
+        response: Result<&Response, &anyhow::Error>,
+        env: Option<&Environment>,
+    ) -> Self {
+        let target = GrpcTarget {
+            url: env.map_or(target.url.clone(), |env| env.redact(&target.url)),
+            ..target
+        };
+        Self {
+            grpc: Some(target),
+            ..Self::new(sent, response, env)
+        }
+    }
+
+    pub fn method(&self) -> &str {
+        &self.entry.request.method
+    }
+
+    pub fn url(&self) -> &str {
+        &self.entry.request.url
+    }
+
+    pub fn host(&self) -> Option<String> {
+        let url = Url::parse(self.url()).ok()?;
+        url.host_str().map(str::to_string)
+    }
+
+    pub fn status(&self) -> Option<u16> {
+        self.entry.response.as_ref().map(|r| r.status)
+    }
+
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f64(self.entry.time.max(0.0) / 1000.0)
+    }
+
+    pub fn started(&self) -> Option<DateTime<FixedOffset>> {
+        DateTime::parse_from_rfc3339(&self.entry.started_date_time).ok()
+    }
+
+    /// The request to send it again, `None` for gRPC calls and requests
+    /// this app cannot send
+    pub fn to_request(&self) -> Option<Request> {
+        if self.grpc.is_some() {
+            return None;
+        }
+        to_request(&self.entry.request)
+    }
+
+    /// The gRPC call to send it again
+    pub fn to_grpc_request(&self) -> Option<GrpcRequest> {
+        let target = self.grpc.clone()?;
+        let request = &self.entry.request;
+        let metadata = request
+            .headers
+            .iter()
+            .map(|h| KeyValue {
+                disabled: false,
+                name: h.name.clone(),
+                value: h.value.clone(),
+            })
+            .collect();
+
+        Some(GrpcRequest {
+            url: target.url,
+            service: target.service,
+            method: target.method,
+            message: request
+                .post_data
+                .as_ref()
+                .map(|p| p.text.clone())
+                .unwrap_or_else(|| "{}".to_string()),
+            metadata: KeyValList::from(metadata),
+            ..GrpcRequest::default()
+        })
+    }
+
+    fn texts(&self) -> impl Iterator<Item = &str> {
+        let request = &self.entry.request;
+        let response = self.entry.response.as_ref();
+        let headers = request
+            .headers
+            .iter()
+            .chain(response.into_iter().flat_map(|r| r.headers.iter()))
+            .flat_map(|h| [h.name.as_str(), h.value.as_str()]);
+
+        [request.url.as_str()]
+            .into_iter()
+            .chain(self.environment.as_deref())
+            .chain(self.error.as_deref())
+            .chain(headers)
+            .chain(request.post_data.as_ref().map(|p| p.text.as_str()))
+            .chain(response.and_then(|r| r.content.text.as_deref()))
+    }
+
+    pub fn matches(&self, filter: &HistoryFilter) -> bool {
+        if let Some(method) = filter.method {
+            if !self.method().eq_ignore_ascii_case(&method.to_string()) {
+                return false;
+            }
+        }
+        if let Some(status) = filter.status {
+            if !status.matches(self.status()) {
+                return false;
+            }
+        }
+        if let Some(host) = &filter.host {
+            if self.host().as_ref() != Some(host) {
+                return false;
+            }
+        }
+
+        let text = filter.text.trim().to_lowercase();
+        text.is_empty() || self.texts().any(|t| t.to_lowercase().contains(&text))
+    }
+}
+
+pub fn history_dir() -> Option<PathBuf> {
+    let dirs = project_dirs()?;
+    Some(dirs.data_dir().join(HISTORY_DIR))
+}
+
+/// Names of the entry files from the oldest to the newest
+async fn entry_files(dir: &Path) -> anyhow::Result<Vec<String>> {
+    let mut files = match fs::read_dir(dir).await {
+        Ok(files) => files,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut names = Vec::new();
+    while let Some(file) = files.next_entry().await? {
+        let name = file.file_name().to_string_lossy().to_string();
+        if name.ends_with(HISTORY_EXTENSION) {
+            names.push(name);
+        }
+    }
+    names.sort_unstable();
+    Ok(names)
+}
+
+/// Entries from the newest to the oldest, files which cannot be read are
+/// skipped
+pub async fn read_history() -> anyhow::Result<Vec<HistoryEntry>> {
+    let dir = history_dir().context("Failed to find the data directory")?;
+    read_entries(&dir).await
+}
+
+async fn read_entries(dir: &Path) -> anyhow::Result<Vec<HistoryEntry>> {
+    let mut entries = Vec::new();
+    for name in entry_files(dir).await?.into_iter().rev() {
+        let Ok(content) = fs::read_to_string(dir.join(&name)).await else {
+            continue;
+        };
+        let Ok(mut entry) = serde_json::from_str::<HistoryEntry>(&content) else {
+            continue;
+        };
+        entry.id = name.trim_end_matches(HISTORY_EXTENSION).to_string();
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// Writes the entry and deletes the oldest ones past [`MAX_ENTRIES`]
+pub async fn save_history_entry(entry: HistoryEntry) -> anyhow::Result<()> {
+    let dir = history_dir().context("Failed to find the data directory")?;
+    save_entry(&dir, entry).await
+}
+
+async fn save_entry(dir: &Path, entry: HistoryEntry) -> anyhow::Result<()> {
+    fs::create_dir_all(dir).await?;
+
+    let path = dir.join(format!("{}{}", entry.id, HISTORY_EXTENSION));
+    write_atomic(&path, serde_json::to_string(&entry)?).await?;
+
+    let files = entry_files(dir).await?;
+    let excess = files.len().saturating_sub(MAX_ENTRIES);
+    for name in files.into_iter().take(excess) {
+        fs::remove_file(dir.join(name)).await?;
+    }
+    Ok(())
+}
+
+pub async fn clear_history() -> anyhow::Result<()> {
+    let dir = history_dir().context("Failed to find the data directory")?;
+    for name in entry_files(&dir).await? {
+        fs::remove_file(dir.join(name)).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+    use reqwest::StatusCode;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::client::{ContentType, ResponseBody};
+
+    fn sent(method: &str, url: &str, headers: &[(&str, &str)], body: &str) -> SentRequest {
+        let method = reqwest::Method::from_bytes(method.as_bytes()).unwrap();
+        let mut req = reqwest::Client::new().request(method, url);
+        for (name, value) in headers {
+            req = req.header(*name, *value);
+        }
+        if !body.is_empty() {
+            req = req.body(body.to_string());
+        }
+        SentRequest::new(&req.build().unwrap())
+    }
+
+    fn response(status: u16, headers: &[(&str, &str)], body: &str) -> Response {
+        let mut map = HeaderMap::new();
+        for (name, value) in headers {
+            map.append(
+                HeaderName::from_bytes(name.as_bytes()).unwrap(),
+                HeaderValue::from_str(value).unwrap(),
+            );
+        }
+        Response {
+            status: StatusCode::from_u16(status).unwrap(),
+            headers: map,
+            body: ResponseBody {
+                content_type: ContentType::Text,
+                data: body.as_bytes().to_vec(),
+            },
+            duration: Default::default(),
+            size_bytes: body.len(),
+        }
+    }
+
+    fn entry(method: &str, url: &str, status: u16) -> HistoryEntry {
+        let res = response(status, &[], "");
+        HistoryEntry::new(sent(method, url, &[], ""), Ok(&res), None)
+    }
+
+    fn failed(url: &str) -> HistoryEntry {
+        let error = anyhow::anyhow!("Connection refused");
+        HistoryEntry::new(sent("GET", url, &[], ""), Err(&error), None)
+    }
+
+    fn env() -> Environment {
+        Environment {
+            name: "prod".to_string(),
+            variables: KeyValList::from(vec![KeyValue {
+                disabled: false,
+                name: "token".to_string(),
+                value: "s3cr3t-t0ken".to_string(),
+            }]),
+            secrets: BTreeSet::from(["token".to_string()]),
+        }
+    }
+
+    #[test]
+    fn redacts_secrets_of_the_environment() {
+        let sent = sent(
+            "POST",
+            "https://api.example.com/users?key=s3cr3t-t0ken",
+            &[("X-Token", "s3cr3t-t0ken")],
+            r#"{"token": "s3cr3t-t0ken"}"#,
+        );
+        let res = response(200, &[("X-Echo", "s3cr3t-t0ken")], "echo s3cr3t-t0ken");
+
+        let entry = HistoryEntry::new(sent, Ok(&res), Some(&env()));
+
+        let text = serde_json::to_string(&entry).unwrap();
+        assert!(!text.contains("s3cr3t-t0ken"), "{}", text);
+        assert!(entry.url().ends_with("key=********"));
+        assert_eq!(entry.environment.as_deref(), Some("prod"));
+    }
+
+    #[test]
+    fn truncates_bodies_on_char_boundaries() {
+        let small = "a".repeat(MAX_BODY_SIZE);
+        let res = response(200, &[], &small);
+        let entry = HistoryEntry::new(sent("POST", "http://a.test/", &[], &small), Ok(&res), None);
+        assert!(!entry.truncated);
+
+        // `é` takes two bytes and straddles the cap
+        let body = format!("{}é{}", "a".repeat(MAX_BODY_SIZE - 1), "b".repeat(10));
+        let res = response(200, &[], &body);
+        let entry = HistoryEntry::new(sent("POST", "http://a.test/", &[], &body), Ok(&res), None);
+
+        assert!(entry.truncated);
+        let request = &entry.entry.request.post_data.as_ref().unwrap().text;
+        let response = entry.entry.response.as_ref().unwrap().content.text.as_ref();
+        assert_eq!(request, &"a".repeat(MAX_BODY_SIZE - 1));
+        assert_eq!(response, Some(request));
+    }
+
+    #[test]
+    fn drops_binary_bodies_past_the_cap() {
+        let mut res = response(200, &[], "");
+        res.body = ResponseBody {
+            content_type: ContentType::Buffer,
+            data: vec![0; MAX_BODY_SIZE],
+        };
+        let entry = HistoryEntry::new(sent("GET", "http://a.test/", &[], ""), Ok(&res), None);
+
+        assert!(entry.truncated);
+        assert_eq!(entry.entry.response.unwrap().content.text, None);
+    }
+
+    #[test]
+    fn searches_all_texts_ignoring_case() {
+        let sent = sent(
+            "POST",
+            "https://api.example.com/users",
+            &[("X-Trace", "Trace-42")],
+            "name=Ann",
+        );
+        let res = response(201, &[("Server", "Gunicorn")], "created user Bob");
+        let entry = HistoryEntry::new(sent, Ok(&res), Some(&env()));
+
+        let search = |text: &str| {
+            entry.matches(&HistoryFilter {
+                text: text.to_string(),
+                ..Default::default()
+            })
+        };
+        for text in [
+            "",
+            "EXAMPLE.com/users",
+            "trace-42",
+            "x-trace",
+            "ann",
+            "gunicorn",
+            "bob",
+            "PROD",
+        ] {
+            assert!(search(text), "{}", text);
+        }
+        assert!(!search("missing"));
+
+        assert!(failed("http://a.test/").matches(&HistoryFilter {
+            text: "connection REFUSED".to_string(),
+            ..Default::default()
+        }));
+    }
+
+    #[test]
+    fn filters_by_status_method_and_host() {
+        let entries = [
+            entry("GET", "https://a.test/ok", 204),
+            entry("POST", "https://b.test/moved", 301),
+            entry("PUT", "https://a.test/missing", 404),
+            entry("DELETE", "https://b.test/broken", 503),
+            failed("https://c.test/down"),
+        ];
+        let matching = |filter: HistoryFilter| {
+            entries
+                .iter()
+                .filter(|e| e.matches(&filter))
+                .map(|e| e.url().rsplit('/').next().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+        let status = |status| HistoryFilter {
+            status: Some(status),
+            ..Default::default()
+        };
+
+        assert_eq!(matching(status(StatusFilter::Success)), ["ok"]);
+        assert_eq!(matching(status(StatusFilter::Redirect)), ["moved"]);
+        assert_eq!(matching(status(StatusFilter::ClientError)), ["missing"]);
+        assert_eq!(matching(status(StatusFilter::ServerError)), ["broken"]);
+        assert_eq!(matching(status(StatusFilter::Failed)), ["down"]);
+
+        let method = HistoryFilter {
+            method: Some(Method::POST),
+            ..Default::default()
+        };
+        assert_eq!(matching(method), ["moved"]);
+
+        let host = HistoryFilter {
+            host: Some("a.test".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(matching(host), ["ok", "missing"]);
+
+        let both = HistoryFilter {
+            host: Some("b.test".to_string()),
+            status: Some(StatusFilter::ServerError),
+            ..Default::default()
+        };
+        assert_eq!(matching(both), ["broken"]);
+    }
+
+    #[tokio::test]
+    async fn evicts_the_oldest_entries() {
+        let dir = TempDir::new().unwrap();
+        for idx in 0..MAX_ENTRIES {
+            let mut entry = entry("GET", &format!("http://a.test/{}", idx), 200);
+            entry.id = format!("{:013}-0000", idx);
+            save_entry(dir.path(), entry).await.unwrap();
+        }
+        assert_eq!(read_entries(dir.path()).await.unwrap().len(), MAX_ENTRIES);
+
+        let mut newest = entry("GET", "http://a.test/newest", 200);
+        newest.id = format!("{:013}-0000", MAX_ENTRIES);
+        save_entry(dir.path(), newest).await.unwrap();
+
+        let entries = read_entries(dir.path()).await.unwrap();
+        assert_eq!(entries.len(), MAX_ENTRIES);
+        assert_eq!(entries[0].url(), "http://a.test/newest");
+        assert_eq!(entries[MAX_ENTRIES - 1].url(), "http://a.test/1");
+    }
+
+    #[test]
+    fn replays_grpc_calls_as_grpc_requests() {
+        let target = GrpcTarget {
+            url: "http://localhost:50051".to_string(),
+            service: "echo.Echo".to_string(),
+            method: "Say".to_string(),
+        };
+        let sent = sent(
+            "POST",
+            "http://localhost:50051/echo.Echo/Say",
+            &[("x-user", "ann")],
+            r#"{"text": "hi"}"#,
+        );
+        let res = response(200, &[("grpc-status", "0")], r#"{"text": "hi"}"#);
+        let entry = HistoryEntry::grpc(target.clone(), sent.clone(), Ok(&res), None);
+
+        assert!(entry.to_request().is_none());
+        let request = entry.to_grpc_request().unwrap();
+        assert_eq!(request.url, "http://localhost:50051");
+        assert_eq!(request.service, "echo.Echo");
+        assert_eq!(request.method, "Say");
+        assert_eq!(request.message, r#"{"text": "hi"}"#);
+        assert!(request
+            .metadata
+            .iter()
+            .any(|kv| kv.name == "x-user" && kv.value == "ann"));
+
+        assert!(failed("http://a.test/").to_grpc_request().is_none());
+
+        let target = GrpcTarget {
+            url: "http://s3cr3t-t0ken@localhost:50051".to_string(),
+            ..target
+        };
+        let entry = HistoryEntry::grpc(target, sent, Ok(&res), Some(&env()));
+        assert_eq!(entry.grpc.unwrap().url, "http://********@localhost:50051");
+    }
+}
@@ -0,0 +1,407 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use anyhow::Context;
+use indexmap::IndexMap;
+use reqwest::Url;
+
+use crate::har::{Har, HarRequest, NameValue, PostData};
+use crate::http::request::{Method, Request, RequestBody};
+use crate::http::{KeyFile, KeyFileList, KeyValList, KeyValue};
+use crate::utils::percent_decode;
+
+use super::{ImportReport, ImportedCollection, ImportedEntry};
+
+// Managed by the client, copying them from a browser capture breaks requests
+const SKIPPED_HEADERS: &[&str] = &[
+    "host",
+    "content-length",
+    "connection",
+    "accept-encoding",
+    "keep-alive",
+    "transfer-encoding",
+];
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HarOptions {
+    /// Keep only the first request for each method and path
+    pub deduplicate: bool,
+}
+
+/// Converts a HAR 1.2 capture into a collection, one request per entry.
+/// Entries are grouped in a folder per host when the capture spans hosts.
+pub fn import(har: &str, options: HarOptions) -> anyhow::Result<ImportedCollection> {
+    let har: Har = serde_json::from_str(har).context("Failed to parse HAR file")?;
+
+    let mut report = ImportReport::default();
+    let mut seen = HashSet::new();
+    let mut hosts: IndexMap<String, Vec<ImportedEntry>> = IndexMap::new();
+
+    for (idx, entry) in har.log.entries.iter().enumerate() {
+        let context = format!("Entry {}", idx + 1);
+        let Some((host, name, request)) = convert(&context, &entry.request, &mut report) else {
+            continue;
+        };
+
+        let key = (request.method, host.clone(), request.url.clone());
+        if options.deduplicate && !seen.insert(key) {
+            continue;
+        }
+
+        hosts.entry(host).or_default().push(ImportedEntry::Request {
+            name,
+            request: Box::new(request),
+        });
+    }
+
+    if hosts.is_empty() {
+        anyhow::bail!("No HTTP requests found in HAR file");
+    }
+
+    let name = match hosts.len() {
+        1 => hosts.keys().next().cloned().unwrap_or_default(),
+        _ => "HAR Import".to_string(),
+    };
+    let entries = match hosts.len() {
+        1 => hosts.into_values().flatten().collect(),
+        _ => hosts
+            .into_iter()
+            .map(|(name, entries)| ImportedEntry::Folder { name, entries })
+            .collect(),
+    };
+
+    Ok(ImportedCollection {
+        name,
+        entries,
+        environments: Vec::new(),
+        default_environment: None,
+        variables: KeyValList::new(),
+        report,
+    })
+}
+
+/// Request to send again a captured one, `None` when it is not an HTTP
+/// request this app can send
+pub fn to_request(har: &HarRequest) -> Option<Request> {
+    let (_, _, request) = convert("", har, &mut ImportReport::default())?;
+    Some(request)
+}
+
+fn convert(
+    context: &str,
+    har: &HarRequest,
+    report: &mut ImportReport,
+) -> Option<(String, String, Request)> {
+    let Ok(url) = Url::parse(&har.url) else {
+        report.warn(context, format!("invalid URL {}", har.url));
+        return None;
+    };
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    let Ok(method) = Method::from_str(&har.method.to_uppercase()) else {
+        report.warn(context, format!("unsupported method {}", har.method));
+        return None;
+    };
+
+    let host = url.host_str().unwrap_or_default().to_string();
+    let context = format!("{} {}", method, url.path());
+
+    let mut base = url.clone();
+    base.set_query(None);
+    base.set_fragment(None);
+
+    // The query string is preferred, it keeps the order and duplicates
+    let query_params = if har.query_string.is_empty() {
+        url.query_pairs()
+            .map(|(name, value)| key_value(&name, &value))
+            .collect()
+    } else {
+        har.query_string
+            .iter()
+            .map(|NameValue { name, value }| key_value(name, value))
+            .collect()
+    };
+
+    let mut headers = har
+        .headers
+        .iter()
+        .filter(|h| !h.name.starts_with(':'))
+        .filter(|h| !SKIPPED_HEADERS.contains(&h.name.to_lowercase().as_str()))
+        .map(|h| key_value(&h.name, &h.value))
+        .collect::<Vec<_>>();
+
+    let body = har
+        .post_data
+        .as_ref()
+        .map(|data| post_body(&context, data, report))
+        .unwrap_or(RequestBody::None);
+
+    // Content type of these bodies is set when sending
+    if !matches!(body, RequestBody::None | RequestBody::Text(_)) {
+        headers.retain(|h| !h.name.eq_ignore_ascii_case("content-type"));
+    }
+
+    let name = format!(
+        "{} {}",
+        method,
+        url.path().trim_matches('/').replace('/', " ")
+    );
+    let request = Request {
+        method,
+        url: base.to_string().trim_end_matches('/').to_string(),
+        headers: KeyValList::from(headers),
+        body,
+        query_params: KeyValList::from(query_params),
+        ..Request::default()
+    };
+
+    Some((host, name.trim().to_string(), request))
+}
+
+fn key_value(name: &str, value: &str) -> KeyValue {
+    KeyValue {
+        disabled: false,
+        name: name.to_string(),
+        value: value.to_string(),
+    }
+}
+
+fn post_body(context: &str, data: &PostData, report: &mut ImportReport) -> RequestBody {
+    let mime = data.mime_type.to_lowercase();
+
+    if mime.contains("x-www-form-urlencoded") {
+        let params = if data.text.is_empty() {
+            data.params
+                .iter()
+                .map(|p| key_value(&p.name, p.value.as_deref().unwrap_or_default()))
+                .collect()
+        } else {
+            data.text
+                .split('&')
+                .filter(|pair| !pair.is_empty())
+                .map(|pair| {
+                    let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+                    key_value(&percent_decode(name), &percent_decode(value))
+                })
+                .collect()
+        };
+        return RequestBody::Form(KeyValList::from(params));
+    }
+
+    if mime.contains("multipart/form-data") {
+        let mut params = Vec::new();
+        let mut files = Vec::new();
+        for param in &data.params {
+            if param.file_name.is_some() {
+                files.push(KeyFile {
+                    name: param.name.clone(),
+                    path: None,
+                    disabled: false,
+                });
+            } else {
+                params.push(key_value(
+                    &param.name,
+                    param.value.as_deref().unwrap_or_default(),
+                ));
+            }
+        }
+        if !files.is_empty() {
+            report.warn(
+                context,
+                "file parts were captured without content, select the files again",
+            );
+        }
+        if data.params.is_empty() && !data.text.is_empty() {
+            report.warn(context, "multipart body could not be split into parts");
+        }
+        return RequestBody::Multipart {
+            params: KeyValList::from(params),
+            files: KeyFileList::from(files),
+        };
+    }
+
+    let text = data.text.clone();
+    if mime.contains("json") {
+        RequestBody::Json(text)
+    } else if mime.contains("xml") {
+        RequestBody::XML(text)
+    } else {
+        RequestBody::Text(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    fn entry(method: &str, url: &str, headers: Value, post_data: Value) -> Value {
+        json!({
+            "request": {
+                "method": method,
+                "url": url,
+                "headers": headers,
+                "postData": post_data,
+            },
+        })
+    }
+
+    fn capture(entries: Vec<Value>) -> String {
+        json!({ "log": { "version": "1.2", "entries": entries } }).to_string()
+    }
+
+    fn requests(collection: &ImportedCollection) -> Vec<&Request> {
+        collection
+            .entries
+            .iter()
+            .filter_map(|entry| match entry {
+                ImportedEntry::Request { request, .. } => Some(&**request),
+                ImportedEntry::Folder { .. } => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn imports_captured_requests() {
+        let har = capture(vec![
+            entry(
+                "post",
+                "https://api.example.com/users/?tag=a&tag=b#top",
+                json!([
+                    { "name": ":authority", "value": "api.example.com" },
+                    { "name": "Host", "value": "api.example.com" },
+                    { "name": "Content-Type", "value": "application/json" },
+                    { "name": "Authorization", "value": "Bearer t0k3n" },
+                ]),
+                json!({ "mimeType": "application/json", "text": "{\"name\": \"a\"}" }),
+            ),
+            entry(
+                "PUT",
+                "https://api.example.com/login",
+                json!([]),
+                json!({
+                    "mimeType": "application/x-www-form-urlencoded",
+                    "text": "user=a%40b.com&note=two+words&empty",
+                }),
+            ),
+            entry("GET", "wss://api.example.com/live", json!([]), Value::Null),
+        ]);
+
+        let imported = import(&har, HarOptions::default()).unwrap();
+        assert_eq!(imported.name, "api.example.com");
+        assert!(imported.report.is_empty(), "{:?}", imported.report);
+
+        let names = imported
+            .entries
+            .iter()
+            .map(|entry| match entry {
+                ImportedEntry::Request { name, .. } => name.as_str(),
+                ImportedEntry::Folder { name, .. } => name.as_str(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["POST users", "PUT login"]);
+
+        let requests = requests(&imported);
+        let create = requests[0];
+        assert_eq!(create.method, Method::POST);
+        assert_eq!(create.url, "https://api.example.com/users");
+        assert_eq!(
+            create.query_params,
+            KeyValList::from(vec![key_value("tag", "a"), key_value("tag", "b")])
+        );
+        assert_eq!(
+            create.headers,
+            KeyValList::from(vec![key_value("Authorization", "Bearer t0k3n")])
+        );
+        assert_eq!(
+            create.body,
+            RequestBody::Json("{\"name\": \"a\"}".to_string())
+        );
+
+        assert_eq!(
+            requests[1].body,
+            RequestBody::Form(KeyValList::from(vec![
+                key_value("user", "a@b.com"),
+                key_value("note", "two words"),
+                key_value("empty", ""),
+            ]))
+        );
+    }
+
+    #[test]
+    fn groups_hosts_and_deduplicates() {
+        let get = |url: &str| entry("GET", url, json!([]), Value::Null);
+        let har = capture(vec![
+            get("https://a.example.com/items?page=1"),
+            get("https://b.example.com/"),
+            get("https://a.example.com/items?page=2"),
+        ]);
+
+        let imported = import(&har, HarOptions { deduplicate: true }).unwrap();
+        assert_eq!(imported.name, "HAR Import");
+        let folders = imported
+            .entries
+            .iter()
+            .map(|entry| match entry {
+                ImportedEntry::Folder { name, entries } => (name.as_str(), entries.len()),
+                ImportedEntry::Request { name, .. } => panic!("unexpected request {}", name),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(folders, [("a.example.com", 1), ("b.example.com", 1)]);
+
+        let imported = import(&har, HarOptions::default()).unwrap();
+        let ImportedEntry::Folder { entries, .. } = &imported.entries[0] else {
+            panic!("expected a folder per host");
+        };
+        assert_eq!(entries.len(), 2);
+    }
+
+    #[test]
+    fn reports_file_parts() {
+        let har = capture(vec![entry(
+            "POST",
+            "https://example.com/upload",
+            json!([{ "name": "Content-Type", "value": "multipart/form-data; boundary=x" }]),
+            json!({
+                "mimeType": "multipart/form-data; boundary=x",
+                "params": [
+                    { "name": "title", "value": "Photo" },
+                    { "name": "photo", "fileName": "photo.jpg" },
+                ],
+            }),
+        )]);
+
+        let imported = import(&har, HarOptions::default()).unwrap();
+        let request = requests(&imported)[0];
+        assert!(request.headers.is_empty());
+        assert_eq!(
+            request.body,
+            RequestBody::Multipart {
+                params: KeyValList::from(vec![key_value("title", "Photo")]),
+                files: KeyFileList::from(vec![KeyFile {
+                    name: "photo".to_string(),
+                    path: None,
+                    disabled: false,
+                }]),
+            }
+        );
+        assert_eq!(
+            imported.report.warnings,
+            ["POST /upload: file parts were captured without content, select the files again"]
+        );
+    }
+
+    #[test]
+    fn rejects_captures_without_http_requests() {
+        let har = capture(vec![entry(
+            "GET",
+            "wss://example.com/live",
+            json!([]),
+            Value::Null,
+        )]);
+        let err = import(&har, HarOptions::default()).unwrap_err();
+        assert_eq!(err.to_string(), "No HTTP requests found in HAR file");
+    }
+}
@@ -0,0 +1,250 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+
+use regex::{Captures, Regex};
+use tokio::fs;
+
+use crate::http::environment::Environment;
+use crate::http::request::Request;
+use crate::http::KeyValList;
+use crate::template::is_builtin;
+
+use super::collections::{save_collection, EncodedCollection};
+use super::environment::{save_environments, EncodedEnvironment};
+use super::files::write_atomic;
+use super::request::encode_request;
+use super::{Version, COLLECTION_ROOT_FILE, HCL_EXTENSION, REQUESTS};
+
+pub mod har;
+pub mod openapi;
+pub mod postman;
+
+/// Things that could not be converted while importing, one line each.
+#[derive(Debug, Clone, Default)]
+pub struct ImportReport {
+    pub warnings: Vec<String>,
+}
+
+impl ImportReport {
+    pub fn is_empty(&self) -> bool {
+        self.warnings.is_empty()
+    }
+
+    pub(crate) fn warn(&mut self, context: &str, msg: impl Display) {
+        if context.is_empty() {
+            self.warnings.push(msg.to_string());
+        } else {
+            self.warnings.push(format!("{}: {}", context, msg));
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ImportedEntry {
+    Request {
+        name: String,
+        request: Box<Request>,
+    },
+    Folder {
+        name: String,
+        entries: Vec<ImportedEntry>,
+    },
+}
+
+/// A collection converted from another tool, ready to be written to disk.
+#[derive(Debug, Clone)]
+pub struct ImportedCollection {
+    pub name: String,
+    pub entries: Vec<ImportedEntry>,
+    pub environments: Vec<Environment>,
+    pub default_environment: Option<String>,
+    /// Variables shared by the environments of the collection
+    pub variables: KeyValList,
+    pub report: ImportReport,
+}
+
+/// Writes the collection in the layout read by `open_collection` and returns
+/// the import report. Refuses to overwrite an existing collection.
+pub async fn write_collection(
+    path: PathBuf,
+    collection: ImportedCollection,
+) -> anyhow::Result<ImportReport> {
+    if fs::try_exists(path.join(COLLECTION_ROOT_FILE)).await? {
+        anyhow::bail!("A collection already exists at {}", path.display());
+    }
+
+    save_collection(
+        path.clone(),
+        EncodedCollection {
+            name: collection.name,
+            version: Version::V1,
+            default_environment: collection.default_environment,
+            variables: collection.variables.into_iter().map(Into::into).collect(),
+        },
+    )
+    .await?;
+
+    write_entries(&path.join(REQUESTS), collection.entries).await?;
+
+    let environments = collection
+        .environments
+        .into_iter()
+        .map(EncodedEnvironment::from)
+        .collect();
+    save_environments(path, environments).await?;
+
+    Ok(collection.report)
+}
+
+async fn write_entries(dir: &Path, entries: Vec<ImportedEntry>) -> anyhow::Result<()> {
+    fs::create_dir_all(dir).await?;
+
+    let mut used = HashSet::new();
+    for entry in entries {
+        match entry {
+            ImportedEntry::Request { name, request } => {
+                let name = unique_name(&mut used, &name);
+                let path = dir.join(format!("{}{}", name, HCL_EXTENSION));
+                // New files, nothing on disk to check for changes
+                write_atomic(&path, hcl::to_string(&encode_request(*request)?)?).await?;
+            }
+            ImportedEntry::Folder { name, entries } => {
+                let name = unique_name(&mut used, &name);
+                Box::pin(write_entries(&dir.join(name), entries)).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Turns an arbitrary name into something usable as a file name.
+pub fn file_name(name: &str) -> String {
+    let name = name
+        .trim()
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '-',
+            c if c.is_control() => '-',
+            c => c,
+        })
+        .collect::<String>();
+    let name = name.trim_matches('.').trim();
+
+    if name.is_empty() {
+        "Untitled".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+pub(crate) fn unique_name(used: &mut HashSet<String>, name: &str) -> String {
+    let base = file_name(name);
+    let mut name = base.clone();
+    let mut counter = 2;
+    // Case insensitive file systems would merge these
+    while !used.insert(name.to_lowercase()) {
+        name = format!("{} ({})", base, counter);
+        counter += 1;
+    }
+    name
+}
+
+/// Variable names can contain alphanumerics, `_`, `-` and `.`, other
+/// characters are replaced, `api key` becomes `api_key`.
+pub fn variable_name(name: &str) -> String {
+    let converted = name
+        .trim()
+        .chars()
+        .map(|c| match c.is_ascii_alphanumeric() || "_-.".contains(c) {
+            true => c,
+            false => '_',
+        })
+        .collect::<String>();
+
+    if converted.is_empty() {
+        "var".to_string()
+    } else {
+        converted
+    }
+}
+
+/// Path param names are limited to alphanumerics, other names are converted
+/// to camel case, `user_id` becomes `userId`.
+pub fn param_name(name: &str) -> String {
+    let mut converted = String::new();
+    for (idx, part) in name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|p| !p.is_empty())
+        .enumerate()
+    {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            if idx == 0 {
+                converted.push(first);
+            } else {
+                converted.push(first.to_ascii_uppercase());
+            }
+            converted.extend(chars);
+        }
+    }
+
+    if converted.is_empty() {
+        "param".to_string()
+    } else {
+        converted
+    }
+}
+
+/// Rewrites `{{var}}` references of imported values and keeps track of what
+/// had to be renamed or left untouched.
+#[derive(Debug, Default)]
+pub(crate) struct Variables {
+    renamed: BTreeMap<String, String>,
+    unsupported: BTreeSet<String>,
+}
+
+impl Variables {
+    pub fn name(&mut self, name: &str) -> String {
+        let converted = variable_name(name);
+        if converted != name {
+            self.renamed.insert(name.to_string(), converted.clone());
+        }
+        converted
+    }
+
+    pub fn template(&mut self, text: &str) -> String {
+        let regex = Regex::new(r"\{\{([^{}]+)\}\}").unwrap();
+        regex
+            .replace_all(text, |cap: &Captures| {
+                let name = cap[1].trim();
+                if name.starts_with('$') {
+                    // Postman names of the same built-ins
+                    let name = match name {
+                        "$guid" | "$randomUUID" => "$uuid",
+                        name => name,
+                    };
+                    if !is_builtin(name) {
+                        self.unsupported.insert(name.to_string());
+                        return cap[0].to_string();
+                    }
+                    format!("{{{{{}}}}}", name)
+                } else {
+                    format!("{{{{{}}}}}", self.name(name))
+                }
+            })
+            .to_string()
+    }
+
+    pub fn report(self, report: &mut ImportReport) {
+        for (from, to) in self.renamed {
+            report.warn("", format!("Variable `{}` was renamed to `{}`", from, to));
+        }
+        for name in self.unsupported {
+            report.warn(
+                "",
+                format!("Dynamic variable `{{{{{}}}}}` is not supported", name),
+            );
+        }
+    }
+}
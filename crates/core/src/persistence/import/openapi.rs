@@ -0,0 +1,1081 @@
+use std::collections::{BTreeSet, HashSet};
+
+use anyhow::Context;
+use indexmap::IndexMap;
+use regex::{Captures, Regex};
+use serde_json::{Map, Value};
+
+use crate::assertions::{Assertion, Assertions, Condition, Matcher};
+use crate::http::environment::Environment;
+use crate::http::request::{Auth, Method, Request, RequestBody};
+use crate::http::{KeyFile, KeyFileList, KeyValList, KeyValue};
+
+use super::{
+    param_name, unique_name, variable_name, ImportReport, ImportedCollection, ImportedEntry,
+};
+
+/// Variable holding the server URL every request is prefixed with
+pub const BASE_URL: &str = "baseUrl";
+
+const METHODS: [(&str, Method); 8] = [
+    ("get", Method::GET),
+    ("put", Method::PUT),
+    ("post", Method::POST),
+    ("delete", Method::DELETE),
+    ("options", Method::OPTIONS),
+    ("head", Method::HEAD),
+    ("patch", Method::PATCH),
+    ("trace", Method::TRACE),
+];
+
+// Deep enough for real payloads while stopping recursive schemas
+const MAX_EXAMPLE_DEPTH: usize = 8;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OpenApiOptions {
+    /// Adds a `status` assertion matching the documented success codes
+    pub status_assertions: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SpecVersion {
+    Swagger2,
+    OpenApi3,
+}
+
+struct Importer<'a> {
+    spec: &'a Value,
+    version: SpecVersion,
+    options: OpenApiOptions,
+    report: ImportReport,
+    /// Variables referenced by auth, added empty to every environment
+    auth_vars: BTreeSet<String>,
+    unresolved: BTreeSet<String>,
+    /// References of the schemas currently being turned into examples
+    expanding: Vec<&'a str>,
+}
+
+/// Converts an OpenAPI 3 or Swagger 2 document, in JSON or YAML, into a
+/// collection with one request per operation grouped in folders by tag.
+pub fn import(spec: &str, options: OpenApiOptions) -> anyhow::Result<ImportedCollection> {
+    let spec = parse(spec)?;
+
+    let version = if spec.get("openapi").is_some() {
+        SpecVersion::OpenApi3
+    } else if spec.get("swagger").and_then(Value::as_str) == Some("2.0") {
+        SpecVersion::Swagger2
+    } else {
+        anyhow::bail!("Not an OpenAPI 3 or Swagger 2 document");
+    };
+
+    let name = spec
+        .pointer("/info/title")
+        .and_then(Value::as_str)
+        .unwrap_or("OpenAPI")
+        .to_string();
+
+    let mut importer = Importer {
+        spec: &spec,
+        version,
+        options,
+        report: ImportReport::default(),
+        auth_vars: BTreeSet::new(),
+        unresolved: BTreeSet::new(),
+        expanding: Vec::new(),
+    };
+
+    let entries = importer.operations();
+    let mut environments = importer.environments();
+    for env in environments.iter_mut() {
+        let mut variables = env.variables.clone().into_iter().collect::<Vec<_>>();
+        variables.extend(importer.auth_vars.iter().map(|name| KeyValue {
+            disabled: false,
+            name: name.clone(),
+            value: String::new(),
+        }));
+        env.variables = KeyValList::from(variables);
+    }
+
+    let Importer {
+        mut report,
+        unresolved,
+        ..
+    } = importer;
+    for reference in unresolved {
+        report.warn(
+            "",
+            format!("Reference `{}` could not be resolved", reference),
+        );
+    }
+
+    Ok(ImportedCollection {
+        name,
+        entries,
+        default_environment: environments.first().map(|env| env.name.clone()),
+        environments,
+        variables: KeyValList::new(),
+        report,
+    })
+}
+
+fn parse(spec: &str) -> anyhow::Result<Value> {
+    if spec.trim_start().starts_with('{') {
+        serde_json::from_str(spec).context("Failed to parse OpenAPI JSON document")
+    } else {
+        serde_yaml::from_str(spec).context("Failed to parse OpenAPI YAML document")
+    }
+}
+
+fn value_str(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn json_body(example: Value) -> RequestBody {
+    match example {
+        Value::Null => RequestBody::Json(String::new()),
+        example => RequestBody::Json(serde_json::to_string_pretty(&example).unwrap_or_default()),
+    }
+}
+
+/// `/users/{user_id}` becomes `/users/:userId`
+fn path_template(path: &str) -> String {
+    Regex::new(r"\{([^{}]+)\}")
+        .unwrap()
+        .replace_all(path, |cap: &Captures| format!(":{}", param_name(&cap[1])))
+        .to_string()
+}
+
+impl<'a> Importer<'a> {
+    /// Follows local `$ref`s, external references are reported and left as is
+    fn resolve(&mut self, mut value: &'a Value) -> &'a Value {
+        for _ in 0..16 {
+            let Some(reference) = value.get("$ref").and_then(Value::as_str) else {
+                break;
+            };
+            match reference
+                .strip_prefix('#')
+                .and_then(|pointer| self.spec.pointer(pointer))
+            {
+                Some(target) => value = target,
+                None => {
+                    self.unresolved.insert(reference.to_string());
+                    break;
+                }
+            }
+        }
+        value
+    }
+
+    fn operations(&mut self) -> Vec<ImportedEntry> {
+        let mut root = Vec::new();
+        // Folders follow the order tags are declared in
+        let mut folders = self
+            .spec
+            .get("tags")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|tag| tag.get("name").and_then(Value::as_str))
+            .map(|name| (name.to_string(), Vec::new()))
+            .collect::<IndexMap<_, _>>();
+
+        let paths = self.spec.get("paths").and_then(Value::as_object);
+        for (path, item) in paths.into_iter().flatten() {
+            let item = self.resolve(item);
+            for (key, method) in METHODS {
+                let Some(operation) = item.get(key) else {
+                    continue;
+                };
+
+                let (name, request) = self.operation(path, method, item, operation);
+                let entry = ImportedEntry::Request {
+                    name,
+                    request: Box::new(request),
+                };
+
+                let tag = operation
+                    .get("tags")
+                    .and_then(Value::as_array)
+                    .and_then(|tags| tags.first())
+                    .and_then(Value::as_str);
+                match tag {
+                    Some(tag) => folders.entry(tag.to_string()).or_default().push(entry),
+                    None => root.push(entry),
+                }
+            }
+        }
+
+        folders
+            .into_iter()
+            .filter(|(_, entries)| !entries.is_empty())
+            .map(|(name, entries)| ImportedEntry::Folder { name, entries })
+            .chain(root)
+            .collect()
+    }
+
+    fn operation(
+        &mut self,
+        path: &str,
+        method: Method,
+        item: &'a Value,
+        operation: &'a Value,
+    ) -> (String, Request) {
+        let context = format!("{} {}", method, path);
+        let text = |key: &str| {
+            operation
+                .get(key)
+                .and_then(Value::as_str)
+                .filter(|s| !s.trim().is_empty())
+        };
+
+        let name = text("summary")
+            .or(text("operationId"))
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("{} {}", method, path.trim_matches('/').replace('/', " ")));
+        let description = text("description")
+            .or(text("summary"))
+            .map(str::to_string)
+            .unwrap_or_else(|| Request::default().description);
+
+        let mut headers = Vec::new();
+        let mut query_params = Vec::new();
+        let mut path_params = Vec::new();
+        let mut body = RequestBody::None;
+        let mut form = Vec::new();
+
+        for param in self.parameters(item, operation) {
+            let name = param
+                .get("name")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+            let location = param.get("in").and_then(Value::as_str).unwrap_or_default();
+            match location {
+                "path" => path_params.push(KeyValue {
+                    disabled: false,
+                    name: param_name(&name),
+                    value: self.param_value(param),
+                }),
+                "query" => query_params.push(KeyValue {
+                    disabled: true,
+                    value: self.param_value(param),
+                    name,
+                }),
+                "header" => headers.push(KeyValue {
+                    disabled: true,
+                    value: self.param_value(param),
+                    name,
+                }),
+                "body" => {
+                    let schema = param.get("schema").unwrap_or(&Value::Null);
+                    body = self.swagger_body(operation, schema);
+                }
+                "formData" => form.push(param),
+                other => self.report.warn(
+                    &context,
+                    format!("{} parameter `{}` was not imported", other, name),
+                ),
+            }
+        }
+
+        if !form.is_empty() {
+            body = self.swagger_form(operation, form);
+        }
+        if let Some(request_body) = operation.get("requestBody") {
+            let request_body = self.resolve(request_body);
+            body = self.request_body(&context, request_body);
+        }
+
+        let auth = self.auth(&context, operation, &mut headers, &mut query_params);
+
+        let assertions = if self.options.status_assertions {
+            self.status_assertions(operation)
+        } else {
+            Assertions::default()
+        };
+
+        let request = Request {
+            description,
+            method,
+            url: format!("{{{{{}}}}}{}", BASE_URL, path_template(path)),
+            headers: KeyValList::from(headers),
+            body,
+            query_params: KeyValList::from(query_params),
+            path_params: KeyValList::from(path_params),
+            auth,
+            assertions,
+            pre_request: None,
+        };
+
+        (name, request)
+    }
+
+    /// Path level parameters with operation parameters overriding them
+    fn parameters(&mut self, item: &'a Value, operation: &'a Value) -> Vec<&'a Value> {
+        let mut params: Vec<&'a Value> = Vec::new();
+        let all = [item, operation]
+            .into_iter()
+            .filter_map(|v| v.get("parameters").and_then(Value::as_array))
+            .flatten();
+
+        for param in all {
+            let param = self.resolve(param);
+            let key = |p: &Value| (p.get("name").cloned(), p.get("in").cloned());
+            params.retain(|p| key(p) != key(param));
+            params.push(param);
+        }
+        params
+    }
+
+    fn param_value(&mut self, param: &'a Value) -> String {
+        if let Some(example) = param.get("example") {
+            return value_str(example);
+        }
+        if let Some(example) = self.first_example(param) {
+            return value_str(&example);
+        }
+        // Swagger 2 keeps the schema inline on the parameter
+        let schema = param.get("schema").unwrap_or(param);
+        match self.example(schema, 0) {
+            Value::Object(_) | Value::Array(_) | Value::Null => String::new(),
+            value => value_str(&value),
+        }
+    }
+
+    /// First entry of an `examples` map
+    fn first_example(&mut self, value: &'a Value) -> Option<Value> {
+        let (_, example) = value.get("examples")?.as_object()?.iter().next()?;
+        let example = self.resolve(example);
+        example.get("value").cloned()
+    }
+
+    fn media_example(&mut self, media: &'a Value) -> Value {
+        if let Some(example) = media.get("example") {
+            return example.clone();
+        }
+        if let Some(example) = self.first_example(media) {
+            return example;
+        }
+        self.example(media.get("schema").unwrap_or(&Value::Null), 0)
+    }
+
+    fn example(&mut self, schema: &'a Value, depth: usize) -> Value {
+        if depth > MAX_EXAMPLE_DEPTH {
+            return Value::Null;
+        }
+
+        // Recursive schemas are expanded once
+        let reference = schema.get("$ref").and_then(Value::as_str);
+        if let Some(reference) = reference {
+            if self.expanding.contains(&reference) {
+                return Value::Null;
+            }
+            self.expanding.push(reference);
+        }
+
+        let resolved = self.resolve(schema);
+        let example = self.schema_example(resolved, depth);
+
+        if reference.is_some() {
+            self.expanding.pop();
+        }
+        example
+    }
+
+    fn schema_example(&mut self, schema: &'a Value, depth: usize) -> Value {
+        for key in ["example", "default"] {
+            if let Some(value) = schema.get(key) {
+                return value.clone();
+            }
+        }
+        let first = |key: &str| {
+            schema
+                .get(key)
+                .and_then(Value::as_array)
+                .and_then(|values| values.first())
+        };
+        if let Some(value) = first("examples").or(first("enum")) {
+            return value.clone();
+        }
+        if let Some(all) = schema.get("allOf").and_then(Value::as_array) {
+            let mut merged = Map::new();
+            for schema in all {
+                match self.example(schema, depth + 1) {
+                    Value::Object(object) => merged.extend(object),
+                    other if all.len() == 1 => return other,
+                    _ => (),
+                }
+            }
+            return Value::Object(merged);
+        }
+        if let Some(schema) = first("oneOf").or(first("anyOf")) {
+            return self.example(schema, depth + 1);
+        }
+
+        // 3.1 allows a list of types, usually a type and null
+        let ty = match schema.get("type") {
+            Some(Value::String(ty)) => Some(ty.as_str()),
+            Some(Value::Array(types)) => types
+                .iter()
+                .filter_map(Value::as_str)
+                .find(|ty| *ty != "null"),
+            _ => None,
+        };
+        let ty = ty.or_else(|| {
+            if schema.get("properties").is_some() {
+                Some("object")
+            } else if schema.get("items").is_some() {
+                Some("array")
+            } else {
+                None
+            }
+        });
+
+        match ty {
+            Some("object") => {
+                let properties = schema.get("properties").and_then(Value::as_object);
+                let object = properties
+                    .into_iter()
+                    .flatten()
+                    .map(|(name, prop)| (name.clone(), self.example(prop, depth + 1)))
+                    .collect();
+                Value::Object(object)
+            }
+            Some("array") => {
+                let items = schema.get("items").unwrap_or(&Value::Null);
+                match self.example(items, depth + 1) {
+                    Value::Null => Value::Array(Vec::new()),
+                    item => Value::Array(vec![item]),
+                }
+            }
+            Some("string") => {
+                let format = schema.get("format").and_then(Value::as_str);
+                let example = match format {
+                    Some("date-time") => "2024-01-01T00:00:00Z",
+                    Some("date") => "2024-01-01",
+                    Some("time") => "00:00:00",
+                    Some("uuid") => "00000000-0000-0000-0000-000000000000",
+                    Some("email") => "user@example.com",
+                    Some("uri") | Some("url") => "https://example.com",
+                    Some("ipv4") => "127.0.0.1",
+                    Some("ipv6") => "::1",
+                    _ => "string",
+                };
+                Value::String(example.to_string())
+            }
+            Some("integer") | Some("number") => Value::from(0),
+            Some("boolean") => Value::Bool(false),
+            _ => Value::Null,
+        }
+    }
+
+    fn request_body(&mut self, context: &str, request_body: &'a Value) -> RequestBody {
+        let Some(content) = request_body.get("content").and_then(Value::as_object) else {
+            return RequestBody::None;
+        };
+        let find = |matches: fn(&str) -> bool| {
+            content
+                .iter()
+                .find(|(media, _)| matches(&media.to_lowercase()))
+                .map(|(_, value)| value)
+        };
+
+        if let Some(media) = find(|m| m.contains("json")) {
+            let example = self.media_example(media);
+            return json_body(example);
+        }
+        if let Some(media) = find(|m| m == "application/x-www-form-urlencoded") {
+            let (params, _) = self.form_fields(media.get("schema"));
+            return RequestBody::Form(KeyValList::from(params));
+        }
+        if let Some(media) = find(|m| m.starts_with("multipart/")) {
+            let (params, files) = self.form_fields(media.get("schema"));
+            return RequestBody::Multipart {
+                params: KeyValList::from(params),
+                files: KeyFileList::from(files),
+            };
+        }
+        if let Some(media) = find(|m| m.contains("xml")) {
+            return match self.media_example(media) {
+                Value::String(xml) => RequestBody::XML(xml),
+                _ => RequestBody::XML(String::new()),
+            };
+        }
+        if let Some(media) = find(|m| m.starts_with("text/")) {
+            return match self.media_example(media) {
+                Value::Null => RequestBody::Text(String::new()),
+                example => RequestBody::Text(value_str(&example)),
+            };
+        }
+        if find(|m| m == "application/octet-stream").is_some() {
+            return RequestBody::File(None);
+        }
+
+        if let Some(media) = content.keys().next() {
+            self.report
+                .warn(context, format!("{} request body is not supported", media));
+        }
+        RequestBody::None
+    }
+
+    /// Splits object properties into plain fields and binary file fields
+    fn form_fields(&mut self, schema: Option<&'a Value>) -> (Vec<KeyValue>, Vec<KeyFile>) {
+        let mut params = Vec::new();
+        let mut files = Vec::new();
+        let Some(schema) = schema else {
+            return (params, files);
+        };
+
+        let schema = self.resolve(schema);
+        let properties = schema.get("properties").and_then(Value::as_object);
+        for (name, prop) in properties.into_iter().flatten() {
+            let prop = self.resolve(prop);
+            let format = prop.get("format").and_then(Value::as_str);
+            if matches!(format, Some("binary") | Some("base64")) {
+                files.push(KeyFile {
+                    name: name.clone(),
+                    path: None,
+                    disabled: false,
+                });
+            } else {
+                let value = match self.example(prop, 0) {
+                    Value::Null => String::new(),
+                    Value::String(value) => value,
+                    value => value.to_string(),
+                };
+                params.push(KeyValue {
+                    disabled: false,
+                    name: name.clone(),
+                    value,
+                });
+            }
+        }
+        (params, files)
+    }
+
+    fn consumes(&self, operation: &'a Value) -> Vec<&'a str> {
+        operation
+            .get("consumes")
+            .or(self.spec.get("consumes"))
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .collect()
+    }
+
+    fn swagger_body(&mut self, operation: &'a Value, schema: &'a Value) -> RequestBody {
+        let consumes = self.consumes(operation);
+        let example = self.example(schema, 0);
+        let is_xml = !consumes.is_empty() && consumes.iter().all(|c| c.contains("xml"));
+        match example {
+            Value::String(text) if is_xml => RequestBody::XML(text),
+            _ if is_xml => RequestBody::XML(String::new()),
+            example => json_body(example),
+        }
+    }
+
+    fn swagger_form(&mut self, operation: &'a Value, fields: Vec<&'a Value>) -> RequestBody {
+        let multipart = self
+            .consumes(operation)
+            .iter()
+            .any(|c| c.starts_with("multipart/"));
+
+        let mut params = Vec::new();
+        let mut files = Vec::new();
+        for field in fields {
+            let name = field
+                .get("name")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+            if field.get("type").and_then(Value::as_str) == Some("file") {
+                files.push(KeyFile {
+                    name,
+                    path: None,
+                    disabled: false,
+                });
+            } else {
+                params.push(KeyValue {
+                    disabled: false,
+                    value: self.param_value(field),
+                    name,
+                });
+            }
+        }
+
+        if multipart || !files.is_empty() {
+            RequestBody::Multipart {
+                params: KeyValList::from(params),
+                files: KeyFileList::from(files),
+            }
+        } else {
+            RequestBody::Form(KeyValList::from(params))
+        }
+    }
+
+    fn security_scheme(&mut self, name: &str) -> Option<&'a Value> {
+        let schemes = match self.version {
+            SpecVersion::OpenApi3 => self.spec.pointer("/components/securitySchemes"),
+            SpecVersion::Swagger2 => self.spec.get("securityDefinitions"),
+        };
+        let scheme = schemes?.get(name)?;
+        Some(self.resolve(scheme))
+    }
+
+    /// Uses the first scheme of the first security requirement, values are
+    /// left as variables to be filled in the environment
+    fn auth(
+        &mut self,
+        context: &str,
+        operation: &'a Value,
+        headers: &mut Vec<KeyValue>,
+        query: &mut Vec<KeyValue>,
+    ) -> Auth {
+        let requirement = operation
+            .get("security")
+            .or(self.spec.get("security"))
+            .and_then(Value::as_array)
+            .and_then(|requirements| requirements.first())
+            .and_then(Value::as_object)
+            .and_then(|requirement| requirement.keys().next());
+        let Some(name) = requirement else {
+            return Auth::None;
+        };
+        let Some(scheme) = self.security_scheme(name) else {
+            self.report.warn(
+                context,
+                format!("security scheme `{}` is not defined", name),
+            );
+            return Auth::None;
+        };
+
+        let ty = scheme
+            .get("type")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let http_scheme = scheme
+            .get("scheme")
+            .and_then(Value::as_str)
+            .map(str::to_lowercase);
+
+        let mut var = |name: &str| {
+            let name = variable_name(name);
+            let template = format!("{{{{{}}}}}", name);
+            self.auth_vars.insert(name);
+            template
+        };
+
+        match (ty, http_scheme.as_deref()) {
+            ("basic", _) | ("http", Some("basic")) => Auth::Basic {
+                username: var("username"),
+                password: var("password"),
+            },
+            ("http", Some("bearer")) => Auth::Bearer {
+                token: var("token"),
+            },
+            ("apiKey", _) => {
+                let key = scheme.get("name").and_then(Value::as_str).unwrap_or(name);
+                let kv = KeyValue {
+                    disabled: false,
+                    name: key.to_string(),
+                    value: var(name),
+                };
+                match scheme.get("in").and_then(Value::as_str) {
+                    Some("header") => headers.push(kv),
+                    Some("query") => query.push(kv),
+                    _ => self.report.warn(
+                        context,
+                        format!("cookie api key `{}` was not imported", name),
+                    ),
+                }
+                Auth::None
+            }
+            (ty, _) => {
+                self.report.warn(
+                    context,
+                    format!("{} security scheme `{}` is not supported", ty, name),
+                );
+                Auth::None
+            }
+        }
+    }
+
+    fn status_assertions(&mut self, operation: &'a Value) -> Assertions {
+        let responses = operation.get("responses").and_then(Value::as_object);
+        let codes = responses
+            .into_iter()
+            .flatten()
+            .filter_map(|(code, _)| code.parse::<u64>().ok())
+            .filter(|code| (200..300).contains(code))
+            .map(|code| hcl::Value::Number(code.into()))
+            .collect::<Vec<_>>();
+
+        let matcher = match codes.as_slice() {
+            [] => return Assertions::default(),
+            [code] => Matcher::Eq(code.clone()),
+            _ => Matcher::In(codes),
+        };
+        Assertions(vec![Assertion::Status(vec![Condition::new(
+            "code", matcher,
+        )])])
+    }
+
+    fn environments(&mut self) -> Vec<Environment> {
+        let servers = match self.version {
+            SpecVersion::OpenApi3 => self.servers(),
+            SpecVersion::Swagger2 => self.swagger_servers(),
+        };
+
+        let servers = if servers.is_empty() {
+            self.report.warn(
+                "",
+                format!(
+                    "No servers are defined, set `{}` in the environment",
+                    BASE_URL
+                ),
+            );
+            vec![("Default".to_string(), "http://localhost".to_string())]
+        } else {
+            servers
+        };
+
+        let mut used = HashSet::new();
+        servers
+            .into_iter()
+            .map(|(name, url)| {
+                if url.starts_with('/') {
+                    self.report.warn(
+                        "",
+                        format!("Server `{}` is relative, prefix it with the API host", url),
+                    );
+                }
+                Environment {
+                    name: unique_name(&mut used, &name),
+                    variables: KeyValList::from(vec![KeyValue {
+                        disabled: false,
+                        name: BASE_URL.to_string(),
+                        value: url,
+                    }]),
+                    secrets: BTreeSet::new(),
+                }
+            })
+            .collect()
+    }
+
+    /// Server variables are replaced by their default values
+    fn servers(&self) -> Vec<(String, String)> {
+        let servers = self.spec.get("servers").and_then(Value::as_array);
+        servers
+            .into_iter()
+            .flatten()
+            .filter_map(|server| {
+                let url = server.get("url").and_then(Value::as_str)?;
+                let variables = server.get("variables");
+                let url = Regex::new(r"\{([^{}]+)\}")
+                    .unwrap()
+                    .replace_all(url, |cap: &Captures| {
+                        variables
+                            .and_then(|vars| vars.get(&cap[1]))
+                            .and_then(|var| var.get("default"))
+                            .map(value_str)
+                            .unwrap_or_else(|| cap[0].to_string())
+                    })
+                    .trim_end_matches('/')
+                    .to_string();
+                let name = server
+                    .get("description")
+                    .and_then(Value::as_str)
+                    .filter(|d| !d.trim().is_empty())
+                    .map(str::to_string)
+                    .unwrap_or_else(|| {
+                        let host = url.split_once("://").map(|(_, host)| host);
+                        host.unwrap_or(&url).trim_matches('/').to_string()
+                    });
+                Some((name, url))
+            })
+            .collect()
+    }
+
+    /// One environment per scheme, `host` and `basePath` are shared
+    fn swagger_servers(&self) -> Vec<(String, String)> {
+        let Some(host) = self.spec.get("host").and_then(Value::as_str) else {
+            return Vec::new();
+        };
+        let base_path = self
+            .spec
+            .get("basePath")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .trim_end_matches('/');
+
+        let schemes = self
+            .spec
+            .get("schemes")
+            .and_then(Value::as_array)
+            .map(|schemes| schemes.iter().filter_map(Value::as_str).collect::<Vec<_>>())
+            .filter(|schemes| !schemes.is_empty())
+            .unwrap_or_else(|| vec!["https"]);
+
+        schemes
+            .into_iter()
+            .map(|scheme| {
+                (
+                    scheme.to_string(),
+                    format!("{}://{}{}", scheme, host, base_path),
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn requests(entries: &[ImportedEntry]) -> Vec<(String, &Request)> {
+        let mut found = Vec::new();
+        for entry in entries {
+            match entry {
+                ImportedEntry::Request { name, request } => found.push((name.clone(), &**request)),
+                ImportedEntry::Folder { name, entries } => {
+                    for (child, request) in requests(entries) {
+                        found.push((format!("{}/{}", name, child), request));
+                    }
+                }
+            }
+        }
+        found
+    }
+
+    fn kv(name: &str, value: &str) -> KeyValue {
+        KeyValue {
+            disabled: false,
+            name: name.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    const PETSTORE: &str = r##"
+openapi: 3.0.3
+info:
+  title: Petstore
+servers:
+  - url: https://{region}.example.com/v1/
+    description: Production
+    variables:
+      region:
+        default: eu
+tags:
+  - name: pets
+security:
+  - token: []
+paths:
+  /pets/{pet_id}:
+    parameters:
+      - name: pet_id
+        in: path
+        required: true
+        schema: { type: integer, example: 7 }
+    put:
+      tags: [pets]
+      summary: Update pet
+      parameters:
+        - name: dry-run
+          in: query
+          schema: { type: boolean }
+      requestBody:
+        content:
+          application/json:
+            schema: { $ref: "#/components/schemas/Pet" }
+      responses:
+        "200": { description: OK }
+        "204": { description: No content }
+        "404": { description: Missing }
+  /health:
+    get:
+      security: []
+      responses:
+        "200": { description: OK }
+components:
+  securitySchemes:
+    token: { type: http, scheme: bearer }
+  schemas:
+    Pet:
+      type: object
+      properties:
+        name: { type: string }
+        born: { type: string, format: date }
+        tags: { type: array, items: { type: string, enum: [cat, dog] } }
+        parent: { $ref: "#/components/schemas/Pet" }
+"##;
+
+    #[test]
+    fn imports_openapi_operations() {
+        let options = OpenApiOptions {
+            status_assertions: true,
+        };
+        let imported = import(PETSTORE, options).unwrap();
+        assert_eq!(imported.name, "Petstore");
+        assert!(imported.report.is_empty(), "{:?}", imported.report);
+
+        let requests = requests(&imported.entries);
+        let names = requests
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["pets/Update pet", "GET health"]);
+
+        let update = requests[0].1;
+        assert_eq!(update.method, Method::PUT);
+        assert_eq!(update.url, "{{baseUrl}}/pets/:petId");
+        assert_eq!(update.path_params, KeyValList::from(vec![kv("petId", "7")]));
+        assert_eq!(
+            update.query_params,
+            KeyValList::from(vec![KeyValue {
+                disabled: true,
+                ..kv("dry-run", "false")
+            }])
+        );
+        assert_eq!(
+            update.auth,
+            Auth::Bearer {
+                token: "{{token}}".to_string()
+            }
+        );
+        let RequestBody::Json(body) = &update.body else {
+            panic!("expected a JSON body, got {:?}", update.body);
+        };
+        // The recursive parent is not expanded again
+        assert_eq!(
+            serde_json::from_str::<Value>(body).unwrap(),
+            json!({
+                "name": "string",
+                "born": "2024-01-01",
+                "tags": ["cat"],
+                "parent": null,
+            })
+        );
+        let codes = vec![
+            hcl::Value::Number(200.into()),
+            hcl::Value::Number(204.into()),
+        ];
+        assert_eq!(
+            update.assertions,
+            Assertions(vec![Assertion::Status(vec![Condition::new(
+                "code",
+                Matcher::In(codes)
+            )])])
+        );
+
+        let health = requests[1].1;
+        assert_eq!(health.auth, Auth::None);
+        assert_eq!(health.body, RequestBody::None);
+
+        let [env] = imported.environments.as_slice() else {
+            panic!("expected one environment");
+        };
+        assert_eq!(env.name, "Production");
+        assert_eq!(env.get(BASE_URL), Some("https://eu.example.com/v1"));
+        assert_eq!(env.get("token"), Some(""));
+        assert_eq!(imported.default_environment.as_deref(), Some("Production"));
+    }
+
+    #[test]
+    fn imports_swagger_operations() {
+        let spec = json!({
+            "swagger": "2.0",
+            "info": { "title": "Files" },
+            "host": "files.example.com",
+            "basePath": "/api/",
+            "schemes": ["http", "https"],
+            "securityDefinitions": {
+                "key": { "type": "apiKey", "name": "api_key", "in": "query" },
+                "oauth": { "type": "oauth2", "flow": "implicit" },
+            },
+            "paths": {
+                "/files": {
+                    "post": {
+                        "operationId": "upload",
+                        "security": [{ "key": [] }],
+                        "consumes": ["multipart/form-data"],
+                        "parameters": [
+                            { "name": "file", "in": "formData", "type": "file" },
+                            { "name": "folder", "in": "formData", "type": "string", "default": "inbox" },
+                            { "name": "session", "in": "cookie", "type": "string" },
+                        ],
+                    },
+                    "delete": {
+                        "security": [{ "oauth": [] }],
+                        "parameters": [{
+                            "name": "body",
+                            "in": "body",
+                            "schema": { "$ref": "#/definitions/Missing" },
+                        }],
+                    },
+                },
+            },
+        });
+
+        let imported = import(&spec.to_string(), OpenApiOptions::default()).unwrap();
+        let requests = requests(&imported.entries);
+
+        let (name, upload) = &requests[0];
+        assert_eq!(name, "upload");
+        assert_eq!(upload.url, "{{baseUrl}}/files");
+        assert_eq!(
+            upload.query_params,
+            KeyValList::from(vec![kv("api_key", "{{key}}")])
+        );
+        assert_eq!(
+            upload.body,
+            RequestBody::Multipart {
+                params: KeyValList::from(vec![kv("folder", "inbox")]),
+                files: KeyFileList::from(vec![KeyFile {
+                    name: "file".to_string(),
+                    path: None,
+                    disabled: false,
+                }]),
+            }
+        );
+        assert_eq!(upload.assertions, Assertions::default());
+
+        let (name, delete) = &requests[1];
+        assert_eq!(name, "DELETE files");
+        assert_eq!(delete.body, RequestBody::Json(String::new()));
+
+        let servers = imported
+            .environments
+            .iter()
+            .map(|env| (env.name.as_str(), env.get(BASE_URL).unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            servers,
+            [
+                ("http", "http://files.example.com/api"),
+                ("https", "https://files.example.com/api"),
+            ]
+        );
+
+        assert_eq!(
+            imported.report.warnings,
+            [
+                "POST /files: cookie parameter `session` was not imported",
+                "DELETE /files: oauth2 security scheme `oauth` is not supported",
+                "Reference `#/definitions/Missing` could not be resolved",
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_other_documents() {
+        let err = import("{\"swagger\": \"1.2\"}", OpenApiOptions::default()).unwrap_err();
+        assert_eq!(err.to_string(), "Not an OpenAPI 3 or Swagger 2 document");
+    }
+}
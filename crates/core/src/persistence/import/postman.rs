@@ -0,0 +1,932 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use anyhow::Context;
+use regex::{Captures, Regex};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::http::environment::Environment;
+use crate::http::request::{Auth, Method, Request, RequestBody};
+use crate::http::{KeyFile, KeyFileList, KeyValList, KeyValue};
+
+use super::{file_name, param_name, ImportReport, ImportedCollection, ImportedEntry, Variables};
+
+#[derive(Debug, Deserialize)]
+struct PostmanCollection {
+    info: Info,
+    #[serde(default)]
+    item: Vec<Item>,
+    #[serde(default)]
+    variable: Vec<Pair>,
+    auth: Option<PostmanAuth>,
+    #[serde(default)]
+    event: Vec<Event>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Info {
+    name: String,
+    #[serde(default)]
+    schema: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Item {
+    #[serde(default)]
+    name: String,
+    description: Option<Description>,
+    item: Option<Vec<Item>>,
+    request: Option<PostmanRequest>,
+    auth: Option<PostmanAuth>,
+    #[serde(default)]
+    event: Vec<Event>,
+    #[serde(default)]
+    response: Vec<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Description {
+    Text(String),
+    Object { content: Option<String> },
+}
+
+impl Description {
+    fn text(&self) -> Option<&str> {
+        match self {
+            Description::Text(text) => Some(text),
+            Description::Object { content } => content.as_deref(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum PostmanRequest {
+    Url(String),
+    Request(Box<RequestDef>),
+}
+
+#[derive(Debug, Deserialize)]
+struct RequestDef {
+    method: Option<String>,
+    #[serde(default)]
+    header: Headers,
+    url: Option<Url>,
+    body: Option<Body>,
+    auth: Option<PostmanAuth>,
+    description: Option<Description>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Headers {
+    List(Vec<Pair>),
+    Raw(String),
+}
+
+impl Default for Headers {
+    fn default() -> Self {
+        Headers::List(Vec::new())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Url {
+    Raw(String),
+    Object(UrlDef),
+}
+
+#[derive(Debug, Deserialize)]
+struct UrlDef {
+    raw: Option<String>,
+    protocol: Option<String>,
+    host: Option<Segments>,
+    port: Option<String>,
+    path: Option<Segments>,
+    #[serde(default)]
+    query: Vec<Pair>,
+    #[serde(default)]
+    variable: Vec<Pair>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Segments {
+    Joined(String),
+    Parts(Vec<Segment>),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Segment {
+    Text(String),
+    Object { value: Option<String> },
+}
+
+impl Segments {
+    fn join(&self, sep: &str) -> String {
+        match self {
+            Segments::Joined(s) => s.clone(),
+            Segments::Parts(parts) => parts
+                .iter()
+                .map(|p| match p {
+                    Segment::Text(s) => s.as_str(),
+                    Segment::Object { value } => value.as_deref().unwrap_or_default(),
+                })
+                .collect::<Vec<_>>()
+                .join(sep),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Pair {
+    key: Option<String>,
+    value: Option<Value>,
+    #[serde(default)]
+    disabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct Body {
+    mode: Option<String>,
+    raw: Option<String>,
+    #[serde(default)]
+    urlencoded: Vec<Pair>,
+    #[serde(default)]
+    formdata: Vec<FormParam>,
+    file: Option<FileSrc>,
+    graphql: Option<GraphQlBody>,
+    options: Option<BodyOptions>,
+    #[serde(default)]
+    disabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct FormParam {
+    key: Option<String>,
+    value: Option<Value>,
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    src: Option<Value>,
+    #[serde(default)]
+    disabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct FileSrc {
+    src: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphQlBody {
+    query: Option<String>,
+    variables: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BodyOptions {
+    raw: Option<RawOptions>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawOptions {
+    language: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct PostmanAuth {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(flatten)]
+    params: HashMap<String, Value>,
+}
+
+impl PostmanAuth {
+    /// Auth params are a list of key/value pairs in v2.1 and an object in v2.0
+    fn param(&self, name: &str) -> Option<String> {
+        match self.params.get(&self.kind)? {
+            Value::Array(params) => params
+                .iter()
+                .find(|p| p.get("key").and_then(Value::as_str) == Some(name))
+                .and_then(|p| p.get("value"))
+                .map(value_str),
+            Value::Object(params) => params.get(name).map(value_str),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Event {
+    listen: String,
+    script: Option<Script>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Script {
+    exec: Option<Value>,
+}
+
+impl Event {
+    fn has_script(&self) -> bool {
+        let Some(exec) = self.script.as_ref().and_then(|s| s.exec.as_ref()) else {
+            return false;
+        };
+        match exec {
+            Value::String(s) => !s.trim().is_empty(),
+            Value::Array(lines) => lines
+                .iter()
+                .any(|l| l.as_str().is_some_and(|l| !l.trim().is_empty())),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct PostmanEnvironment {
+    name: String,
+    #[serde(default)]
+    values: Vec<EnvValue>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EnvValue {
+    key: String,
+    value: Option<Value>,
+    #[serde(default = "enabled")]
+    enabled: bool,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+fn value_str(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+struct Importer {
+    report: ImportReport,
+    vars: Variables,
+}
+
+/// Converts a Postman v2.1 collection and optionally its environment exports.
+/// Collection variables are kept with the collection, environment values
+/// take precedence over them.
+pub fn import(collection: &str, environments: &[String]) -> anyhow::Result<ImportedCollection> {
+    let collection: PostmanCollection =
+        serde_json::from_str(collection).context("Failed to parse Postman collection")?;
+
+    if !collection.info.schema.is_empty() && !collection.info.schema.contains("collection/v2") {
+        anyhow::bail!(
+            "Unsupported Postman collection schema {}, export the collection as v2.1",
+            collection.info.schema
+        );
+    }
+
+    let mut importer = Importer {
+        report: ImportReport::default(),
+        vars: Variables::default(),
+    };
+
+    importer.scripts("Collection", &collection.event);
+
+    let entries = importer.items(&collection.item, &[], collection.auth.as_ref());
+
+    let environments = environments
+        .iter()
+        .map(|env| importer.environment(env))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let variables = importer
+        .pairs(&collection.variable)
+        .into_iter()
+        .map(|kv| KeyValue {
+            name: importer.vars.name(&kv.name),
+            ..kv
+        })
+        .collect::<Vec<_>>();
+
+    let default_environment = match environments.as_slice() {
+        [env] => Some(env.name.clone()),
+        _ => None,
+    };
+
+    let Importer { mut report, vars } = importer;
+    vars.report(&mut report);
+
+    Ok(ImportedCollection {
+        name: collection.info.name,
+        entries,
+        environments,
+        default_environment,
+        variables: KeyValList::from(variables),
+        report,
+    })
+}
+
+impl Importer {
+    fn items(
+        &mut self,
+        items: &[Item],
+        parents: &[&str],
+        auth: Option<&PostmanAuth>,
+    ) -> Vec<ImportedEntry> {
+        let mut entries = Vec::new();
+        for item in items {
+            let mut path = parents.to_vec();
+            path.push(&item.name);
+            let context = path.join(" / ");
+
+            self.scripts(&context, &item.event);
+
+            if let Some(children) = &item.item {
+                let auth = item.auth.as_ref().or(auth);
+                entries.push(ImportedEntry::Folder {
+                    name: item.name.clone(),
+                    entries: self.items(children, &path, auth),
+                });
+            } else if let Some(request) = &item.request {
+                if !item.response.is_empty() {
+                    self.report.warn(
+                        &context,
+                        format!("{} saved responses were not imported", item.response.len()),
+                    );
+                }
+                let description = item.description.as_ref().and_then(Description::text);
+                entries.push(ImportedEntry::Request {
+                    name: item.name.clone(),
+                    request: Box::new(self.request(&context, request, description, auth)),
+                });
+            }
+        }
+        entries
+    }
+
+    fn request(
+        &mut self,
+        context: &str,
+        request: &PostmanRequest,
+        description: Option<&str>,
+        auth: Option<&PostmanAuth>,
+    ) -> Request {
+        let def = match request {
+            PostmanRequest::Url(url) => {
+                let (url, query_params) = self.raw_url(url);
+                return Request {
+                    description: description
+                        .map(str::to_string)
+                        .unwrap_or_else(|| Request::default().description),
+                    url,
+                    query_params,
+                    ..Request::default()
+                };
+            }
+            PostmanRequest::Request(def) => def,
+        };
+
+        let method = def.method.as_deref().unwrap_or("GET").to_uppercase();
+        let method = Method::from_str(&method).unwrap_or_else(|_| {
+            self.report.warn(
+                context,
+                format!("method {} is not supported, using GET", method),
+            );
+            Method::GET
+        });
+
+        let (url, query_params, path_params) = match &def.url {
+            Some(url) => self.url(url),
+            None => (String::new(), KeyValList::new(), KeyValList::new()),
+        };
+
+        let mut headers = match &def.header {
+            Headers::List(headers) => self.pairs(headers),
+            Headers::Raw(raw) => raw
+                .lines()
+                .filter_map(|line| line.split_once(':'))
+                .map(|(name, value)| KeyValue {
+                    disabled: false,
+                    name: name.trim().to_string(),
+                    value: self.vars.template(value.trim()),
+                })
+                .collect(),
+        };
+        let mut query_params = query_params.into_iter().collect::<Vec<_>>();
+
+        let auth = match def.auth.as_ref().or(auth) {
+            Some(auth) => self.auth(context, auth, &mut headers, &mut query_params),
+            None => Auth::None,
+        };
+
+        let content_type = headers
+            .iter()
+            .find(|h| h.name.eq_ignore_ascii_case("content-type"))
+            .map(|h| h.value.to_lowercase())
+            .unwrap_or_default();
+        let body = match &def.body {
+            Some(body) if !body.disabled => self.body(context, body, &content_type),
+            _ => RequestBody::None,
+        };
+
+        let description = def
+            .description
+            .as_ref()
+            .and_then(Description::text)
+            .or(description)
+            .map(str::to_string)
+            .unwrap_or_else(|| Request::default().description);
+
+        Request {
+            description,
+            method,
+            url,
+            headers: KeyValList::from(headers),
+            body,
+            query_params: KeyValList::from(query_params),
+            path_params,
+            auth,
+            ..Request::default()
+        }
+    }
+
+    fn scripts(&mut self, context: &str, events: &[Event]) {
+        for event in events.iter().filter(|e| e.has_script()) {
+            self.report
+                .warn(context, format!("{} script was not imported", event.listen));
+        }
+    }
+
+    fn pairs(&mut self, pairs: &[Pair]) -> Vec<KeyValue> {
+        pairs
+            .iter()
+            .filter_map(|p| {
+                let name = p.key.as_deref()?;
+                Some(KeyValue {
+                    disabled: p.disabled,
+                    name: name.to_string(),
+                    value: self
+                        .vars
+                        .template(&p.value.as_ref().map(value_str).unwrap_or_default()),
+                })
+            })
+            .collect()
+    }
+
+    /// Query params are kept separately, the URL is stored without them
+    fn raw_url(&mut self, raw: &str) -> (String, KeyValList) {
+        let (url, query) = raw.split_once('?').unwrap_or((raw, ""));
+        let params = query
+            .split('&')
+            .filter(|p| !p.is_empty())
+            .map(|p| {
+                let (name, value) = p.split_once('=').unwrap_or((p, ""));
+                KeyValue {
+                    disabled: false,
+                    name: name.to_string(),
+                    value: self.vars.template(value),
+                }
+            })
+            .collect();
+        (self.path(url), KeyValList::from(params))
+    }
+
+    fn url(&mut self, url: &Url) -> (String, KeyValList, KeyValList) {
+        let url = match url {
+            Url::Raw(raw) => {
+                let (url, query) = self.raw_url(raw);
+                return (url, query, KeyValList::new());
+            }
+            Url::Object(url) => url,
+        };
+
+        let base = match &url.raw {
+            Some(raw) => raw
+                .split_once('?')
+                .map(|(u, _)| u)
+                .unwrap_or(raw)
+                .to_string(),
+            None => {
+                let mut base = String::new();
+                if let Some(protocol) = &url.protocol {
+                    base.push_str(protocol);
+                    base.push_str("://");
+                }
+                if let Some(host) = &url.host {
+                    base.push_str(&host.join("."));
+                }
+                if let Some(port) = &url.port {
+                    base.push(':');
+                    base.push_str(port);
+                }
+                if let Some(path) = &url.path {
+                    base.push('/');
+                    base.push_str(&path.join("/"));
+                }
+                base
+            }
+        };
+
+        let query = self.pairs(&url.query);
+        let path_params = self
+            .pairs(&url.variable)
+            .into_iter()
+            .map(|kv| KeyValue {
+                name: param_name(&kv.name),
+                ..kv
+            })
+            .collect();
+
+        (
+            self.path(&base),
+            KeyValList::from(query),
+            KeyValList::from(path_params),
+        )
+    }
+
+    /// Converts variables and `:param` path segments to supported names
+    fn path(&mut self, url: &str) -> String {
+        let url = self.vars.template(url);
+        Regex::new(r"/:([^/?#]+)")
+            .unwrap()
+            .replace_all(&url, |cap: &Captures| format!("/:{}", param_name(&cap[1])))
+            .to_string()
+    }
+
+    fn auth(
+        &mut self,
+        context: &str,
+        auth: &PostmanAuth,
+        headers: &mut Vec<KeyValue>,
+        query: &mut Vec<KeyValue>,
+    ) -> Auth {
+        let mut param = |name: &str| {
+            let value = auth.param(name).unwrap_or_default();
+            self.vars.template(&value)
+        };
+        let converted = match auth.kind.as_str() {
+            "noauth" => Some(Auth::None),
+            "basic" => Some(Auth::Basic {
+                username: param("username"),
+                password: param("password"),
+            }),
+            "bearer" => Some(Auth::Bearer {
+                token: param("token"),
+            }),
+            "apikey" => {
+                let kv = KeyValue {
+                    disabled: false,
+                    name: param("key"),
+                    value: param("value"),
+                };
+                if param("in") == "query" {
+                    query.push(kv);
+                } else {
+                    headers.push(kv);
+                }
+                Some(Auth::None)
+            }
+            _ => None,
+        };
+
+        converted.unwrap_or_else(|| {
+            self.report
+                .warn(context, format!("{} auth is not supported", auth.kind));
+            Auth::None
+        })
+    }
+
+    fn body(&mut self, context: &str, body: &Body, content_type: &str) -> RequestBody {
+        match body.mode.as_deref() {
+            Some("raw") => {
+                let raw = self.vars.template(body.raw.as_deref().unwrap_or_default());
+                let language = body
+                    .options
+                    .as_ref()
+                    .and_then(|o| o.raw.as_ref())
+                    .and_then(|r| r.language.as_deref());
+                match language {
+                    Some("json") => RequestBody::Json(raw),
+                    Some("xml") => RequestBody::XML(raw),
+                    None if content_type.contains("json") => RequestBody::Json(raw),
+                    None if content_type.contains("xml") => RequestBody::XML(raw),
+                    _ => RequestBody::Text(raw),
+                }
+            }
+            Some("urlencoded") => RequestBody::Form(KeyValList::from(self.pairs(&body.urlencoded))),
+            Some("formdata") => {
+                let mut params = Vec::new();
+                let mut files = Vec::new();
+                for param in &body.formdata {
+                    let Some(name) = param.key.clone() else {
+                        continue;
+                    };
+                    if param.kind.as_deref() == Some("file") {
+                        let path = match &param.src {
+                            Some(Value::String(src)) => Some(PathBuf::from(src)),
+                            Some(Value::Array(srcs)) => {
+                                if srcs.len() > 1 {
+                                    self.report.warn(
+                                        context,
+                                        format!("only the first file of `{}` was imported", name),
+                                    );
+                                }
+                                srcs.first().and_then(Value::as_str).map(PathBuf::from)
+                            }
+                            _ => None,
+                        };
+                        files.push(KeyFile {
+                            name,
+                            path,
+                            disabled: param.disabled,
+                        });
+                    } else {
+                        params.push(KeyValue {
+                            disabled: param.disabled,
+                            name,
+                            value: self
+                                .vars
+                                .template(&param.value.as_ref().map(value_str).unwrap_or_default()),
+                        });
+                    }
+                }
+                RequestBody::Multipart {
+                    params: KeyValList::from(params),
+                    files: KeyFileList::from(files),
+                }
+            }
+            Some("file") => RequestBody::File(
+                body.file
+                    .as_ref()
+                    .and_then(|f| f.src.as_ref())
+                    .map(PathBuf::from),
+            ),
+            Some("graphql") => {
+                let graphql = body.graphql.as_ref();
+                let variables = match graphql.and_then(|g| g.variables.as_ref()) {
+                    Some(Value::String(vars)) => vars.clone(),
+                    Some(Value::Null) | None => String::new(),
+                    Some(vars) => serde_json::to_string_pretty(vars).unwrap_or_default(),
+                };
+                RequestBody::GraphQL {
+                    query: self
+                        .vars
+                        .template(graphql.and_then(|g| g.query.as_deref()).unwrap_or_default()),
+                    variables: self.vars.template(&variables),
+                    operation_name: None,
+                }
+            }
+            None => RequestBody::None,
+            Some(mode) => {
+                self.report
+                    .warn(context, format!("{} body is not supported", mode));
+                RequestBody::None
+            }
+        }
+    }
+
+    fn environment(&mut self, data: &str) -> anyhow::Result<Environment> {
+        let env: PostmanEnvironment =
+            serde_json::from_str(data).context("Failed to parse Postman environment")?;
+
+        let variables = env
+            .values
+            .into_iter()
+            .map(|v| KeyValue {
+                disabled: !v.enabled,
+                name: self.vars.name(&v.key),
+                value: self
+                    .vars
+                    .template(&v.value.as_ref().map(value_str).unwrap_or_default()),
+            })
+            .collect();
+
+        Ok(Environment {
+            name: file_name(&env.name),
+            variables: KeyValList::from(variables),
+            secrets: BTreeSet::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn requests(entries: &[ImportedEntry]) -> Vec<(String, &Request)> {
+        let mut found = Vec::new();
+        for entry in entries {
+            match entry {
+                ImportedEntry::Request { name, request } => found.push((name.clone(), &**request)),
+                ImportedEntry::Folder { name, entries } => {
+                    for (child, request) in requests(entries) {
+                        found.push((format!("{}/{}", name, child), request));
+                    }
+                }
+            }
+        }
+        found
+    }
+
+    fn kv(name: &str, value: &str) -> KeyValue {
+        KeyValue {
+            disabled: false,
+            name: name.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn converts_requests_in_folders() {
+        let collection = json!({
+            "info": {
+                "name": "Users",
+                "schema": "https://schema.getpostman.com/json/collection/v2.1.0/collection.json",
+            },
+            "item": [{
+                "name": "Admin",
+                "auth": {
+                    "type": "bearer",
+                    "bearer": [{ "key": "token", "value": "{{token}}" }],
+                },
+                "item": [{
+                    "name": "Update user",
+                    "request": {
+                        "method": "put",
+                        "header": [
+                            { "key": "Content-Type", "value": "application/json" },
+                            { "key": "X-Debug", "value": "1", "disabled": true },
+                        ],
+                        "url": {
+                            "raw": "{{baseUrl}}/users/:user_id?notify=true",
+                            "query": [{ "key": "notify", "value": "true" }],
+                            "variable": [{ "key": "user_id", "value": "42" }],
+                        },
+                        "body": { "mode": "raw", "raw": "{\"name\": \"{{name}}\"}" },
+                    },
+                }],
+            }],
+        });
+
+        let imported = import(&collection.to_string(), &[]).unwrap();
+        assert_eq!(imported.name, "Users");
+        assert!(imported.report.is_empty(), "{:?}", imported.report);
+
+        let requests = requests(&imported.entries);
+        let [(name, request)] = requests.as_slice() else {
+            panic!("expected one request, got {:?}", requests);
+        };
+        assert_eq!(name, "Admin/Update user");
+        assert_eq!(request.method, Method::PUT);
+        assert_eq!(request.url, "{{baseUrl}}/users/:userId");
+        assert_eq!(
+            request.query_params,
+            KeyValList::from(vec![kv("notify", "true")])
+        );
+        assert_eq!(
+            request.path_params,
+            KeyValList::from(vec![kv("userId", "42")])
+        );
+        assert_eq!(
+            request.headers,
+            KeyValList::from(vec![
+                kv("Content-Type", "application/json"),
+                KeyValue {
+                    disabled: true,
+                    ..kv("X-Debug", "1")
+                },
+            ])
+        );
+        assert_eq!(
+            request.body,
+            RequestBody::Json("{\"name\": \"{{name}}\"}".to_string())
+        );
+        assert_eq!(
+            request.auth,
+            Auth::Bearer {
+                token: "{{token}}".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn keeps_valid_variable_names() {
+        let collection = json!({
+            "info": { "name": "Keys" },
+            "item": [{
+                "name": "Search",
+                "request": {
+                    "url": "{{base-url}}/search?key={{api_key}}&q={{search term}}&id={{$guid}}&n={{$randomFirstName}}",
+                    "auth": {
+                        "type": "apikey",
+                        "apikey": { "key": "X-Api-Key", "value": "{{api.key}}", "in": "header" },
+                    },
+                },
+            }],
+        });
+
+        let imported = import(&collection.to_string(), &[]).unwrap();
+        let requests = requests(&imported.entries);
+        let request = requests[0].1;
+        assert_eq!(request.url, "{{base-url}}/search");
+        assert_eq!(
+            request.query_params,
+            KeyValList::from(vec![
+                kv("key", "{{api_key}}"),
+                kv("q", "{{search_term}}"),
+                kv("id", "{{$uuid}}"),
+                kv("n", "{{$randomFirstName}}"),
+            ])
+        );
+        assert_eq!(
+            request.headers,
+            KeyValList::from(vec![kv("X-Api-Key", "{{api.key}}")])
+        );
+        assert_eq!(request.auth, Auth::None);
+        assert_eq!(
+            imported.report.warnings,
+            [
+                "Variable `search term` was renamed to `search_term`",
+                "Dynamic variable `{{$randomFirstName}}` is not supported",
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_collection_variables_with_the_collection() {
+        let collection = json!({
+            "info": { "name": "Vars" },
+            "variable": [
+                { "key": "baseUrl", "value": "https://example.com" },
+                { "key": "page size", "value": 10 },
+            ],
+            "event": [{ "listen": "prerequest", "script": { "exec": ["pm.variables.set('a', 1)"] } }],
+            "item": [{
+                "name": "List",
+                "request": { "method": "GET", "url": "{{baseUrl}}/items" },
+                "response": [{ "name": "ok" }],
+            }],
+        });
+        let environment = json!({
+            "name": "Staging / EU",
+            "values": [
+                { "key": "baseUrl", "value": "https://staging.example.com" },
+                { "key": "token", "value": "secret", "enabled": false },
+            ],
+        });
+
+        let imported = import(&collection.to_string(), &[environment.to_string()]).unwrap();
+        let [env] = imported.environments.as_slice() else {
+            panic!("expected one environment");
+        };
+        assert_eq!(env.name, "Staging - EU");
+        assert_eq!(
+            imported.default_environment.as_deref(),
+            Some("Staging - EU")
+        );
+        assert_eq!(env.get("baseUrl"), Some("https://staging.example.com"));
+        assert_eq!(env.get("page_size"), None);
+        assert_eq!(
+            imported.variables,
+            KeyValList::from(vec![
+                kv("baseUrl", "https://example.com"),
+                kv("page_size", "10"),
+            ])
+        );
+        assert!(env
+            .variables
+            .iter()
+            .any(|kv| kv.name == "token" && kv.disabled));
+
+        assert_eq!(
+            imported.report.warnings,
+            [
+                "Collection: prerequest script was not imported",
+                "List: 1 saved responses were not imported",
+                "Variable `page size` was renamed to `page_size`",
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_old_collection_schemas() {
+        let collection = json!({
+            "info": {
+                "name": "Old",
+                "schema": "https://schema.getpostman.com/json/collection/v1.0.0/collection.json",
+            },
+        });
+        let err = import(&collection.to_string(), &[]).unwrap_err();
+        assert!(err.to_string().contains("export the collection as v2.1"));
+    }
+}
@@ -5,8 +5,18 @@ use strum::{Display, EnumString};
 use crate::http::KeyValue;
 
 pub mod collections;
+pub mod diagnostics;
 pub mod environment;
+pub mod examples;
+pub mod export;
+pub mod files;
+pub mod globals;
+pub mod history;
+pub mod import;
 pub mod request;
+pub mod secrets;
+pub mod session;
+pub mod watch;
 
 pub const HCL_SUFFIX: &str = "hcl";
 pub const HCL_EXTENSION: &str = ".hcl";
@@ -16,13 +26,17 @@ pub const COLLECTION_ROOT_FILE: &str = "collection.hcl";
 pub const ENVIRONMENTS: &str = "environments";
 pub const SCRIPTS: &str = "scripts";
 pub const REQUESTS: &str = "requests";
+pub const SNAPSHOTS: &str = "__snapshots__";
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EncodedKeyValue {
     pub name: String,
     pub value: String,
     #[serde(default, skip_serializing_if = "Not::not")]
     pub disabled: bool,
+    /// Only used by environments, the value is kept in the secret store
+    #[serde(default, skip_serializing_if = "Not::not")]
+    pub secret: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -40,6 +54,7 @@ impl From<KeyValue> for EncodedKeyValue {
             name: value.name,
             value: value.value,
             disabled: value.disabled,
+            secret: false,
         }
     }
 }
@@ -1,18 +1,22 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use hcl::expr::{Heredoc, TemplateExpr};
 use hcl::structure::BodyBuilder;
 use hcl::value::to_value;
-use hcl::{Block, Body, Expression};
+use hcl::{Block, BlockLabel, Body, Expression};
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use tokio::fs;
-use tokio::io::{AsyncReadExt, AsyncWriteExt, BufWriter};
 
 use crate::assertions::{self, Assertions};
+use crate::http::grpc::GrpcRequest;
 use crate::http::request::{Auth, Method, Request, RequestBody};
+use crate::http::websocket::{MessageKind, MessageTemplate, WebSocketRequest};
 use crate::http::{KeyFile, KeyFileList, KeyValList, KeyValue};
 use crate::persistence::Version;
 
+use super::diagnostics::Diagnostic;
+use super::files::{read_tracked, read_versioned, track, write_tracked, FileVersion};
 use super::{EncodedKeyFile, EncodedKeyValue};
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -44,6 +48,21 @@ impl From<EncodedMethod> for Method {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EncodedRequestType {
+    #[default]
+    Http,
+    WebSocket,
+    Grpc,
+}
+
+#[derive(Debug, Deserialize)]
+struct EncodedRequestHeader {
+    #[serde(default, rename = "type")]
+    request_type: EncodedRequestType,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct EncodedRequest {
     #[serde(default)]
@@ -74,11 +93,71 @@ pub enum EncodedRequestBody {
         files: Vec<EncodedKeyFile>,
     },
     Json(String),
+    GraphQL {
+        query: String,
+        #[serde(default)]
+        variables: String,
+        operation_name: Option<String>,
+    },
     XML(String),
     Text(String),
     File(Option<PathBuf>),
 }
 
+#[derive(Debug, Deserialize)]
+pub struct EncodedWebSocketRequest {
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub version: Version,
+    pub url: String,
+    #[serde(default)]
+    pub queries: Vec<EncodedKeyValue>,
+    #[serde(default)]
+    pub headers: Vec<EncodedKeyValue>,
+    pub auth: Option<EncodedAuthType>,
+    #[serde(default, rename = "message")]
+    pub messages: IndexMap<String, EncodedMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EncodedGrpcRequest {
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub version: Version,
+    pub url: String,
+    #[serde(default)]
+    pub proto_files: Vec<PathBuf>,
+    #[serde(default)]
+    pub service: String,
+    #[serde(default)]
+    pub method: String,
+    #[serde(default)]
+    pub message: String,
+    #[serde(default)]
+    pub metadata: Vec<EncodedKeyValue>,
+    #[serde(default)]
+    pub assertions: Assertions,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EncodedMessageKind {
+    #[default]
+    Text,
+    Json,
+    Binary,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EncodedMessage {
+    #[serde(default)]
+    pub kind: EncodedMessageKind,
+    #[serde(default)]
+    pub body: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub enum EncodedAuthType {
     Basic { username: String, password: String },
@@ -119,6 +198,80 @@ pub fn encode_request(req: Request) -> hcl::Result<Body> {
     Ok(builder.build())
 }
 
+pub fn encode_websocket(req: WebSocketRequest) -> hcl::Result<Body> {
+    let WebSocketRequest {
+        description,
+        url,
+        headers,
+        query_params,
+        auth,
+        messages,
+    } = req;
+
+    let mut builder = Body::builder()
+        .add_attribute(("type", "websocket"))
+        .add_attribute(("version", Version::V1.to_string()))
+        .add_attribute(("description", description))
+        .add_attribute(("url", url));
+
+    builder = add_auth_block(builder, auth)?;
+    builder = add_kv_block(builder, "queries", query_params)?;
+    builder = add_kv_block(builder, "headers", headers)?;
+
+    for message in messages {
+        let kind = match message.kind {
+            MessageKind::Text => "text",
+            MessageKind::Json => "json",
+            MessageKind::Binary => "binary",
+        };
+        builder = builder.add_block(
+            Block::builder("message")
+                .add_label(BlockLabel::String(message.name))
+                .add_attribute(("kind", kind))
+                .add_attribute(("body", multiline_text(message.body)))
+                .build(),
+        );
+    }
+
+    Ok(builder.build())
+}
+
+pub fn encode_grpc(req: GrpcRequest) -> hcl::Result<Body> {
+    let GrpcRequest {
+        description,
+        url,
+        proto_files,
+        service,
+        method,
+        message,
+        metadata,
+        assertions,
+    } = req;
+
+    let mut builder = Body::builder()
+        .add_attribute(("type", "grpc"))
+        .add_attribute(("version", Version::V1.to_string()))
+        .add_attribute(("description", description))
+        .add_attribute(("url", url));
+
+    if !proto_files.is_empty() {
+        let files = proto_files
+            .into_iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        builder = builder.add_attribute(("proto_files", files));
+    }
+
+    builder = builder
+        .add_attribute(("service", service))
+        .add_attribute(("method", method))
+        .add_attribute(("message", multiline_text(message)));
+    builder = add_kv_block(builder, "metadata", metadata)?;
+    builder = assertions::encode(builder, assertions);
+
+    Ok(builder.build())
+}
+
 fn add_body_block(builder: BodyBuilder, body: RequestBody) -> hcl::Result<BodyBuilder> {
     let block = Block::builder("body");
     let block = match body {
@@ -127,6 +280,21 @@ fn add_body_block(builder: BodyBuilder, body: RequestBody) -> hcl::Result<BodyBu
             block.add_attribute(("form", to_value(encode_key_values(form))?))
         }
         RequestBody::Json(data) => block.add_attribute(("json", multiline_text(data))),
+        RequestBody::GraphQL {
+            query,
+            variables,
+            operation_name,
+        } => {
+            let mut graphql =
+                Block::builder("graphql").add_attribute(("query", multiline_text(query)));
+            if !variables.trim().is_empty() {
+                graphql = graphql.add_attribute(("variables", multiline_text(variables)));
+            }
+            if let Some(name) = operation_name {
+                graphql = graphql.add_attribute(("operation_name", name));
+            }
+            block.add_block(graphql.build())
+        }
         RequestBody::XML(data) => block.add_attribute(("xml", multiline_text(data))),
         RequestBody::Text(data) => block.add_attribute(("text", multiline_text(data))),
         RequestBody::File(path) => block.add_attribute(("file", path_expr(path))),
@@ -236,6 +404,15 @@ fn decode_body(body: Option<EncodedRequestBody>) -> Option<RequestBody> {
     let decode = match body {
         EncodedRequestBody::Form(form) => RequestBody::Form(decode_key_values(form)),
         EncodedRequestBody::Json(data) => RequestBody::Json(data),
+        EncodedRequestBody::GraphQL {
+            query,
+            variables,
+            operation_name,
+        } => RequestBody::GraphQL {
+            query,
+            variables,
+            operation_name,
+        },
         EncodedRequestBody::XML(data) => RequestBody::XML(data),
         EncodedRequestBody::Text(data) => RequestBody::Text(data),
         EncodedRequestBody::File(path) => RequestBody::File(path),
@@ -285,32 +462,170 @@ fn decode_request(req: EncodedRequest) -> Request {
     }
 }
 
-pub async fn read_request(path: &PathBuf) -> anyhow::Result<Request> {
+fn decode_websocket(req: EncodedWebSocketRequest) -> WebSocketRequest {
+    let EncodedWebSocketRequest {
+        description,
+        url,
+        queries,
+        headers,
+        auth,
+        messages,
+        ..
+    } = req;
+
+    let messages = messages
+        .into_iter()
+        .map(|(name, message)| MessageTemplate {
+            name,
+            kind: match message.kind {
+                EncodedMessageKind::Text => MessageKind::Text,
+                EncodedMessageKind::Json => MessageKind::Json,
+                EncodedMessageKind::Binary => MessageKind::Binary,
+            },
+            body: message.body,
+        })
+        .collect();
+
+    WebSocketRequest {
+        description,
+        url,
+        headers: decode_key_values(headers),
+        query_params: decode_key_values(queries),
+        auth: decode_auth(auth),
+        messages,
+    }
+}
+
+fn decode_grpc(req: EncodedGrpcRequest) -> GrpcRequest {
+    let EncodedGrpcRequest {
+        description,
+        url,
+        proto_files,
+        service,
+        method,
+        message,
+        metadata,
+        assertions,
+        ..
+    } = req;
+
+    GrpcRequest {
+        description,
+        url,
+        proto_files,
+        service,
+        method,
+        message,
+        metadata: decode_key_values(metadata),
+        assertions,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RequestFile {
+    Http(Request),
+    WebSocket(WebSocketRequest),
+    Grpc(GrpcRequest),
+}
+
+pub async fn read_request(path: &Path) -> anyhow::Result<Request> {
     let request = load_from_file(path).await?;
     Ok(decode_request(request))
 }
 
+/// A request file as read from disk, with the version to track once the
+/// app holds it, e.g. in a tab
+#[derive(Debug, Clone)]
+pub struct RequestOnDisk {
+    pub path: PathBuf,
+    pub request: RequestFile,
+    pub version: FileVersion,
+}
+
+impl RequestOnDisk {
+    /// Saving the request fails from now on if the file changes on disk
+    pub fn track(&self) {
+        track(&self.path, self.version);
+    }
+}
+
+fn parse_located(path: &Path, content: &str) -> anyhow::Result<RequestFile> {
+    parse_request_file(content).map_err(|e| Diagnostic::new(path, content, &e).into())
+}
+
+/// Reads a request file of any type, using the `type` attribute to pick the
+/// encoding. Files without it are plain HTTP requests. Parse errors are
+/// returned as a [`Diagnostic`].
+pub async fn read_request_file(path: &Path) -> anyhow::Result<RequestFile> {
+    let buffer = fs::read_to_string(path).await?;
+    parse_located(path, &buffer)
+}
+
+/// Reads a request file to open it in the app, saving it refuses to
+/// overwrite changes made on disk since
+pub async fn open_request_file(path: &Path) -> anyhow::Result<RequestFile> {
+    let buffer = read_tracked(path).await?;
+    parse_located(path, &buffer)
+}
+
+/// Reads a request file without tracking it, see [`RequestOnDisk::track`]
+pub async fn read_request_on_disk(path: &Path) -> anyhow::Result<RequestOnDisk> {
+    let (buffer, version) = read_versioned(path).await?;
+    Ok(RequestOnDisk {
+        path: path.to_path_buf(),
+        request: parse_located(path, &buffer)?,
+        version,
+    })
+}
+
+/// Parses the content of a request file of any type
+pub fn parse_request_file(content: &str) -> anyhow::Result<RequestFile> {
+    let header: EncodedRequestHeader = hcl::from_str(content)?;
+
+    let request = match header.request_type {
+        EncodedRequestType::Http => RequestFile::Http(decode_request(hcl::from_str(content)?)),
+        EncodedRequestType::WebSocket => {
+            RequestFile::WebSocket(decode_websocket(hcl::from_str(content)?))
+        }
+        EncodedRequestType::Grpc => RequestFile::Grpc(decode_grpc(hcl::from_str(content)?)),
+    };
+
+    Ok(request)
+}
+
+/// Encodes a request of any type as the content of its file
+pub fn encode_request_file(request: RequestFile) -> anyhow::Result<String> {
+    let body = match request {
+        RequestFile::Http(req) => encode_request(req)?,
+        RequestFile::WebSocket(req) => encode_websocket(req)?,
+        RequestFile::Grpc(req) => encode_grpc(req)?,
+    };
+    Ok(hcl::to_string(&body)?)
+}
+
+/// Saves the request, refusing with a [`FileConflict`] to overwrite a file
+/// changed on disk since it was read
+///
+/// [`FileConflict`]: super::files::FileConflict
 pub async fn save_req_to_file(path: PathBuf, req: hcl::Body) -> Result<(), anyhow::Error> {
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent).await?;
     }
 
-    let file = fs::File::create(path).await?;
-    let mut writer = BufWriter::new(file);
     let encoded = hcl::to_string(&req)?;
-
-    writer.write_all(encoded.as_bytes()).await?;
-    writer.flush().await?;
-    Ok(())
+    write_tracked(&path, encoded).await
 }
 
-pub async fn load_from_file(path: &PathBuf) -> anyhow::Result<EncodedRequest> {
-    let file = fs::File::open(path).await?;
-    let mut reader = tokio::io::BufReader::new(file);
-    let mut buffer = String::new();
+pub async fn load_from_file(path: &Path) -> anyhow::Result<EncodedRequest> {
+    let buffer = fs::read_to_string(path).await?;
+    let diagnostic = |e: hcl::Error| Diagnostic::new(path, &buffer, &e.into());
+
+    let header: EncodedRequestHeader = hcl::from_str(&buffer).map_err(diagnostic)?;
+    if header.request_type != EncodedRequestType::Http {
+        anyhow::bail!("{} is not an HTTP request", path.display());
+    }
 
-    reader.read_to_string(&mut buffer).await?;
-    let decoded: EncodedRequest = hcl::from_str(&buffer)?;
+    let decoded: EncodedRequest = hcl::from_str(&buffer).map_err(diagnostic)?;
 
     Ok(decoded)
 }
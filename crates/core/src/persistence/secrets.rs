@@ -0,0 +1,404 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::pbkdf2;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::http::collection::Collection;
+use crate::http::environment::Environment;
+
+use super::collections::project_dirs;
+use super::files::write_atomic;
+use super::Version;
+
+pub const SECRETS_FILE: &str = "secrets.hcl";
+
+const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+const ITERATIONS: u32 = 600_000;
+
+/// Values by collection, environment and variable name
+type Values = BTreeMap<String, BTreeMap<String, BTreeMap<String, String>>>;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct EncodedSecrets {
+    version: Version,
+    salt: String,
+    nonce: String,
+    data: String,
+}
+
+/// Secret values of the environments of all collections, encrypted with a
+/// key derived from the passphrase. The file lives in the data directory of
+/// the app so it never ends up in a collection.
+#[derive(Clone)]
+pub struct SecretStore {
+    path: PathBuf,
+    salt: [u8; SALT_LEN],
+    key: LessSafeKey,
+    values: Values,
+}
+
+impl fmt::Debug for SecretStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecretStore")
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+pub fn secrets_file() -> Option<PathBuf> {
+    let dirs = project_dirs()?;
+    Some(dirs.data_dir().join(SECRETS_FILE))
+}
+
+fn random<const N: usize>() -> anyhow::Result<[u8; N]> {
+    let mut bytes = [0u8; N];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| anyhow::anyhow!("Failed to generate random bytes"))?;
+    Ok(bytes)
+}
+
+/// Stretching the passphrase takes a while, it runs on a blocking thread to
+/// keep the executor free
+async fn derive_key(passphrase: &str, salt: [u8; SALT_LEN]) -> anyhow::Result<LessSafeKey> {
+    let passphrase = passphrase.to_string();
+    let key = tokio::task::spawn_blocking(move || {
+        let mut key = [0u8; KEY_LEN];
+        pbkdf2::derive(
+            pbkdf2::PBKDF2_HMAC_SHA256,
+            NonZeroU32::new(ITERATIONS).expect("non zero iterations"),
+            &salt,
+            passphrase.as_bytes(),
+            &mut key,
+        );
+        key
+    })
+    .await?;
+    Ok(LessSafeKey::new(
+        UnboundKey::new(&AES_256_GCM, &key).expect("AES-256 key length"),
+    ))
+}
+
+/// Collections are told apart by their canonical directory, the path
+/// [`open_collection`] gives them
+///
+/// [`open_collection`]: super::collections::open_collection
+fn collection_id(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
+
+impl SecretStore {
+    /// Opens the store of the app, it is created on the first save
+    pub async fn open(passphrase: &str) -> anyhow::Result<Self> {
+        let path = secrets_file().context("Failed to find the data directory")?;
+        Self::open_at(path, passphrase).await
+    }
+
+    pub async fn open_at(path: PathBuf, passphrase: &str) -> anyhow::Result<Self> {
+        let content = match fs::read_to_string(&path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let salt = random()?;
+                return Ok(Self {
+                    key: derive_key(passphrase, salt).await?,
+                    path,
+                    salt,
+                    values: Values::new(),
+                });
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let encoded: EncodedSecrets = hcl::from_str(&content)
+            .with_context(|| format!("Failed to parse secrets file {}", path.display()))?;
+        let salt: [u8; SALT_LEN] = STANDARD
+            .decode(&encoded.salt)?
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("Invalid salt in {}", path.display()))?;
+        let nonce = Nonce::try_assume_unique_for_key(&STANDARD.decode(&encoded.nonce)?)
+            .map_err(|_| anyhow::anyhow!("Invalid nonce in {}", path.display()))?;
+        let mut data = STANDARD.decode(&encoded.data)?;
+
+        let key = derive_key(passphrase, salt).await?;
+        let plain = key
+            .open_in_place(nonce, Aad::empty(), &mut data)
+            .map_err(|_| anyhow::anyhow!("Wrong passphrase for {}", path.display()))?;
+        let values = serde_json::from_slice(plain)?;
+
+        Ok(Self {
+            path,
+            salt,
+            key,
+            values,
+        })
+    }
+
+    /// Sets the values of the secret variables of every environment
+    pub fn fill(&self, collection: &mut Collection) {
+        let path = collection.path.clone();
+        for (_, env) in collection.environments.entries_mut() {
+            self.fill_environment(&path, env);
+        }
+    }
+
+    /// Sets the secret values of the environment of the collection at the
+    /// canonical path `collection`
+    pub fn fill_environment(&self, collection: &Path, env: &mut Environment) {
+        let Some(values) = self
+            .values
+            .get(&collection_id(collection))
+            .and_then(|envs| envs.get(&env.name))
+        else {
+            return;
+        };
+
+        for kv in env.variables.iter_mut() {
+            if let Some(value) = values
+                .get(&kv.name)
+                .filter(|_| env.secrets.contains(&kv.name))
+            {
+                kv.value = value.clone();
+            }
+        }
+    }
+
+    /// Replaces the stored values of the collection with the secret values
+    /// of its environments
+    pub fn store(&mut self, collection: &Collection) {
+        let envs = collection
+            .environments
+            .entries()
+            .map(|(_, env)| {
+                let values = env
+                    .variables
+                    .iter()
+                    .filter(|kv| env.is_secret(&kv.name) && !kv.value.is_empty())
+                    .map(|kv| (kv.name.clone(), kv.value.clone()))
+                    .collect::<BTreeMap<_, _>>();
+                (env.name.clone(), values)
+            })
+            .filter(|(_, values)| !values.is_empty())
+            .collect::<BTreeMap<_, _>>();
+
+        let id = collection_id(&collection.path);
+        if envs.is_empty() {
+            self.values.remove(&id);
+        } else {
+            self.values.insert(id, envs);
+        }
+    }
+
+    /// Encrypts the values with a fresh nonce and writes the store
+    pub async fn save(&self) -> anyhow::Result<()> {
+        let nonce = random::<NONCE_LEN>()?;
+        let mut data = serde_json::to_vec(&self.values)?;
+        self.key
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut data)
+            .map_err(|_| anyhow::anyhow!("Failed to encrypt secrets"))?;
+
+        let encoded = EncodedSecrets {
+            version: Version::V1,
+            salt: STANDARD.encode(self.salt),
+            nonce: STANDARD.encode(nonce),
+            data: STANDARD.encode(data),
+        };
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        write_atomic(&self.path, hcl::to_string(&encoded)?).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use crate::http::environment::Environments;
+    use crate::http::{KeyValList, KeyValue};
+    use crate::persistence::environment::{encode_environments, save_environments};
+    use crate::persistence::ENVIRONMENTS;
+
+    use super::*;
+
+    fn kv(name: &str, value: &str) -> KeyValue {
+        KeyValue {
+            disabled: false,
+            name: name.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    fn collection(dir: &TempDir) -> Collection {
+        let mut env = Environment::new("dev".to_string());
+        env.variables = KeyValList::from(vec![kv("host", "example.com"), kv("token", "s3cret")]);
+        env.secrets.insert("token".to_string());
+
+        let mut environments = Environments::new();
+        environments.insert(env);
+        let path = std::fs::canonicalize(dir.path()).unwrap();
+        Collection::new("col".to_string(), vec![], vec![], path, environments, None)
+    }
+
+    fn token(collection: &Collection) -> String {
+        let (_, env) = collection.environments.entries().next().unwrap();
+        env.get("token").unwrap().to_string()
+    }
+
+    fn clear_secrets(collection: &mut Collection) {
+        for (_, env) in collection.environments.entries_mut() {
+            for kv in env.variables.iter_mut() {
+                if env.secrets.contains(&kv.name) {
+                    kv.value.clear();
+                }
+            }
+        }
+    }
+
+    fn parse(content: &str) -> EncodedSecrets {
+        hcl::from_str(content).unwrap()
+    }
+
+    #[tokio::test]
+    async fn round_trips_secret_values() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(SECRETS_FILE);
+        let mut col = collection(&dir);
+
+        let mut store = SecretStore::open_at(path.clone(), "passphrase")
+            .await
+            .unwrap();
+        store.store(&col);
+        store.save().await.unwrap();
+        assert!(!std::fs::read_to_string(&path).unwrap().contains("s3cret"));
+
+        clear_secrets(&mut col);
+        assert_eq!(token(&col), "");
+
+        let store = SecretStore::open_at(path, "passphrase").await.unwrap();
+        store.fill(&mut col);
+        assert_eq!(token(&col), "s3cret");
+    }
+
+    #[tokio::test]
+    async fn fails_with_wrong_passphrase() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(SECRETS_FILE);
+
+        let mut store = SecretStore::open_at(path.clone(), "passphrase")
+            .await
+            .unwrap();
+        store.store(&collection(&dir));
+        store.save().await.unwrap();
+
+        let err = SecretStore::open_at(path, "wrong").await.unwrap_err();
+        assert!(err.to_string().starts_with("Wrong passphrase"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn uses_fresh_nonce_for_every_save() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(SECRETS_FILE);
+
+        let mut store = SecretStore::open_at(path.clone(), "passphrase")
+            .await
+            .unwrap();
+        store.store(&collection(&dir));
+        store.save().await.unwrap();
+        let first = parse(&std::fs::read_to_string(&path).unwrap());
+        store.save().await.unwrap();
+        let second = parse(&std::fs::read_to_string(&path).unwrap());
+
+        assert_eq!(first.salt, second.salt);
+        assert_ne!(first.nonce, second.nonce);
+        assert_ne!(first.data, second.data);
+    }
+
+    #[tokio::test]
+    async fn fails_on_corrupt_files() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(SECRETS_FILE);
+
+        let mut store = SecretStore::open_at(path.clone(), "passphrase")
+            .await
+            .unwrap();
+        store.store(&collection(&dir));
+        store.save().await.unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        let encoded = parse(&content);
+
+        let write = |encoded: &EncodedSecrets| {
+            std::fs::write(&path, hcl::to_string(encoded).unwrap()).unwrap();
+        };
+        let mut data = STANDARD.decode(&encoded.data).unwrap();
+
+        // Truncated file
+        std::fs::write(&path, &content[..content.len() / 2]).unwrap();
+        assert!(SecretStore::open_at(path.clone(), "passphrase")
+            .await
+            .is_err());
+
+        // Truncated data, shorter than the tag
+        write(&EncodedSecrets {
+            data: STANDARD.encode(&data[..8]),
+            ..parse(&content)
+        });
+        assert!(SecretStore::open_at(path.clone(), "passphrase")
+            .await
+            .is_err());
+
+        // Changed data
+        data[0] ^= 1;
+        write(&EncodedSecrets {
+            data: STANDARD.encode(&data),
+            ..parse(&content)
+        });
+        assert!(SecretStore::open_at(path.clone(), "passphrase")
+            .await
+            .is_err());
+
+        // Bad salt and nonce
+        write(&EncodedSecrets {
+            salt: STANDARD.encode([0u8; 4]),
+            ..parse(&content)
+        });
+        assert!(SecretStore::open_at(path.clone(), "passphrase")
+            .await
+            .is_err());
+        write(&EncodedSecrets {
+            nonce: "not base64!".to_string(),
+            ..parse(&content)
+        });
+        assert!(SecretStore::open_at(path, "passphrase").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn keeps_secret_values_out_of_environment_files() {
+        let dir = TempDir::new().unwrap();
+        let col = collection(&dir);
+
+        save_environments(col.path.clone(), encode_environments(&col.environments))
+            .await
+            .unwrap();
+
+        let content =
+            std::fs::read_to_string(dir.path().join(ENVIRONMENTS).join("dev.hcl")).unwrap();
+        assert!(content.contains("example.com"));
+        assert!(!content.contains("s3cret"));
+
+        let (_, env) = col.environments.entries().next().unwrap();
+        let env = env.without_secrets();
+        assert_eq!(env.get("host"), Some("example.com"));
+        assert_eq!(env.get("token"), None);
+    }
+}
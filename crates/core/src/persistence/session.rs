@@ -0,0 +1,247 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use super::collections::project_dirs;
+use super::files::write_atomic;
+use super::request::{encode_request_file, parse_request_file, RequestFile};
+
+pub const SESSION_FILE: &str = "session.hcl";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionTabKind {
+    Http,
+    WebSocket,
+    Grpc,
+    Collection,
+}
+
+/// An open tab, request tabs keep their request as it was edited so unsaved
+/// changes survive a restart
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionTab {
+    pub kind: SessionTabKind,
+    pub name: String,
+    /// Root of the collection the tab belongs to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collection: Option<PathBuf>,
+    /// File of the request in the collection, unsaved requests have none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request: Option<PathBuf>,
+    /// Content of the request file as edited
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub draft: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub split_ratio: Option<f32>,
+}
+
+impl SessionTab {
+    pub fn new(kind: SessionTabKind, name: String) -> Self {
+        Self {
+            kind,
+            name,
+            collection: None,
+            request: None,
+            draft: None,
+            split_ratio: None,
+        }
+    }
+
+    pub fn with_draft(mut self, request: RequestFile) -> anyhow::Result<Self> {
+        self.draft = Some(encode_request_file(request)?);
+        Ok(self)
+    }
+
+    /// The request as edited, `None` when it was not kept
+    pub fn draft(&self) -> anyhow::Result<Option<RequestFile>> {
+        self.draft.as_deref().map(parse_request_file).transpose()
+    }
+}
+
+/// State of the collection tree and the environment picked for a collection
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionCollection {
+    pub path: PathBuf,
+    #[serde(default)]
+    pub expanded: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub expanded_folders: Vec<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<String>,
+}
+
+/// Open tabs and layout of the app, restored on the next launch
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub split_ratio: Option<f32>,
+    /// Index of the active tab in `tabs`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_tab: Option<usize>,
+    #[serde(default, rename = "tab")]
+    pub tabs: Vec<SessionTab>,
+    #[serde(default, rename = "collection")]
+    pub collections: Vec<SessionCollection>,
+}
+
+pub fn session_file() -> Option<PathBuf> {
+    let dirs = project_dirs()?;
+    Some(dirs.data_dir().join(SESSION_FILE))
+}
+
+/// Reads the session of the last run, `None` on the first launch
+pub async fn read_session() -> anyhow::Result<Option<Session>> {
+    let path = session_file().context("Failed to find the data directory")?;
+    let content = match fs::read_to_string(&path).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    let session = hcl::from_str(&content)
+        .with_context(|| format!("Failed to parse session {}", path.display()))?;
+    Ok(Some(session))
+}
+
+pub async fn save_session(session: Session) -> anyhow::Result<()> {
+    let path = session_file().context("Failed to find the data directory")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+
+    write_atomic(&path, hcl::to_string(&session)?).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::grpc::GrpcRequest;
+    use crate::http::request::{Method, Request, RequestBody};
+    use crate::http::websocket::{MessageKind, MessageTemplate, WebSocketRequest};
+    use crate::http::{KeyValList, KeyValue};
+
+    fn kv(name: &str, value: &str) -> KeyValue {
+        KeyValue {
+            disabled: false,
+            name: name.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    fn requests() -> Vec<RequestFile> {
+        let http = Request {
+            method: Method::POST,
+            url: "{{host}}/users".to_string(),
+            headers: KeyValList::from(vec![kv("Accept", "application/json")]),
+            // Multi line bodies end with a newline, as in request files
+            body: RequestBody::Json("{\n  \"name\": \"a \\\"b\\\"\"\n}\n".to_string()),
+            query_params: KeyValList::from(vec![kv("page", "1")]),
+            ..Request::default()
+        };
+        let websocket = WebSocketRequest {
+            url: "wss://{{host}}/events".to_string(),
+            messages: vec![MessageTemplate {
+                name: "hello".to_string(),
+                kind: MessageKind::Json,
+                body: "{\"type\": \"hello\"}".to_string(),
+            }],
+            ..WebSocketRequest::default()
+        };
+        let grpc = GrpcRequest {
+            service: "echo.Echo".to_string(),
+            method: "Say".to_string(),
+            message: "{\"text\": \"hi\"}".to_string(),
+            metadata: KeyValList::from(vec![kv("x-user", "ann")]),
+            ..GrpcRequest::default()
+        };
+        vec![
+            RequestFile::Http(http),
+            RequestFile::WebSocket(websocket),
+            RequestFile::Grpc(grpc),
+        ]
+    }
+
+    #[test]
+    fn keeps_tabs_and_layout() {
+        let kinds = [
+            SessionTabKind::Http,
+            SessionTabKind::WebSocket,
+            SessionTabKind::Grpc,
+        ];
+        let mut tabs = kinds
+            .into_iter()
+            .zip(requests())
+            .enumerate()
+            .map(|(idx, (kind, request))| {
+                let mut tab = SessionTab::new(kind, format!("Tab {}", idx))
+                    .with_draft(request)
+                    .unwrap();
+                tab.collection = Some(PathBuf::from("/work/api"));
+                tab.request = Some(PathBuf::from(format!("/work/api/requests/{}.hcl", idx)));
+                tab.split_ratio = Some(0.3);
+                tab
+            })
+            .collect::<Vec<_>>();
+        let mut settings = SessionTab::new(SessionTabKind::Collection, "api".to_string());
+        settings.collection = Some(PathBuf::from("/work/api"));
+        tabs.push(settings);
+        // Requests which were never saved
+        tabs.push(
+            SessionTab::new(SessionTabKind::Http, "Untitled".to_string())
+                .with_draft(RequestFile::Http(Request::default()))
+                .unwrap(),
+        );
+
+        let session = Session {
+            theme: Some("Gruvbox Light".to_string()),
+            split_ratio: Some(0.25),
+            active_tab: Some(2),
+            tabs,
+            collections: vec![SessionCollection {
+                path: PathBuf::from("/work/api"),
+                expanded: true,
+                expanded_folders: vec![
+                    PathBuf::from("/work/api/requests/users"),
+                    PathBuf::from("/work/api/requests/users/admin"),
+                ],
+                environment: Some("dev".to_string()),
+            }],
+        };
+
+        let read = hcl::from_str::<Session>(&hcl::to_string(&session).unwrap()).unwrap();
+
+        assert_eq!(read, session);
+        let drafts = read.tabs[..3]
+            .iter()
+            .map(|tab| tab.draft().unwrap().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(drafts, requests());
+        assert_eq!(read.tabs[3].draft().unwrap(), None);
+    }
+
+    #[test]
+    fn reports_corrupt_drafts() {
+        let mut tab = SessionTab::new(SessionTabKind::Http, "Broken".to_string());
+        tab.draft = Some("method = \"GET\"\nurl = ".to_string());
+
+        assert!(tab.draft().is_err());
+    }
+
+    #[test]
+    fn reads_sessions_of_older_versions() {
+        let session = hcl::from_str::<Session>("active_tab = 0\n").unwrap();
+        assert_eq!(
+            session,
+            Session {
+                active_tab: Some(0),
+                ..Session::default()
+            }
+        );
+    }
+}
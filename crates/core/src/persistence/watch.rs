@@ -0,0 +1,283 @@
+//! Watching of collection directories for files changed outside of the
+//! app, by an editor or a `git pull`.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use notify::event::{AccessKind, AccessMode};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::fs;
+use tokio::sync::mpsc;
+
+use crate::http::collection::{Entry, Script};
+use crate::http::environment::Environments;
+
+use super::collections::{
+    find_all_requests, find_all_scripts, read_collection_file, EncodedCollection,
+};
+use super::diagnostics::{lint_file, Diagnostic};
+use super::environment::read_environments;
+use super::examples::is_example;
+use super::request::{read_request_on_disk, RequestOnDisk};
+use super::{COLLECTION_ROOT_FILE, ENVIRONMENTS, HCL_EXTENSION, REQUESTS, SCRIPTS};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct FileStamp {
+    modified: Option<SystemTime>,
+    len: u64,
+    dir: bool,
+}
+
+impl FileStamp {
+    fn new(meta: &std::fs::Metadata) -> Self {
+        Self {
+            modified: meta.modified().ok(),
+            len: meta.len(),
+            dir: meta.is_dir(),
+        }
+    }
+}
+
+/// Modification times of the files of a collection
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CollectionSnapshot {
+    files: HashMap<PathBuf, FileStamp>,
+}
+
+/// What changed in a collection between two snapshots
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CollectionChanges {
+    /// The collection file changed
+    pub collection: bool,
+    /// Requests, folders or examples were added or removed
+    pub entries: bool,
+    pub scripts: bool,
+    pub environments: bool,
+    /// Request files whose content changed
+    pub requests: Vec<PathBuf>,
+    /// Collection, request and environment files added, changed or removed
+    pub files: Vec<PathBuf>,
+}
+
+impl CollectionChanges {
+    pub fn is_empty(&self) -> bool {
+        !self.collection
+            && !self.entries
+            && !self.scripts
+            && !self.environments
+            && self.requests.is_empty()
+    }
+}
+
+/// Parts of a collection read again after they changed
+#[derive(Debug, Clone, Default)]
+pub struct CollectionUpdate {
+    /// The collection file, when it changed and parses
+    pub collection: Option<EncodedCollection>,
+    pub entries: Option<Vec<Entry>>,
+    pub scripts: Option<Vec<Script>>,
+    pub environments: Option<Environments>,
+    /// Changed request files with their new content, files which cannot be
+    /// parsed, while still being written for instance, are left out
+    pub requests: Vec<RequestOnDisk>,
+    /// Changed files which fail to parse
+    pub diagnostics: Vec<Diagnostic>,
+    /// Files linted again, their problems replace the ones known before
+    pub linted: Vec<PathBuf>,
+}
+
+/// Stamps of the collection file, requests, environments and scripts of the
+/// collection
+pub async fn snapshot(root: &Path) -> anyhow::Result<CollectionSnapshot> {
+    let mut files = HashMap::new();
+    let root_file = root.join(COLLECTION_ROOT_FILE);
+    if let Ok(meta) = fs::metadata(&root_file).await {
+        files.insert(root_file, FileStamp::new(&meta));
+    }
+    let mut dirs = vec![
+        root.join(REQUESTS),
+        root.join(ENVIRONMENTS),
+        root.join(SCRIPTS),
+    ];
+
+    while let Some(dir) = dirs.pop() {
+        let mut entries = match fs::read_dir(&dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+
+        while let Some(entry) = entries.next_entry().await? {
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let Ok(meta) = entry.metadata().await else {
+                continue;
+            };
+            let path = entry.path();
+            if meta.is_dir() {
+                dirs.push(path.clone());
+            }
+            files.insert(path, FileStamp::new(&meta));
+        }
+    }
+
+    Ok(CollectionSnapshot { files })
+}
+
+impl CollectionSnapshot {
+    /// Changes from this snapshot to a newer one of the collection at `root`
+    pub fn changes(&self, newer: &CollectionSnapshot, root: &Path) -> CollectionChanges {
+        let mut changes = CollectionChanges::default();
+
+        let removed = self
+            .files
+            .keys()
+            .filter(|path| !newer.files.contains_key(*path))
+            .map(|path| (path, None));
+        let updated = newer
+            .files
+            .iter()
+            .filter(|(path, stamp)| self.files.get(*path) != Some(stamp))
+            .map(|(path, stamp)| (path, Some(stamp)));
+
+        for (path, stamp) in removed.chain(updated) {
+            let Ok(relative) = path.strip_prefix(root) else {
+                continue;
+            };
+            let Some(top) = relative.components().next() else {
+                continue;
+            };
+            let existed = self.files.contains_key(path);
+            let is_dir = stamp
+                .or_else(|| self.files.get(path))
+                .is_some_and(|s| s.dir);
+            let is_hcl = path.to_string_lossy().ends_with(HCL_EXTENSION);
+            let linted = matches!(
+                top.as_os_str().to_str(),
+                Some(COLLECTION_ROOT_FILE | ENVIRONMENTS | REQUESTS)
+            );
+            if linted && is_hcl && !is_dir && !is_example(path) {
+                changes.files.push(path.clone());
+            }
+
+            match top.as_os_str().to_str() {
+                Some(COLLECTION_ROOT_FILE) => changes.collection = true,
+                Some(ENVIRONMENTS) => changes.environments = true,
+                Some(SCRIPTS) => changes.scripts = true,
+                Some(REQUESTS) => match stamp {
+                    // Added or removed
+                    None => changes.entries = true,
+                    Some(_) if !existed => changes.entries = true,
+                    // Directory times change with their content, which is
+                    // already compared file by file
+                    Some(stamp) if stamp.dir => (),
+                    Some(_) if is_example(path) => (),
+                    Some(_) => {
+                        if path.to_string_lossy().ends_with(HCL_EXTENSION) {
+                            changes.requests.push(path.clone());
+                        }
+                    }
+                },
+                _ => (),
+            }
+        }
+
+        changes.requests.sort_unstable();
+        changes.files.sort_unstable();
+        changes
+    }
+}
+
+/// Reads the parts of the collection that changed
+pub async fn read_update(
+    root: &Path,
+    changes: &CollectionChanges,
+) -> anyhow::Result<CollectionUpdate> {
+    let mut update = CollectionUpdate::default();
+    if changes.collection {
+        update.collection = read_collection_file(root).await?;
+    }
+    if changes.entries {
+        update.entries = Some(find_all_requests(root).await?);
+    }
+    if changes.scripts {
+        update.scripts = Some(find_all_scripts(root).await?);
+    }
+    if changes.environments {
+        update.environments = Some(read_environments(root).await?);
+    }
+    for path in &changes.requests {
+        if let Ok(request) = read_request_on_disk(path).await {
+            update.requests.push(request);
+        }
+    }
+    for path in &changes.files {
+        update.diagnostics.extend(lint_file(root, path).await?);
+    }
+    update.linted = changes.files.clone();
+    Ok(update)
+}
+
+fn is_read(kind: &EventKind) -> bool {
+    let written = AccessKind::Close(AccessMode::Write);
+    matches!(kind, EventKind::Access(access) if *access != written)
+}
+
+/// Watches the directories of collections, the paths changed under them are
+/// sent to the receiver returned by [`CollectionWatcher::new`]
+pub struct CollectionWatcher {
+    watcher: RecommendedWatcher,
+}
+
+impl CollectionWatcher {
+    pub fn new() -> anyhow::Result<(Self, mpsc::UnboundedReceiver<PathBuf>)> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            match event {
+                // Reading files is not a change, closing a written one is
+                Ok(event) if is_read(&event.kind) => (),
+                Ok(event) => {
+                    for path in event.paths {
+                        let _ = sender.send(path);
+                    }
+                }
+                Err(e) => log::error!("Error watching collections: {:?}", e),
+            }
+        })?;
+        Ok((Self { watcher }, receiver))
+    }
+
+    /// Watches the directory of the collection at `root` and its subdirectories
+    pub fn watch(&mut self, root: &Path) -> anyhow::Result<()> {
+        self.watcher.watch(root, RecursiveMode::Recursive)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn reads_the_collection_file_once_it_parses() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        let file = root.join(COLLECTION_ROOT_FILE);
+        std::fs::write(&file, "name = \"col\"\nvariables = [\n").unwrap();
+        let seen = snapshot(root).await.unwrap();
+
+        std::fs::write(&file, "name = \"api\"\nversion = \"V1\"\n").unwrap();
+        let changes = seen.changes(&snapshot(root).await.unwrap(), root);
+        assert!(changes.collection);
+        assert_eq!(changes.files, std::slice::from_ref(&file));
+
+        let update = read_update(root, &changes).await.unwrap();
+        assert_eq!(update.collection.map(|c| c.name).as_deref(), Some("api"));
+        assert!(update.diagnostics.is_empty());
+        assert_eq!(update.linted, [file]);
+    }
+}
@@ -0,0 +1,287 @@
+use std::fmt::Write;
+
+use regex::Regex;
+
+pub const HEX_BYTES_PER_LINE: usize = 16;
+
+/// Renders `len` bytes starting at `offset` as a classic hex dump with an
+/// offset column, hex bytes and an ASCII gutter.
+pub fn hex_dump(data: &[u8], offset: usize, len: usize) -> String {
+    let start = offset.min(data.len());
+    let end = offset.saturating_add(len).min(data.len());
+
+    let mut out = String::new();
+    for (idx, chunk) in data[start..end].chunks(HEX_BYTES_PER_LINE).enumerate() {
+        let line_offset = start + idx * HEX_BYTES_PER_LINE;
+        let _ = write!(out, "{:08x}  ", line_offset);
+
+        for i in 0..HEX_BYTES_PER_LINE {
+            match chunk.get(i) {
+                Some(byte) => {
+                    let _ = write!(out, "{:02x} ", byte);
+                }
+                None => out.push_str("   "),
+            }
+            if i == HEX_BYTES_PER_LINE / 2 - 1 {
+                out.push(' ');
+            }
+        }
+
+        out.push_str(" |");
+        for byte in chunk {
+            let c = if byte.is_ascii_graphic() || *byte == b' ' {
+                *byte as char
+            } else {
+                '.'
+            };
+            out.push(c);
+        }
+        out.push_str("|\n");
+    }
+
+    out
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PdfInfo {
+    pub version: Option<String>,
+    pub pages: usize,
+    pub encrypted: bool,
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub subject: Option<String>,
+    pub creator: Option<String>,
+    pub producer: Option<String>,
+    pub created: Option<String>,
+    pub modified: Option<String>,
+}
+
+impl PdfInfo {
+    pub fn entries(&self) -> Vec<(&'static str, String)> {
+        let mut entries = vec![
+            ("Version", self.version.clone().unwrap_or_default()),
+            ("Pages", self.pages.to_string()),
+            ("Encrypted", self.encrypted.to_string()),
+        ];
+
+        let optional = [
+            ("Title", &self.title),
+            ("Author", &self.author),
+            ("Subject", &self.subject),
+            ("Creator", &self.creator),
+            ("Producer", &self.producer),
+            ("Created", &self.created),
+            ("Modified", &self.modified),
+        ];
+        for (name, value) in optional {
+            if let Some(value) = value {
+                entries.push((name, value.clone()));
+            }
+        }
+
+        entries
+    }
+}
+
+/// Extracts basic metadata from a PDF document without fully parsing it.
+/// Compressed object streams are not inflated, so values stored only inside
+/// them are reported as missing.
+pub fn pdf_info(data: &[u8]) -> PdfInfo {
+    let text = String::from_utf8_lossy(data);
+
+    let version = Regex::new(r"^%PDF-(\d+\.\d+)")
+        .unwrap()
+        .captures(&text)
+        .map(|cap| cap[1].to_string());
+
+    let pages = Regex::new(r"/Type\s*/Page\b")
+        .unwrap()
+        .find_iter(&text)
+        .count();
+
+    let info_entry = |key: &str| {
+        let pattern = format!(r"/{}\s*\(((?:[^()\\]|\\.)*)\)", key);
+        Regex::new(&pattern)
+            .ok()?
+            .captures(&text)
+            .map(|cap| unescape_pdf_string(&cap[1]))
+            .filter(|v| !v.is_empty())
+    };
+
+    PdfInfo {
+        version,
+        pages,
+        encrypted: text.contains("/Encrypt"),
+        title: info_entry("Title"),
+        author: info_entry("Author"),
+        subject: info_entry("Subject"),
+        creator: info_entry("Creator"),
+        producer: info_entry("Producer"),
+        created: info_entry("CreationDate"),
+        modified: info_entry("ModDate"),
+    }
+}
+
+fn unescape_pdf_string(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some('t') => out.push('\t'),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out
+}
+
+/// Produces a readable plain text rendition of an HTML document by dropping
+/// scripts, styles and tags and keeping block level line breaks.
+pub fn html_to_text(html: &str) -> String {
+    let hidden =
+        Regex::new(r"(?is)<(script|style|head|noscript)\b.*?</(script|style|head|noscript)\s*>")
+            .unwrap();
+    let comments = Regex::new(r"(?s)<!--.*?-->").unwrap();
+    let breaks =
+        Regex::new(r"(?i)<br\s*/?>|</(p|div|tr|h[1-6]|section|article|header|footer|pre)\s*>")
+            .unwrap();
+    let list_items = Regex::new(r"(?i)<li\b[^>]*>").unwrap();
+    let tags = Regex::new(r"(?s)<[^>]*>").unwrap();
+
+    let text = hidden.replace_all(html, "");
+    let text = comments.replace_all(&text, "");
+    let text = breaks.replace_all(&text, "\n");
+    let text = list_items.replace_all(&text, "\n• ");
+    let text = tags.replace_all(&text, "");
+    let text = decode_entities(&text);
+
+    let mut out = String::new();
+    let mut blank = false;
+    for line in text.lines() {
+        let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+        if line.is_empty() {
+            if !blank && !out.is_empty() {
+                out.push('\n');
+            }
+            blank = true;
+            continue;
+        }
+        blank = false;
+        out.push_str(&line);
+        out.push('\n');
+    }
+
+    out.trim_end().to_string()
+}
+
+fn decode_entities(text: &str) -> String {
+    let entities = Regex::new(r"&(#x[0-9a-fA-F]+|#[0-9]+|[a-zA-Z]+);").unwrap();
+    entities
+        .replace_all(text, |cap: &regex::Captures| -> String {
+            let entity = &cap[1];
+            let decoded = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                "copy" => Some('©'),
+                "reg" => Some('®'),
+                "hellip" => Some('…'),
+                "mdash" => Some('—'),
+                "ndash" => Some('–'),
+                _ if entity.starts_with("#x") => u32::from_str_radix(&entity[2..], 16)
+                    .ok()
+                    .and_then(char::from_u32),
+                _ if entity.starts_with('#') => {
+                    entity[1..].parse::<u32>().ok().and_then(char::from_u32)
+                }
+                _ => None,
+            };
+            decoded
+                .map(String::from)
+                .unwrap_or_else(|| cap[0].to_string())
+        })
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dumps_a_window_of_the_bytes() {
+        let data = (0..=40u8).collect::<Vec<_>>();
+
+        let dump = hex_dump(&data, 16, 20);
+        assert_eq!(
+            dump,
+            "00000010  10 11 12 13 14 15 16 17  18 19 1a 1b 1c 1d 1e 1f  |................|\n\
+             00000020  20 21 22 23                                       | !\"#|\n"
+        );
+
+        // The window is cut at the end of the data
+        assert_eq!(hex_dump(&data, 40, 100).lines().count(), 1);
+        assert!(hex_dump(&data, 100, 16).is_empty());
+    }
+
+    #[test]
+    fn reads_pdf_metadata() {
+        let pdf = b"%PDF-1.7\n\
+            1 0 obj << /Type /Pages /Kids [2 0 R 3 0 R] >> endobj\n\
+            2 0 obj << /Type /Page >> endobj\n\
+            3 0 obj << /Type/Page >> endobj\n\
+            4 0 obj << /Title (Report \\(draft\\)) /Author () /Producer (Writer) >> endobj\n";
+
+        let info = pdf_info(pdf);
+        assert_eq!(info.version.as_deref(), Some("1.7"));
+        assert_eq!(info.pages, 2);
+        assert!(!info.encrypted);
+        assert_eq!(info.title.as_deref(), Some("Report (draft)"));
+        // Empty values are reported as missing
+        assert_eq!(info.author, None);
+        assert_eq!(info.producer.as_deref(), Some("Writer"));
+    }
+
+    #[test]
+    fn reports_nothing_for_other_documents() {
+        assert_eq!(pdf_info(b"<html>%PDF-1.4</html>"), PdfInfo::default());
+        assert_eq!(
+            pdf_info(&[0xff, 0xd8, 0xff, 0x00, 0x28]),
+            PdfInfo::default()
+        );
+
+        // Cut off in the middle of a value
+        let info = pdf_info(b"%PDF-2.0\n<< /Encrypt 5 0 R /Title (Unfinished");
+        assert_eq!(info.version.as_deref(), Some("2.0"));
+        assert!(info.encrypted);
+        assert_eq!(info.title, None);
+    }
+
+    #[test]
+    fn strips_scripts_styles_and_tags() {
+        let html = r#"<html>
+<head><title>Ignored</title><style>p { color: red; }</style></head>
+<body>
+  <script type="text/javascript">alert("<p>hidden</p>");</script>
+  <!-- comment -->
+  <h1>Title &amp; more</h1>
+  <p>First   line<br/>second &lt;line&gt;</p>
+  <ul><li>One</li><li>Two&#x21;</li></ul>
+  <SCRIPT>var x = 1;</SCRIPT>
+</body>
+</html>"#;
+
+        assert_eq!(
+            html_to_text(html),
+            "Title & more\n\nFirst line\nsecond <line>\n\n• One\n• Two!"
+        );
+    }
+}
@@ -0,0 +1,210 @@
+use chrono::{DateTime, Local};
+
+pub const DEFAULT_EVENT: &str = "message";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SseEvent {
+    pub event: String,
+    pub id: Option<String>,
+    pub data: String,
+    pub retry: Option<u64>,
+    pub received: DateTime<Local>,
+}
+
+/// Incremental parser for `text/event-stream` bodies as described in the
+/// HTML living standard. Bytes can be fed in arbitrary chunks, complete
+/// events are returned as soon as their terminating blank line is seen.
+#[derive(Debug, Default)]
+pub struct SseParser {
+    buffer: Vec<u8>,
+    event: String,
+    data: String,
+    retry: Option<u64>,
+    last_id: Option<String>,
+    skip_lf: bool,
+}
+
+impl SseParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        let mut events = Vec::new();
+
+        for &byte in chunk {
+            if self.skip_lf {
+                self.skip_lf = false;
+                if byte == b'\n' {
+                    continue;
+                }
+            }
+
+            match byte {
+                b'\r' | b'\n' => {
+                    self.skip_lf = byte == b'\r';
+                    let line = std::mem::take(&mut self.buffer);
+                    let line = String::from_utf8_lossy(&line);
+                    if let Some(event) = self.process_line(&line) {
+                        events.push(event);
+                    }
+                }
+                _ => self.buffer.push(byte),
+            }
+        }
+
+        events
+    }
+
+    /// Ends the stream. An event without its terminating blank line is
+    /// incomplete and is discarded rather than dispatched.
+    pub fn finish(&mut self) -> Option<SseEvent> {
+        self.buffer.clear();
+        self.skip_lf = false;
+        self.event.clear();
+        self.data.clear();
+        self.retry = None;
+        None
+    }
+
+    fn process_line(&mut self, line: &str) -> Option<SseEvent> {
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+
+        match field {
+            "event" => self.event = value.to_string(),
+            "data" => {
+                self.data.push_str(value);
+                self.data.push('\n');
+            }
+            // An empty id resets the last event id
+            "id" if !value.contains('\0') => {
+                self.last_id = (!value.is_empty()).then(|| value.to_string());
+            }
+            "retry" if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) => {
+                self.retry = value.parse().ok();
+            }
+            _ => {}
+        }
+
+        None
+    }
+
+    fn dispatch(&mut self) -> Option<SseEvent> {
+        let event = std::mem::take(&mut self.event);
+        let mut data = std::mem::take(&mut self.data);
+        let retry = self.retry.take();
+
+        if data.is_empty() {
+            return None;
+        }
+        if data.ends_with('\n') {
+            data.pop();
+        }
+
+        Some(SseEvent {
+            event: if event.is_empty() {
+                DEFAULT_EVENT.to_string()
+            } else {
+                event
+            },
+            id: self.last_id.clone(),
+            data,
+            retry,
+            received: Local::now(),
+        })
+    }
+}
+
+pub fn is_event_stream(content_type: Option<&str>) -> bool {
+    content_type
+        .and_then(|ct| ct.split(';').next())
+        .is_some_and(|ct| ct.trim().eq_ignore_ascii_case("text/event-stream"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(chunks: &[&str]) -> Vec<SseEvent> {
+        let mut parser = SseParser::new();
+        let mut events = chunks
+            .iter()
+            .flat_map(|chunk| parser.feed(chunk.as_bytes()))
+            .collect::<Vec<_>>();
+        events.extend(parser.finish());
+        events
+    }
+
+    fn fields(events: &[SseEvent]) -> Vec<(&str, Option<&str>, &str, Option<u64>)> {
+        events
+            .iter()
+            .map(|e| (e.event.as_str(), e.id.as_deref(), e.data.as_str(), e.retry))
+            .collect()
+    }
+
+    #[test]
+    fn joins_multi_line_data() {
+        let events = parse(&["event: update\ndata: first\ndata:second\ndata\n\n"]);
+        assert_eq!(fields(&events), [("update", None, "first\nsecond\n", None)]);
+    }
+
+    #[test]
+    fn splits_lines_on_any_line_ending() {
+        // A CRLF split across chunks is a single line ending
+        let events = parse(&["data: a\r", "\ndata: b\r\r", "data: c\rid: 1\r\n\r\n"]);
+        assert_eq!(
+            fields(&events),
+            [
+                (DEFAULT_EVENT, None, "a\nb", None),
+                (DEFAULT_EVENT, Some("1"), "c", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_comments_and_invalid_retries() {
+        let events = parse(&[
+            ": keep-alive\n\n",
+            "retry: soon\ndata: a\n\n",
+            "retry: 1500\n:comment\ndata: b\n\n",
+            "retry: +15\ndata: c\n\n",
+        ]);
+        assert_eq!(
+            fields(&events),
+            [
+                (DEFAULT_EVENT, None, "a", None),
+                (DEFAULT_EVENT, None, "b", Some(1500)),
+                (DEFAULT_EVENT, None, "c", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_the_last_event_id_until_reset() {
+        let events = parse(&[
+            "id: 7\ndata: a\n\n",
+            "data: b\n\n",
+            "id\ndata: c\n\n",
+            // Never dispatched, the stream ends before its blank line
+            "id: 8\nid:\ndata: d",
+        ]);
+        assert_eq!(
+            fields(&events),
+            [
+                (DEFAULT_EVENT, Some("7"), "a", None),
+                (DEFAULT_EVENT, Some("7"), "b", None),
+                (DEFAULT_EVENT, None, "c", None),
+            ]
+        );
+    }
+}
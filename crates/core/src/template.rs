@@ -0,0 +1,288 @@
+use std::sync::LazyLock;
+
+use anyhow::Context;
+use chrono::{SecondsFormat, Utc};
+use regex::Regex;
+
+use crate::http::environment::Environment;
+
+/// `{{name}}` reference to a variable, names can contain `_`, `-` and `.`
+/// and built-ins start with `$`
+pub const VARIABLE_PATTERN: &str = r"\{\{\s*(\$?[a-zA-Z0-9_.\-]+)\s*\}\}";
+
+static VARIABLES: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(VARIABLE_PATTERN).expect("variable pattern"));
+
+/// Built-ins evaluated again for every reference
+pub const BUILTINS: [&str; 4] = ["$uuid", "$timestamp", "$isoTimestamp", "$randomInt"];
+
+/// Prefix of the built-in reading a process environment variable,
+/// `{{$env.HOME}}`
+pub const ENV_PREFIX: &str = "$env.";
+
+/// What is done with references which are not replaced by a value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// Missing variables are an error
+    Fail,
+    /// Missing variables are left as they are
+    Keep,
+    /// Missing variables and built-ins are left as they are
+    KeepBuiltins,
+}
+
+/// Replaces the variables of the template with their values from the
+/// environment. Values can reference other variables, a missing variable
+/// or a cycle between variables is an error.
+pub fn render(template: &str, env: Option<&Environment>) -> anyhow::Result<String> {
+    render_with(template, env, &mut Vec::new(), Mode::Fail)
+}
+
+/// Like [`render`] but variables which cannot be resolved are left as they
+/// are, for text shown or exported rather than sent. Without an environment
+/// the template is kept as it is, built-ins included.
+pub fn render_lenient(template: &str, env: Option<&Environment>) -> String {
+    if env.is_none() {
+        return template.to_string();
+    }
+    render_with(template, env, &mut Vec::new(), Mode::Keep).unwrap_or_else(|_| template.to_string())
+}
+
+/// Like [`render_lenient`] but built-ins are kept as they are too, for text
+/// which has to be the same every time it is rendered
+pub fn render_variables(template: &str, env: Option<&Environment>) -> String {
+    if env.is_none() {
+        return template.to_string();
+    }
+    render_with(template, env, &mut Vec::new(), Mode::KeepBuiltins)
+        .unwrap_or_else(|_| template.to_string())
+}
+
+/// Whether the name, without braces, is one of the built-ins
+pub fn is_builtin(name: &str) -> bool {
+    BUILTINS.contains(&name) || name.starts_with(ENV_PREFIX)
+}
+
+fn render_with(
+    template: &str,
+    env: Option<&Environment>,
+    stack: &mut Vec<String>,
+    mode: Mode,
+) -> anyhow::Result<String> {
+    let mut rendered = String::with_capacity(template.len());
+    let mut last = 0;
+    for cap in VARIABLES.captures_iter(template) {
+        let matched = cap.get(0).expect("whole match");
+        rendered.push_str(&template[last..matched.start()]);
+        last = matched.end();
+
+        if mode == Mode::KeepBuiltins && cap[1].starts_with('$') {
+            rendered.push_str(matched.as_str());
+            continue;
+        }
+        match resolve(&cap[1], env, stack, mode) {
+            Ok(value) => rendered.push_str(&value),
+            Err(_) if mode != Mode::Fail => rendered.push_str(matched.as_str()),
+            Err(e) => return Err(e),
+        }
+    }
+    rendered.push_str(&template[last..]);
+
+    Ok(rendered)
+}
+
+fn resolve(
+    name: &str,
+    env: Option<&Environment>,
+    stack: &mut Vec<String>,
+    mode: Mode,
+) -> anyhow::Result<String> {
+    if name.starts_with('$') {
+        return builtin(name);
+    }
+
+    let value = env
+        .and_then(|env| env.get(name))
+        .with_context(|| match env {
+            Some(env) => format!(
+                "Variable `{}` is not defined in environment `{}`",
+                name, env.name
+            ),
+            None => format!("Variable `{}` is used without an environment", name),
+        })?;
+
+    if let Some(env) = env.filter(|env| value.is_empty() && env.is_secret(name)) {
+        anyhow::bail!(
+            "Secret `{}` of environment `{}` has no value, unlock the secret store or provide it",
+            name,
+            env.name
+        );
+    }
+
+    if stack.iter().any(|seen| seen == name) {
+        let chain = stack.join(" -> ");
+        anyhow::bail!("Variables reference each other: {} -> {}", chain, name);
+    }
+
+    stack.push(name.to_string());
+    let value = render_with(value, env, stack, mode);
+    stack.pop();
+    value
+}
+
+fn builtin(name: &str) -> anyhow::Result<String> {
+    if let Some(var) = name.strip_prefix(ENV_PREFIX) {
+        return std::env::var(var).with_context(|| {
+            format!(
+                "Environment variable `{}` of `{{{{{}}}}}` is not set",
+                var, name
+            )
+        });
+    }
+
+    let value = match name {
+        "$uuid" => uuid_v4(),
+        "$timestamp" => Utc::now().timestamp().to_string(),
+        "$isoTimestamp" => Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+        "$randomInt" => fastrand::u32(0..=1000).to_string(),
+        _ => anyhow::bail!("Unknown built-in variable `{{{{{}}}}}`", name),
+    };
+    Ok(value)
+}
+
+fn uuid_v4() -> String {
+    let mut bytes = [0u8; 16];
+    bytes.iter_mut().for_each(|byte| *byte = fastrand::u8(..));
+    // Version 4, RFC 4122 variant
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex = bytes
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::{KeyValList, KeyValue};
+
+    fn env(vars: &[(&str, &str)]) -> Environment {
+        let mut env = Environment::new("dev".to_string());
+        env.variables = KeyValList::from(
+            vars.iter()
+                .map(|(name, value)| KeyValue {
+                    disabled: false,
+                    name: name.to_string(),
+                    value: value.to_string(),
+                })
+                .collect(),
+        );
+        env
+    }
+
+    #[test]
+    fn renders_nested_variables() {
+        let env = env(&[
+            ("host", "example.com"),
+            ("base_url", "https://{{ host }}/v1"),
+            ("api.key", "k-1"),
+        ]);
+        let rendered = render("{{base_url}}/users?key={{api.key}}", Some(&env)).unwrap();
+        assert_eq!(rendered, "https://example.com/v1/users?key=k-1");
+    }
+
+    #[test]
+    fn allows_whitespace_inside_braces() {
+        let env = env(&[("name", "value")]);
+        for template in ["{{name}}", "{{ name }}", "{{  name\t}}"] {
+            assert_eq!(render(template, Some(&env)).unwrap(), "value");
+        }
+        // Not a variable reference
+        assert_eq!(
+            render("{{ two words }}", Some(&env)).unwrap(),
+            "{{ two words }}"
+        );
+    }
+
+    #[test]
+    fn detects_cycles() {
+        let env = env(&[
+            ("a", "{{b}}"),
+            ("b", "x{{c}}"),
+            ("c", "{{a}}"),
+            ("d", "{{d}}"),
+        ]);
+
+        let err = render("{{a}}", Some(&env)).unwrap_err().to_string();
+        assert_eq!(err, "Variables reference each other: a -> b -> c -> a");
+        assert!(render("{{d}}", Some(&env)).is_err());
+
+        // The same variable twice is not a cycle
+        let env = self::env(&[("a", "1"), ("b", "{{a}}{{a}}")]);
+        assert_eq!(render("{{b}}", Some(&env)).unwrap(), "11");
+    }
+
+    #[test]
+    fn missing_variables_fail_only_when_strict() {
+        let env = env(&[("known", "1")]);
+
+        let err = render("{{known}}/{{unknown}}", Some(&env)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Variable `unknown` is not defined in environment `dev`"
+        );
+        assert!(render("{{known}}", None).is_err());
+
+        let rendered = render_lenient("{{known}}/{{unknown}}", Some(&env));
+        assert_eq!(rendered, "1/{{unknown}}");
+        assert_eq!(render_lenient("{{known}}", None), "{{known}}");
+
+        // Cycles are kept as they are too
+        let env = self::env(&[("a", "{{a}}")]);
+        assert_eq!(render_lenient("x{{a}}", Some(&env)), "x{{a}}");
+    }
+
+    #[test]
+    fn keeps_builtins_when_rendering_only_variables() {
+        let env = env(&[("id", "{{$uuid}}"), ("v", "2")]);
+
+        let rendered = render_variables("/v{{v}}/{{ $randomInt }}/{{id}}", Some(&env));
+        assert_eq!(rendered, "/v2/{{ $randomInt }}/{{$uuid}}");
+    }
+
+    #[test]
+    fn renders_builtins() {
+        let uuid = render("{{$uuid}}", None).unwrap();
+        assert_eq!(uuid.len(), 36);
+        assert_eq!(&uuid[14..15], "4");
+        assert_ne!(uuid, render("{{$uuid}}", None).unwrap());
+
+        let timestamp = render("{{$timestamp}}", None).unwrap();
+        assert!(timestamp.parse::<i64>().unwrap() > 1_600_000_000);
+
+        let iso = render("{{ $isoTimestamp }}", None).unwrap();
+        assert!(chrono::DateTime::parse_from_rfc3339(&iso).is_ok());
+
+        let int = render("{{$randomInt}}", None).unwrap();
+        assert!(int.parse::<u32>().unwrap() <= 1000);
+
+        // Setting variables races with tests reading them in other threads
+        let path = std::env::var("PATH").unwrap();
+        assert_eq!(render("{{$env.PATH}}", None).unwrap(), path);
+        assert!(render("{{$env.SANCHAAR_TEMPLATE_UNSET}}", None).is_err());
+
+        assert!(render("{{$unknown}}", None).is_err());
+        assert!(is_builtin("$uuid") && is_builtin("$env.HOME"));
+        assert!(!is_builtin("uuid"));
+    }
+}
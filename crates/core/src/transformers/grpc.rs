@@ -0,0 +1,66 @@
+use anyhow::Context;
+use prost_reflect::{DescriptorPool, DynamicMessage};
+use tonic::metadata::{AsciiMetadataKey, AsciiMetadataValue, MetadataMap};
+
+use crate::grpc::GrpcCall;
+use crate::http::{environment::Environment, grpc::GrpcRequest};
+
+use crate::template::render;
+
+use super::request::param_enabled;
+
+pub fn transform_grpc(
+    req: GrpcRequest,
+    pool: &DescriptorPool,
+    env: Option<Environment>,
+) -> anyhow::Result<GrpcCall> {
+    let GrpcRequest {
+        url,
+        service,
+        method,
+        message,
+        metadata,
+        ..
+    } = req;
+
+    let env = env.as_ref();
+
+    let service = pool
+        .get_service_by_name(&service)
+        .with_context(|| format!("Unknown gRPC service '{}'", service))?;
+    let method = service
+        .methods()
+        .find(|m| m.name() == method)
+        .with_context(|| format!("Unknown method '{}' in '{}'", method, service.full_name()))?;
+    if method.is_client_streaming() {
+        anyhow::bail!("Client streaming methods are not supported");
+    }
+
+    let message = render(&message, env)?;
+    let mut deserializer = serde_json::Deserializer::from_str(&message);
+    let message = DynamicMessage::deserialize(method.input(), &mut deserializer)
+        .context("Invalid gRPC message")?;
+    deserializer.end().context("Invalid gRPC message")?;
+
+    let mut map = MetadataMap::new();
+    for entry in metadata.into_iter().filter(param_enabled) {
+        let key = AsciiMetadataKey::from_bytes(entry.name.to_lowercase().as_bytes())
+            .with_context(|| format!("Invalid metadata key '{}'", entry.name))?;
+        let value = AsciiMetadataValue::try_from(render(&entry.value, env)?)
+            .with_context(|| format!("Invalid metadata value for '{}'", entry.name))?;
+        map.append(key, value);
+    }
+
+    Ok(GrpcCall {
+        url: render(&url, env)?,
+        method,
+        message,
+        metadata: map,
+    })
+}
+
+/// Server URL with environment variables replaced, needed to load
+/// descriptors through reflection before the call can be built.
+pub fn transform_url(url: &str, env: Option<&Environment>) -> anyhow::Result<String> {
+    render(url, env)
+}
@@ -1,2 +1,4 @@
+pub mod grpc;
 pub mod request;
+pub mod websocket;
 // pub mod script;
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::Context;
 use mime::{APPLICATION_JSON, TEXT_PLAIN, TEXT_XML};
@@ -9,30 +9,71 @@ use reqwest::RequestBuilder;
 use reqwest::{header::CONTENT_TYPE, multipart::Form};
 use tokio::fs::File;
 
+use crate::graphql;
 use crate::http::{
     environment::Environment,
     request::{Auth, Method, Request, RequestBody},
     KeyFileList, KeyValList, KeyValue,
 };
+use crate::template::{render, render_lenient};
+use crate::utils::percent_encode;
 
-fn param_enabled(param: &KeyValue) -> bool {
+pub(crate) fn param_enabled(param: &KeyValue) -> bool {
     !param.disabled && !param.name.is_empty()
 }
 
-fn enabled_params(params: KeyValList, env: Option<&Environment>) -> Vec<(String, String)> {
+pub(crate) fn enabled_params(
+    params: KeyValList,
+    env: Option<&Environment>,
+) -> anyhow::Result<Vec<(String, String)>> {
     params
         .into_iter()
         .filter(param_enabled)
-        .map(|param| (param.name, replace_env_vars(&param.value, env)))
+        .map(|param| Ok((param.name, render(&param.value, env)?)))
         .collect()
 }
 
-fn enabled_files(files: KeyFileList, _env: Option<&Environment>) -> Vec<(String, PathBuf)> {
-    files
+/// Renders the `{{vars}}` of a file path, relative paths are resolved
+/// against the collection root
+fn resolve_path(path: &Path, root: &Path, env: Option<&Environment>) -> anyhow::Result<PathBuf> {
+    let path = render(&path.to_string_lossy(), env)?;
+    Ok(root.join(path))
+}
+
+/// Resolves the files of the request and fails with all of the missing ones
+/// at once, before anything is opened
+async fn check_files(
+    files: Vec<(String, PathBuf)>,
+    root: &Path,
+    env: Option<&Environment>,
+) -> anyhow::Result<Vec<(String, PathBuf)>> {
+    let mut resolved = Vec::new();
+    let mut missing = Vec::new();
+    for (name, path) in files {
+        let path = resolve_path(&path, root, env)?;
+        match tokio::fs::metadata(&path).await {
+            Ok(meta) if meta.is_file() => resolved.push((name, path)),
+            _ => missing.push(path.display().to_string()),
+        }
+    }
+
+    if !missing.is_empty() {
+        anyhow::bail!("Files not found: {}", missing.join(", "));
+    }
+    Ok(resolved)
+}
+
+async fn enabled_files(
+    files: KeyFileList,
+    root: &Path,
+    env: Option<&Environment>,
+) -> anyhow::Result<Vec<(String, PathBuf)>> {
+    let files = files
         .into_iter()
-        .filter(|file| !file.name.is_empty())
+        .filter(|file| !file.disabled && !file.name.is_empty())
         .filter_map(|file| file.path.map(|path| (file.name, path)))
-        .collect()
+        .collect();
+    check_files(files, root, env).await
 }
 
 fn req_method(method: Method) -> reqwest::Method {
@@ -53,26 +94,29 @@ fn req_headers(
     mut builder: RequestBuilder,
     headers: KeyValList,
     env: Option<&Environment>,
-) -> RequestBuilder {
+) -> anyhow::Result<RequestBuilder> {
     let iter = headers.into_iter().filter(param_enabled);
     for header in iter {
-        builder = builder.header(header.name, replace_env_vars(&header.value, env));
+        builder = builder.header(header.name, render(&header.value, env)?);
     }
-    builder
+    Ok(builder)
 }
 
 fn req_params(
     builder: RequestBuilder,
     params: KeyValList,
     env: Option<&Environment>,
-) -> RequestBuilder {
-    let params = enabled_params(params, env);
-    builder.query(&params)
+) -> anyhow::Result<RequestBuilder> {
+    let params = enabled_params(params, env)?;
+    Ok(builder.query(&params))
 }
+/// Builds the request to send, files are looked up relative to the
+/// collection `root`
 pub async fn transform_request(
     client: reqwest::Client,
     req: Request,
     env: Option<Environment>,
+    root: PathBuf,
 ) -> anyhow::Result<reqwest::Request> {
     let Request {
         method,
@@ -87,78 +131,122 @@ pub async fn transform_request(
 
     let env = env.as_ref();
 
-    let url = replace_path_params(url, path_params, env);
+    let url = replace_path_params(&render(&url, env)?, &path_params, |value| {
+        render(value, env)
+    })?;
     let mut builder = client.request(req_method(method), url);
 
-    builder = req_headers(builder, headers, env);
-    builder = req_params(builder, query_params, env);
-    builder = req_auth(builder, auth, env);
-    builder = req_body(builder, body, env).await;
+    builder = req_headers(builder, headers, env)?;
+    builder = req_params(builder, query_params, env)?;
+    builder = req_auth(builder, auth, env)?;
+    builder = req_body(builder, body, &root, env).await?;
 
     builder.build().context("Failed to build request")
 }
 
-fn replace_env_vars(source: &str, env: Option<&Environment>) -> String {
-    let Some(env) = env else {
-        return source.to_string();
-    };
-    let replaced = Regex::new(r"\{\{([a-zA-Z0-9]+)\}\}").unwrap().replace_all(
-        source,
-        |cap: &regex::Captures| -> String {
-            let name = &cap[1];
-            env.get(name).unwrap_or(name).to_string()
-        },
-    );
-    replaced.to_string()
-}
-
-fn replace_path_params(url: String, params: KeyValList, env: Option<&Environment>) -> String {
-    let url = replace_env_vars(&url, env);
-    let replaced = Regex::new(r":([a-zA-Z0-9]+)").unwrap().replace_all(
-        &url,
-        |cap: &regex::Captures| -> String {
-            let name = &cap[1];
-            let value = params
-                .iter()
-                .find(|param| param.name == name)
-                .map(|param| replace_env_vars(&param.value, env))
-                .unwrap_or_else(|| name.to_owned());
-            value
-        },
-    );
-    replaced.to_string()
+/// Puts the rendered values of the path params in place of their `:name`,
+/// the variables of the URL are expected to be rendered already so they can
+/// contain path params too
+fn replace_path_params(
+    url: &str,
+    params: &KeyValList,
+    render: impl Fn(&str) -> anyhow::Result<String>,
+) -> anyhow::Result<String> {
+    let mut replaced = String::with_capacity(url.len());
+    let mut last = 0;
+    for cap in Regex::new(r":([a-zA-Z0-9]+)").unwrap().captures_iter(url) {
+        let matched = cap.get(0).expect("whole match");
+        replaced.push_str(&url[last..matched.start()]);
+        last = matched.end();
+
+        match params.iter().find(|param| param.name == cap[1]) {
+            Some(param) => replaced.push_str(&render(&param.value)?),
+            // Not a path param, e.g. the port of the host
+            None => replaced.push_str(matched.as_str()),
+        }
+    }
+    replaced.push_str(&url[last..]);
+
+    Ok(replaced)
+}
+
+/// URL with path params and enabled query params applied, for requests
+/// exported outside of the client
+pub(crate) fn full_url(
+    url: String,
+    path_params: KeyValList,
+    query_params: KeyValList,
+    env: Option<&Environment>,
+) -> String {
+    let url = render_lenient(&url, env);
+    let mut url = replace_path_params(&url, &path_params, |value| Ok(render_lenient(value, env)))
+        .unwrap_or(url);
+    let query = query_params
+        .into_iter()
+        .filter(param_enabled)
+        .map(|param| {
+            let value = render_lenient(&param.value, env);
+            format!("{}={}", percent_encode(&param.name), percent_encode(&value))
+        })
+        .collect::<Vec<_>>();
+    if !query.is_empty() {
+        url.push(if url.contains('?') { '&' } else { '?' });
+        url.push_str(&query.join("&"));
+    }
+    url
 }
 
 async fn req_body(
     builder: RequestBuilder,
     body: RequestBody,
+    root: &Path,
     env: Option<&Environment>,
-) -> RequestBuilder {
+) -> anyhow::Result<RequestBuilder> {
     let body_header = |builder: RequestBuilder, data, content_type: Mime| {
-        builder
-            .body(replace_env_vars(data, env))
-            .header(CONTENT_TYPE, content_type.as_ref())
+        anyhow::Ok(
+            builder
+                .body(render(data, env)?)
+                .header(CONTENT_TYPE, content_type.as_ref()),
+        )
     };
 
-    match body {
-        RequestBody::Text(text) => body_header(builder, &text, TEXT_PLAIN),
-        RequestBody::Json(json) => body_header(builder, &json, APPLICATION_JSON),
-        RequestBody::XML(xml) => body_header(builder, &xml, TEXT_XML),
-        RequestBody::Form(form) => builder.form(&enabled_params(form, env)),
-        RequestBody::File(Some(file)) => file_body(file, builder).await,
+    let builder = match body {
+        RequestBody::Text(text) => body_header(builder, &text, TEXT_PLAIN)?,
+        RequestBody::Json(json) => body_header(builder, &json, APPLICATION_JSON)?,
+        RequestBody::GraphQL {
+            query,
+            variables,
+            operation_name,
+        } => {
+            let variables = render(&variables, env)?;
+            let payload = graphql::payload(&query, &variables, operation_name.as_deref())?;
+            builder
+                .body(payload.to_string())
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+        }
+        RequestBody::XML(xml) => body_header(builder, &xml, TEXT_XML)?,
+        RequestBody::Form(form) => builder.form(&enabled_params(form, env)?),
+        RequestBody::File(Some(file)) => {
+            let mut files = check_files(vec![(String::new(), file)], root, env).await?;
+            file_body(files.remove(0).1, builder).await?
+        }
         RequestBody::None | RequestBody::File(None) => builder,
-        RequestBody::Multipart { params, files } => multipart(builder, params, files, env).await,
-    }
+        RequestBody::Multipart { params, files } => {
+            multipart(builder, params, files, root, env).await?
+        }
+    };
+    Ok(builder)
 }
 
 async fn multipart(
     builder: RequestBuilder,
     params: KeyValList,
     files: KeyFileList,
+    root: &Path,
     env: Option<&Environment>,
-) -> RequestBuilder {
-    let params = enabled_params(params, env);
-    let files = enabled_files(files, env);
+) -> anyhow::Result<RequestBuilder> {
+    let params = enabled_params(params, env)?;
+    let files = enabled_files(files, root, env).await?;
     let mut form = Form::new();
 
     for (name, value) in params {
@@ -166,27 +254,29 @@ async fn multipart(
     }
 
     for (name, path) in files {
-        let (content_type, file) = open_file(&path).await;
-        let filename = path.file_name().unwrap().to_string_lossy().to_string();
+        let (content_type, file) = open_file(&path).await?;
+        let filename = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
 
         let part = Part::stream(file)
             .file_name(filename)
-            .mime_str(&content_type)
-            .unwrap();
+            .mime_str(&content_type)?;
 
         form = form.part(name, part);
     }
 
-    builder.multipart(form)
+    Ok(builder.multipart(form))
 }
 
-async fn file_body(file: PathBuf, builder: RequestBuilder) -> RequestBuilder {
-    let (content_type, file) = open_file(&file).await;
-    builder.body(file).header(CONTENT_TYPE, content_type)
+async fn file_body(file: PathBuf, builder: RequestBuilder) -> anyhow::Result<RequestBuilder> {
+    let (content_type, file) = open_file(&file).await?;
+    Ok(builder.body(file).header(CONTENT_TYPE, content_type))
 }
 
 // Files are only sent with non GET requests
-async fn open_file(file: &PathBuf) -> (String, File) {
+async fn open_file(file: &Path) -> anyhow::Result<(String, File)> {
     let content_type = mime_guess::from_path(file)
         .first_or_octet_stream()
         .to_string();
@@ -195,21 +285,113 @@ async fn open_file(file: &PathBuf) -> (String, File) {
         .read(true)
         .open(file)
         .await
-        .expect("Failed to open file for request body");
-    (content_type, file)
+        .with_context(|| format!("Failed to open {}", file.display()))?;
+    Ok((content_type, file))
 }
 
-fn req_auth(builder: RequestBuilder, auth: Auth, env: Option<&Environment>) -> RequestBuilder {
-    match auth {
+fn req_auth(
+    builder: RequestBuilder,
+    auth: Auth,
+    env: Option<&Environment>,
+) -> anyhow::Result<RequestBuilder> {
+    let builder = match auth {
         Auth::None => builder,
         Auth::Basic { username, password } => {
-            let username = replace_env_vars(&username, env);
-            let password = replace_env_vars(&password, env);
+            let username = render(&username, env)?;
+            let password = render(&password, env)?;
             builder.basic_auth(username, Some(password))
         }
         Auth::Bearer { token } => {
-            let token = replace_env_vars(&token, env);
+            let token = render(&token, env)?;
             builder.bearer_auth(token)
         }
+    };
+    Ok(builder)
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use crate::http::{KeyFile, KeyValue};
+
+    use super::*;
+
+    fn env() -> Environment {
+        let mut env = Environment::new("dev".to_string());
+        env.variables = KeyValList::from(vec![KeyValue {
+            disabled: false,
+            name: "fixtures".to_string(),
+            value: "data/fixtures".to_string(),
+        }]);
+        env
+    }
+
+    #[test]
+    fn resolves_paths_against_the_collection() {
+        let root = Path::new("/work/api");
+        let env = env();
+
+        let path = resolve_path(Path::new("{{fixtures}}/a.json"), root, Some(&env)).unwrap();
+        assert_eq!(path, root.join("data/fixtures/a.json"));
+
+        let absolute = Path::new("/tmp/{{fixtures}}/a.json");
+        let path = resolve_path(absolute, root, Some(&env)).unwrap();
+        assert_eq!(path, Path::new("/tmp/data/fixtures/a.json"));
+
+        assert!(resolve_path(Path::new("{{missing}}/a.json"), root, Some(&env)).is_err());
+    }
+
+    #[tokio::test]
+    async fn reports_all_missing_files() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().to_path_buf();
+        std::fs::create_dir_all(root.join("data/fixtures")).unwrap();
+        std::fs::write(root.join("data/fixtures/a.json"), "{}").unwrap();
+        let env = env();
+
+        let file = |name: &str, path: &str| (name.to_string(), PathBuf::from(path));
+
+        let resolved = check_files(vec![file("a", "{{fixtures}}/a.json")], &root, Some(&env))
+            .await
+            .unwrap();
+        assert_eq!(
+            resolved,
+            [("a".to_string(), root.join("data/fixtures/a.json"))]
+        );
+
+        let files = vec![
+            file("a", "{{fixtures}}/a.json"),
+            file("b", "{{fixtures}}/b.json"),
+            file("dir", "{{fixtures}}"),
+        ];
+        let err = check_files(files, &root, Some(&env)).await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Files not found: {}, {}",
+                root.join("data/fixtures/b.json").display(),
+                root.join("data/fixtures").display()
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn skips_disabled_files() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.json"), "{}").unwrap();
+
+        let file = |name: &str, path: &str, disabled| KeyFile {
+            name: name.to_string(),
+            path: Some(PathBuf::from(path)),
+            disabled,
+        };
+        let files = KeyFileList::from(vec![
+            file("a", "a.json", false),
+            file("b", "missing.json", true),
+        ]);
+
+        let files = enabled_files(files, dir.path(), None).await.unwrap();
+        assert_eq!(files, [("a".to_string(), dir.path().join("a.json"))]);
     }
 }
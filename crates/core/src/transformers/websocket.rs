@@ -0,0 +1,104 @@
+use anyhow::Context;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use reqwest::Url;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::handshake::client::Request as HandshakeRequest;
+use tokio_tungstenite::tungstenite::http::header::{HeaderName, HeaderValue, AUTHORIZATION};
+
+use crate::http::{
+    environment::Environment,
+    request::Auth,
+    websocket::{MessageKind, WebSocketRequest},
+};
+use crate::websocket::WsMessage;
+
+use crate::template::render;
+
+use super::request::{enabled_params, param_enabled};
+
+pub fn transform_websocket(
+    req: WebSocketRequest,
+    env: Option<Environment>,
+) -> anyhow::Result<HandshakeRequest> {
+    let WebSocketRequest {
+        url,
+        headers,
+        query_params,
+        auth,
+        ..
+    } = req;
+
+    let env = env.as_ref();
+
+    let mut url = Url::parse(&render(&url, env)?).context("Invalid WebSocket URL")?;
+    let params = enabled_params(query_params, env)?;
+    if !params.is_empty() {
+        url.query_pairs_mut().extend_pairs(params);
+    }
+
+    let mut request = url.as_str().into_client_request()?;
+    let req_headers = request.headers_mut();
+
+    for header in headers.into_iter().filter(param_enabled) {
+        let name = HeaderName::from_bytes(header.name.as_bytes())?;
+        let value = HeaderValue::from_str(&render(&header.value, env)?)?;
+        req_headers.append(name, value);
+    }
+
+    if let Some(auth) = auth_header(auth, env)? {
+        req_headers.insert(AUTHORIZATION, HeaderValue::from_str(&auth)?);
+    }
+
+    Ok(request)
+}
+
+fn auth_header(auth: Auth, env: Option<&Environment>) -> anyhow::Result<Option<String>> {
+    let header = match auth {
+        Auth::None => None,
+        Auth::Basic { username, password } => {
+            let username = render(&username, env)?;
+            let password = render(&password, env)?;
+            let encoded = STANDARD.encode(format!("{}:{}", username, password));
+            Some(format!("Basic {}", encoded))
+        }
+        Auth::Bearer { token } => Some(format!("Bearer {}", render(&token, env)?)),
+    };
+    Ok(header)
+}
+
+/// Builds the frame to send for a composed message. JSON is validated before
+/// sending and binary messages are written as hex bytes, e.g. `de ad be ef`.
+pub fn transform_message(
+    kind: MessageKind,
+    body: &str,
+    env: Option<&Environment>,
+) -> anyhow::Result<WsMessage> {
+    let body = render(body, env)?;
+    match kind {
+        MessageKind::Text => Ok(WsMessage::Text(body)),
+        MessageKind::Json => {
+            serde_json::from_str::<serde_json::Value>(&body).context("Invalid JSON message")?;
+            Ok(WsMessage::Text(body))
+        }
+        MessageKind::Binary => parse_hex(&body).map(WsMessage::Binary),
+    }
+}
+
+fn parse_hex(data: &str) -> anyhow::Result<Vec<u8>> {
+    let digits = data
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<Vec<_>>();
+
+    if digits.len() % 2 != 0 {
+        anyhow::bail!("Binary message must have an even number of hex digits");
+    }
+
+    digits
+        .chunks(2)
+        .map(|pair| {
+            let byte = pair.iter().collect::<String>();
+            u8::from_str_radix(&byte, 16).with_context(|| format!("Invalid hex byte '{}'", byte))
+        })
+        .collect()
+}
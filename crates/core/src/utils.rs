@@ -12,3 +12,89 @@ pub fn fmt_duration(d: std::time::Duration) -> String {
 
     duration
 }
+
+/// Decodes `%XX` escapes and `+` as used in query strings and forms
+pub(crate) fn percent_decode(value: &str) -> String {
+    decode(value, true)
+}
+
+/// Decodes `%XX` escapes of a URL path, where `+` is a plain character
+pub(crate) fn percent_decode_path(value: &str) -> String {
+    decode(value, false)
+}
+
+fn decode(value: &str, plus_as_space: bool) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        match bytes[idx] {
+            b'+' if plus_as_space => decoded.push(b' '),
+            // Checked first, `from_str_radix` accepts a sign like `%+1`
+            b'%' if idx + 2 < bytes.len()
+                && bytes[idx + 1].is_ascii_hexdigit()
+                && bytes[idx + 2].is_ascii_hexdigit() =>
+            {
+                let hex = std::str::from_utf8(&bytes[idx + 1..idx + 3]).expect("hex digits");
+                decoded.push(u8::from_str_radix(hex, 16).expect("hex digits"));
+                idx += 2;
+            }
+            byte => decoded.push(byte),
+        }
+        idx += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// Encodes everything except unreserved characters
+pub(crate) fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            b => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Splits the URL into the server part and the path, either of them can be
+/// a `{{variable}}`. The query and fragment are dropped.
+pub(crate) fn split_url(url: &str) -> (&str, &str) {
+    let url = url.split(['?', '#']).next().unwrap_or_default();
+
+    let path_start = if let Some(scheme) = url.find("://") {
+        url[scheme + 3..].find('/').map(|idx| idx + scheme + 3)
+    } else if url.starts_with("{{") {
+        url.find("}}").map(|idx| idx + 2)
+    } else {
+        Some(0)
+    };
+
+    match path_start {
+        Some(idx) => (&url[..idx], &url[idx..]),
+        None => (url, ""),
+    }
+}
+
+/// Headers describing how a body was sent rather than what it is, they no
+/// longer apply once the body is stored or rewritten
+pub(crate) fn is_framing_header(name: &str) -> bool {
+    ["content-length", "transfer-encoding", "content-encoding"]
+        .iter()
+        .any(|framing| name.eq_ignore_ascii_case(framing))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_only_two_hex_digits() {
+        assert_eq!(percent_decode("a%20b+c%2B"), "a b c+");
+        assert_eq!(percent_decode_path("a+b%2fc"), "a+b/c");
+        // Signs, partial and trailing escapes are kept as they are
+        assert_eq!(percent_decode_path("%+1%-1%2%zz%"), "%+1%-1%2%zz%");
+    }
+}
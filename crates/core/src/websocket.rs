@@ -0,0 +1,130 @@
+use std::time::{Duration, Instant};
+
+use futures::stream::{self, Stream};
+use futures::{SinkExt, StreamExt};
+use reqwest::{header::HeaderMap, StatusCode};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::handshake::client::Request as HandshakeRequest;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WsMessage {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+impl WsMessage {
+    pub fn len(&self) -> usize {
+        match self {
+            WsMessage::Text(text) => text.len(),
+            WsMessage::Binary(data) => data.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn into_message(self) -> Message {
+        match self {
+            WsMessage::Text(text) => Message::Text(text),
+            WsMessage::Binary(data) => Message::Binary(data),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WsHandshake {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub duration: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CloseReason {
+    pub code: u16,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum WsEvent {
+    Connected(WsHandshake),
+    Sent(WsMessage),
+    Received(WsMessage),
+    Closed(Option<CloseReason>),
+}
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+enum ConnState {
+    Start(Box<HandshakeRequest>, mpsc::UnboundedReceiver<WsMessage>),
+    Open(Box<Socket>, mpsc::UnboundedReceiver<WsMessage>),
+    Done,
+}
+
+/// Opens a WebSocket connection and reports everything happening on it as a
+/// stream. Messages pushed into `outgoing` are written to the socket, dropping
+/// the sender closes the connection gracefully.
+pub fn connect(
+    request: HandshakeRequest,
+    outgoing: mpsc::UnboundedReceiver<WsMessage>,
+) -> impl Stream<Item = anyhow::Result<WsEvent>> + Send + 'static {
+    stream::unfold(ConnState::Start(Box::new(request), outgoing), |state| async move {
+        match state {
+            ConnState::Start(request, outgoing) => {
+                let start = Instant::now();
+                let (socket, response) = match connect_async(*request).await {
+                    Ok(res) => res,
+                    Err(err) => return Some((Err(err.into()), ConnState::Done)),
+                };
+
+                let handshake = WsHandshake {
+                    status: response.status(),
+                    headers: response.headers().clone(),
+                    duration: start.elapsed(),
+                };
+                let next = ConnState::Open(Box::new(socket), outgoing);
+                Some((Ok(WsEvent::Connected(handshake)), next))
+            }
+            ConnState::Open(mut socket, mut outgoing) => loop {
+                tokio::select! {
+                    msg = outgoing.recv() => {
+                        let Some(msg) = msg else {
+                            let _ = socket.close().await;
+                            return Some((Ok(WsEvent::Closed(None)), ConnState::Done));
+                        };
+
+                        if let Err(err) = socket.send(msg.clone().into_message()).await {
+                            return Some((Err(err.into()), ConnState::Done));
+                        }
+                        let next = ConnState::Open(socket, outgoing);
+                        return Some((Ok(WsEvent::Sent(msg)), next));
+                    }
+                    incoming = socket.next() => {
+                        let event = match incoming {
+                            Some(Ok(Message::Text(text))) => WsEvent::Received(WsMessage::Text(text)),
+                            Some(Ok(Message::Binary(data))) => {
+                                WsEvent::Received(WsMessage::Binary(data))
+                            }
+                            Some(Ok(Message::Close(frame))) => {
+                                let reason = frame.map(|frame| CloseReason {
+                                    code: frame.code.into(),
+                                    reason: frame.reason.to_string(),
+                                });
+                                return Some((Ok(WsEvent::Closed(reason)), ConnState::Done));
+                            }
+                            // Pings are answered by tungstenite itself
+                            Some(Ok(_)) => continue,
+                            Some(Err(err)) => return Some((Err(err.into()), ConnState::Done)),
+                            None => return Some((Ok(WsEvent::Closed(None)), ConnState::Done)),
+                        };
+                        return Some((Ok(event), ConnState::Open(socket, outgoing)));
+                    }
+                }
+            },
+            ConnState::Done => None,
+        }
+    })
+}
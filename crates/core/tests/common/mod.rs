@@ -0,0 +1,9 @@
+/// Runs a test future to completion, `#[tokio::test]` cannot be used as the
+/// crate named `core` shadows the `::core` paths of its expansion
+pub fn block_on<F: std::future::Future>(fut: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(fut)
+}
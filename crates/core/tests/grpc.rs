@@ -0,0 +1,278 @@
+use std::convert::Infallible;
+use std::future::{ready, Ready};
+use std::path::PathBuf;
+use std::task::{Context, Poll};
+
+use core::assertions::{runner, Assertions};
+use core::client::ResponseEvent;
+use core::grpc::{call, execute, list_methods, load_descriptors, parse_proto_files, DynamicCodec};
+use core::http::grpc::GrpcRequest;
+use core::http::{KeyValList, KeyValue};
+use core::transformers::grpc::transform_grpc;
+use futures::stream::{self, BoxStream};
+use futures::{FutureExt, StreamExt};
+use prost_reflect::{DescriptorPool, DynamicMessage, MessageDescriptor, Value};
+use tokio::net::TcpListener;
+use tonic::body::Body;
+use tonic::codegen::{http, BoxFuture, Service};
+use tonic::server::NamedService;
+use tonic::transport::server::TcpIncoming;
+use tonic::transport::Server;
+use tonic::{Request, Response, Status};
+
+use common::block_on;
+
+mod common;
+
+fn proto_files() -> Vec<PathBuf> {
+    vec![PathBuf::from(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/echo.proto"
+    ))]
+}
+
+fn reply(desc: &MessageDescriptor, message: String, index: i32) -> DynamicMessage {
+    let mut reply = DynamicMessage::new(desc.clone());
+    reply.set_field_by_name("message", Value::String(message));
+    reply.set_field_by_name("index", Value::I32(index));
+    reply
+}
+
+fn string_field(msg: &DynamicMessage, name: &str) -> String {
+    msg.get_field_by_name(name)
+        .and_then(|v| v.as_str().map(str::to_owned))
+        .unwrap_or_default()
+}
+
+/// Replies with the request message, `fail` is answered with an error status.
+struct Say(MessageDescriptor);
+
+impl Service<Request<DynamicMessage>> for Say {
+    type Response = Response<DynamicMessage>;
+    type Error = Status;
+    type Future = Ready<Result<Self::Response, Status>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Status>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<DynamicMessage>) -> Self::Future {
+        let token = req.metadata().get("x-token").cloned();
+        let message = string_field(req.get_ref(), "message");
+        if message == "fail" {
+            return ready(Err(Status::invalid_argument("asked to fail")));
+        }
+
+        let mut res = Response::new(reply(&self.0, message, 0));
+        if let Some(token) = token {
+            res.metadata_mut().insert("x-echo", token);
+        }
+        ready(Ok(res))
+    }
+}
+
+/// Streams the request message `times` times.
+struct Repeat(MessageDescriptor);
+
+impl Service<Request<DynamicMessage>> for Repeat {
+    type Response = Response<BoxStream<'static, Result<DynamicMessage, Status>>>;
+    type Error = Status;
+    type Future = Ready<Result<Self::Response, Status>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Status>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<DynamicMessage>) -> Self::Future {
+        let message = string_field(req.get_ref(), "message");
+        let times = req
+            .get_ref()
+            .get_field_by_name("times")
+            .and_then(|v| v.as_i32())
+            .unwrap_or_default();
+
+        let replies = (0..times)
+            .map(|i| Ok(reply(&self.0, message.clone(), i)))
+            .collect::<Vec<_>>();
+        ready(Ok(Response::new(stream::iter(replies).boxed())))
+    }
+}
+
+#[derive(Clone)]
+struct EchoServer(DescriptorPool);
+
+impl NamedService for EchoServer {
+    const NAME: &'static str = "echo.Echo";
+}
+
+impl Service<http::Request<Body>> for EchoServer {
+    type Response = http::Response<Body>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Infallible>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<Body>) -> Self::Future {
+        let service = self.0.get_service_by_name(Self::NAME).unwrap();
+        let name = req.uri().path().rsplit('/').next().unwrap_or_default();
+        let method = service.methods().find(|m| m.name() == name).unwrap();
+
+        let mut grpc = tonic::server::Grpc::new(DynamicCodec::new(method.input()));
+        let output = method.output();
+        async move {
+            let res = match method.name() {
+                "Say" => grpc.unary(Say(output), req).await,
+                _ => grpc.server_streaming(Repeat(output), req).await,
+            };
+            Ok(res)
+        }
+        .boxed()
+    }
+}
+
+/// Serves the echo service along with server reflection.
+async fn echo_server() -> String {
+    let pool = parse_proto_files(&proto_files()).unwrap();
+    let reflection = tonic_reflection::server::Builder::configure()
+        .register_encoded_file_descriptor_set(pool.encode_to_vec().leak())
+        .build_v1()
+        .unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(
+        Server::builder()
+            .add_service(EchoServer(pool))
+            .add_service(reflection)
+            .serve_with_incoming(TcpIncoming::from(listener)),
+    );
+
+    format!("http://{}", addr)
+}
+
+fn request(url: String, method: &str, message: &str) -> GrpcRequest {
+    GrpcRequest {
+        url,
+        proto_files: proto_files(),
+        service: "echo.Echo".to_string(),
+        method: method.to_string(),
+        message: message.to_string(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn lists_methods_from_proto_files() {
+    let pool = parse_proto_files(&proto_files()).unwrap();
+    let methods = list_methods(&pool);
+
+    assert_eq!(methods.len(), 2);
+    assert!(!methods[0].server_streaming);
+    assert!(methods[1].server_streaming);
+}
+
+#[test]
+fn unary_call() {
+    block_on(async {
+        let url = echo_server().await;
+        let mut req = request(url, "Say", r#"{"message": "hello"}"#);
+        req.metadata = KeyValList::from(vec![KeyValue {
+            name: "x-token".to_string(),
+            value: "secret".to_string(),
+            disabled: false,
+        }]);
+        req.assertions = hcl::from_str::<Assertions>(
+            r#"
+            status "grpc" { eq = 0 }
+            body "string" { contains = "hello" }
+            header "x-echo" { eq = "secret" }
+            "#,
+        )
+        .unwrap();
+
+        let pool = load_descriptors(&req.url, &req.proto_files).await.unwrap();
+        let grpc = transform_grpc(req.clone(), &pool, None).unwrap();
+        let res = execute(grpc).await.unwrap();
+
+        let body: serde_json::Value = serde_json::from_slice(&res.body.data).unwrap();
+        assert_eq!(body["message"], "hello");
+
+        for outcome in runner::run(&res, &req.assertions) {
+            for result in outcome.results {
+                assert!(
+                    matches!(result.result, runner::MatcherResult::Passed),
+                    "{}: {:?}",
+                    result.name,
+                    result.result
+                );
+            }
+        }
+    });
+}
+
+#[test]
+fn error_status() {
+    block_on(async {
+        let url = echo_server().await;
+        let req = request(url, "Say", r#"{"message": "fail"}"#);
+
+        let pool = parse_proto_files(&req.proto_files).unwrap();
+        let res = execute(transform_grpc(req, &pool, None).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(res.headers["grpc-status"], "3");
+        let body: serde_json::Value = serde_json::from_slice(&res.body.data).unwrap();
+        assert_eq!(body["message"], "asked to fail");
+    });
+}
+
+#[test]
+fn server_streaming_call() {
+    block_on(async {
+        let url = echo_server().await;
+        let req = request(url, "Repeat", r#"{"message": "ping", "times": 3}"#);
+
+        let pool = parse_proto_files(&req.proto_files).unwrap();
+        let grpc = transform_grpc(req, &pool, None).unwrap();
+
+        let events = call(grpc.clone())
+            .map(Result::unwrap)
+            .collect::<Vec<_>>()
+            .await;
+        assert!(matches!(events[0], ResponseEvent::StreamOpened(_)));
+        assert_eq!(events.len(), 5);
+        let ResponseEvent::Event(event) = &events[3] else {
+            panic!("expected message");
+        };
+        assert_eq!(event.id.as_deref(), Some("2"));
+        assert!(matches!(events[4], ResponseEvent::StreamClosed));
+
+        let res = execute(grpc).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&res.body.data).unwrap();
+        assert_eq!(body.as_array().unwrap().len(), 3);
+        assert_eq!(body[2]["index"], 2);
+    });
+}
+
+#[test]
+fn server_reflection() {
+    block_on(async {
+        let url = echo_server().await;
+        let mut req = request(url, "Say", r#"{"message": "reflected"}"#);
+        req.proto_files.clear();
+
+        let pool = load_descriptors(&req.url, &req.proto_files).await.unwrap();
+        let methods = list_methods(&pool);
+        assert_eq!(methods.len(), 2);
+
+        let res = execute(transform_grpc(req, &pool, None).unwrap())
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&res.body.data).unwrap();
+        assert_eq!(body["message"], "reflected");
+    });
+}
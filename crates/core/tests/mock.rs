@@ -0,0 +1,150 @@
+use std::path::PathBuf;
+
+use core::http::environment::Environment;
+use core::http::{KeyValList, KeyValue};
+use core::mock::{Mock, MockOptions};
+use core::persistence::collections::open_collection;
+use tempfile::TempDir;
+use tokio::net::TcpListener;
+
+use common::block_on;
+
+mod common;
+
+const REQUEST: &str = r#"
+version = "V1"
+method  = "GET"
+url     = "{{baseUrl}}/users/:id"
+"#;
+
+// Saved from a response of a different length, sent in chunks
+const EXAMPLE: &str = r#"
+version = "V1"
+status  = 200
+headers = [
+  {
+    name  = "content-type"
+    value = "application/json"
+  },
+  {
+    name  = "content-length"
+    value = "13"
+  },
+  {
+    name  = "transfer-encoding"
+    value = "chunked"
+  }
+]
+body = "{\"id\":\"{{path.id}}\"}"
+"#;
+
+const SEARCH: &str = r#"
+version = "V1"
+method  = "GET"
+url     = "{{baseUrl}}/tags/:tag"
+"#;
+
+const SEARCH_EXAMPLE: &str = r#"
+version = "V1"
+status  = 200
+body    = "{{path.tag}}|{{query.q}}"
+"#;
+
+fn collection_dir() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    let requests = dir.path().join("requests");
+    std::fs::create_dir_all(requests.join("Get user.examples")).unwrap();
+    std::fs::write(dir.path().join("collection.hcl"), "name = \"Mock\"\n").unwrap();
+    std::fs::write(requests.join("Get user.hcl"), REQUEST).unwrap();
+    std::fs::write(requests.join("Get user.examples").join("ok.hcl"), EXAMPLE).unwrap();
+    std::fs::create_dir_all(requests.join("Search.examples")).unwrap();
+    std::fs::write(requests.join("Search.hcl"), SEARCH).unwrap();
+    std::fs::write(
+        requests.join("Search.examples").join("ok.hcl"),
+        SEARCH_EXAMPLE,
+    )
+    .unwrap();
+    dir
+}
+
+#[test]
+fn serves_templated_bodies_with_their_own_length() {
+    let dir = collection_dir();
+    block_on(templated_bodies(dir.path().to_path_buf()));
+}
+
+async fn templated_bodies(dir: PathBuf) {
+    let collection = open_collection(dir).await.unwrap();
+    let (mock, warnings) = Mock::load(&collection, None).await.unwrap();
+    assert!(warnings.is_empty(), "{:?}", warnings);
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(mock.serve(listener, MockOptions::default(), |_| ()));
+
+    let client = reqwest::Client::new();
+    for id in ["1", "12345678901234"] {
+        let response = client
+            .get(format!("http://{}/users/{}", addr, id))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status().as_u16(), 200);
+
+        let headers = response.headers();
+        assert_eq!(headers["content-type"], "application/json");
+        assert!(headers.get("transfer-encoding").is_none());
+
+        let body = format!(r#"{{"id":"{}"}}"#, id);
+        assert_eq!(headers["content-length"], body.len().to_string().as_str());
+        assert_eq!(response.text().await.unwrap(), body);
+    }
+}
+
+#[test]
+fn decodes_plus_only_in_queries() {
+    let dir = collection_dir();
+    block_on(decoded_values(dir.path().to_path_buf()));
+}
+
+async fn decoded_values(dir: PathBuf) {
+    let collection = open_collection(dir).await.unwrap();
+    let (mock, _) = Mock::load(&collection, None).await.unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(mock.serve(listener, MockOptions::default(), |_| ()));
+
+    let url = format!("http://{}/tags/c++%20lang?q=a+b%2Bc", addr);
+    let response = reqwest::get(url).await.unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.text().await.unwrap(), "c++ lang|a b+c");
+}
+
+#[test]
+fn keeps_builtins_in_routes() {
+    let dir = collection_dir();
+    let items = "version = \"V1\"\nmethod = \"PUT\"\nurl = \"{{baseUrl}}/{{v}}/items/{{$uuid}}\"\n";
+    std::fs::write(dir.path().join("requests").join("Put item.hcl"), items).unwrap();
+    block_on(route_paths(dir.path().to_path_buf()));
+}
+
+async fn route_paths(dir: PathBuf) {
+    let collection = open_collection(dir).await.unwrap();
+    let mut env = Environment::new("dev".to_string());
+    env.variables = KeyValList::from(vec![KeyValue {
+        disabled: false,
+        name: "v".to_string(),
+        value: "v2".to_string(),
+    }]);
+
+    // Loading twice gives the same route, the built-in is not evaluated
+    for _ in 0..2 {
+        let (mock, _) = Mock::load(&collection, Some(&env)).await.unwrap();
+        let path = mock
+            .routes()
+            .find(|(_, _, name, _)| *name == "Put item")
+            .map(|(_, path, _, _)| path.to_string());
+        assert_eq!(path.as_deref(), Some("/v2/items/{{$uuid}}"));
+    }
+}
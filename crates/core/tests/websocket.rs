@@ -0,0 +1,88 @@
+use core::http::websocket::{MessageKind, WebSocketRequest};
+use core::transformers::websocket::{transform_message, transform_websocket};
+use core::websocket::{connect, WsEvent, WsMessage};
+use futures::{SinkExt, StreamExt};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+
+use common::block_on;
+
+mod common;
+
+/// Echoes every data frame back to the client until it closes the connection.
+async fn echo_server() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                while let Some(Ok(msg)) = ws.next().await {
+                    if msg.is_text() || msg.is_binary() {
+                        ws.send(msg).await.unwrap();
+                    }
+                }
+            });
+        }
+    });
+
+    format!("ws://{}", addr)
+}
+
+#[test]
+fn echoes_messages() {
+    block_on(echo_round_trip());
+}
+
+async fn echo_round_trip() {
+    let req = WebSocketRequest {
+        url: echo_server().await,
+        ..Default::default()
+    };
+    let request = transform_websocket(req, None).unwrap();
+
+    let (tx, rx) = mpsc::unbounded_channel();
+    let mut events = Box::pin(connect(request, rx));
+
+    let Some(Ok(WsEvent::Connected(handshake))) = events.next().await else {
+        panic!("expected connection");
+    };
+    assert_eq!(handshake.status.as_u16(), 101);
+
+    let messages = [
+        transform_message(MessageKind::Json, r#"{"hello": "world"}"#, None).unwrap(),
+        transform_message(MessageKind::Binary, "de ad be ef", None).unwrap(),
+    ];
+
+    for msg in messages {
+        tx.send(msg.clone()).unwrap();
+
+        let Some(Ok(WsEvent::Sent(sent))) = events.next().await else {
+            panic!("expected sent message");
+        };
+        assert_eq!(sent, msg);
+
+        let Some(Ok(WsEvent::Received(received))) = events.next().await else {
+            panic!("expected echoed message");
+        };
+        assert_eq!(received, msg);
+    }
+
+    drop(tx);
+    assert!(matches!(
+        events.next().await,
+        Some(Ok(WsEvent::Closed(None)))
+    ));
+    assert!(events.next().await.is_none());
+}
+
+#[test]
+fn rejects_invalid_messages() {
+    assert!(transform_message(MessageKind::Json, "{", None).is_err());
+    assert!(transform_message(MessageKind::Binary, "abc", None).is_err());
+    assert_eq!(
+        transform_message(MessageKind::Binary, "0aff", None).unwrap(),
+        WsMessage::Binary(vec![0x0a, 0xff])
+    );
+}
@@ -1,16 +1,26 @@
+use std::path::Path;
+
 use iced::alignment::Horizontal;
 use iced::widget::scrollable::Direction;
+use iced::widget::tooltip::Position;
 use iced::widget::{button, column, container, row, text, Button, Column, Row, Scrollable};
 use iced::{clipboard, padding, Element, Length, Task};
 
-use components::{context_menu, horizontal_line, icon, icons, menu_item, tooltip, NerdIcon};
+use components::{
+    context_menu, horizontal_line, icon, icons, menu_item, tooltip, tooltip_style, NerdIcon,
+};
 use core::http::collection::{Collection, Entry, FolderId, RequestId, RequestRef};
-use core::http::{request::Request, CollectionKey, CollectionRequest};
+use core::http::{CollectionKey, CollectionRequest};
+use core::persistence::diagnostics::Diagnostic;
+use core::persistence::examples::ResponseExample;
+use core::persistence::request::RequestFile;
+use core::persistence::{COLLECTION_ROOT_FILE, ENVIRONMENTS};
 
-use crate::commands::builders::{self, open_collection_cmd, open_request_cmd};
+use crate::commands::builders::{self, open_collection_cmd, open_example_cmd, open_request_cmd};
 use crate::state::collection_tab::CollectionTab;
 use crate::state::popups::{Popup, PopupNameAction};
-use crate::state::{AppState, HttpTab, Tab};
+use crate::state::response::{CompletedResponse, ResponseState};
+use crate::state::{AppState, GrpcTab, HttpTab, Tab, WebSocketTab};
 
 #[derive(Debug, Clone)]
 pub enum CollectionTreeMsg {
@@ -20,7 +30,10 @@ pub enum CollectionTreeMsg {
     CreateCollection,
     OpenCollection,
     OpenCollectionHandle(Option<Collection>),
-    RequestLoaded(CollectionRequest, Box<Option<(Request, String)>>),
+    ImportCollection,
+    RequestLoaded(CollectionRequest, Box<Option<(RequestFile, String)>>),
+    OpenExample(CollectionRequest, String),
+    ExampleLoaded(CollectionRequest, Box<Option<ResponseExample>>),
     ContextMenu(CollectionKey, MenuAction),
     ActionComplete,
     OpenSettings,
@@ -46,6 +59,33 @@ impl CollectionTreeMsg {
                     });
                 };
             }
+            CollectionTreeMsg::OpenExample(col, name) => {
+                // The request is opened first when it has no tab yet
+                let open = if state.switch_to_tab(col) {
+                    Task::none()
+                } else {
+                    open_request_cmd(state, col, move |res| {
+                        Self::RequestLoaded(col, Box::new(res))
+                    })
+                };
+                let example = open_example_cmd(state, col, name, move |res| {
+                    Self::ExampleLoaded(col, Box::new(res))
+                });
+                return open.chain(example);
+            }
+            CollectionTreeMsg::ExampleLoaded(col, example) => {
+                let tab = state
+                    .tabs
+                    .values_mut()
+                    .find(|tab| tab.collection_ref() == Some(col));
+                if let (Some(Tab::Http(tab)), Some(example)) = (tab, *example) {
+                    tab.cancel_tasks();
+                    tab.response.state =
+                        ResponseState::Completed(CompletedResponse::new(example.to_response()));
+                    tab.response.example = Some(example.name);
+                    tab.response.refresh_diff();
+                }
+            }
             CollectionTreeMsg::CreateCollection => {
                 Popup::create_collection(state);
             }
@@ -53,14 +93,26 @@ impl CollectionTreeMsg {
                 return open_collection_cmd(Self::OpenCollectionHandle);
             }
             CollectionTreeMsg::OpenCollectionHandle(handle) => {
-                if let Some(handle) = handle {
+                if let Some(mut handle) = handle {
+                    if let Some(secrets) = &state.secrets {
+                        secrets.fill(&mut handle);
+                    }
                     state.collections.insert(handle);
                 }
             }
+            CollectionTreeMsg::ImportCollection => {
+                Popup::import_collection(state);
+            }
             CollectionTreeMsg::RequestLoaded(col, req) => {
-                if let Some((req, name)) = *req {
-                    state.open_tab(Tab::Http(HttpTab::new(name, req, col)));
-                }
+                let tab = match *req {
+                    Some((RequestFile::Http(req), name)) => Tab::Http(HttpTab::new(name, req, col)),
+                    Some((RequestFile::WebSocket(req), name)) => {
+                        Tab::WebSocket(WebSocketTab::new(name, req, col))
+                    }
+                    Some((RequestFile::Grpc(req), name)) => Tab::Grpc(GrpcTab::new(name, req, col)),
+                    None => return Task::none(),
+                };
+                state.open_tab(tab);
             }
             CollectionTreeMsg::ContextMenu(col, action) => {
                 return handle_context_menu(state, col, action);
@@ -88,6 +140,22 @@ fn handle_context_menu(
             );
             Task::none()
         }
+        MenuAction::NewWebSocket(folder_id) => {
+            Popup::popup_name(
+                state,
+                String::new(),
+                PopupNameAction::NewWebSocket(key, folder_id),
+            );
+            Task::none()
+        }
+        MenuAction::NewGrpc(folder_id) => {
+            Popup::popup_name(
+                state,
+                String::new(),
+                PopupNameAction::NewGrpc(key, folder_id),
+            );
+            Task::none()
+        }
         MenuAction::NewFolder(folder_id) => {
             Popup::popup_name(
                 state,
@@ -103,6 +171,8 @@ fn handle_context_menu(
         }
         MenuAction::RemoveCollection => {
             state.collections.remove(key);
+            state.mocks.remove(&key);
+            state.watched.remove(&key);
             Task::none()
         }
         MenuAction::RenameFolder(name, folder_id) => {
@@ -139,6 +209,12 @@ fn handle_context_menu(
                 Task::none()
             }
         }
+        MenuAction::CopyCurl(req) => builders::copy_curl_cmd(state, CollectionRequest(key, req)),
+        MenuAction::DeleteExample(req, name) => {
+            builders::delete_example_cmd(state, CollectionRequest(key, req), name, || {
+                CollectionTreeMsg::ActionComplete
+            })
+        }
         MenuAction::OpenCollection => {
             if let Some(col) = state.collections.get(key) {
                 state.open_tab(Tab::Collection(CollectionTab::new(key, col)));
@@ -152,12 +228,60 @@ fn icon_button<'a>(ico: NerdIcon) -> Button<'a, CollectionTreeMsg> {
     components::icon_button(ico, Some(20), Some(8)).style(button::secondary)
 }
 
+fn describe(diagnostic: &Diagnostic, file: &Path) -> String {
+    match diagnostic.location {
+        Some((line, column)) => format!(
+            "{}:{}:{}: {}",
+            file.display(),
+            line,
+            column,
+            diagnostic.message
+        ),
+        None => format!("{}: {}", file.display(), diagnostic.message),
+    }
+}
+
+/// Problems of the collection and environment files, requests are marked on
+/// their own in the tree
+fn collection_problems(diagnostics: &[Diagnostic]) -> Vec<String> {
+    diagnostics
+        .iter()
+        .filter_map(|d| {
+            let name = Path::new(d.path.file_name()?);
+            let parent = d.path.parent()?;
+            if parent.ends_with(ENVIRONMENTS) {
+                Some(describe(d, &Path::new(ENVIRONMENTS).join(name)))
+            } else if name == Path::new(COLLECTION_ROOT_FILE) {
+                Some(describe(d, name))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Error icon with the problems of the files which could not be read
+fn error_icon<'a>(problems: Vec<String>) -> Element<'a, CollectionTreeMsg> {
+    let error = icon(icons::Error).style(|t| text::Style {
+        color: Some(t.extended_palette().danger.base.color),
+    });
+    iced::widget::tooltip(
+        error,
+        container(text(problems.join("\n")).size(14))
+            .style(tooltip_style)
+            .padding([2, 4]),
+        Position::Bottom,
+    )
+    .into()
+}
+
 pub fn view(state: &AppState) -> Element<CollectionTreeMsg> {
     let it = state.collections.iter().map(|(key, collection)| {
         expandable(
             key,
             &collection.name,
             &collection.entries,
+            &collection.diagnostics,
             collection.expanded,
             CollectionTreeMsg::ToggleExpandCollection(key),
             None,
@@ -166,6 +290,7 @@ pub fn view(state: &AppState) -> Element<CollectionTreeMsg> {
 
     let create_col = icon_button(icons::Plus).on_press(CollectionTreeMsg::CreateCollection);
     let open_col = icon_button(icons::FolderOpen).on_press(CollectionTreeMsg::OpenCollection);
+    let import_col = icon_button(icons::Import).on_press(CollectionTreeMsg::ImportCollection);
     let settings = icon_button(icons::Gear).on_press(CollectionTreeMsg::OpenSettings);
 
     Column::new()
@@ -174,6 +299,7 @@ pub fn view(state: &AppState) -> Element<CollectionTreeMsg> {
                 Row::new()
                     .push(tooltip("Create Collection", create_col))
                     .push(tooltip("Open Collection", open_col))
+                    .push(tooltip("Import Collection", import_col))
                     .push(tooltip("Settings", settings))
                     .width(Length::Shrink)
                     .spacing(4),
@@ -201,13 +327,21 @@ pub fn view(state: &AppState) -> Element<CollectionTreeMsg> {
         .into()
 }
 
-fn folder_tree(col: CollectionKey, entries: &[Entry]) -> Element<CollectionTreeMsg> {
+fn folder_tree<'a>(
+    col: CollectionKey,
+    entries: &'a [Entry],
+    diagnostics: &'a [Diagnostic],
+) -> Element<'a, CollectionTreeMsg> {
     let it = entries.iter().map(|entry| match entry {
-        Entry::Item(item) => context_button_request(item, col),
+        Entry::Item(item) => {
+            let diagnostic = diagnostics.iter().find(|d| d.path == item.path);
+            context_button_request(item, col, diagnostic)
+        }
         Entry::Folder(folder) => expandable(
             col,
             &folder.name,
             &folder.entries,
+            diagnostics,
             folder.expanded,
             CollectionTreeMsg::ToggleFolder(col, folder.id),
             Some(folder.id),
@@ -225,12 +359,13 @@ fn expandable<'a>(
     col: CollectionKey,
     name: &'a str,
     entries: &'a [Entry],
+    diagnostics: &'a [Diagnostic],
     expanded: bool,
     on_expand_toggle: CollectionTreeMsg,
     folder_id: Option<FolderId>,
 ) -> Element<'a, CollectionTreeMsg> {
     if expanded {
-        let children = folder_tree(col, entries);
+        let children = folder_tree(col, entries, diagnostics);
         Column::new()
             .push(expandable_button(
                 name,
@@ -238,25 +373,44 @@ fn expandable<'a>(
                 icons::FolderOpen,
                 col,
                 folder_id,
+                diagnostics,
             ))
             .push(children)
             .spacing(2)
             .width(Length::Shrink)
             .into()
     } else {
-        expandable_button(name, on_expand_toggle, icons::Folder, col, folder_id).into()
+        expandable_button(
+            name,
+            on_expand_toggle,
+            icons::Folder,
+            col,
+            folder_id,
+            diagnostics,
+        )
+        .into()
     }
 }
 
-fn expandable_button(
-    name: &str,
+fn expandable_button<'a>(
+    name: &'a str,
     on_expand_toggle: CollectionTreeMsg,
     arrow: NerdIcon,
     col: CollectionKey,
     folder_id: Option<FolderId>,
-) -> impl Into<Element<CollectionTreeMsg>> {
+    diagnostics: &[Diagnostic],
+) -> impl Into<Element<'a, CollectionTreeMsg>> {
+    let problems = match folder_id {
+        Some(_) => Vec::new(),
+        None => collection_problems(diagnostics),
+    };
+    let problems = (!problems.is_empty()).then(|| error_icon(problems));
+
     let base = button(
-        row([icon(arrow).into(), text(name).into()])
+        Row::new()
+            .push(icon(arrow))
+            .push(text(name))
+            .push_maybe(problems)
             .align_y(iced::Alignment::Center)
             .width(Length::Shrink)
             .spacing(4),
@@ -280,8 +434,12 @@ pub enum MenuAction {
     DeleteFolder(FolderId),
     RenameRequest(String, RequestId),
     CopyPath(RequestId),
+    CopyCurl(RequestId),
     DeleteRequest(RequestId),
+    DeleteExample(RequestId, String),
     NewRequest(Option<FolderId>),
+    NewWebSocket(Option<FolderId>),
+    NewGrpc(Option<FolderId>),
     RenameCollection(String),
     RemoveCollection,
     OpenCollection,
@@ -304,6 +462,14 @@ fn context_button_folder<'a>(
                 "New Request",
                 CollectionTreeMsg::ContextMenu(col, MenuAction::NewRequest(Some(folder_id))),
             ),
+            menu_item(
+                "New WebSocket",
+                CollectionTreeMsg::ContextMenu(col, MenuAction::NewWebSocket(Some(folder_id))),
+            ),
+            menu_item(
+                "New gRPC",
+                CollectionTreeMsg::ContextMenu(col, MenuAction::NewGrpc(Some(folder_id))),
+            ),
             menu_item(
                 "New Folder",
                 CollectionTreeMsg::ContextMenu(col, MenuAction::NewFolder(Some(folder_id))),
@@ -316,21 +482,30 @@ fn context_button_folder<'a>(
     )
 }
 
-fn context_button_request(item: &RequestRef, col: CollectionKey) -> Element<'_, CollectionTreeMsg> {
+fn context_button_request<'a>(
+    item: &'a RequestRef,
+    col: CollectionKey,
+    diagnostic: Option<&Diagnostic>,
+) -> Element<'a, CollectionTreeMsg> {
     let collection_request = CollectionRequest(col, item.id);
 
+    let request_icon = match diagnostic {
+        Some(diagnostic) => {
+            let file = Path::new(diagnostic.path.file_name().unwrap_or_default());
+            error_icon(vec![describe(diagnostic, file)])
+        }
+        None => icon(icons::API)
+            .style(|t| text::Style {
+                color: Some(t.extended_palette().success.strong.color),
+            })
+            .into(),
+    };
+
     let base = button(
-        row([
-            icon(icons::API)
-                .style(|t| text::Style {
-                    color: Some(t.extended_palette().success.strong.color),
-                })
-                .into(),
-            text(&item.name).into(),
-        ])
-        .align_y(iced::Alignment::Center)
-        .width(Length::Shrink)
-        .spacing(6),
+        row([request_icon, text(&item.name).into()])
+            .align_y(iced::Alignment::Center)
+            .width(Length::Shrink)
+            .spacing(6),
     )
     .style(button::text)
     .padding(0)
@@ -338,7 +513,7 @@ fn context_button_request(item: &RequestRef, col: CollectionKey) -> Element<'_,
     .on_press(CollectionTreeMsg::OpenRequest(collection_request));
 
     let request_id = item.id;
-    context_menu(
+    let request = context_menu(
         base,
         vec![
             menu_item(
@@ -352,11 +527,67 @@ fn context_button_request(item: &RequestRef, col: CollectionKey) -> Element<'_,
                 "Copy Path",
                 CollectionTreeMsg::ContextMenu(col, MenuAction::CopyPath(request_id)),
             ),
+            menu_item(
+                "Copy as cURL",
+                CollectionTreeMsg::ContextMenu(col, MenuAction::CopyCurl(request_id)),
+            ),
             menu_item(
                 "Delete",
                 CollectionTreeMsg::ContextMenu(col, MenuAction::DeleteRequest(request_id)),
             ),
         ],
+    );
+
+    if item.examples.is_empty() {
+        return request;
+    }
+
+    let examples = item
+        .examples
+        .iter()
+        .map(|name| context_button_example(name, collection_request));
+
+    Column::new()
+        .push(request)
+        .push(
+            column(examples)
+                .spacing(2)
+                .padding(padding::left(12))
+                .width(Length::Shrink),
+        )
+        .spacing(2)
+        .width(Length::Shrink)
+        .into()
+}
+
+fn context_button_example(name: &str, req: CollectionRequest) -> Element<'_, CollectionTreeMsg> {
+    let base = button(
+        row([
+            icon(icons::FileDocument)
+                .style(|t| text::Style {
+                    color: Some(t.extended_palette().secondary.strong.color),
+                })
+                .into(),
+            text(name).size(14).into(),
+        ])
+        .align_y(iced::Alignment::Center)
+        .width(Length::Shrink)
+        .spacing(6),
+    )
+    .style(button::text)
+    .padding(0)
+    .width(Length::Shrink)
+    .on_press(CollectionTreeMsg::OpenExample(req, name.to_owned()));
+
+    context_menu(
+        base,
+        vec![menu_item(
+            "Delete",
+            CollectionTreeMsg::ContextMenu(
+                req.0,
+                MenuAction::DeleteExample(req.1, name.to_owned()),
+            ),
+        )],
     )
 }
 
@@ -380,6 +611,14 @@ fn context_button_collection<'a>(
                 "New Request",
                 CollectionTreeMsg::ContextMenu(col, MenuAction::NewRequest(None)),
             ),
+            menu_item(
+                "New WebSocket",
+                CollectionTreeMsg::ContextMenu(col, MenuAction::NewWebSocket(None)),
+            ),
+            menu_item(
+                "New gRPC",
+                CollectionTreeMsg::ContextMenu(col, MenuAction::NewGrpc(None)),
+            ),
             menu_item(
                 "New Folder",
                 CollectionTreeMsg::ContextMenu(col, MenuAction::NewFolder(None)),
@@ -5,13 +5,15 @@ use iced::widget::text::Shaping::Advanced;
 use iced::widget::{container, pane_grid, text, Column, PaneGrid};
 use iced::{padding, Color, Element, Font, Length, Task};
 
+use crate::app::history::HistoryMsg;
 use crate::app::panels::PanelMsg;
 
-use crate::app::{collection_tree, panels};
+use crate::app::{collection_tree, history, panels};
 use crate::state::collection_tab::CollectionTab;
-use crate::state::{AppState, HttpTab, SplitState, Tab, TabKey};
+use crate::state::{AppState, GrpcTab, HttpTab, SideBarTab, SplitState, Tab, TabKey, WebSocketTab};
 use components::{
-    bordered_left, bordered_right, card_tab, card_tabs, colors, icon, icons, CardTab, TabBarAction,
+    bordered_left, bordered_right, button_tab, button_tabs, card_tab, card_tabs, colors, icon,
+    icons, CardTab, TabBarAction,
 };
 use core::http::request::Method;
 use core::http::CollectionRequest;
@@ -23,6 +25,8 @@ pub enum MainPageMsg {
     TabBarAction(TabBarAction<TabKey>),
     Panel(PanelMsg),
     CollectionTree(CollectionTreeMsg),
+    History(HistoryMsg),
+    SideBarTab(SideBarTab),
     SplitResize(ResizeEvent),
 }
 
@@ -44,6 +48,11 @@ impl MainPageMsg {
             }
             Self::Panel(msg) => msg.update(state).map(Self::Panel),
             Self::CollectionTree(msg) => msg.update(state).map(Self::CollectionTree),
+            Self::History(msg) => msg.update(state).map(Self::History),
+            Self::SideBarTab(tab) => {
+                state.side_bar = tab;
+                Task::none()
+            }
             Self::SplitResize(ResizeEvent { split, ratio }) => {
                 // Only allow resizing if the ratio is min 0.15 and max 0.3
                 if ratio > 0.1 && ratio < 0.3 {
@@ -76,10 +85,24 @@ pub fn view(state: &AppState) -> Element<MainPageMsg> {
 }
 
 fn side_bar(state: &AppState) -> Element<MainPageMsg> {
+    let tabs = button_tabs(
+        state.side_bar,
+        [
+            button_tab(SideBarTab::Collections, || text("Collections")),
+            button_tab(SideBarTab::History, || text("History")),
+        ]
+        .into_iter(),
+        MainPageMsg::SideBarTab,
+        None,
+    );
+    let content = match state.side_bar {
+        SideBarTab::Collections => collection_tree::view(state).map(MainPageMsg::CollectionTree),
+        SideBarTab::History => history::view(state).map(MainPageMsg::History),
+    };
+
     bordered_right(
         BORDER_WIDTH,
-        container(collection_tree::view(state).map(MainPageMsg::CollectionTree))
-            .padding(padding::right(4)),
+        container(Column::new().push(tabs).push(content).spacing(8)).padding(padding::right(4)),
     )
 }
 
@@ -114,6 +137,8 @@ fn tabs_view<'a>(
         .map(|(key, tab)| match tab {
             Tab::Http(tab) => tab_card(*key, tab),
             Tab::Collection(tab) => col_tab(*key, tab),
+            Tab::WebSocket(tab) => ws_tab(*key, tab),
+            Tab::Grpc(tab) => grpc_tab(*key, tab),
         })
         .collect();
 
@@ -130,6 +155,28 @@ fn col_tab(key: TabKey, tab: &CollectionTab) -> CardTab<TabKey> {
     card_tab(key, icon(icons::Folder), text(&tab.name))
 }
 
+fn ws_tab(key: TabKey, tab: &WebSocketTab) -> CardTab<TabKey> {
+    let color = if tab.is_active() {
+        colors::LIME_GREEN
+    } else {
+        colors::CYAN
+    };
+    let dirty_flag = if tab.is_request_dirty() { " " } else { "" };
+    let name = text(format!("{}{}", dirty_flag, tab.name)).shaping(Advanced);
+    card_tab(key, icon(icons::LanConnect).color(color), name)
+}
+
+fn grpc_tab(key: TabKey, tab: &GrpcTab) -> CardTab<TabKey> {
+    let color = if tab.response.is_executing() {
+        colors::LIME_GREEN
+    } else {
+        colors::CYAN
+    };
+    let dirty_flag = if tab.is_request_dirty() { " " } else { "" };
+    let name = text(format!("{}{}", dirty_flag, tab.name)).shaping(Advanced);
+    card_tab(key, icon(icons::Protocol).color(color), name)
+}
+
 fn tab_card<'a>(key: TabKey, tab: &'a HttpTab) -> CardTab<'a, TabKey> {
     let dirty_flag = if tab.is_request_dirty() { "" } else { "" };
     card_tab(
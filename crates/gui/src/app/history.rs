@@ -0,0 +1,231 @@
+use iced::font::Weight;
+use iced::widget::{
+    button, column, container, pick_list, row, text, text_input, Column, Scrollable,
+};
+use iced::{clipboard, padding, Color, Element, Font, Length, Task};
+use strum::VariantArray;
+
+use components::{colors, context_menu, horizontal_line, icon, icons, menu_item, tooltip};
+use core::http::request::Method;
+use core::http::CollectionRequest;
+use core::persistence::history::{HistoryEntry, StatusFilter};
+use core::utils::fmt_duration;
+
+use crate::commands::builders::clear_history_cmd;
+use crate::state::popups::Popup;
+use crate::state::{AppState, GrpcTab, HttpTab, Tab};
+
+#[derive(Debug, Clone)]
+pub enum HistoryMsg {
+    Search(String),
+    Method(Method),
+    Status(StatusFilter),
+    Host(String),
+    ClearFilters,
+    Replay(String),
+    SaveToCollection(String),
+    CopyUrl(String),
+    Clear,
+    Cleared,
+}
+
+impl HistoryMsg {
+    pub fn update(self, state: &mut AppState) -> Task<Self> {
+        let filter = &mut state.history.filter;
+        match self {
+            HistoryMsg::Search(search) => filter.text = search,
+            HistoryMsg::Method(method) => filter.method = Some(method),
+            HistoryMsg::Status(status) => filter.status = Some(status),
+            HistoryMsg::Host(host) => filter.host = Some(host),
+            HistoryMsg::ClearFilters => *filter = Default::default(),
+            HistoryMsg::Replay(id) => {
+                replay(state, &id);
+            }
+            HistoryMsg::SaveToCollection(id) => {
+                if replay(state, &id) {
+                    if let Some(key) = state.active_tab {
+                        Popup::save_request(state, key);
+                    }
+                }
+            }
+            HistoryMsg::CopyUrl(id) => {
+                if let Some(entry) = state.history.get(&id) {
+                    return clipboard::write(entry.url().to_owned());
+                }
+            }
+            HistoryMsg::Clear => {
+                state.history.entries.clear();
+                return clear_history_cmd(|| HistoryMsg::Cleared);
+            }
+            HistoryMsg::Cleared => (),
+        }
+        Task::none()
+    }
+}
+
+/// Opens the request of the entry in a new tab, outside of any collection
+fn replay(state: &mut AppState, id: &str) -> bool {
+    let Some(entry) = state.history.get(id) else {
+        return false;
+    };
+    let req_ref = CollectionRequest(Default::default(), Default::default());
+    if let Some(request) = entry.to_grpc_request() {
+        let tab = GrpcTab::new(tab_name(entry), request, req_ref);
+        state.open_tab(Tab::Grpc(tab));
+        return true;
+    }
+    let Some(request) = entry.to_request() else {
+        log::error!("Cannot replay {} {}", entry.method(), entry.url());
+        return false;
+    };
+
+    let tab = HttpTab::new(tab_name(entry), request, req_ref);
+    state.open_tab(Tab::Http(tab));
+    true
+}
+
+fn tab_name(entry: &HistoryEntry) -> String {
+    let path = entry
+        .url()
+        .split(['?', '#'])
+        .next()
+        .and_then(|url| url.trim_end_matches('/').rsplit('/').next())
+        .unwrap_or_default();
+    match path.is_empty() {
+        true => "Untitled".to_string(),
+        false => path.to_string(),
+    }
+}
+
+fn status_color(status: Option<u16>) -> Color {
+    match status {
+        Some(200..=299) => Color::from_rgb8(0, 200, 0),
+        Some(300..=399) => Color::from_rgb8(0, 0, 200),
+        Some(400..=499) => Color::from_rgb8(200, 200, 0),
+        Some(500..=599) | None => Color::from_rgb8(200, 0, 0),
+        Some(_) => Color::WHITE,
+    }
+}
+
+fn entry_view(entry: &HistoryEntry) -> Element<HistoryMsg> {
+    let status = match entry.status() {
+        Some(status) => status.to_string(),
+        None => "ERR".to_string(),
+    };
+    let started = entry
+        .started()
+        .map(|time| time.format("%d %b %H:%M:%S").to_string())
+        .unwrap_or_default();
+    let mut details = vec![started, fmt_duration(entry.duration())];
+    details.extend(entry.environment.clone());
+
+    let method = match entry.grpc {
+        Some(_) => "gRPC",
+        None => entry.method(),
+    };
+    let summary = row![
+        text(method).size(12).color(colors::CYAN).font(Font {
+            weight: Weight::Bold,
+            ..Default::default()
+        }),
+        text(status).size(12).color(status_color(entry.status())),
+        text(entry.url()).size(12),
+    ]
+    .spacing(6);
+
+    let base = button(
+        column![
+            summary,
+            text(details.join(" · ")).size(10).color(colors::DARK_GREY),
+        ]
+        .spacing(2),
+    )
+    .style(button::text)
+    .padding([2, 4])
+    .width(Length::Fill)
+    .on_press(HistoryMsg::Replay(entry.id.clone()));
+
+    // Only HTTP tabs are saved to a collection from the popup
+    let mut items = vec![menu_item(
+        "Open in New Tab",
+        HistoryMsg::Replay(entry.id.clone()),
+    )];
+    if entry.grpc.is_none() {
+        items.push(menu_item(
+            "Save to Collection",
+            HistoryMsg::SaveToCollection(entry.id.clone()),
+        ));
+    }
+    items.push(menu_item("Copy URL", HistoryMsg::CopyUrl(entry.id.clone())));
+
+    context_menu(base, items)
+}
+
+pub fn view(state: &AppState) -> Element<HistoryMsg> {
+    let history = &state.history;
+    let filter = &history.filter;
+
+    let search = text_input("Search history", &filter.text)
+        .on_input(HistoryMsg::Search)
+        .size(14)
+        .padding([2, 4]);
+
+    let filters = row![
+        pick_list(Method::VARIANTS, filter.method, HistoryMsg::Method)
+            .placeholder("Method")
+            .text_size(12)
+            .padding([2, 4]),
+        pick_list(StatusFilter::VARIANTS, filter.status, HistoryMsg::Status)
+            .placeholder("Status")
+            .text_size(12)
+            .padding([2, 4]),
+        pick_list(history.hosts(), filter.host.clone(), HistoryMsg::Host)
+            .placeholder("Host")
+            .text_size(12)
+            .padding([2, 4])
+            .width(Length::Fill),
+        tooltip(
+            "Clear Filters",
+            components::icon_button(icons::Close, Some(20), Some(8))
+                .style(button::secondary)
+                .on_press(HistoryMsg::ClearFilters),
+        ),
+        tooltip(
+            "Clear History",
+            components::icon_button(icons::Delete, Some(20), Some(8))
+                .style(button::secondary)
+                .on_press(HistoryMsg::Clear),
+        ),
+    ]
+    .spacing(4);
+
+    let entries = history.filtered().map(entry_view).collect::<Vec<_>>();
+    let list: Element<HistoryMsg> = if entries.is_empty() {
+        container(
+            Column::new()
+                .push(icon(icons::History).size(40.0))
+                .push(text("No requests").size(14))
+                .align_x(iced::Alignment::Center),
+        )
+        .center_x(Length::Fill)
+        .padding(16)
+        .into()
+    } else {
+        Scrollable::new(
+            Column::with_children(entries)
+                .spacing(4)
+                .padding(padding::right(12).bottom(12)),
+        )
+        .height(Length::Fill)
+        .into()
+    };
+
+    Column::new()
+        .push(search)
+        .push(filters)
+        .push(horizontal_line(2))
+        .push(list)
+        .spacing(7)
+        .width(Length::Fill)
+        .into()
+}
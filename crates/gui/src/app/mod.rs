@@ -1,4 +1,4 @@
-use iced::Task;
+use iced::{Subscription, Task};
 
 use components::modal;
 use popups::PopupMsg;
@@ -9,6 +9,7 @@ use crate::{commands::TaskMsg, AppState};
 
 mod collection_tree;
 mod content_section;
+mod history;
 mod panels;
 mod popups;
 
@@ -28,6 +29,10 @@ pub fn update(state: &mut AppState, msg: AppMsg) -> Task<AppMsg> {
     Task::batch([cmd, commands::background(state).map(AppMsg::Command)])
 }
 
+pub fn subscription(state: &AppState) -> Subscription<AppMsg> {
+    commands::subscription(state).map(AppMsg::Command)
+}
+
 pub fn view(state: &AppState) -> iced::Element<AppMsg> {
     let main_page = content_section::view(state).map(AppMsg::MainPage);
 
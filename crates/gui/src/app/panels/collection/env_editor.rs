@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use iced::widget::{button, horizontal_space, pick_list, scrollable, Column, Row};
 use iced::{Alignment, Element, Length, Task};
 
@@ -17,7 +19,8 @@ pub enum Message {
     EnvUpdate(EnvironmentKey, components::KeyValUpdateMsg),
     CreatNewEnv,
     RenameEnv(EnvironmentKey),
-    Saved,
+    UnlockSecrets,
+    Saved(Option<Arc<anyhow::Error>>),
 }
 
 impl Message {
@@ -39,13 +42,23 @@ impl Message {
                 }
             }
             Message::SaveEnvs => {
+                // Secret values typed while the store is locked would be
+                // lost, they stay in the editor until it is unlocked
+                if state.secrets.is_none() && data.has_secret_values() {
+                    Popup::unlock_secrets(state);
+                    return Task::none();
+                }
                 if let Some(collection) = state.collections.get_mut(collection_key) {
                     for (key, env) in data.environments.iter() {
                         collection.update_environment(*key, env.into());
                     }
-                    return builders::save_environments_cmd(collection, &data.deleted, || {
-                        Message::Saved
-                    });
+                    let secrets = state.secrets.as_mut();
+                    return builders::save_environments_cmd(
+                        collection,
+                        &data.deleted,
+                        secrets,
+                        Message::Saved,
+                    );
                 }
             }
             Message::EnvUpdate(env, update) => {
@@ -60,9 +73,14 @@ impl Message {
                 data.deleted.push(env);
                 data.selected_env = None;
             }
-            Message::Saved => {
+            Message::Saved(None) => {
                 data.edited = false;
             }
+            Message::Saved(Some(e)) => {
+                log::error!("Error saving environments: {:?}", e);
+                let name = format!("Environments of {}", tab.name);
+                Popup::save_error(state, name, &e);
+            }
             Message::CreatNewEnv => {
                 Popup::popup_name(
                     state,
@@ -70,6 +88,7 @@ impl Message {
                     PopupNameAction::CreateEnvironment(key),
                 );
             }
+            Message::UnlockSecrets => Popup::unlock_secrets(state),
             Message::RenameEnv(env_key) => {
                 let name = data
                     .environments
@@ -100,11 +119,12 @@ fn icon_button<'a>(
     )
 }
 
-pub fn view<'a>(tab: &'a CollectionTab) -> Element<'a, Message> {
+pub fn view<'a>(state: &'a AppState, tab: &'a CollectionTab) -> Element<'a, Message> {
     let editor = &tab.env_editor;
     let environments = &editor.environments;
 
     let selected = editor.selected_env;
+    let locked = state.secrets.is_none();
 
     let env_tabs: Vec<_> = environments
         .iter()
@@ -119,6 +139,9 @@ pub fn view<'a>(tab: &'a CollectionTab) -> Element<'a, Message> {
         .push("Edit Environments")
         .push(horizontal_space().width(Length::FillPortion(3)))
         .push(icon_button("Create New", icons::Plus, Message::CreatNewEnv))
+        .push_maybe(
+            locked.then(|| icon_button("Unlock Secrets", icons::Lock, Message::UnlockSecrets)),
+        )
         .push_maybe(selected.map(|s| icon_button("Rename", icons::Pencil, Message::RenameEnv(s))))
         .push_maybe(selected.map(|s| icon_button("Delete", icons::Delete, Message::DeleteEnv(s))))
         .push_maybe(editor.edited.then_some(icon_button(
@@ -139,7 +162,10 @@ pub fn view<'a>(tab: &'a CollectionTab) -> Element<'a, Message> {
         .and_then(|s| environments.get(&s).map(|e| (e, s)))
         .map(|(env, selected)| {
             let update_env = move |u| Message::EnvUpdate(selected, u);
-            scrollable(key_value_editor(&env.variables).on_change(update_env)).width(Length::Fill)
+            let editor = key_value_editor(&env.variables)
+                .secrets(locked)
+                .on_change(update_env);
+            scrollable(editor).width(Length::Fill)
         });
 
     Column::new()
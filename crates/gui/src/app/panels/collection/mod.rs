@@ -18,12 +18,11 @@ pub enum CollectionTabMsg {
 
 impl CollectionTabMsg {
     pub fn update(self, state: &mut AppState) -> Task<Self> {
-        let Some(Tab::Collection(tab)) = state.active_tab_mut() else {
-            return Task::none();
-        };
         match self {
             CollectionTabMsg::TabChange(id) => {
-                tab.tab = id;
+                if let Some(Tab::Collection(tab)) = state.active_tab_mut() {
+                    tab.tab = id;
+                }
                 Task::none()
             }
             CollectionTabMsg::EnvEditor(msg) => msg.update(state).map(CollectionTabMsg::EnvEditor),
@@ -32,10 +31,12 @@ impl CollectionTabMsg {
     }
 }
 
-pub fn view<'a>(tab: &'a CollectionTab) -> Element<'a, CollectionTabMsg> {
+pub fn view<'a>(state: &'a AppState, tab: &'a CollectionTab) -> Element<'a, CollectionTabMsg> {
     let tab_content = match tab.tab {
-        CollectionTabId::Environments => env_editor::view(tab).map(CollectionTabMsg::EnvEditor),
-        CollectionTabId::Settings => settings::view(tab).map(CollectionTabMsg::Settings),
+        CollectionTabId::Environments => {
+            env_editor::view(state, tab).map(CollectionTabMsg::EnvEditor)
+        }
+        CollectionTabId::Settings => settings::view(state, tab).map(CollectionTabMsg::Settings),
     };
 
     let tabs = button_tabs(
@@ -1,19 +1,57 @@
-use components::{icon, tooltip, NerdIcon};
+use std::sync::Arc;
+use std::time::Duration;
+
+use components::{colors, icon, icons, key_value_editor, tooltip, KeyValUpdateMsg, NerdIcon};
+use core::http::CollectionKey;
+use core::mock::MockOptions;
 use iced::{
     padding,
-    widget::{button, horizontal_space, pick_list, scrollable, Column, Row},
+    widget::{button, horizontal_space, pick_list, scrollable, text, text_input, Column, Row},
     Alignment, Element, Length, Task,
 };
 
-use crate::state::{collection_tab::CollectionTab, AppState, Tab};
+use crate::commands::builders::{save_collection_file_cmd, start_mock_cmd, MockStatus};
+use crate::state::collection_tab::{CollectionTab, MockServer};
+use crate::state::popups::Popup;
+use crate::state::utils::to_core_kv_list;
+use crate::state::{AppState, Tab};
 
 #[derive(Debug, Clone)]
 pub enum Message {
     UpdateDefaultEnv(String),
+    VariablesChanged(KeyValUpdateMsg),
+    SaveVariables,
+    VariablesSaved(Option<Arc<anyhow::Error>>),
+    MockPort(String),
+    MockLatency(String),
+    MockErrorRate(String),
+    ToggleMock,
+    MockStatus(CollectionKey, MockStatus),
 }
 
 impl Message {
     pub fn update(self, state: &mut AppState) -> Task<Message> {
+        // Arrives while any tab is active
+        if let Message::MockStatus(col, status) = self {
+            let server = state.mocks.remove(&col);
+            match (server, status) {
+                (Some(MockServer::Starting(cancel)), MockStatus::Listening(addr)) => {
+                    state
+                        .mocks
+                        .insert(col, MockServer::Running { addr, cancel });
+                }
+                (Some(server), MockStatus::Listening(_)) => {
+                    state.mocks.insert(col, server);
+                }
+                (_, MockStatus::Failed(e)) => {
+                    log::error!("Mock server failed: {:?}", e);
+                    state.mocks.insert(col, MockServer::Failed(e.to_string()));
+                }
+                (None, MockStatus::Listening(_)) => (),
+            }
+            return Task::none();
+        }
+
         let active_tab = state.active_tab.and_then(|key| state.tabs.get_mut(&key));
         let Some(Tab::Collection(tab)) = active_tab else {
             return Task::none();
@@ -32,6 +70,54 @@ impl Message {
                     tab.default_env = Some(name);
                 }
             }
+            Message::VariablesChanged(msg) => {
+                tab.variables.update(msg);
+                tab.variables_edited = true;
+            }
+            Message::SaveVariables => {
+                let variables = to_core_kv_list(&tab.variables);
+                if let Some(collection) = state.collections.get_mut(tab.collection_key) {
+                    collection.variables = variables;
+                    return save_collection_file_cmd(collection, Message::VariablesSaved);
+                }
+            }
+            Message::VariablesSaved(None) => tab.variables_edited = false,
+            Message::VariablesSaved(Some(e)) => {
+                log::error!("Error saving collection variables: {:?}", e);
+                let name = format!("Variables of {}", tab.name);
+                Popup::save_error(state, name, &e);
+            }
+            Message::MockPort(port) => tab.mock.port = port,
+            Message::MockLatency(latency) => tab.mock.latency = latency,
+            Message::MockErrorRate(rate) => tab.mock.error_rate = rate,
+            Message::ToggleMock => {
+                let col = tab.collection_key;
+                // Dropping the running server stops it
+                if state.mocks.remove(&col).is_some_and(|m| m.is_active()) {
+                    return Task::none();
+                }
+
+                let port = tab.mock.port.trim().parse::<u16>();
+                let latency = tab.mock.latency.trim().parse::<u64>();
+                let error_rate = tab.mock.error_rate.trim().parse::<f64>();
+                let (Ok(port), Ok(latency), Ok(error_rate @ 0.0..=100.0)) =
+                    (port, latency, error_rate)
+                else {
+                    let msg = "Port, latency or error rate is not valid".to_string();
+                    state.mocks.insert(col, MockServer::Failed(msg));
+                    return Task::none();
+                };
+
+                let options = MockOptions {
+                    latency: Duration::from_millis(latency),
+                    error_rate: error_rate / 100.0,
+                    ..MockOptions::default()
+                };
+                return start_mock_cmd(state, col, port, options, move |status| {
+                    Message::MockStatus(col, status)
+                });
+            }
+            Message::MockStatus(..) => (),
         };
 
         Task::none()
@@ -51,7 +137,95 @@ fn icon_button<'a>(
     )
 }
 
-pub fn view<'a>(tab: &'a CollectionTab) -> Element<'a, Message> {
+fn variables(tab: &CollectionTab) -> Element<'_, Message> {
+    let actions = Row::new()
+        .push("Variables")
+        .push(horizontal_space())
+        .push_maybe(
+            tab.variables_edited
+                .then(|| icon_button("Save Changes", icons::ContentSave, Message::SaveVariables)),
+        )
+        .spacing(8)
+        .align_y(Alignment::Center);
+
+    let info = text("Shared by the environments of the collection, which override them").size(12);
+    let editor = key_value_editor(&tab.variables).on_change(Message::VariablesChanged);
+
+    Column::new()
+        .push(actions)
+        .push(info)
+        .push(editor)
+        .spacing(8)
+        .into()
+}
+
+fn mock_server<'a>(state: &'a AppState, tab: &'a CollectionTab) -> Element<'a, Message> {
+    let size = 14;
+    let server = state.mocks.get(&tab.collection_key);
+    let active = server.is_some_and(MockServer::is_active);
+
+    let status = match server {
+        Some(MockServer::Starting(_)) => text("Starting...").size(size),
+        Some(MockServer::Running { addr, .. }) => {
+            text(format!("Listening on http://{}", addr)).size(size)
+        }
+        Some(MockServer::Failed(e)) => text(e).size(size).color(colors::RED),
+        None => text("Serves the saved examples of the requests").size(size),
+    };
+
+    let toggle = button(text(if active { "Stop" } else { "Start" }).size(size))
+        .padding([2, 8])
+        .style(if active {
+            button::danger
+        } else {
+            button::primary
+        })
+        .on_press(Message::ToggleMock);
+
+    let field = |label, value: &'a str, on_input: fn(String) -> Message| {
+        Row::new()
+            .push(text(label).size(size))
+            .push(
+                text_input("", value)
+                    .on_input_maybe((!active).then_some(on_input))
+                    .size(size)
+                    .width(Length::Fixed(80.)),
+            )
+            .spacing(4)
+            .align_y(Alignment::Center)
+    };
+
+    let options = Row::new()
+        .push(field("Port", &tab.mock.port, Message::MockPort))
+        .push(field(
+            "Latency (ms)",
+            &tab.mock.latency,
+            Message::MockLatency,
+        ))
+        .push(field(
+            "Error rate (%)",
+            &tab.mock.error_rate,
+            Message::MockErrorRate,
+        ))
+        .spacing(16)
+        .align_y(Alignment::Center);
+
+    Column::new()
+        .push(
+            Row::new()
+                .push("Mock Server")
+                .push(horizontal_space())
+                .push(status)
+                .push(toggle)
+                .spacing(8)
+                .align_y(Alignment::Center),
+        )
+        .push(options)
+        .spacing(8)
+        .into()
+}
+
+pub fn view<'a>(state: &'a AppState, tab: &'a CollectionTab) -> Element<'a, Message> {
     let environments = &tab.env_editor.environments;
     let envs: Vec<_> = environments
         .iter()
@@ -75,6 +249,8 @@ pub fn view<'a>(tab: &'a CollectionTab) -> Element<'a, Message> {
     scrollable(
         Column::new()
             .push(default_env)
+            .push(variables(tab))
+            .push(mock_server(state, tab))
             .spacing(8)
             .width(Length::Fill)
             .height(Length::Shrink)
@@ -0,0 +1,137 @@
+use iced::widget::{button, container, horizontal_space, scrollable, text, Column, Row};
+use iced::{Alignment, Element, Length};
+
+use components::{
+    button_tab, button_tabs, code_editor, colors, icon_button, icons, key_value_editor, tooltip,
+    ContentType,
+};
+
+use super::GrpcTabMsg;
+use crate::state::grpc_tab::{Descriptors, GrpcTabId};
+use crate::state::GrpcTab;
+
+fn descriptors_status(tab: &GrpcTab) -> Element<GrpcTabMsg> {
+    let size = 12;
+    let status = match &tab.descriptors {
+        Descriptors::NotLoaded => text("Services not loaded").color(colors::DARK_GREY),
+        Descriptors::Loading => text("Loading services...").color(colors::DARK_GREY),
+        Descriptors::Loaded { methods, .. } => {
+            text(format!("{} methods available", methods.len())).color(colors::DARK_GREY)
+        }
+        Descriptors::Failed(e) => text(format!("{:#}", e)).color(colors::RED),
+    };
+    status.size(size).into()
+}
+
+fn message_view(tab: &GrpcTab) -> Element<GrpcTabMsg> {
+    let streaming = tab.selected_method().is_some_and(|m| m.server_streaming);
+
+    let header = Row::new()
+        .push(text("Message").size(14))
+        .push_maybe(streaming.then(|| text("server streaming").size(12).color(colors::DARK_GREY)))
+        .spacing(8)
+        .align_y(Alignment::Center);
+
+    let editor = container(
+        code_editor(&tab.message, ContentType::Json)
+            .editable()
+            .on_action(GrpcTabMsg::Message),
+    )
+    .height(Length::Fill)
+    .width(Length::Fill);
+
+    Column::new()
+        .push(header)
+        .push(editor)
+        .push(descriptors_status(tab))
+        .spacing(4)
+        .into()
+}
+
+fn metadata_view(tab: &GrpcTab) -> Element<GrpcTabMsg> {
+    scrollable(
+        Column::new()
+            .push("Metadata")
+            .push(key_value_editor(&tab.metadata).on_change(GrpcTabMsg::Metadata))
+            .width(Length::Fill)
+            .spacing(4),
+    )
+    .height(Length::Fill)
+    .width(Length::Fill)
+    .into()
+}
+
+fn proto_view(tab: &GrpcTab) -> Element<GrpcTabMsg> {
+    let size = 14;
+    let header = Row::new()
+        .push(text("Proto Files").size(size))
+        .push(horizontal_space())
+        .push(
+            button(text("Add Files").size(size))
+                .padding([2, 8])
+                .on_press(GrpcTabMsg::AddProtoFiles),
+        )
+        .align_y(Alignment::Center);
+
+    let files: Element<GrpcTabMsg> = if tab.proto_files.is_empty() {
+        text("No proto files, services are loaded using server reflection")
+            .size(12)
+            .color(colors::DARK_GREY)
+            .into()
+    } else {
+        tab.proto_files
+            .iter()
+            .enumerate()
+            .fold(Column::new(), |col, (idx, file)| {
+                let row = Row::new()
+                    .push(text(file.display().to_string()).size(size))
+                    .push(horizontal_space())
+                    .push(tooltip(
+                        "Remove",
+                        icon_button(icons::Delete, Some(20), Some(12))
+                            .on_press(GrpcTabMsg::RemoveProtoFile(idx))
+                            .style(button::secondary),
+                    ))
+                    .spacing(4)
+                    .align_y(Alignment::Center);
+                col.push(row)
+            })
+            .spacing(4)
+            .into()
+    };
+
+    Column::new()
+        .push(header)
+        .push(scrollable(files).height(Length::Fill).width(Length::Fill))
+        .push(descriptors_status(tab))
+        .spacing(8)
+        .into()
+}
+
+pub(super) fn view(tab: &GrpcTab) -> Element<GrpcTabMsg> {
+    let tab_content = match tab.tab {
+        GrpcTabId::Message => message_view(tab),
+        GrpcTabId::Metadata => metadata_view(tab),
+        GrpcTabId::Proto => proto_view(tab),
+    };
+
+    let tabs = button_tabs(
+        tab.tab,
+        [
+            button_tab(GrpcTabId::Message, || text("Message")),
+            button_tab(GrpcTabId::Metadata, || text("Metadata")),
+            button_tab(GrpcTabId::Proto, || text("Proto")),
+        ]
+        .into_iter(),
+        GrpcTabMsg::TabSelected,
+        None,
+    );
+
+    Column::new()
+        .push(tabs)
+        .push(tab_content)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .spacing(4)
+        .into()
+}
@@ -0,0 +1,253 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use iced::padding;
+use iced::widget::pane_grid::ResizeEvent;
+use iced::widget::{container, pane_grid, Column, PaneGrid};
+use iced::{Element, Task};
+use prost_reflect::DescriptorPool;
+
+use components::text_editor::{self, ContentAction};
+use components::{bordered_left, bordered_right, CodeEditorMsg, KeyValUpdateMsg};
+use core::grpc::{message_template, GrpcMethod};
+use core::persistence::history::{GrpcTarget, HistoryEntry};
+use core::persistence::request::RequestOnDisk;
+use core::transformers::grpc::transform_url;
+
+use crate::app::panels::http::panes::response::{self, update_response, ResponsePaneMsg};
+use crate::commands::builders::{
+    load_grpc_descriptors_cmd, save_failed_cmd, save_grpc_cmd, save_history_cmd, send_grpc_cmd,
+    ResponseResult,
+};
+use crate::commands::dialog::open_files_dialog;
+use crate::state::grpc_tab::{Descriptors, GrpcTabId};
+use crate::state::watch::save_conflict;
+use crate::state::{AppState, GrpcTab, SplitState, Tab, TabKey};
+
+mod config;
+mod url_bar;
+
+const BORDER_WIDTH: u16 = 1;
+
+#[derive(Debug, Clone)]
+pub enum GrpcTabMsg {
+    UrlChanged(ContentAction),
+    MethodSelected(GrpcMethod),
+    LoadDescriptors,
+    DescriptorsLoaded(TabKey, Result<DescriptorPool, Arc<anyhow::Error>>),
+    Send,
+    RequestResult(TabKey, ResponseResult),
+    HistorySaved,
+    Save,
+    Saved(TabKey, Option<Arc<anyhow::Error>>),
+    SaveConflict(TabKey, Option<RequestOnDisk>),
+    TabSelected(GrpcTabId),
+    Message(CodeEditorMsg),
+    Metadata(KeyValUpdateMsg),
+    AddProtoFiles,
+    ProtoFilesPicked(TabKey, Vec<PathBuf>),
+    RemoveProtoFile(usize),
+    Res(ResponsePaneMsg),
+    SplitResize(ResizeEvent),
+}
+
+impl GrpcTabMsg {
+    /// Changes the request saved to the file
+    fn is_edit(&self) -> bool {
+        match self {
+            Self::UrlChanged(action) => action.is_edit(),
+            Self::Message(CodeEditorMsg::EditorAction(action, _)) => action.is_edit(),
+            Self::MethodSelected(_) | Self::Metadata(_) | Self::RemoveProtoFile(_) => true,
+            _ => false,
+        }
+    }
+
+    pub fn update(self, state: &mut AppState) -> Task<Self> {
+        let active = state.active_tab.zip(state.active_tab_mut());
+        let Some((active_tab, Tab::Grpc(tab))) = active else {
+            return Task::none();
+        };
+
+        if self.is_edit() {
+            tab.check_dirty();
+        }
+        match self {
+            Self::UrlChanged(action) => {
+                let edited = action.is_edit();
+                tab.url_content.perform(action);
+                // Reflected services belong to the server the URL points to
+                if edited && tab.proto_files.is_empty() {
+                    tab.descriptors = Descriptors::NotLoaded;
+                }
+            }
+            Self::MethodSelected(method) => {
+                let message = tab.message.text();
+                if message.trim().is_empty() || message.trim() == "{}" {
+                    let template = tab
+                        .descriptors
+                        .pool()
+                        .and_then(|pool| message_template(pool, &method));
+                    if let Some(template) = template {
+                        tab.message = text_editor::Content::with_text(&template);
+                    }
+                }
+                tab.service = method.service;
+                tab.method = method.method;
+            }
+            Self::LoadDescriptors => {
+                let cb = move |r| Self::DescriptorsLoaded(active_tab, r);
+                return load_grpc_descriptors_cmd(state, active_tab, cb);
+            }
+            Self::DescriptorsLoaded(key, result) => {
+                if let Some(Tab::Grpc(tab)) = state.get_tab_mut(key) {
+                    tab.descriptors = match result {
+                        Ok(pool) => Descriptors::loaded(pool),
+                        Err(e) => Descriptors::Failed(e),
+                    };
+                }
+            }
+            Self::Send => {
+                tab.cancel_tasks();
+                let cb = move |r| Self::RequestResult(active_tab, r);
+                return send_grpc_cmd(state, active_tab, cb);
+            }
+            Self::RequestResult(key, result) => {
+                let entry = history_entry(state, key, &result);
+                if let Some(tab) = state.get_tab_mut(key) {
+                    update_response(tab, result);
+                }
+                if let Some(entry) = entry {
+                    state.history.push(entry.clone());
+                    return save_history_cmd(entry, || Self::HistorySaved);
+                }
+            }
+            Self::HistorySaved => (),
+            Self::Save => {
+                let req = tab.to_request();
+                let collection_ref = tab.collection_ref;
+                if let Some(req_ref) = state.collections.get_ref(collection_ref) {
+                    let path = req_ref.path.clone();
+                    let cb = move |r| Self::Saved(active_tab, r);
+                    return save_grpc_cmd(req, path, cb);
+                }
+            }
+            Self::Saved(key, None) => {
+                if let Some(Tab::Grpc(tab)) = state.get_tab_mut(key) {
+                    tab.check_dirty();
+                }
+            }
+            Self::Saved(key, Some(e)) => {
+                return save_failed_cmd(state, key, &e, move |r| Self::SaveConflict(key, r));
+            }
+            Self::SaveConflict(key, theirs) => {
+                if let Some(theirs) = theirs {
+                    save_conflict(state, key, theirs);
+                }
+            }
+            Self::TabSelected(id) => tab.tab = id,
+            Self::Message(msg) => msg.update(&mut tab.message),
+            Self::Metadata(msg) => tab.metadata.update(msg),
+            Self::AddProtoFiles => {
+                return open_files_dialog(
+                    "Select Proto Files",
+                    ("Protobuf", &["proto"]),
+                    move |files| Self::ProtoFilesPicked(active_tab, files),
+                );
+            }
+            Self::ProtoFilesPicked(key, files) => {
+                let dir = state
+                    .get_tab(key)
+                    .and_then(Tab::collection_ref)
+                    .and_then(|req| state.collections.get_ref(req))
+                    .and_then(|req| req.path.parent())
+                    .map(|dir| dir.to_path_buf());
+
+                let Some(Tab::Grpc(tab)) = state.get_tab_mut(key) else {
+                    return Task::none();
+                };
+                for file in files {
+                    // Files next to the request are stored relative to it so
+                    // the collection can be moved around
+                    let file = dir
+                        .as_deref()
+                        .and_then(|dir| file.strip_prefix(dir).ok())
+                        .map(|f| f.to_path_buf())
+                        .unwrap_or(file);
+                    if !tab.proto_files.contains(&file) {
+                        tab.proto_files.push(file);
+                    }
+                }
+                tab.descriptors = Descriptors::NotLoaded;
+                tab.check_dirty();
+            }
+            Self::RemoveProtoFile(idx) => {
+                if idx < tab.proto_files.len() {
+                    tab.proto_files.remove(idx);
+                    tab.descriptors = Descriptors::NotLoaded;
+                }
+            }
+            Self::Res(msg) => return msg.update(state).map(Self::Res),
+            Self::SplitResize(ResizeEvent { split, ratio }) => {
+                // Only allow resizing if the ratio is min 0.25 on both sides
+                if ratio > 0.25 && ratio < 0.75 {
+                    tab.panes.resize(split, ratio);
+                }
+            }
+        }
+        Task::none()
+    }
+}
+
+/// History entry of a unary call once its response is complete or it failed,
+/// streamed responses are not kept like those of HTTP requests
+fn history_entry(state: &AppState, key: TabKey, res: &ResponseResult) -> Option<HistoryEntry> {
+    let Some(Tab::Grpc(tab)) = state.get_tab(key) else {
+        return None;
+    };
+    let sent = tab.response.sent.clone()?;
+    let env = state.environment(tab.collection_key());
+
+    let response = match res {
+        ResponseResult::Completed(res) => Ok(res),
+        ResponseResult::Error(e) => Err(e.as_ref()),
+        _ => return None,
+    };
+    let req = tab.to_request();
+    let target = GrpcTarget {
+        url: transform_url(&req.url, env.as_ref()).unwrap_or(req.url),
+        service: req.service,
+        method: req.method,
+    };
+    Some(HistoryEntry::grpc(target, sent, response, env.as_ref()))
+}
+
+pub fn view(tab: &GrpcTab) -> Element<GrpcTabMsg> {
+    let url_bar = url_bar::view(tab);
+
+    let panes = PaneGrid::new(&tab.panes, move |_, pane, _| {
+        let pane = match pane {
+            SplitState::First => bordered_right(
+                BORDER_WIDTH,
+                container(config::view(tab)).padding(padding::right(4)),
+            ),
+            SplitState::Second => bordered_left(
+                BORDER_WIDTH,
+                container(response::view(&tab.response, None).map(GrpcTabMsg::Res))
+                    .padding(padding::left(4)),
+            ),
+        };
+
+        pane_grid::Content::new(pane)
+    })
+    .height(iced::Length::Fill)
+    .width(iced::Length::Fill)
+    .on_resize(8, GrpcTabMsg::SplitResize);
+
+    Column::new()
+        .push(url_bar)
+        .push(container(panes).padding(padding::top(4)))
+        .height(iced::Length::Fill)
+        .width(iced::Length::Fill)
+        .spacing(4)
+        .into()
+}
@@ -0,0 +1,86 @@
+use iced::widget::{button, container, pick_list, text, vertical_rule, Button, Row};
+use iced::{border, Alignment, Border, Element};
+
+use components::text_editor::{self, line_editor};
+use components::{icon, icons, NerdIcon};
+
+use super::GrpcTabMsg;
+use crate::state::grpc_tab::Descriptors;
+use crate::state::GrpcTab;
+
+fn icon_button<'a>(ico: NerdIcon) -> Button<'a, GrpcTabMsg> {
+    button(container(icon(ico)).padding([0, 8])).style(|t, s| button::Style {
+        border: border::rounded(0),
+        ..button::primary(t, s)
+    })
+}
+
+pub(super) fn view(tab: &GrpcTab) -> Element<GrpcTabMsg> {
+    let border = Border::default();
+
+    let scheme = container(text("gRPC").size(14))
+        .padding([4, 10])
+        .align_y(Alignment::Center)
+        .style(move |theme| container::Style {
+            border: border.rounded(border::left(4)),
+            ..container::bordered_box(theme)
+        });
+
+    let url = line_editor(&tab.url_content)
+        .style(move |t: &iced::Theme, _| text_editor::Style {
+            border,
+            ..text_editor::default(t, text_editor::Status::Active)
+        })
+        .on_action(GrpcTabMsg::UrlChanged);
+
+    let placeholder = if tab.service.is_empty() {
+        "Select method".to_string()
+    } else {
+        format!("{}/{}", tab.service, tab.method)
+    };
+    let methods = pick_list(
+        tab.descriptors.methods(),
+        tab.selected_method().cloned(),
+        GrpcTabMsg::MethodSelected,
+    )
+    .placeholder(placeholder)
+    .text_size(14)
+    .padding([4, 8]);
+
+    let loading = matches!(tab.descriptors, Descriptors::Loading);
+    let buttons = Row::new()
+        .push(
+            icon_button(icons::Refresh)
+                .on_press_maybe((!loading).then_some(GrpcTabMsg::LoadDescriptors)),
+        )
+        .push(vertical_rule(1))
+        .push(icon_button(icons::Send).on_press(GrpcTabMsg::Send))
+        .push(vertical_rule(1))
+        .push(
+            icon_button(icons::ContentSave)
+                .on_press(GrpcTabMsg::Save)
+                .style(|t, s| button::Style {
+                    border: border::rounded(border::right(4)),
+                    ..button::primary(t, s)
+                }),
+        );
+
+    container(
+        Row::new()
+            .push(scheme)
+            .push(url)
+            .push(methods)
+            .push(buttons)
+            .height(iced::Length::Shrink)
+            .width(iced::Length::Fill),
+    )
+    .style(|theme| {
+        let base = container::bordered_box(theme);
+        container::Style {
+            border: base.border.width(2).rounded(4),
+            ..base
+        }
+    })
+    .padding(1)
+    .into()
+}
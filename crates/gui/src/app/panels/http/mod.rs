@@ -4,6 +4,7 @@ use iced::widget::pane_grid::ResizeEvent;
 use iced::widget::{pane_grid, Column, PaneGrid};
 use iced::{widget::container, Element, Task};
 
+use crate::state::environment::editor_variables;
 use crate::state::{AppState, HttpTab, SplitState, Tab};
 
 use self::panes::{request, response};
@@ -47,20 +48,27 @@ impl HttpTabMsg {
 pub fn view<'a>(state: &'a AppState, tab: &'a HttpTab) -> Element<'a, HttpTabMsg> {
     let col = state.collections.get(tab.collection_key());
 
-    let url_bar = url_bar::view(tab).map(HttpTabMsg::Url);
+    let examples = state
+        .collections
+        .get_ref(tab.collection_ref)
+        .map(|req| req.examples.as_slice());
+
+    let variables = editor_variables(state.environment(tab.collection_key()).as_ref());
+
+    let url_bar = url_bar::view(tab, &variables).map(HttpTabMsg::Url);
     let action_bar = col.map(|col| action_bar::view(tab, col).map(HttpTabMsg::Actions));
 
     let req_res = PaneGrid::new(&tab.panes, move |_, pane, _| {
         let pane = match pane {
             SplitState::First => {
-                let request_view = request::view(tab, col).map(HttpTabMsg::Req);
+                let request_view = request::view(tab, col, &variables).map(HttpTabMsg::Req);
                 bordered_right(
                     BORDER_WIDTH,
                     container(request_view).padding(padding::right(4)),
                 )
             }
             SplitState::Second => {
-                let response_view = response::view(tab).map(HttpTabMsg::Res);
+                let response_view = response::view(&tab.response, examples).map(HttpTabMsg::Res);
                 bordered_left(
                     BORDER_WIDTH,
                     container(response_view).padding(padding::left(4)),
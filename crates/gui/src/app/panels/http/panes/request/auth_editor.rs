@@ -7,7 +7,7 @@ use iced::{
     Element, Length,
 };
 
-use crate::state::request::{RawAuthType, RequestPane};
+use crate::state::request::RawAuthType;
 
 #[derive(Debug, Clone)]
 pub enum AuthEditorMsg {
@@ -17,21 +17,21 @@ pub enum AuthEditorMsg {
     BasicPassword(ContentAction),
 }
 impl AuthEditorMsg {
-    pub(crate) fn update(self, request: &mut RequestPane) {
+    pub(crate) fn update(self, auth: &mut RawAuthType) {
         match self {
-            AuthEditorMsg::ChangeAuthType(auth) => request.change_auth_type(auth),
+            AuthEditorMsg::ChangeAuthType(auth_type) => auth.change_type(auth_type),
             AuthEditorMsg::BearerToken(action) => {
-                if let RawAuthType::Bearer { token } = &mut request.auth {
+                if let RawAuthType::Bearer { token } = auth {
                     token.perform(action);
                 }
             }
             AuthEditorMsg::BasicUsername(action) => {
-                if let RawAuthType::Basic { username, .. } = &mut request.auth {
+                if let RawAuthType::Basic { username, .. } = auth {
                     username.perform(action);
                 }
             }
             AuthEditorMsg::BasicPassword(action) => {
-                if let RawAuthType::Basic { password, .. } = &mut request.auth {
+                if let RawAuthType::Basic { password, .. } = auth {
                     password.perform(action);
                 }
             }
@@ -50,8 +50,7 @@ fn field_row<'a>(
         .into()
 }
 
-pub fn auth_view(request: &RequestPane) -> Element<AuthEditorMsg> {
-    let auth = &request.auth;
+pub fn auth_view(auth: &RawAuthType) -> Element<AuthEditorMsg> {
     let header = Row::new()
         .push(text(format!("Auth Method: {}", auth.as_str())))
         .push(horizontal_space())
@@ -1,14 +1,30 @@
-use components::{code_editor, text_editor, ContentType};
+use components::{
+    code_editor, text_editor, variable_completions, CodeEditorMsg, ContentType, Variables,
+};
 
 use crate::app::panels::http::panes::request::RequestPaneMsg;
-use iced::widget::container;
+use iced::widget::{container, Column};
 use iced::Element;
 
-pub fn view(content: &text_editor::Content, content_type: ContentType) -> Element<RequestPaneMsg> {
+pub fn view<'a>(
+    content: &'a text_editor::Content,
+    content_type: ContentType,
+    variables: &Variables,
+) -> Element<'a, RequestPaneMsg> {
+    let completions = variable_completions(content, variables, |action| {
+        RequestPaneMsg::BodyEditorAction(CodeEditorMsg::EditorAction(action, true))
+    });
+
     container(
-        code_editor(content, content_type)
-            .editable()
-            .on_action(RequestPaneMsg::BodyEditorAction),
+        Column::new()
+            .push(
+                code_editor(content, content_type)
+                    .editable()
+                    .variables(variables)
+                    .on_action(RequestPaneMsg::BodyEditorAction),
+            )
+            .push_maybe(completions)
+            .spacing(4),
     )
     .height(iced::Length::Fill)
     .width(iced::Length::Fill)
@@ -1,7 +1,8 @@
-use super::{body_editor, RequestPaneMsg};
-use crate::state::request::RawRequestBody;
+use super::{body_editor, graphql_editor, RequestPaneMsg};
+use crate::state::request::{RawRequestBody, RequestPane};
 use components::{
     icon, icons, key_value_editor, multi_file_picker, ContentType, KeyFileList, KeyValList,
+    Variables,
 };
 use iced::{
     widget::{
@@ -11,7 +12,11 @@ use iced::{
 };
 use std::path::PathBuf;
 
-pub fn body_tab(body: &RawRequestBody) -> iced::Element<RequestPaneMsg> {
+pub fn body_tab<'a>(
+    request: &'a RequestPane,
+    variables: &Variables,
+) -> iced::Element<'a, RequestPaneMsg> {
+    let body = &request.body;
     let header = Row::new()
         .push(text(format!("Content Type: {}", body.as_str())))
         .push(horizontal_space())
@@ -27,9 +32,14 @@ pub fn body_tab(body: &RawRequestBody) -> iced::Element<RequestPaneMsg> {
         .align_y(iced::Alignment::Center);
 
     let body = match body {
-        RawRequestBody::Json(content) => body_editor::view(content, ContentType::Json),
-        RawRequestBody::XML(content) => body_editor::view(content, ContentType::XML),
-        RawRequestBody::Text(content) => body_editor::view(content, ContentType::Text),
+        RawRequestBody::Json(content) => body_editor::view(content, ContentType::Json, variables),
+        RawRequestBody::GraphQL {
+            query,
+            variables,
+            operation_name,
+        } => graphql_editor::view(query, variables, operation_name, &request.graphql_schema),
+        RawRequestBody::XML(content) => body_editor::view(content, ContentType::XML, variables),
+        RawRequestBody::Text(content) => body_editor::view(content, ContentType::Text, variables),
         RawRequestBody::Form(values) => form(values),
         RawRequestBody::Multipart(values, files) => multipart_editor(values, files),
         RawRequestBody::File(path) => file(path),
@@ -0,0 +1,134 @@
+use core::graphql::Schema;
+
+use iced::widget::{
+    button, container, horizontal_space, scrollable, text, text_input, Column, Row,
+};
+use iced::{Alignment, Element, Length};
+
+use components::{code_editor, colors, text_editor, ContentType};
+
+use crate::app::panels::http::panes::request::RequestPaneMsg;
+use crate::state::request::GraphQLSchema;
+
+/// Maximum number of field suggestions shown below the query editor.
+const MAX_COMPLETIONS: usize = 20;
+
+fn completions_view<'a>(
+    schema: &'a Schema,
+    query: &text_editor::Content,
+) -> Option<Element<'a, RequestPaneMsg>> {
+    let completions = schema.completions(&query.text_before_cursor());
+    if completions.fields.is_empty() {
+        return None;
+    }
+
+    let prefix_len = completions.prefix.len();
+    let suggestions = completions
+        .fields
+        .into_iter()
+        .take(MAX_COMPLETIONS)
+        .fold(Row::new(), |row, field| {
+            let suffix = field.name[prefix_len..].to_string();
+            row.push(
+                button(
+                    Row::new()
+                        .push(text(&field.name).size(12))
+                        .push(text(&field.type_name).size(10).color(colors::DARK_GREY))
+                        .spacing(4)
+                        .align_y(Alignment::Center),
+                )
+                .padding([2, 6])
+                .style(button::secondary)
+                .on_press(RequestPaneMsg::GraphQLComplete(suffix)),
+            )
+        })
+        .spacing(4);
+
+    Some(
+        scrollable(suggestions)
+            .direction(scrollable::Direction::Horizontal(Default::default()))
+            .width(Length::Fill)
+            .into(),
+    )
+}
+
+fn schema_status(schema: &GraphQLSchema) -> Element<RequestPaneMsg> {
+    let size = 12;
+    let status = match schema {
+        GraphQLSchema::None => text("No schema").color(colors::DARK_GREY),
+        GraphQLSchema::Loading => text("Fetching schema...").color(colors::GOLD),
+        GraphQLSchema::Loaded(schema) => {
+            text(format!("{} types", schema.types.len())).color(colors::LIME_GREEN)
+        }
+        GraphQLSchema::Failed(e) => text(format!("{:#}", e)).color(colors::RED),
+    };
+
+    let loading = matches!(schema, GraphQLSchema::Loading);
+    Row::new()
+        .push(status.size(size))
+        .push(
+            button(text("Fetch Schema").size(size))
+                .padding([2, 6])
+                .style(button::secondary)
+                .on_press_maybe((!loading).then_some(RequestPaneMsg::FetchSchema)),
+        )
+        .spacing(8)
+        .align_y(Alignment::Center)
+        .into()
+}
+
+pub fn view<'a>(
+    query: &'a text_editor::Content,
+    variables: &'a text_editor::Content,
+    operation_name: &'a str,
+    schema: &'a GraphQLSchema,
+) -> Element<'a, RequestPaneMsg> {
+    let header = Row::new()
+        .push(
+            text_input("Operation Name", operation_name)
+                .on_input(RequestPaneMsg::GraphQLOperationName)
+                .size(14)
+                .width(Length::FillPortion(2)),
+        )
+        .push(horizontal_space())
+        .push(schema_status(schema))
+        .spacing(8)
+        .align_y(Alignment::Center);
+
+    let completions = match schema {
+        GraphQLSchema::Loaded(schema) => completions_view(schema, query),
+        _ => None,
+    };
+
+    let query_editor = Column::new()
+        .push(text("Query").size(14))
+        .push(
+            code_editor(query, ContentType::Text)
+                .editable()
+                .on_action(RequestPaneMsg::GraphQLQueryAction),
+        )
+        .push_maybe(completions)
+        .spacing(4)
+        .width(Length::FillPortion(3));
+
+    let variables_editor = Column::new()
+        .push(text("Variables").size(14))
+        .push(
+            code_editor(variables, ContentType::Json)
+                .editable()
+                .on_action(RequestPaneMsg::GraphQLVariablesAction),
+        )
+        .spacing(4)
+        .width(Length::FillPortion(2));
+
+    let editors = Row::new()
+        .push(query_editor)
+        .push(variables_editor)
+        .spacing(8)
+        .height(Length::Fill);
+
+    container(Column::new().push(header).push(editors).spacing(8))
+        .height(Length::Fill)
+        .width(Length::Fill)
+        .into()
+}
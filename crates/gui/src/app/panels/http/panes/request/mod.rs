@@ -1,27 +1,32 @@
+use core::graphql::Schema;
 use core::http::collection::Collection;
 use core::http::CollectionKey;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use iced::widget::{button, horizontal_space, pick_list, scrollable, Column, Row};
 use iced::{widget::text, Length, Task};
 
+use crate::commands::builders::introspect_schema_cmd;
 use crate::commands::dialog::open_file_dialog;
 use crate::state::popups::{Popup, PopupNameAction};
 use crate::state::request::ReqTabId;
-use crate::state::request::{RawRequestBody, RequestPane};
-use crate::state::{AppState, HttpTab, Tab};
+use crate::state::request::{GraphQLSchema, RawRequestBody, RequestPane};
+use crate::state::{AppState, HttpTab, Tab, TabKey};
+use components::text_editor::{Action, ContentAction, Edit};
 use components::CodeEditorMsg;
 use components::{
     button_tab, button_tabs, icon_button, icons, key_value_editor, tooltip, FilePickerAction,
-    KeyValUpdateMsg,
+    KeyValUpdateMsg, Variables,
 };
 
 use self::auth_editor::{auth_view, AuthEditorMsg};
 use self::body_view::body_tab;
 
-mod auth_editor;
+pub(crate) mod auth_editor;
 mod body_editor;
 mod body_view;
+mod graphql_editor;
 
 #[derive(Debug, Clone)]
 pub enum RequestPaneMsg {
@@ -30,6 +35,12 @@ pub enum RequestPaneMsg {
     Queries(KeyValUpdateMsg),
     PathParams(KeyValUpdateMsg),
     BodyEditorAction(CodeEditorMsg),
+    GraphQLQueryAction(CodeEditorMsg),
+    GraphQLVariablesAction(CodeEditorMsg),
+    GraphQLOperationName(String),
+    GraphQLComplete(String),
+    FetchSchema,
+    SchemaFetched(TabKey, Result<Arc<Schema>, Arc<anyhow::Error>>),
     AuthEditorAction(AuthEditorMsg),
     FormBodyEditAction(KeyValUpdateMsg),
     MultipartParamsAction(KeyValUpdateMsg),
@@ -44,7 +55,8 @@ pub enum RequestPaneMsg {
 
 impl RequestPaneMsg {
     pub fn update(self, state: &mut AppState) -> Task<Self> {
-        let Some(Tab::Http(tab)) = state.active_tab_mut() else {
+        let active = state.active_tab.zip(state.active_tab_mut());
+        let Some((active_tab, Tab::Http(tab))) = active else {
             return Task::none();
         };
         let request = tab.request_mut();
@@ -71,6 +83,41 @@ impl RequestPaneMsg {
                 | RawRequestBody::Text(content) => action.update(content),
                 _ => {}
             },
+            Self::GraphQLQueryAction(action) => {
+                if let RawRequestBody::GraphQL { query, .. } = &mut request.body {
+                    action.update(query);
+                }
+            }
+            Self::GraphQLVariablesAction(action) => {
+                if let RawRequestBody::GraphQL { variables, .. } = &mut request.body {
+                    action.update(variables);
+                }
+            }
+            Self::GraphQLOperationName(name) => {
+                if let RawRequestBody::GraphQL { operation_name, .. } = &mut request.body {
+                    *operation_name = name;
+                }
+            }
+            Self::GraphQLComplete(suffix) => {
+                if let RawRequestBody::GraphQL { query, .. } = &mut request.body {
+                    let edit = Edit::Paste(Arc::new(suffix));
+                    query.perform(ContentAction::Action(Action::Edit(edit)));
+                }
+            }
+            Self::FetchSchema => {
+                request.graphql_schema = GraphQLSchema::Loading;
+                return introspect_schema_cmd(state, active_tab, move |res| {
+                    Self::SchemaFetched(active_tab, res)
+                });
+            }
+            Self::SchemaFetched(key, res) => {
+                if let Some(Tab::Http(tab)) = state.get_tab_mut(key) {
+                    tab.request_mut().graphql_schema = match res {
+                        Ok(schema) => GraphQLSchema::Loaded(schema),
+                        Err(e) => GraphQLSchema::Failed(e),
+                    };
+                }
+            }
             Self::FormBodyEditAction(edit) => {
                 if let RawRequestBody::Form(form) = &mut request.body {
                     form.update(edit);
@@ -97,7 +144,7 @@ impl RequestPaneMsg {
                 return task;
             }
             Self::ChangeBodyType(ct) => request.change_body_type(ct),
-            Self::AuthEditorAction(action) => action.update(request),
+            Self::AuthEditorAction(action) => action.update(&mut request.auth),
             Self::OpenFilePicker => {
                 let task = open_file_dialog("Select File", |path| {
                     RequestPaneMsg::ChangeBodyFile(path.map(|p| p.path().to_path_buf()))
@@ -134,11 +181,18 @@ fn params_view(request: &RequestPane) -> iced::Element<RequestPaneMsg> {
         .into()
 }
 
-fn headers_view(request: &RequestPane) -> iced::Element<RequestPaneMsg> {
+fn headers_view<'a>(
+    request: &'a RequestPane,
+    variables: &Variables,
+) -> iced::Element<'a, RequestPaneMsg> {
     scrollable(
         Column::new()
             .push("Headers")
-            .push(key_value_editor(&request.headers).on_change(RequestPaneMsg::Headers))
+            .push(
+                key_value_editor(&request.headers)
+                    .variables(variables)
+                    .on_change(RequestPaneMsg::Headers),
+            )
             .width(Length::Fill)
             .spacing(4),
     )
@@ -201,14 +255,15 @@ fn script_view<'a>(
 pub(crate) fn view<'a>(
     tab: &'a HttpTab,
     col: Option<&'a Collection>,
+    variables: &Variables,
 ) -> iced::Element<'a, RequestPaneMsg> {
     let request = tab.request();
 
     let tab_content = match request.tab {
         ReqTabId::Params => params_view(request),
-        ReqTabId::Headers => headers_view(request),
-        ReqTabId::Auth => auth_view(request).map(RequestPaneMsg::AuthEditorAction),
-        ReqTabId::Body => body_tab(&request.body),
+        ReqTabId::Headers => headers_view(request, variables),
+        ReqTabId::Auth => auth_view(&request.auth).map(RequestPaneMsg::AuthEditorAction),
+        ReqTabId::Body => body_tab(request, variables),
         ReqTabId::PreRequest => script_view(col, tab),
     };
 
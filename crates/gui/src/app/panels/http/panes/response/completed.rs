@@ -1,16 +1,29 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use core::client;
+use core::diff::{BodyDiff, Change, LineTag, ResponseDiff, ValueChange};
+use core::har::Har;
+use core::http::environment::Environment;
+use core::preview::hex_dump;
 use core::utils::fmt_duration;
 
 use humansize::{format_size, BINARY};
-use iced::widget::{button, container, text, Column, Row};
-use iced::{clipboard, Alignment, Border, Color, Element, Task, Theme};
+use iced::widget::scrollable::Direction;
+use iced::widget::{
+    button, center, container, horizontal_space, image, pick_list, scrollable, svg, text,
+    text_input, Column, Row,
+};
+use iced::{clipboard, Alignment, Border, Color, ContentFit, Element, Font, Length, Task, Theme};
 
 use components::{
-    button_tab, button_tabs, code_editor, key_value_viewer, CodeEditorMsg, ContentType,
+    button_tab, button_tabs, code_editor, colors, key_value_viewer, CodeEditorMsg, ContentType,
 };
 
-use crate::state::response::ResponseTabId;
-use crate::state::response::{BodyMode, CompletedResponse, ResponseState};
-use crate::state::HttpTab;
+use crate::commands::builders::export_har_cmd;
+use crate::commands::dialog::save_file_dialog;
+use crate::state::response::{BodyMode, BodyPreview, CompletedResponse, DiffBase, ResponseState};
+use crate::state::response::{ResponsePane, ResponseTabId};
 
 #[derive(Debug, Clone)]
 pub enum CompletedMsg {
@@ -18,35 +31,86 @@ pub enum CompletedMsg {
     CodeViewerMsg(CodeEditorMsg),
     SetBodyMode(BodyMode),
     CopyBodyToClipboard,
+    HexPage(usize),
+    ExportHar,
+    HarLocation(Option<PathBuf>),
+    HarExported(Option<Arc<anyhow::Error>>),
+    SaveExample,
+    DiffBase(DiffBase),
+    DiffIgnore(String),
 }
 
 impl CompletedMsg {
-    pub fn update(self, active_tab: &mut HttpTab) -> Task<CompletedMsg> {
+    pub fn update(
+        self,
+        response: &mut ResponsePane,
+        env: Option<&Environment>,
+    ) -> Task<CompletedMsg> {
         match self {
             Self::TabChanged(tab) => {
-                active_tab.response.active_tab = tab;
+                response.active_tab = tab;
+                response.refresh_diff();
             }
             Self::CodeViewerMsg(msg) => {
-                if let ResponseState::Completed(ref mut res) = active_tab.response.state {
+                if let ResponseState::Completed(ref mut res) = response.state {
                     msg.update(res.selected_content_mut());
                 }
             }
             Self::SetBodyMode(mode) => {
-                if let ResponseState::Completed(ref mut res) = active_tab.response.state {
+                if let ResponseState::Completed(ref mut res) = response.state {
                     res.mode = mode;
                 }
             }
+            Self::HexPage(page) => {
+                if let ResponseState::Completed(ref mut res) = response.state {
+                    res.hex_page = page;
+                }
+            }
             CompletedMsg::CopyBodyToClipboard => {
-                if let ResponseState::Completed(ref res) = active_tab.response.state {
+                if let ResponseState::Completed(ref res) = response.state {
                     return clipboard::write(res.selected_content().text());
                 }
             }
+            CompletedMsg::ExportHar => {
+                return save_file_dialog(
+                    "Export HAR",
+                    "request.har",
+                    ("HAR", &["har"]),
+                    CompletedMsg::HarLocation,
+                );
+            }
+            CompletedMsg::HarLocation(path) => {
+                let (Some(path), Some(sent), ResponseState::Completed(res)) =
+                    (path, response.sent.clone(), &response.state)
+                else {
+                    return Task::none();
+                };
+                let mut entry = sent.entry(&res.result);
+                if let Some(env) = env {
+                    entry.redact(env);
+                }
+                let har = Har::new(vec![entry]);
+                return export_har_cmd(path, har, CompletedMsg::HarExported);
+            }
+            CompletedMsg::HarExported(_) => (),
+            // Opens a popup, handled by the response pane
+            CompletedMsg::SaveExample => (),
+            // Examples are loaded by the response pane first
+            CompletedMsg::DiffBase(base) => {
+                response.diff.base = base;
+                response.diff.example = None;
+                response.refresh_diff();
+            }
+            CompletedMsg::DiffIgnore(ignore) => {
+                response.diff.ignore = ignore;
+                response.refresh_diff();
+            }
         }
         Task::none()
     }
 }
 
-fn status_color(status: reqwest::StatusCode) -> Color {
+pub(super) fn status_color(status: reqwest::StatusCode) -> Color {
     match status.as_u16() {
         200..=299 => Color::from_rgb8(0, 200, 0),
         300..=399 => Color::from_rgb8(0, 0, 200),
@@ -56,61 +120,363 @@ fn status_color(status: reqwest::StatusCode) -> Color {
     }
 }
 
-fn body_view(cr: &CompletedResponse) -> Element<CompletedMsg> {
-    fn button_style(theme: &Theme, _status: button::Status, selected: bool) -> button::Style {
-        if selected {
-            button::secondary(theme, button::Status::Active)
-        } else {
-            button::text(theme, button::Status::Active)
+const HEX_PAGE_SIZE: usize = 4096;
+
+fn mode_labels(cr: &CompletedResponse) -> Option<(&'static str, &'static str)> {
+    match cr.preview {
+        BodyPreview::Text if cr.result.body.content_type == client::ContentType::Html => {
+            Some(("Preview", "Source"))
         }
+        BodyPreview::Text => Some(("Preview", "Raw")),
+        BodyPreview::Image(_) | BodyPreview::Svg(_) | BodyPreview::Pdf(_) => {
+            Some(("Preview", "Hex"))
+        }
+        BodyPreview::Binary => None,
     }
+}
+
+fn code_content_type(cr: &CompletedResponse) -> ContentType {
+    match (cr.result.body.content_type, cr.mode) {
+        (client::ContentType::Json, _) => ContentType::Json,
+        (client::ContentType::Xml, _) => ContentType::XML,
+        (client::ContentType::Html, BodyMode::Raw) => ContentType::Html,
+        _ => ContentType::Text,
+    }
+}
+
+fn hex_view(cr: &CompletedResponse) -> Element<CompletedMsg> {
+    let data = &cr.result.body.data;
+    let pages = data.len().div_ceil(HEX_PAGE_SIZE).max(1);
+    let page = cr.hex_page.min(pages - 1);
+    let offset = page * HEX_PAGE_SIZE;
+    let end = (offset + HEX_PAGE_SIZE).min(data.len());
 
-    let mode = cr.mode;
     let size = 14;
-    let actions = Row::new()
+    let pager = Row::new()
         .push(
-            button(text("Preview").size(size))
+            button(text("Prev").size(size))
                 .padding([2, 4])
-                .on_press(CompletedMsg::SetBodyMode(BodyMode::Pretty))
-                .style(move |t, s| button_style(t, s, BodyMode::Pretty == mode)),
+                .style(button::secondary)
+                .on_press_maybe((page > 0).then(|| CompletedMsg::HexPage(page - 1))),
         )
+        .push(text(format!("Page {} of {}", page + 1, pages)).size(size))
         .push(
-            button(text("Raw").size(size))
+            button(text("Next").size(size))
                 .padding([2, 4])
-                .on_press(CompletedMsg::SetBodyMode(BodyMode::Raw))
-                .style(move |t, s| button_style(t, s, BodyMode::Raw == mode)),
+                .style(button::secondary)
+                .on_press_maybe((page + 1 < pages).then(|| CompletedMsg::HexPage(page + 1))),
         )
-        .spacing(2);
+        .push(horizontal_space())
+        .push(
+            text(format!(
+                "0x{:08x} - 0x{:08x} of {} bytes",
+                offset,
+                end,
+                data.len()
+            ))
+            .size(12)
+            .color(colors::DARK_GREY),
+        )
+        .spacing(8)
+        .align_y(Alignment::Center);
 
-    let action_bar = Row::new()
-        .push(container(actions).style(|theme: &Theme| {
-            container::Style {
-                border: Border::default()
-                    .width(1)
-                    .color(theme.extended_palette().background.weak.color),
-                ..container::transparent(theme)
-            }
-        }))
+    let dump = text(hex_dump(data, offset, HEX_PAGE_SIZE))
+        .font(Font::MONOSPACE)
+        .size(12);
+
+    Column::new()
+        .push(pager)
         .push(
-            button(text("Copy").size(size))
-                .padding([2, 4])
-                .style(button::secondary)
-                .on_press(CompletedMsg::CopyBodyToClipboard),
+            scrollable(container(dump).padding([4, 8]))
+                .direction(Direction::Both {
+                    vertical: Default::default(),
+                    horizontal: Default::default(),
+                })
+                .width(Length::Fill)
+                .height(Length::Fill),
         )
+        .spacing(4)
+        .into()
+}
+
+fn preview_view(cr: &CompletedResponse) -> Element<CompletedMsg> {
+    match (&cr.preview, cr.mode) {
+        (BodyPreview::Text, _) => code_editor(cr.selected_content(), code_content_type(cr))
+            .on_action(CompletedMsg::CodeViewerMsg)
+            .into(),
+        (BodyPreview::Image(handle), BodyMode::Pretty) => center(
+            image(handle.clone())
+                .content_fit(ContentFit::ScaleDown)
+                .width(Length::Fill)
+                .height(Length::Fill),
+        )
+        .into(),
+        (BodyPreview::Svg(handle), BodyMode::Pretty) => center(
+            svg(handle.clone())
+                .content_fit(ContentFit::ScaleDown)
+                .width(Length::Fill)
+                .height(Length::Fill),
+        )
+        .into(),
+        (BodyPreview::Pdf(info), BodyMode::Pretty) => {
+            let values = info
+                .iter()
+                .map(|(k, v)| (*k, v.as_str()))
+                .collect::<Vec<_>>();
+            key_value_viewer(&values)
+        }
+        _ => hex_view(cr),
+    }
+}
+
+fn graphql_errors_view(cr: &CompletedResponse) -> Option<Element<CompletedMsg>> {
+    if cr.graphql_errors.is_empty() {
+        return None;
+    }
+
+    let errors = cr
+        .graphql_errors
+        .iter()
+        .fold(Column::new(), |col, err| {
+            let path = err.path.as_ref().map(|p| {
+                text(p)
+                    .size(12)
+                    .font(Font::MONOSPACE)
+                    .color(colors::DARK_GREY)
+            });
+            col.push(
+                Row::new()
+                    .push_maybe(path)
+                    .push(text(&err.message).size(13).color(colors::RED))
+                    .spacing(8)
+                    .align_y(Alignment::Center),
+            )
+        })
+        .spacing(2);
+
+    let errors = container(
+        Column::new()
+            .push(text("GraphQL Errors").size(13).color(colors::RED))
+            .push(scrollable(errors).height(Length::Shrink))
+            .spacing(4),
+    )
+    .padding([4, 8])
+    .width(Length::Fill)
+    .max_height(120)
+    .style(|theme: &Theme| container::Style {
+        border: Border::default().width(1).color(colors::RED).rounded(4),
+        ..container::transparent(theme)
+    });
+
+    Some(errors.into())
+}
+
+fn body_view(cr: &CompletedResponse, has_sent: bool, save: bool) -> Element<CompletedMsg> {
+    fn button_style(theme: &Theme, _status: button::Status, selected: bool) -> button::Style {
+        if selected {
+            button::secondary(theme, button::Status::Active)
+        } else {
+            button::text(theme, button::Status::Active)
+        }
+    }
+
+    let mode = cr.mode;
+    let size = 14;
+    let actions = mode_labels(cr).map(|(pretty, raw)| {
+        let actions = Row::new()
+            .push(
+                button(text(pretty).size(size))
+                    .padding([2, 4])
+                    .on_press(CompletedMsg::SetBodyMode(BodyMode::Pretty))
+                    .style(move |t, s| button_style(t, s, BodyMode::Pretty == mode)),
+            )
+            .push(
+                button(text(raw).size(size))
+                    .padding([2, 4])
+                    .on_press(CompletedMsg::SetBodyMode(BodyMode::Raw))
+                    .style(move |t, s| button_style(t, s, BodyMode::Raw == mode)),
+            )
+            .spacing(2);
+
+        container(actions).style(|theme: &Theme| container::Style {
+            border: Border::default()
+                .width(1)
+                .color(theme.extended_palette().background.weak.color),
+            ..container::transparent(theme)
+        })
+    });
+
+    let copy = matches!(cr.preview, BodyPreview::Text).then(|| {
+        button(text("Copy").size(size))
+            .padding([2, 4])
+            .style(button::secondary)
+            .on_press(CompletedMsg::CopyBodyToClipboard)
+    });
+
+    // Only requests sent over HTTP can be exported
+    let export = has_sent.then(|| {
+        button(text("Export HAR").size(size))
+            .padding([2, 4])
+            .style(button::secondary)
+            .on_press(CompletedMsg::ExportHar)
+    });
+
+    let save = save.then(|| {
+        button(text("Save Example").size(size))
+            .padding([2, 4])
+            .style(button::secondary)
+            .on_press(CompletedMsg::SaveExample)
+    });
+
+    let action_bar = Row::new()
+        .push_maybe(actions)
+        .push_maybe(copy)
+        .push(horizontal_space())
+        .push_maybe(save)
+        .push_maybe(export)
         .spacing(8);
 
-    let content = cr.selected_content();
     Column::new()
         .push(action_bar)
-        .push(code_editor(content, ContentType::Json).on_action(CompletedMsg::CodeViewerMsg))
+        .push_maybe(graphql_errors_view(cr))
+        .push(preview_view(cr))
         .spacing(4)
         .height(iced::Length::Fill)
         .width(iced::Length::Fill)
         .into()
 }
 
-pub fn view<'a>(tab: &'a HttpTab, cr: &'a CompletedResponse) -> Element<'a, CompletedMsg> {
+fn change_view<'a>(change: &'a ValueChange, root: &'a str) -> Element<'a, CompletedMsg> {
+    let size = 13;
+    let path = if change.path.is_empty() {
+        root
+    } else {
+        &change.path
+    };
+    let path = text(path).size(size).font(Font::MONOSPACE);
+
+    let row = match &change.change {
+        Change::Added(new) => Row::new()
+            .push(text("+").size(size).color(colors::LIME_GREEN))
+            .push(path)
+            .push(text(new).size(size).color(colors::LIME_GREEN)),
+        Change::Removed(old) => Row::new()
+            .push(text("-").size(size).color(colors::RED))
+            .push(path)
+            .push(text(old).size(size).color(colors::RED)),
+        Change::Changed(old, new) => Row::new()
+            .push(text("~").size(size).color(colors::YELLOW))
+            .push(path)
+            .push(text(old).size(size).color(colors::RED))
+            .push(text("->").size(size).color(colors::DARK_GREY))
+            .push(text(new).size(size).color(colors::LIME_GREEN)),
+    };
+
+    row.spacing(6).align_y(Alignment::Center).into()
+}
+
+fn section<'a>(title: &'a str) -> Element<'a, CompletedMsg> {
+    text(title).size(14).color(colors::DARK_GREY).into()
+}
+
+fn diff_result_view(diff: &ResponseDiff) -> Element<CompletedMsg> {
+    if diff.is_empty() {
+        return center(text("No differences")).into();
+    }
+
+    let mut col = Column::new().spacing(2);
+    if let Some((old, new)) = diff.status {
+        col = col.push(section("Status")).push(
+            Row::new()
+                .push(text(old.to_string()).size(13).color(colors::RED))
+                .push(text("->").size(13).color(colors::DARK_GREY))
+                .push(text(new.to_string()).size(13).color(colors::LIME_GREEN))
+                .spacing(6),
+        );
+    }
+    if !diff.headers.is_empty() {
+        col = col
+            .push(section("Headers"))
+            .extend(diff.headers.iter().map(|change| change_view(change, "")));
+    }
+    if !diff.body.is_empty() {
+        col = col.push(section("Body"));
+    }
+    col = match &diff.body {
+        BodyDiff::Json(changes) => col.extend(changes.iter().map(|c| change_view(c, "(root)"))),
+        BodyDiff::Lines(hunks) => hunks.iter().enumerate().fold(col, |col, (idx, hunk)| {
+            let col = col.push_maybe((idx > 0).then(|| text("...").color(colors::DARK_GREY)));
+            col.extend(hunk.iter().map(|line| {
+                let (prefix, color) = match line.tag {
+                    LineTag::Equal => (" ", None),
+                    LineTag::Insert => ("+", Some(colors::LIME_GREEN)),
+                    LineTag::Delete => ("-", Some(colors::RED)),
+                };
+                text(format!("{} {}", prefix, line.text))
+                    .size(13)
+                    .font(Font::MONOSPACE)
+                    .style(move |_| text::Style { color })
+                    .into()
+            }))
+        }),
+    };
+
+    scrollable(container(col).padding([4, 8]))
+        .direction(Direction::Both {
+            vertical: Default::default(),
+            horizontal: Default::default(),
+        })
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+}
+
+fn diff_view<'a>(response: &'a ResponsePane, examples: &'a [String]) -> Element<'a, CompletedMsg> {
+    let size = 14;
+    let bases = std::iter::once(DiffBase::Previous)
+        .chain(examples.iter().cloned().map(DiffBase::Example))
+        .collect::<Vec<_>>();
+
+    let options = Row::new()
+        .push(
+            pick_list(bases, Some(&response.diff.base), CompletedMsg::DiffBase)
+                .text_size(size)
+                .padding([2, 4]),
+        )
+        .push(
+            text_input(
+                "Ignore paths, like items.*.id, header.date",
+                &response.diff.ignore,
+            )
+            .on_input(CompletedMsg::DiffIgnore)
+            .size(size)
+            .padding([2, 4]),
+        )
+        .spacing(8)
+        .align_y(Alignment::Center);
+
+    let result = match (&response.diff.result, &response.diff.base) {
+        (Some(diff), _) => diff_result_view(diff),
+        (None, DiffBase::Previous) => center(text("No previous response to compare with")).into(),
+        (None, DiffBase::Example(_)) => center(text("Loading example...")).into(),
+    };
+
+    Column::new()
+        .push(options)
+        .push(result)
+        .spacing(4)
+        .height(Length::Fill)
+        .width(Length::Fill)
+        .into()
+}
+
+pub fn view<'a>(
+    response: &'a ResponsePane,
+    cr: &'a CompletedResponse,
+    examples: Option<&'a [String]>,
+) -> Element<'a, CompletedMsg> {
     let res = &cr.result;
+    let example = response.example.as_ref();
 
     let status_size = 12;
     let status = Row::new()
@@ -124,11 +490,20 @@ pub fn view<'a>(tab: &'a HttpTab, cr: &'a CompletedResponse) -> Element<'a, Comp
                 .size(status_size)
                 .color(Color::from_rgb8(182, 128, 182)),
         )
-        .push(
-            text(fmt_duration(res.duration))
+        .push(match example {
+            // Saved examples have no timing
+            Some(name) => text(format!("Example: {}", name))
+                .size(status_size)
+                .color(colors::CYAN),
+            None => text(fmt_duration(res.duration))
                 .size(status_size)
                 .color(Color::from_rgb8(160, 160, 160)),
-        )
+        })
+        .push_maybe((!cr.graphql_errors.is_empty()).then(|| {
+            text(format!("{} GraphQL errors", cr.graphql_errors.len()))
+                .size(status_size)
+                .color(colors::RED)
+        }))
         .padding([4, 8])
         .spacing(8)
         .align_y(Alignment::Center);
@@ -139,18 +514,30 @@ pub fn view<'a>(tab: &'a HttpTab, cr: &'a CompletedResponse) -> Element<'a, Comp
         .map(|(k, v)| (k.as_str(), v.to_str().unwrap_or_default()))
         .collect::<Vec<_>>();
 
-    let tab_content = match tab.response.active_tab {
-        ResponseTabId::Body => body_view(cr),
+    let diff_examples = examples.unwrap_or_default();
+    let can_diff = response.previous.is_some() || !diff_examples.is_empty();
+
+    let tab_content = match response.active_tab {
+        ResponseTabId::Diff if can_diff => diff_view(response, diff_examples),
+        ResponseTabId::Body | ResponseTabId::Diff => body_view(
+            cr,
+            response.sent.is_some() && example.is_none(),
+            examples.is_some() && example.is_none(),
+        ),
         ResponseTabId::Headers => key_value_viewer(&headers),
     };
 
+    let mut tabs = vec![
+        button_tab(ResponseTabId::Body, || text("Body")),
+        button_tab(ResponseTabId::Headers, || text("Headers")),
+    ];
+    if can_diff {
+        tabs.push(button_tab(ResponseTabId::Diff, || text("Diff")));
+    }
+
     let tabs = button_tabs(
-        tab.response.active_tab,
-        [
-            button_tab(ResponseTabId::Body, || text("Body")),
-            button_tab(ResponseTabId::Headers, || text("Headers")),
-        ]
-        .into_iter(),
+        response.active_tab,
+        tabs.into_iter(),
         CompletedMsg::TabChanged,
         Some(status.into()),
     );
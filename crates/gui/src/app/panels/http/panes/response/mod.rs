@@ -1,44 +1,158 @@
-use crate::state::{response::ResponseState, AppState, HttpTab, Tab};
+use crate::commands::builders::{open_example_cmd, ResponseResult};
+use crate::state::popups::{Popup, PopupNameAction};
+use crate::state::response::{
+    CompletedResponse, DiffBase, ResponsePane, ResponseState, StreamingResponse,
+};
+use crate::state::{AppState, Tab};
+use core::persistence::examples::ResponseExample;
 use iced::{widget::center, Element, Task};
 
 mod completed;
 mod executing;
 mod failed;
 mod idle;
+mod streaming;
 
 #[derive(Debug, Clone)]
 pub enum ResponsePaneMsg {
     Completed(completed::CompletedMsg),
+    Streaming(streaming::StreamingMsg),
     CancelRequest,
+    DiffExampleLoaded(Box<Option<ResponseExample>>),
 }
 
 impl ResponsePaneMsg {
     pub fn update(self, state: &mut AppState) -> Task<Self> {
+        // Secret values of the environment are redacted from exported HARs
+        let env = match self {
+            Self::Completed(completed::CompletedMsg::HarLocation(_)) => state
+                .active_tab()
+                .and_then(|tab| tab.collection_ref())
+                .and_then(|col| state.environment(col.0)),
+            _ => None,
+        };
+
         let active_tab = state.active_tab.zip(state.active_tab_mut());
-        let Some((active_tab, Tab::Http(tab))) = active_tab else {
+        let Some((active_tab, tab)) = active_tab else {
+            return Task::none();
+        };
+        if let Self::Streaming(streaming::StreamingMsg::Stop) = self {
+            tab.cancel_tasks();
+        }
+        if let Self::Completed(completed::CompletedMsg::SaveExample) = self {
+            Popup::popup_name(
+                state,
+                String::new(),
+                PopupNameAction::SaveExample(active_tab),
+            );
+            return Task::none();
+        }
+        if let Self::Completed(completed::CompletedMsg::DiffBase(DiffBase::Example(name))) = self {
+            let Some(col) = tab.collection_ref() else {
+                return Task::none();
+            };
+            if let Some(response) = tab.response_mut() {
+                response.diff.base = DiffBase::Example(name.clone());
+                response.diff.example = None;
+                response.refresh_diff();
+            }
+            return open_example_cmd(state, col, name, |example| {
+                Self::DiffExampleLoaded(Box::new(example))
+            });
+        }
+        let Some(response) = tab.response_mut() else {
             return Task::none();
         };
         match self {
-            Self::Completed(msg) => msg.update(tab).map(ResponsePaneMsg::Completed),
+            Self::Completed(msg) => msg
+                .update(response, env.as_ref())
+                .map(ResponsePaneMsg::Completed),
+            Self::Streaming(msg) => msg.update(response).map(ResponsePaneMsg::Streaming),
             Self::CancelRequest => {
-                let res_state = &tab.response.state;
-                if let ResponseState::Executing = res_state {
+                if let ResponseState::Executing = response.state {
                     state.cancel_tab_tasks(active_tab);
                 }
                 Task::none()
             }
+            Self::DiffExampleLoaded(example) => {
+                // The selection could have changed while loading
+                let selected = match response.diff.base {
+                    DiffBase::Example(ref name) => Some(name),
+                    DiffBase::Previous => None,
+                };
+                if let Some(example) = (*example).filter(|e| Some(&e.name) == selected) {
+                    response.diff.example = Some(example.to_response());
+                    response.refresh_diff();
+                }
+                Task::none()
+            }
         }
     }
 }
 
-pub fn view(tab: &HttpTab) -> Element<ResponsePaneMsg> {
-    let res = &tab.response;
+/// Applies the result of a request sent from a tab showing the response pane.
+pub fn update_response(tab: &mut Tab, result: ResponseResult) {
+    let finished = matches!(
+        result,
+        ResponseResult::Completed(_) | ResponseResult::StreamClosed | ResponseResult::Error(_)
+    );
+    if finished {
+        tab.cancel_tasks();
+    }
+    let Some(response) = tab.response_mut() else {
+        return;
+    };
+
+    match result {
+        ResponseResult::Sent(sent) => {
+            response.sent = Some(*sent);
+        }
+        ResponseResult::Completed(res) => {
+            response.state = ResponseState::Completed(CompletedResponse::new(res));
+            response.example = None;
+            response.refresh_diff();
+        }
+        ResponseResult::StreamOpened(head) => {
+            response.state = ResponseState::Streaming(StreamingResponse::new(head));
+            response.example = None;
+        }
+        ResponseResult::Event(event) => {
+            if let ResponseState::Streaming(ref mut stream) = response.state {
+                stream.push(event);
+            }
+        }
+        ResponseResult::StreamClosed => {
+            if let ResponseState::Streaming(ref mut stream) = response.state {
+                stream.closed = true;
+            }
+        }
+        ResponseResult::Error(e) => match response.state {
+            ResponseState::Streaming(ref mut stream) => {
+                stream.closed = true;
+                stream.error = Some(e);
+            }
+            _ => response.state = ResponseState::Failed(e),
+        },
+        // Tasks have already been drained by whoever cancelled the request,
+        // draining again here could stop a request sent in the meantime.
+        ResponseResult::Cancelled => (),
+    }
+}
 
-    let res = match res.state {
+/// `examples` lists the saved examples of the request, it is only set for
+/// requests saved in a collection
+pub fn view<'a>(
+    response: &'a ResponsePane,
+    examples: Option<&'a [String]>,
+) -> Element<'a, ResponsePaneMsg> {
+    let res = match response.state {
         ResponseState::Idle => idle::view(),
         ResponseState::Executing => executing::view(),
         ResponseState::Completed(ref result) => {
-            completed::view(tab, result).map(ResponsePaneMsg::Completed)
+            completed::view(response, result, examples).map(ResponsePaneMsg::Completed)
+        }
+        ResponseState::Streaming(ref stream) => {
+            streaming::view(response, stream).map(ResponsePaneMsg::Streaming)
         }
         ResponseState::Failed(ref e) => failed::view(e.clone()),
     };
@@ -0,0 +1,202 @@
+use core::sse::SseEvent;
+use core::utils::fmt_duration;
+
+use iced::widget::text::Wrapping;
+use iced::widget::{button, container, horizontal_space, scrollable, text, Column, Row};
+use iced::{Alignment, Color, Element, Font, Length, Task};
+
+use components::{button_tab, button_tabs, colors, horizontal_line, key_value_viewer};
+
+use super::completed::status_color;
+use crate::state::response::{ResponsePane, ResponseState, ResponseTabId, StreamingResponse};
+
+#[derive(Debug, Clone)]
+pub enum StreamingMsg {
+    TabChanged(ResponseTabId),
+    SetPaused(bool),
+    Clear,
+    Stop,
+}
+
+impl StreamingMsg {
+    pub fn update(self, response: &mut ResponsePane) -> Task<StreamingMsg> {
+        match self {
+            Self::TabChanged(tab) => {
+                response.active_tab = tab;
+            }
+            Self::SetPaused(paused) => {
+                if let ResponseState::Streaming(ref mut stream) = response.state {
+                    stream.set_paused(paused);
+                }
+            }
+            Self::Clear => {
+                if let ResponseState::Streaming(ref mut stream) = response.state {
+                    stream.clear();
+                }
+            }
+            // The tab's tasks are cancelled by the response pane
+            Self::Stop => {
+                if let ResponseState::Streaming(ref mut stream) = response.state {
+                    stream.closed = true;
+                }
+            }
+        }
+        Task::none()
+    }
+}
+
+fn event_view(event: &SseEvent) -> Element<StreamingMsg> {
+    let size = 12;
+    let header = Row::new()
+        .push(
+            text(event.received.format("%H:%M:%S%.3f").to_string())
+                .size(size)
+                .color(colors::DARK_GREY),
+        )
+        .push(text(&event.event).size(size).color(colors::DODGER_BLUE))
+        .push_maybe(
+            event
+                .id
+                .as_ref()
+                .map(|id| text(format!("#{}", id)).size(size).color(colors::ORCHID)),
+        )
+        .spacing(8)
+        .align_y(Alignment::Center);
+
+    Column::new()
+        .push(header)
+        .push(
+            text(&event.data)
+                .size(13)
+                .font(Font::MONOSPACE)
+                .wrapping(Wrapping::WordOrGlyph),
+        )
+        .spacing(2)
+        .padding([4, 8])
+        .width(Length::Fill)
+        .into()
+}
+
+fn events_view(stream: &StreamingResponse) -> Element<StreamingMsg> {
+    let size = 14;
+    let actions = Row::new()
+        .push(
+            button(text(if stream.paused { "Resume" } else { "Pause" }).size(size))
+                .padding([2, 4])
+                .style(button::secondary)
+                .on_press(StreamingMsg::SetPaused(!stream.paused)),
+        )
+        .push(
+            button(text("Clear").size(size))
+                .padding([2, 4])
+                .style(button::secondary)
+                .on_press(StreamingMsg::Clear),
+        )
+        .push_maybe((!stream.closed).then(|| {
+            button(text("Stop").size(size))
+                .padding([2, 4])
+                .style(button::danger)
+                .on_press(StreamingMsg::Stop)
+        }))
+        .push(horizontal_space())
+        .push_maybe((!stream.buffered.is_empty()).then(|| {
+            let status = match stream.dropped {
+                0 => format!("{} new while paused", stream.buffered.len()),
+                dropped => format!(
+                    "{} new while paused, {} older dropped",
+                    stream.buffered.len(),
+                    dropped
+                ),
+            };
+            text(status).size(12).color(colors::DARK_GREY)
+        }))
+        .spacing(8)
+        .align_y(Alignment::Center);
+
+    let events = stream
+        .events
+        .iter()
+        .fold(Column::new(), |col, event| {
+            col.push(event_view(event)).push(horizontal_line(1))
+        })
+        .push_maybe(
+            stream
+                .error
+                .as_ref()
+                .map(|e| text(e.to_string()).size(13).color(colors::RED)),
+        )
+        .width(Length::Fill);
+
+    Column::new()
+        .push(actions)
+        .push(scrollable(events).height(Length::Fill))
+        .spacing(4)
+        .height(Length::Fill)
+        .width(Length::Fill)
+        .into()
+}
+
+pub fn view<'a>(
+    response: &'a ResponsePane,
+    stream: &'a StreamingResponse,
+) -> Element<'a, StreamingMsg> {
+    let head = &stream.head;
+
+    let (state, state_color) = match (stream.closed, stream.paused) {
+        (true, _) => ("Closed", Color::from_rgb8(160, 160, 160)),
+        (false, true) => ("Paused", colors::GOLD),
+        (false, false) => ("Live", colors::LIME_GREEN),
+    };
+
+    let status_size = 12;
+    let status = Row::new()
+        .push(
+            text(head.status.to_string())
+                .size(status_size)
+                .color(status_color(head.status)),
+        )
+        .push(text(state).size(status_size).color(state_color))
+        .push(
+            text(format!("{} events", stream.received))
+                .size(status_size)
+                .color(Color::from_rgb8(182, 128, 182)),
+        )
+        .push(
+            text(fmt_duration(head.duration))
+                .size(status_size)
+                .color(Color::from_rgb8(160, 160, 160)),
+        )
+        .padding([4, 8])
+        .spacing(8)
+        .align_y(Alignment::Center);
+
+    let headers = head
+        .headers
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.to_str().unwrap_or_default()))
+        .collect::<Vec<_>>();
+
+    let tab_content = match response.active_tab {
+        ResponseTabId::Body | ResponseTabId::Diff => events_view(stream),
+        ResponseTabId::Headers => key_value_viewer(&headers),
+    };
+
+    let tabs = button_tabs(
+        response.active_tab,
+        [
+            button_tab(ResponseTabId::Body, || text("Events")),
+            button_tab(ResponseTabId::Headers, || text("Headers")),
+        ]
+        .into_iter(),
+        StreamingMsg::TabChanged,
+        Some(status.into()),
+    );
+
+    Column::new()
+        .push(tabs)
+        .push(container(tab_content).height(Length::Fill))
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .spacing(4)
+        .into()
+}
@@ -1,20 +1,28 @@
-use iced::widget::{vertical_rule, Button, Row};
+use std::sync::Arc;
+
+use iced::widget::{vertical_rule, Button, Column, Row};
 use iced::{border, Border};
 use iced::{
     widget::{button, container, pick_list},
     Element, Task,
 };
 use reqwest::Url;
-use serde_json::Value;
 use strum::VariantArray;
 
-use components::text_editor::{self, line_editor, Content, ContentAction};
-use components::{icon, icons, NerdIcon};
-use core::http::request::Method;
+use components::text_editor::{self, line_editor, Action, ContentAction, Edit};
+use components::{icon, icons, variable_completions, variable_hover, NerdIcon, Variables};
+use core::curl;
+use core::http::request::{Method, Request};
+use core::persistence::history::HistoryEntry;
+use core::persistence::request::RequestOnDisk;
 
-use crate::commands::builders::{save_request_cmd, send_request_cmd, ResponseResult};
+use super::panes::response::update_response;
+use crate::commands::builders::{
+    save_failed_cmd, save_history_cmd, save_request_cmd, send_request_cmd, ResponseResult,
+};
 use crate::state::popups::Popup;
-use crate::state::response::{BodyMode, CompletedResponse, ResponseState};
+use crate::state::request::RequestPane;
+use crate::state::watch::save_conflict;
 use crate::state::{AppState, HttpTab, Tab, TabKey};
 
 #[derive(Debug, Clone)]
@@ -23,8 +31,11 @@ pub enum UrlBarMsg {
     UrlChanged(ContentAction),
     SendRequest,
     SaveRequest,
-    RequestSaved,
+    GenerateCode,
+    RequestSaved(TabKey, Option<Arc<anyhow::Error>>),
+    SaveConflict(TabKey, Option<RequestOnDisk>),
     RequestResult(TabKey, ResponseResult),
+    HistorySaved,
 }
 
 fn parse_path_params(url: &str) -> Option<Vec<String>> {
@@ -41,34 +52,30 @@ fn parse_path_params(url: &str) -> Option<Vec<String>> {
     Some(params)
 }
 
-fn pretty_body(body: &[u8]) -> (String, Option<String>) {
-    let raw = String::from_utf8_lossy(body).to_string();
-
-    let json = serde_json::from_slice::<Value>(body)
-        .ok()
-        .and_then(|v| serde_json::to_string_pretty(&v).ok());
+/// History entry of the request once its response is complete or it failed
+fn history_entry(state: &AppState, key: TabKey, res: &ResponseResult) -> Option<HistoryEntry> {
+    let Some(Tab::Http(tab)) = state.get_tab(key) else {
+        return None;
+    };
+    let sent = tab.response.sent.clone()?;
+    let env = state.environment(tab.collection_key());
 
-    (raw, json)
+    let response = match res {
+        ResponseResult::Completed(res) => Ok(res),
+        ResponseResult::Error(e) => Err(e.as_ref()),
+        _ => return None,
+    };
+    Some(HistoryEntry::new(sent, response, env.as_ref()))
 }
 
-fn update_response(tab: &mut HttpTab, result: ResponseResult) {
-    match result {
-        ResponseResult::Completed(res) => {
-            tab.cancel_tasks();
-            let (raw, pretty) = pretty_body(&res.body.data);
-            tab.response.state = ResponseState::Completed(CompletedResponse {
-                result: res,
-                content: pretty.map(|p| Content::with_text(p.as_str())),
-                raw: Content::with_text(raw.as_str()),
-                mode: BodyMode::Pretty,
-            });
-        }
-        ResponseResult::Error(e) => {
-            tab.cancel_tasks();
-            tab.response.state = ResponseState::Failed(e);
-        }
-        ResponseResult::Cancelled => tab.cancel_tasks(),
-    }
+/// Replaces the request with a pasted curl command, the pre request script
+/// and open tab are kept as curl has no notion of them
+fn paste_curl(tab: &mut HttpTab, parsed: Request) {
+    let current = tab.request_mut();
+    let mut pane = RequestPane::from(parsed);
+    pane.tab = current.tab;
+    pane.pre_request = current.pre_request.take();
+    *current = pane;
 }
 
 impl UrlBarMsg {
@@ -83,6 +90,18 @@ impl UrlBarMsg {
                 tab.request_mut().method = method;
             }
             UrlBarMsg::UrlChanged(action) => {
+                if let ContentAction::Action(Action::Edit(Edit::Paste(text))) = &action {
+                    if curl::is_curl(text) {
+                        match curl::parse(text) {
+                            Ok(parsed) => {
+                                paste_curl(tab, parsed);
+                                return Task::none();
+                            }
+                            Err(e) => log::error!("Failed to parse curl command: {:?}", e),
+                        }
+                    }
+                }
+
                 tab.request_mut().url_content.perform(action);
 
                 let url = tab.request().url_content.text();
@@ -111,18 +130,37 @@ impl UrlBarMsg {
                 let req_ref = state.collections.get_ref(tab.collection_ref);
                 if let Some(req_res) = req_ref {
                     let path = req_res.path.clone();
-                    return save_request_cmd(tab.request(), path, |_| Self::RequestSaved);
+                    let cb = move |r| Self::RequestSaved(active_tab, r);
+                    return save_request_cmd(tab.request(), path, cb);
                 } else {
                     Popup::save_request(state, active_tab);
                 }
             }
-            UrlBarMsg::RequestSaved => tab.check_dirty(),
-            UrlBarMsg::RequestResult(tab, res) => {
-                let tab = state.get_tab_mut(tab);
-                if let Some(Tab::Http(tab)) = tab {
+            UrlBarMsg::GenerateCode => Popup::generate_code(state, active_tab),
+            UrlBarMsg::RequestSaved(key, None) => {
+                if let Some(Tab::Http(tab)) = state.get_tab_mut(key) {
+                    tab.check_dirty();
+                }
+            }
+            UrlBarMsg::RequestSaved(key, Some(e)) => {
+                return save_failed_cmd(state, key, &e, move |r| Self::SaveConflict(key, r));
+            }
+            UrlBarMsg::SaveConflict(key, theirs) => {
+                if let Some(theirs) = theirs {
+                    save_conflict(state, key, theirs);
+                }
+            }
+            UrlBarMsg::RequestResult(key, res) => {
+                let entry = history_entry(state, key, &res);
+                if let Some(tab) = state.get_tab_mut(key) {
                     update_response(tab, res)
                 }
+                if let Some(entry) = entry {
+                    state.history.push(entry.clone());
+                    return save_history_cmd(entry, || UrlBarMsg::HistorySaved);
+                }
             }
+            UrlBarMsg::HistorySaved => (),
         }
         Task::none()
     }
@@ -135,7 +173,7 @@ fn icon_button<'a>(ico: NerdIcon) -> Button<'a, UrlBarMsg> {
     })
 }
 
-pub(crate) fn view(tab: &HttpTab) -> Element<UrlBarMsg> {
+pub(crate) fn view<'a>(tab: &'a HttpTab, variables: &Variables) -> Element<'a, UrlBarMsg> {
     let request = tab.request();
     let executing = tab.response.is_executing();
 
@@ -152,11 +190,14 @@ pub(crate) fn view(tab: &HttpTab) -> Element<UrlBarMsg> {
     });
 
     let url = line_editor(&request.url_content)
+        .variables(variables)
         .style(move |t: &iced::Theme, _| text_editor::Style {
             border,
             ..text_editor::default(t, text_editor::Status::Active)
         })
         .on_action(UrlBarMsg::UrlChanged);
+    let url = variable_hover(url, &request.url_content, variables);
+    let completions = variable_completions(&request.url_content, variables, UrlBarMsg::UrlChanged);
 
     let on_press = if executing {
         None
@@ -167,6 +208,8 @@ pub(crate) fn view(tab: &HttpTab) -> Element<UrlBarMsg> {
     let buttons = Row::new()
         .push(icon_button(icons::Send).on_press_maybe(on_press))
         .push(vertical_rule(1))
+        .push(icon_button(icons::CodeTags).on_press(UrlBarMsg::GenerateCode))
+        .push(vertical_rule(1))
         .push(
             icon_button(icons::ContentSave)
                 .on_press(UrlBarMsg::SaveRequest)
@@ -176,7 +219,7 @@ pub(crate) fn view(tab: &HttpTab) -> Element<UrlBarMsg> {
                 }),
         );
 
-    container(
+    let bar = container(
         Row::new()
             .push(method)
             .push(url)
@@ -191,6 +234,11 @@ pub(crate) fn view(tab: &HttpTab) -> Element<UrlBarMsg> {
             ..base
         }
     })
-    .padding(1)
-    .into()
+    .padding(1);
+
+    Column::new()
+        .push(bar)
+        .push_maybe(completions)
+        .spacing(4)
+        .into()
 }
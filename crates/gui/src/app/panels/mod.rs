@@ -4,12 +4,16 @@ use iced::Task;
 use crate::state::{AppState, Tab};
 
 pub mod collection;
+pub mod grpc;
 pub mod http;
+pub mod websocket;
 
 #[derive(Debug, Clone)]
 pub enum PanelMsg {
     HttpTab(http::HttpTabMsg),
     CollectionTab(collection::CollectionTabMsg),
+    WebSocketTab(websocket::WebSocketTabMsg),
+    GrpcTab(grpc::GrpcTabMsg),
 }
 
 impl PanelMsg {
@@ -17,6 +21,8 @@ impl PanelMsg {
         match self {
             PanelMsg::HttpTab(msg) => msg.update(state).map(PanelMsg::HttpTab),
             PanelMsg::CollectionTab(msg) => msg.update(state).map(PanelMsg::CollectionTab),
+            PanelMsg::WebSocketTab(msg) => msg.update(state).map(PanelMsg::WebSocketTab),
+            PanelMsg::GrpcTab(msg) => msg.update(state).map(PanelMsg::GrpcTab),
         }
     }
 }
@@ -24,7 +30,9 @@ impl PanelMsg {
 pub fn view<'a>(state: &'a AppState, tab: &'a Tab) -> iced::Element<'a, PanelMsg> {
     let req = match tab {
         Tab::Http(tab) => http::view(state, tab).map(PanelMsg::HttpTab),
-        Tab::Collection(tab) => collection::view(tab).map(PanelMsg::CollectionTab),
+        Tab::Collection(tab) => collection::view(state, tab).map(PanelMsg::CollectionTab),
+        Tab::WebSocket(tab) => websocket::view(tab).map(PanelMsg::WebSocketTab),
+        Tab::Grpc(tab) => grpc::view(tab).map(PanelMsg::GrpcTab),
     };
 
     container::Container::new(req)
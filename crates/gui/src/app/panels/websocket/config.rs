@@ -0,0 +1,171 @@
+use iced::widget::{button, container, horizontal_space, pick_list, scrollable, text, Column, Row};
+use iced::{Alignment, Element, Length};
+use strum::VariantArray;
+
+use components::{
+    button_tab, button_tabs, code_editor, colors, icon_button, icons, key_value_editor, tooltip,
+    ContentType, KeyValList, KeyValUpdateMsg,
+};
+use core::http::websocket::MessageKind;
+
+use super::WebSocketTabMsg;
+use crate::app::panels::http::panes::request::auth_editor::auth_view;
+use crate::state::ws_tab::WsTabId;
+use crate::state::WebSocketTab;
+
+fn composer_view(tab: &WebSocketTab) -> Element<WebSocketTabMsg> {
+    let size = 14;
+    let header = Row::new()
+        .push(
+            pick_list(
+                MessageKind::VARIANTS,
+                Some(tab.composer_kind),
+                WebSocketTabMsg::ComposerKind,
+            )
+            .padding([2, 6]),
+        )
+        .push(horizontal_space())
+        .push(
+            button(text("Save").size(size))
+                .padding([2, 8])
+                .style(button::secondary)
+                .on_press(WebSocketTabMsg::SaveMessage),
+        )
+        .push(
+            button(text("Send").size(size))
+                .padding([2, 8])
+                .on_press_maybe(tab.sender.as_ref().map(|_| WebSocketTabMsg::Send)),
+        )
+        .spacing(8)
+        .align_y(Alignment::Center);
+
+    let content_type = match tab.composer_kind {
+        MessageKind::Json => ContentType::Json,
+        MessageKind::Text | MessageKind::Binary => ContentType::Text,
+    };
+
+    let editor = container(
+        code_editor(&tab.composer, content_type)
+            .editable()
+            .on_action(WebSocketTabMsg::Composer),
+    )
+    .height(Length::Fill)
+    .width(Length::Fill);
+
+    let hint = (tab.composer_kind == MessageKind::Binary).then(|| {
+        text("Hex bytes, e.g. de ad be ef")
+            .size(12)
+            .color(colors::DARK_GREY)
+    });
+
+    Column::new()
+        .push(header)
+        .push_maybe(hint)
+        .push(editor)
+        .push_maybe(
+            tab.composer_error
+                .as_ref()
+                .map(|e| text(e).size(13).color(colors::RED)),
+        )
+        .spacing(4)
+        .into()
+}
+
+fn saved_view(tab: &WebSocketTab) -> Element<WebSocketTabMsg> {
+    if tab.messages.is_empty() {
+        return container(text("No saved messages").color(colors::DARK_GREY))
+            .center(Length::Fill)
+            .into();
+    }
+
+    let connected = tab.sender.is_some();
+    let messages = tab
+        .messages
+        .iter()
+        .enumerate()
+        .fold(Column::new(), |col, (idx, msg)| {
+            let row = Row::new()
+                .push(text(&msg.name).size(14))
+                .push(text(msg.kind.to_string()).size(12).color(colors::DARK_GREY))
+                .push(horizontal_space())
+                .push(tooltip(
+                    "Edit",
+                    icon_button(icons::Pencil, Some(20), Some(12))
+                        .on_press(WebSocketTabMsg::LoadMessage(idx))
+                        .style(button::secondary),
+                ))
+                .push(tooltip(
+                    "Send",
+                    icon_button(icons::Send, Some(20), Some(12))
+                        .on_press_maybe(connected.then_some(WebSocketTabMsg::SendSaved(idx)))
+                        .style(button::secondary),
+                ))
+                .push(tooltip(
+                    "Delete",
+                    icon_button(icons::Delete, Some(20), Some(12))
+                        .on_press(WebSocketTabMsg::DeleteMessage(idx))
+                        .style(button::secondary),
+                ))
+                .spacing(4)
+                .align_y(Alignment::Center);
+            col.push(row)
+        })
+        .spacing(4)
+        .width(Length::Fill);
+
+    scrollable(messages)
+        .height(Length::Fill)
+        .width(Length::Fill)
+        .into()
+}
+
+fn key_values_view<'a>(
+    title: &'a str,
+    values: &'a KeyValList,
+    on_change: fn(KeyValUpdateMsg) -> WebSocketTabMsg,
+) -> Element<'a, WebSocketTabMsg> {
+    scrollable(
+        Column::new()
+            .push(title)
+            .push(key_value_editor(values).on_change(on_change))
+            .width(Length::Fill)
+            .spacing(4),
+    )
+    .height(Length::Fill)
+    .width(Length::Fill)
+    .into()
+}
+
+pub(super) fn view(tab: &WebSocketTab) -> Element<WebSocketTabMsg> {
+    let tab_content = match tab.tab {
+        WsTabId::Message => composer_view(tab),
+        WsTabId::Saved => saved_view(tab),
+        WsTabId::Params => {
+            key_values_view("Query Params", &tab.query_params, WebSocketTabMsg::Queries)
+        }
+        WsTabId::Headers => key_values_view("Headers", &tab.headers, WebSocketTabMsg::Headers),
+        WsTabId::Auth => auth_view(&tab.auth).map(WebSocketTabMsg::Auth),
+    };
+
+    let tabs = button_tabs(
+        tab.tab,
+        [
+            button_tab(WsTabId::Message, || text("Message")),
+            button_tab(WsTabId::Saved, || text("Saved")),
+            button_tab(WsTabId::Params, || text("Params")),
+            button_tab(WsTabId::Auth, || text("Auth")),
+            button_tab(WsTabId::Headers, || text("Headers")),
+        ]
+        .into_iter(),
+        WebSocketTabMsg::TabSelected,
+        None,
+    );
+
+    Column::new()
+        .push(tabs)
+        .push(tab_content)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .spacing(4)
+        .into()
+}
@@ -0,0 +1,113 @@
+use core::preview::hex_dump;
+use core::utils::fmt_duration;
+
+use iced::widget::text::Wrapping;
+use iced::widget::{button, horizontal_space, scrollable, text, Column, Row};
+use iced::{Alignment, Color, Element, Font, Length};
+
+use components::{colors, horizontal_line, icon, icons};
+use core::websocket::WsMessage;
+
+use super::WebSocketTabMsg;
+use crate::state::ws_tab::{ConnectionState, LoggedMessage, MessageDirection};
+use crate::state::WebSocketTab;
+
+fn message_view(logged: &LoggedMessage) -> Element<WebSocketTabMsg> {
+    let size = 12;
+    let (arrow, color) = match logged.direction {
+        MessageDirection::Sent => (icons::ArrowUpBold, colors::DODGER_BLUE),
+        MessageDirection::Received => (icons::ArrowDownBold, colors::LIME_GREEN),
+    };
+
+    let (kind, body) = match &logged.message {
+        WsMessage::Text(text) => ("text", text.clone()),
+        WsMessage::Binary(data) => ("binary", hex_dump(data, 0, data.len())),
+    };
+
+    let header = Row::new()
+        .push(icon(arrow).size(size).color(color))
+        .push(
+            text(logged.time.format("%H:%M:%S%.3f").to_string())
+                .size(size)
+                .color(colors::DARK_GREY),
+        )
+        .push(
+            text(format!("{} · {} bytes", kind, logged.message.len()))
+                .size(size)
+                .color(colors::DARK_GREY),
+        )
+        .spacing(8)
+        .align_y(Alignment::Center);
+
+    Column::new()
+        .push(header)
+        .push(
+            text(body)
+                .size(13)
+                .font(Font::MONOSPACE)
+                .wrapping(Wrapping::WordOrGlyph),
+        )
+        .spacing(2)
+        .padding([4, 8])
+        .width(Length::Fill)
+        .into()
+}
+
+fn status_view(tab: &WebSocketTab) -> Element<WebSocketTabMsg> {
+    let size = 12;
+    let grey = Color::from_rgb8(160, 160, 160);
+
+    let status = match &tab.connection {
+        ConnectionState::Disconnected => text("Disconnected").color(grey),
+        ConnectionState::Connecting => text("Connecting...").color(colors::GOLD),
+        ConnectionState::Connected(handshake) => {
+            text(format!("Connected in {}", fmt_duration(handshake.duration)))
+                .color(colors::LIME_GREEN)
+        }
+        ConnectionState::Closed(Some(reason)) if !reason.reason.is_empty() => {
+            text(format!("Closed ({}: {})", reason.code, reason.reason)).color(grey)
+        }
+        ConnectionState::Closed(Some(reason)) => {
+            text(format!("Closed ({})", reason.code)).color(grey)
+        }
+        ConnectionState::Closed(None) => text("Closed").color(grey),
+        ConnectionState::Failed(e) => text(format!("{:#}", e)).color(colors::RED),
+    };
+
+    Row::new()
+        .push(status.size(size))
+        .push(horizontal_space())
+        .push(
+            text(format!("{} messages", tab.log.len()))
+                .size(size)
+                .color(Color::from_rgb8(182, 128, 182)),
+        )
+        .push(
+            button(text("Clear").size(14))
+                .padding([2, 4])
+                .style(button::secondary)
+                .on_press(WebSocketTabMsg::ClearLog),
+        )
+        .spacing(8)
+        .padding([4, 8])
+        .align_y(Alignment::Center)
+        .into()
+}
+
+pub(super) fn view(tab: &WebSocketTab) -> Element<WebSocketTabMsg> {
+    let messages = tab
+        .log
+        .iter()
+        .fold(Column::new(), |col, logged| {
+            col.push(message_view(logged)).push(horizontal_line(1))
+        })
+        .width(Length::Fill);
+
+    Column::new()
+        .push(status_view(tab))
+        .push(scrollable(messages).height(Length::Fill))
+        .spacing(4)
+        .height(Length::Fill)
+        .width(Length::Fill)
+        .into()
+}
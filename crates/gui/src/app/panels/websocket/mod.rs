@@ -0,0 +1,245 @@
+use std::sync::Arc;
+
+use iced::padding;
+use iced::widget::pane_grid::ResizeEvent;
+use iced::widget::{container, pane_grid, Column, PaneGrid};
+use iced::{Element, Task};
+
+use components::text_editor::{self, ContentAction};
+use components::{bordered_left, bordered_right, CodeEditorMsg, KeyValUpdateMsg};
+use core::http::websocket::MessageKind;
+use core::persistence::request::RequestOnDisk;
+use core::transformers::websocket::transform_message;
+use core::websocket::WsEvent;
+
+use crate::app::panels::http::panes::request::auth_editor::AuthEditorMsg;
+use crate::commands::builders::{
+    connect_websocket_cmd, save_failed_cmd, save_websocket_cmd, WebSocketResult,
+};
+use crate::state::popups::{Popup, PopupNameAction};
+use crate::state::watch::save_conflict;
+use crate::state::ws_tab::{ConnectionState, MessageDirection, WsTabId};
+use crate::state::{AppState, SplitState, Tab, TabKey, WebSocketTab};
+
+mod config;
+mod message_log;
+mod url_bar;
+
+const BORDER_WIDTH: u16 = 1;
+
+#[derive(Debug, Clone)]
+pub enum WebSocketTabMsg {
+    UrlChanged(ContentAction),
+    Connect,
+    Disconnect,
+    Save,
+    Saved(TabKey, Option<Arc<anyhow::Error>>),
+    SaveConflict(TabKey, Option<RequestOnDisk>),
+    Event(TabKey, WebSocketResult),
+    TabSelected(WsTabId),
+    Headers(KeyValUpdateMsg),
+    Queries(KeyValUpdateMsg),
+    Auth(AuthEditorMsg),
+    ComposerKind(MessageKind),
+    Composer(CodeEditorMsg),
+    Send,
+    SaveMessage,
+    LoadMessage(usize),
+    SendSaved(usize),
+    DeleteMessage(usize),
+    ClearLog,
+    SplitResize(ResizeEvent),
+}
+
+fn update_connection(tab: &mut WebSocketTab, result: WebSocketResult) {
+    match result {
+        WebSocketResult::Event(WsEvent::Connected(handshake)) => {
+            tab.connection = ConnectionState::Connected(handshake);
+        }
+        WebSocketResult::Event(WsEvent::Sent(msg)) => {
+            tab.log_message(MessageDirection::Sent, msg);
+        }
+        WebSocketResult::Event(WsEvent::Received(msg)) => {
+            tab.log_message(MessageDirection::Received, msg);
+        }
+        WebSocketResult::Event(WsEvent::Closed(reason)) => {
+            tab.sender = None;
+            tab.cancel_tasks();
+            tab.connection = ConnectionState::Closed(reason);
+        }
+        WebSocketResult::Error(e) => {
+            tab.sender = None;
+            tab.cancel_tasks();
+            tab.connection = ConnectionState::Failed(e);
+        }
+        // Whoever cancelled the connection has already updated the state
+        WebSocketResult::Cancelled => (),
+    }
+}
+
+impl WebSocketTabMsg {
+    /// Changes the request saved to the file
+    fn is_edit(&self) -> bool {
+        match self {
+            Self::UrlChanged(action) => action.is_edit(),
+            Self::Headers(_)
+            | Self::Queries(_)
+            | Self::Auth(_)
+            | Self::SaveMessage
+            | Self::DeleteMessage(_) => true,
+            _ => false,
+        }
+    }
+
+    pub fn update(self, state: &mut AppState) -> Task<Self> {
+        let active = state.active_tab.zip(state.active_tab_mut());
+        let Some((active_tab, Tab::WebSocket(tab))) = active else {
+            return Task::none();
+        };
+
+        if self.is_edit() {
+            tab.check_dirty();
+        }
+        match self {
+            Self::UrlChanged(action) => tab.url_content.perform(action),
+            Self::Connect => {
+                tab.disconnect();
+                tab.cancel_tasks();
+                let cb = move |r| Self::Event(active_tab, r);
+                return connect_websocket_cmd(state, active_tab, cb);
+            }
+            Self::Disconnect => tab.disconnect(),
+            Self::Save => {
+                let req = tab.to_request();
+                let collection_ref = tab.collection_ref;
+                if let Some(req_ref) = state.collections.get_ref(collection_ref) {
+                    let path = req_ref.path.clone();
+                    let cb = move |r| Self::Saved(active_tab, r);
+                    return save_websocket_cmd(req, path, cb);
+                }
+            }
+            Self::Saved(key, None) => {
+                if let Some(Tab::WebSocket(tab)) = state.get_tab_mut(key) {
+                    tab.check_dirty();
+                }
+            }
+            Self::Saved(key, Some(e)) => {
+                return save_failed_cmd(state, key, &e, move |r| Self::SaveConflict(key, r));
+            }
+            Self::SaveConflict(key, theirs) => {
+                if let Some(theirs) = theirs {
+                    save_conflict(state, key, theirs);
+                }
+            }
+            Self::Event(key, result) => {
+                if let Some(Tab::WebSocket(tab)) = state.get_tab_mut(key) {
+                    update_connection(tab, result);
+                }
+            }
+            Self::TabSelected(id) => tab.tab = id,
+            Self::Headers(msg) => tab.headers.update(msg),
+            Self::Queries(msg) => tab.query_params.update(msg),
+            Self::Auth(msg) => msg.update(&mut tab.auth),
+            Self::ComposerKind(kind) => {
+                tab.composer_kind = kind;
+                tab.composer_error = None;
+            }
+            Self::Composer(msg) => msg.update(&mut tab.composer),
+            Self::Send => {
+                let body = tab.composer.text();
+                let kind = tab.composer_kind;
+                send_message(state, active_tab, kind, &body);
+            }
+            Self::SaveMessage => {
+                Popup::popup_name(
+                    state,
+                    String::new(),
+                    PopupNameAction::SaveWebSocketMessage(active_tab),
+                );
+            }
+            Self::LoadMessage(idx) => {
+                if let Some(template) = tab.messages.get(idx) {
+                    tab.composer_kind = template.kind;
+                    tab.composer = text_editor::Content::with_text(&template.body);
+                    tab.composer_error = None;
+                    tab.tab = WsTabId::Message;
+                }
+            }
+            Self::SendSaved(idx) => {
+                if let Some(template) = tab.messages.get(idx).cloned() {
+                    send_message(state, active_tab, template.kind, &template.body);
+                }
+            }
+            Self::DeleteMessage(idx) => {
+                if idx < tab.messages.len() {
+                    tab.messages.remove(idx);
+                }
+            }
+            Self::ClearLog => tab.log.clear(),
+            Self::SplitResize(ResizeEvent { split, ratio }) => {
+                // Only allow resizing if the ratio is min 0.25 on both sides
+                if ratio > 0.25 && ratio < 0.75 {
+                    tab.panes.resize(split, ratio);
+                }
+            }
+        }
+        Task::none()
+    }
+}
+
+fn send_message(state: &mut AppState, key: TabKey, kind: MessageKind, body: &str) {
+    let env = state
+        .get_tab(key)
+        .and_then(|tab| match tab {
+            Tab::WebSocket(tab) => Some(tab.collection_key()),
+            _ => None,
+        })
+        .and_then(|col| state.environment(col));
+
+    let Some(Tab::WebSocket(tab)) = state.get_tab_mut(key) else {
+        return;
+    };
+
+    let message = match transform_message(kind, body, env.as_ref()) {
+        Ok(message) => message,
+        Err(e) => {
+            tab.composer_error = Some(format!("{:#}", e));
+            return;
+        }
+    };
+
+    tab.composer_error = match tab.sender.as_ref() {
+        Some(sender) if sender.send(message).is_ok() => None,
+        _ => Some("Not connected".to_string()),
+    };
+}
+
+pub fn view(tab: &WebSocketTab) -> Element<WebSocketTabMsg> {
+    let url_bar = url_bar::view(tab);
+
+    let panes = PaneGrid::new(&tab.panes, move |_, pane, _| {
+        let pane = match pane {
+            SplitState::First => bordered_right(
+                BORDER_WIDTH,
+                container(config::view(tab)).padding(padding::right(4)),
+            ),
+            SplitState::Second => bordered_left(
+                BORDER_WIDTH,
+                container(message_log::view(tab)).padding(padding::left(4)),
+            ),
+        };
+
+        pane_grid::Content::new(pane)
+    })
+    .height(iced::Length::Fill)
+    .width(iced::Length::Fill)
+    .on_resize(8, WebSocketTabMsg::SplitResize);
+
+    Column::new()
+        .push(url_bar)
+        .push(container(panes).padding(padding::top(4)))
+        .height(iced::Length::Fill)
+        .width(iced::Length::Fill)
+        .spacing(4)
+        .into()
+}
@@ -0,0 +1,74 @@
+use iced::widget::{button, container, text, vertical_rule, Button, Row};
+use iced::{border, Alignment, Border, Element};
+
+use components::text_editor::{self, line_editor};
+use components::{icon, icons, NerdIcon};
+
+use super::WebSocketTabMsg;
+use crate::state::ws_tab::ConnectionState;
+use crate::state::WebSocketTab;
+
+fn icon_button<'a>(ico: NerdIcon) -> Button<'a, WebSocketTabMsg> {
+    button(container(icon(ico)).padding([0, 8])).style(|t, s| button::Style {
+        border: border::rounded(0),
+        ..button::primary(t, s)
+    })
+}
+
+pub(super) fn view(tab: &WebSocketTab) -> Element<WebSocketTabMsg> {
+    let border = Border::default();
+
+    let scheme = container(text("WS").size(14))
+        .padding([4, 10])
+        .align_y(Alignment::Center)
+        .style(move |theme| container::Style {
+            border: border.rounded(border::left(4)),
+            ..container::bordered_box(theme)
+        });
+
+    let url = line_editor(&tab.url_content)
+        .style(move |t: &iced::Theme, _| text_editor::Style {
+            border,
+            ..text_editor::default(t, text_editor::Status::Active)
+        })
+        .on_action(WebSocketTabMsg::UrlChanged);
+
+    let connection = match tab.connection {
+        ConnectionState::Connecting | ConnectionState::Connected(_) => {
+            icon_button(icons::LanDisconnect)
+                .on_press(WebSocketTabMsg::Disconnect)
+                .style(|t, s| button::Style {
+                    border: border::rounded(0),
+                    ..button::danger(t, s)
+                })
+        }
+        _ => icon_button(icons::LanConnect).on_press(WebSocketTabMsg::Connect),
+    };
+
+    let buttons = Row::new().push(connection).push(vertical_rule(1)).push(
+        icon_button(icons::ContentSave)
+            .on_press(WebSocketTabMsg::Save)
+            .style(|t, s| button::Style {
+                border: border::rounded(border::right(4)),
+                ..button::primary(t, s)
+            }),
+    );
+
+    container(
+        Row::new()
+            .push(scheme)
+            .push(url)
+            .push(buttons)
+            .height(iced::Length::Shrink)
+            .width(iced::Length::Fill),
+    )
+    .style(|theme| {
+        let base = container::bordered_box(theme);
+        container::Style {
+            border: base.border.width(2).rounded(4),
+            ..base
+        }
+    })
+    .padding(1)
+    .into()
+}
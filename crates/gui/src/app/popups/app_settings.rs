@@ -1,9 +1,11 @@
 use std::borrow::Cow;
 
-use components::{button_tab, button_tabs};
-use iced::widget::{horizontal_space, pick_list, text, Column, Row};
-use iced::{Task, Element, Theme};
+use components::{button_tab, button_tabs, colors, key_value_editor, KeyValUpdateMsg};
+use core::http::environment::Environment;
+use iced::widget::{horizontal_space, pick_list, scrollable, text, Column, Row};
+use iced::{Element, Task, Theme};
 
+use crate::commands::builders::save_globals_cmd;
 use crate::state::popups::{AppSettingTabs, AppSettingsState, Popup};
 use crate::state::AppState;
 
@@ -11,6 +13,8 @@ use crate::state::AppState;
 pub enum Message {
     TabChange(AppSettingTabs),
     ChangeTheme(Theme),
+    GlobalsChanged(KeyValUpdateMsg),
+    GlobalsSaved,
     Done,
 }
 
@@ -22,7 +26,9 @@ impl Message {
 
         match self {
             Message::Done => {
+                state.globals = Environment::from(&data.globals);
                 state.popup = None;
+                return save_globals_cmd(state.globals.clone(), || Message::GlobalsSaved);
             }
             Message::TabChange(tab) => {
                 data.active_tab = tab;
@@ -30,6 +36,10 @@ impl Message {
             Message::ChangeTheme(theme) => {
                 state.theme = theme;
             }
+            Message::GlobalsChanged(msg) => {
+                data.globals.variables.update(msg);
+            }
+            Message::GlobalsSaved => (),
         }
         Task::none()
     }
@@ -46,19 +56,24 @@ pub fn done(_data: &AppSettingsState) -> Option<Message> {
 pub(crate) fn view<'a>(state: &'a AppState, data: &'a AppSettingsState) -> Element<'a, Message> {
     let tab_bar = button_tabs(
         data.active_tab,
-        [button_tab(AppSettingTabs::General, move || text("General"))].into_iter(),
+        [
+            button_tab(AppSettingTabs::General, move || text("General")),
+            button_tab(AppSettingTabs::Globals, move || text("Global Variables")),
+        ]
+        .into_iter(),
         Message::TabChange,
         None,
     );
     let content = match data.active_tab {
         AppSettingTabs::General => general_tab(state),
+        AppSettingTabs::Globals => globals_tab(data),
     };
 
     Column::new()
         .push(tab_bar)
         .push(content)
         .spacing(16)
-        .width(400)
+        .width(500)
         .into()
 }
 
@@ -72,3 +87,18 @@ fn general_tab(state: &AppState) -> Element<Message> {
 
     Column::new().push(theme).spacing(8).into()
 }
+
+fn globals_tab(data: &AppSettingsState) -> Element<Message> {
+    let info = text("Shared by all collections, environments override them").size(12);
+    let warning = text("Saved in plain text, keep secrets in a collection environment")
+        .size(12)
+        .color(colors::WARNING);
+    let editor = key_value_editor(&data.globals.variables).on_change(Message::GlobalsChanged);
+
+    Column::new()
+        .push(info)
+        .push(warning)
+        .push(scrollable(editor).height(300))
+        .spacing(8)
+        .into()
+}
@@ -0,0 +1,111 @@
+use std::borrow::Cow;
+
+use iced::widget::{button, checkbox, container, horizontal_space, pick_list, scrollable, text};
+use iced::widget::{Column, Row};
+use iced::{clipboard, Alignment, Element, Font, Length, Task};
+use strum::VariantArray;
+
+use core::codegen::{self, Language};
+
+use crate::state::popups::{GenerateCodeState, Popup};
+use crate::state::{AppState, Tab};
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    LanguageSelected(Language),
+    KeepVariables(bool),
+    Copy,
+    Done,
+}
+
+impl Message {
+    pub fn update(self, state: &mut AppState) -> Task<Message> {
+        let code = snippet(state);
+        let Some(Popup::GenerateCode(data)) = state.popup.as_mut() else {
+            return Task::none();
+        };
+
+        match self {
+            Message::LanguageSelected(language) => data.language = language,
+            Message::KeepVariables(keep) => data.keep_variables = keep,
+            Message::Copy => {
+                if let Some(code) = code {
+                    return clipboard::write(code);
+                }
+            }
+            Message::Done => state.popup = None,
+        }
+        Task::none()
+    }
+}
+
+/// Generates the code for the popup's tab, the unsaved edits are included
+fn snippet(state: &AppState) -> Option<String> {
+    let Some(Popup::GenerateCode(data)) = state.popup.as_ref() else {
+        return None;
+    };
+    let Some(Tab::Http(tab)) = state.get_tab(data.tab) else {
+        return None;
+    };
+
+    let env = state
+        .environment(tab.collection_ref.0)
+        .filter(|_| !data.keep_variables);
+    let root = state
+        .collections
+        .get(tab.collection_ref.0)
+        .map(|c| c.path.clone())
+        .unwrap_or_default();
+
+    Some(codegen::generate(
+        data.language,
+        tab.request().to_request(),
+        &root,
+        env.as_ref(),
+    ))
+}
+
+pub fn title<'a>() -> Cow<'a, str> {
+    Cow::Borrowed("Generate Code")
+}
+
+pub fn done(_data: &GenerateCodeState) -> Option<Message> {
+    Some(Message::Done)
+}
+
+pub(crate) fn view<'a>(state: &'a AppState, data: &'a GenerateCodeState) -> Element<'a, Message> {
+    let size = 14;
+
+    let options = Row::new()
+        .push(
+            pick_list(
+                Language::VARIANTS,
+                Some(data.language),
+                Message::LanguageSelected,
+            )
+            .text_size(size),
+        )
+        .push(
+            checkbox("Keep {{variables}}", data.keep_variables)
+                .on_toggle(Message::KeepVariables)
+                .text_size(size),
+        )
+        .push(horizontal_space())
+        .push(
+            button(text("Copy").size(size))
+                .padding([2, 8])
+                .style(button::secondary)
+                .on_press(Message::Copy),
+        )
+        .align_y(Alignment::Center)
+        .spacing(8);
+
+    let code = snippet(state).unwrap_or_default();
+    let code = container(scrollable(text(code).size(size).font(Font::MONOSPACE)))
+        .padding(8)
+        .style(container::bordered_box)
+        .width(Length::Fixed(560.))
+        .height(Length::Fixed(360.));
+
+    Column::new().push(options).push(code).spacing(12).into()
+}
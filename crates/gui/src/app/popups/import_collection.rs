@@ -0,0 +1,308 @@
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use iced::widget::{button, checkbox, horizontal_space, pick_list, scrollable, text, Column, Row};
+use iced::{Alignment, Element, Task};
+use rfd::FileHandle;
+use strum::VariantArray;
+
+use components::{colors, icon_button, icons, tooltip};
+use core::http::collection::Collection;
+use core::persistence::import::har::HarOptions;
+use core::persistence::import::openapi::OpenApiOptions;
+use core::persistence::import::ImportReport;
+
+use crate::commands::builders::import_collection_cmd;
+use crate::commands::dialog::{open_file_dialog, open_files_dialog, open_folder_dialog};
+use crate::state::popups::Popup::ImportCollection;
+use crate::state::popups::{ImportCollectionState, ImportFormat};
+use crate::state::AppState;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    FormatSelected(ImportFormat),
+    SelectSource,
+    SourceSelected(Option<Arc<FileHandle>>),
+    AddEnvironments,
+    EnvironmentsSelected(Vec<PathBuf>),
+    RemoveEnvironment(usize),
+    StatusAssertions(bool),
+    Deduplicate(bool),
+    SelectLocation,
+    LocationSelected(Option<Arc<FileHandle>>),
+    Import,
+    Imported(Result<(Collection, ImportReport), Arc<anyhow::Error>>),
+    Close,
+}
+
+impl Message {
+    pub fn update(self, state: &mut AppState) -> Task<Message> {
+        // The collection is opened even if the popup was closed meanwhile
+        if let Message::Imported(result) = self {
+            let report = match result {
+                Ok((collection, report)) => {
+                    state.collections.insert(collection);
+                    Ok(report)
+                }
+                Err(e) => {
+                    log::error!("Error importing collection: {:?}", e);
+                    Err(e.to_string())
+                }
+            };
+            if let Some(ImportCollection(data)) = state.popup.as_mut() {
+                data.importing = false;
+                match report {
+                    Ok(report) if report.is_empty() => state.popup = None,
+                    Ok(report) => data.report = Some(report),
+                    Err(e) => data.error = Some(e),
+                }
+            }
+            return Task::none();
+        }
+
+        let Some(ImportCollection(data)) = state.popup.as_mut() else {
+            return Task::none();
+        };
+
+        match self {
+            Message::FormatSelected(format) => {
+                data.format = format;
+            }
+            Message::SelectSource => {
+                return open_file_dialog("Select file to import", Message::SourceSelected);
+            }
+            Message::SourceSelected(handle) => {
+                if let Some(handle) = handle {
+                    data.source = Some(handle.path().to_owned());
+                }
+            }
+            Message::AddEnvironments => {
+                return open_files_dialog(
+                    "Select Postman Environments",
+                    ("JSON", &["json"]),
+                    Message::EnvironmentsSelected,
+                );
+            }
+            Message::EnvironmentsSelected(files) => {
+                for file in files {
+                    if !data.environments.contains(&file) {
+                        data.environments.push(file);
+                    }
+                }
+            }
+            Message::RemoveEnvironment(idx) => {
+                if idx < data.environments.len() {
+                    data.environments.remove(idx);
+                }
+            }
+            Message::StatusAssertions(enabled) => {
+                data.status_assertions = enabled;
+            }
+            Message::Deduplicate(enabled) => {
+                data.deduplicate = enabled;
+            }
+            Message::SelectLocation => {
+                return open_folder_dialog("Select location", Message::LocationSelected);
+            }
+            Message::LocationSelected(handle) => {
+                if let Some(handle) = handle {
+                    data.path = Some(handle.path().to_owned());
+                }
+            }
+            Message::Import => {
+                let (Some(source), Some(path)) = (data.source.clone(), data.path.clone()) else {
+                    return Task::none();
+                };
+                data.importing = true;
+                data.error = None;
+                let environments = match data.format {
+                    ImportFormat::Postman => data.environments.clone(),
+                    ImportFormat::OpenApi | ImportFormat::Har => Vec::new(),
+                };
+                let openapi_options = OpenApiOptions {
+                    status_assertions: data.status_assertions,
+                };
+                let har_options = HarOptions {
+                    deduplicate: data.deduplicate,
+                };
+                return import_collection_cmd(
+                    data.format,
+                    source,
+                    environments,
+                    openapi_options,
+                    har_options,
+                    path,
+                    Message::Imported,
+                );
+            }
+            Message::Close => {
+                state.popup = None;
+            }
+            Message::Imported(_) => (),
+        }
+        Task::none()
+    }
+}
+
+pub fn title<'a>() -> Cow<'a, str> {
+    Cow::Borrowed("Import Collection")
+}
+
+pub fn done(data: &ImportCollectionState) -> Option<Message> {
+    if data.report.is_some() {
+        return Some(Message::Close);
+    }
+    if data.importing || data.source.is_none() || data.path.is_none() {
+        return None;
+    }
+    Some(Message::Import)
+}
+
+fn browse_button<'a>(
+    path: Option<&'a Path>,
+    placeholder: &'a str,
+    msg: Message,
+) -> Element<'a, Message> {
+    let label = path
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|| placeholder.to_string());
+
+    button(text(label).size(16))
+        .style(button::text)
+        .padding([2, 6])
+        .on_press(msg)
+        .into()
+}
+
+fn report_view(report: &ImportReport) -> Element<Message> {
+    let warnings = report
+        .warnings
+        .iter()
+        .fold(Column::new(), |col, warning| {
+            col.push(text(warning).size(13))
+        })
+        .spacing(2);
+
+    Column::new()
+        .push(text("Imported with warnings").color(colors::WARNING))
+        .push(scrollable(warnings).height(200))
+        .spacing(8)
+        .width(450)
+        .into()
+}
+
+pub(crate) fn view<'a>(
+    _state: &'a AppState,
+    data: &'a ImportCollectionState,
+) -> Element<'a, Message> {
+    if let Some(report) = &data.report {
+        return report_view(report);
+    }
+
+    let size = 14;
+
+    let format = Row::new()
+        .push(text("Format"))
+        .push(horizontal_space())
+        .push(
+            pick_list(
+                ImportFormat::VARIANTS,
+                Some(data.format),
+                Message::FormatSelected,
+            )
+            .text_size(size),
+        )
+        .align_y(Alignment::Center)
+        .spacing(4);
+
+    let source = Row::new()
+        .push(text("File"))
+        .push(horizontal_space())
+        .push(browse_button(
+            data.source.as_deref(),
+            "Browse file",
+            Message::SelectSource,
+        ))
+        .align_y(Alignment::Center)
+        .spacing(4);
+
+    let environments = (data.format == ImportFormat::Postman).then(|| {
+        let header = Row::new()
+            .push(text("Environments"))
+            .push(horizontal_space())
+            .push(
+                button(text("Add").size(size))
+                    .padding([2, 8])
+                    .style(button::secondary)
+                    .on_press(Message::AddEnvironments),
+            )
+            .align_y(Alignment::Center);
+
+        data.environments
+            .iter()
+            .enumerate()
+            .fold(Column::new().push(header), |col, (idx, file)| {
+                col.push(
+                    Row::new()
+                        .push(text(file.display().to_string()).size(size))
+                        .push(horizontal_space())
+                        .push(tooltip(
+                            "Remove",
+                            icon_button(icons::Delete, Some(20), Some(12))
+                                .on_press(Message::RemoveEnvironment(idx))
+                                .style(button::secondary),
+                        ))
+                        .spacing(4)
+                        .align_y(Alignment::Center),
+                )
+            })
+            .spacing(4)
+    });
+
+    let assertions = (data.format == ImportFormat::OpenApi).then(|| {
+        checkbox(
+            "Add status assertions from responses",
+            data.status_assertions,
+        )
+        .on_toggle(Message::StatusAssertions)
+        .text_size(size)
+    });
+
+    let deduplicate = (data.format == ImportFormat::Har).then(|| {
+        checkbox("Keep one request per method and path", data.deduplicate)
+            .on_toggle(Message::Deduplicate)
+            .text_size(size)
+    });
+
+    let path = Row::new()
+        .push(text("Location"))
+        .push(horizontal_space())
+        .push(browse_button(
+            data.path.as_deref(),
+            "Browse location",
+            Message::SelectLocation,
+        ))
+        .align_y(Alignment::Center)
+        .spacing(4);
+
+    let status = if data.importing {
+        Some(text("Importing...").size(size))
+    } else {
+        data.error
+            .as_ref()
+            .map(|e| text(e).size(size).color(colors::RED))
+    };
+
+    Column::new()
+        .push(format)
+        .push(source)
+        .push_maybe(environments)
+        .push_maybe(assertions)
+        .push_maybe(deduplicate)
+        .push(path)
+        .push_maybe(status)
+        .spacing(8)
+        .width(400)
+        .into()
+}
@@ -7,15 +7,25 @@ use crate::state::AppState;
 
 mod app_settings;
 mod create_collection;
+mod generate_code;
+mod import_collection;
 mod name_popup;
+mod request_conflict;
+mod save_error;
 mod save_request;
+mod unlock_secrets;
 
 #[derive(Clone, Debug)]
 pub enum PopupMsg {
     CreateCollection(create_collection::Message),
+    ImportCollection(import_collection::Message),
     SaveRequest(save_request::Message),
     RenamePopup(name_popup::Message),
     AppSettings(app_settings::Message),
+    GenerateCode(generate_code::Message),
+    UnlockSecrets(unlock_secrets::Message),
+    RequestConflict(request_conflict::Message),
+    SaveError(save_error::Message),
     ClosePopup,
     Ignore,
 }
@@ -24,9 +34,14 @@ impl PopupMsg {
     pub fn update(self, state: &mut AppState) -> Task<PopupMsg> {
         match self {
             PopupMsg::CreateCollection(msg) => msg.update(state).map(PopupMsg::CreateCollection),
+            PopupMsg::ImportCollection(msg) => msg.update(state).map(PopupMsg::ImportCollection),
             PopupMsg::SaveRequest(msg) => msg.update(state).map(PopupMsg::SaveRequest),
             PopupMsg::RenamePopup(msg) => msg.update(state).map(PopupMsg::RenamePopup),
             PopupMsg::AppSettings(msg) => msg.update(state).map(PopupMsg::AppSettings),
+            PopupMsg::GenerateCode(msg) => msg.update(state).map(PopupMsg::GenerateCode),
+            PopupMsg::UnlockSecrets(msg) => msg.update(state).map(PopupMsg::UnlockSecrets),
+            PopupMsg::RequestConflict(msg) => msg.update(state).map(PopupMsg::RequestConflict),
+            PopupMsg::SaveError(msg) => msg.update(state).map(PopupMsg::SaveError),
             PopupMsg::ClosePopup => {
                 Popup::close(state);
                 Task::none()
@@ -43,6 +58,11 @@ pub fn view<'a>(state: &'a AppState, popup: &'a Popup) -> Element<'a, PopupMsg>
             create_collection::view(state, data).map(PopupMsg::CreateCollection),
             create_collection::done(data).map(PopupMsg::CreateCollection),
         ),
+        Popup::ImportCollection(data) => (
+            import_collection::title(),
+            import_collection::view(state, data).map(PopupMsg::ImportCollection),
+            import_collection::done(data).map(PopupMsg::ImportCollection),
+        ),
         Popup::SaveRequest(data) => (
             save_request::title(),
             save_request::view(state, data).map(PopupMsg::SaveRequest),
@@ -58,6 +78,26 @@ pub fn view<'a>(state: &'a AppState, popup: &'a Popup) -> Element<'a, PopupMsg>
             app_settings::view(state, data).map(PopupMsg::AppSettings),
             app_settings::done(data).map(PopupMsg::AppSettings),
         ),
+        Popup::GenerateCode(data) => (
+            generate_code::title(),
+            generate_code::view(state, data).map(PopupMsg::GenerateCode),
+            generate_code::done(data).map(PopupMsg::GenerateCode),
+        ),
+        Popup::UnlockSecrets(data) => (
+            unlock_secrets::title(),
+            unlock_secrets::view(state, data).map(PopupMsg::UnlockSecrets),
+            unlock_secrets::done(data).map(PopupMsg::UnlockSecrets),
+        ),
+        Popup::RequestConflict(data) => (
+            request_conflict::title(),
+            request_conflict::view(state, data).map(PopupMsg::RequestConflict),
+            request_conflict::done(data).map(PopupMsg::RequestConflict),
+        ),
+        Popup::SaveError(data) => (
+            save_error::title(),
+            save_error::view(state, data).map(PopupMsg::SaveError),
+            save_error::done(data).map(PopupMsg::SaveError),
+        ),
     };
 
     let buttons = Row::new()
@@ -1,13 +1,16 @@
+use core::http::grpc::GrpcRequest;
 use core::http::request::Request;
+use core::http::websocket::{MessageTemplate, WebSocketRequest};
 use core::http::CollectionRequest;
 use std::borrow::Cow;
+use std::sync::Arc;
 
 use iced::widget::{horizontal_space, text, text_input, Column, Row};
 use iced::{Element, Task};
 
 use crate::commands::builders::{
-    create_folder_cmd, create_new_request_cmd, create_script_cmd, rename_folder_cmd,
-    rename_request_cmd,
+    create_folder_cmd, create_new_grpc_cmd, create_new_request_cmd, create_new_websocket_cmd,
+    create_script_cmd, rename_folder_cmd, rename_request_cmd, save_example_cmd,
 };
 use crate::state::environment::Env;
 use crate::state::popups::{Popup, PopupNameAction, PopupNameState};
@@ -17,11 +20,24 @@ use crate::state::{AppState, Tab};
 pub enum Message {
     NameChanged(String),
     Rename(String),
+    ExampleSaved(CollectionRequest, String, Option<Arc<anyhow::Error>>),
     Done,
 }
 
 impl Message {
     pub fn update(self, state: &mut AppState) -> Task<Message> {
+        // Listed once the file is written
+        if let Message::ExampleSaved(req, name, result) = self {
+            match result {
+                None => {
+                    state.collections.add_example(req, &name);
+                    state.popup = None;
+                }
+                Some(e) => Popup::save_error(state, format!("Example {}", name), &e),
+            }
+            return Task::none();
+        }
+
         let Some(Popup::PopupName(data)) = state.popup.as_mut() else {
             return Task::none();
         };
@@ -50,6 +66,37 @@ impl Message {
                         Message::Done
                     })
                 }
+                PopupNameAction::NewWebSocket(col, folder) => create_new_websocket_cmd(
+                    state,
+                    col,
+                    folder,
+                    name,
+                    WebSocketRequest::default(),
+                    |_| Message::Done,
+                ),
+                PopupNameAction::NewGrpc(col, folder) => {
+                    create_new_grpc_cmd(state, col, folder, name, GrpcRequest::default(), |_| {
+                        Message::Done
+                    })
+                }
+                PopupNameAction::SaveWebSocketMessage(tab) => {
+                    if let Some(Tab::WebSocket(tab)) = state.get_tab_mut(tab) {
+                        let template = MessageTemplate {
+                            name,
+                            kind: tab.composer_kind,
+                            body: tab.composer.text(),
+                        };
+                        match tab.messages.iter_mut().find(|m| m.name == template.name) {
+                            Some(existing) => *existing = template,
+                            None => tab.messages.push(template),
+                        }
+                        tab.check_dirty();
+                    }
+                    Task::done(Message::Done)
+                }
+                PopupNameAction::SaveExample(tab) => {
+                    save_example_cmd(state, tab, name, Message::ExampleSaved)
+                }
                 PopupNameAction::NewScript(col) => {
                     create_script_cmd(state, col, name, || Message::Done)
                 }
@@ -73,6 +120,7 @@ impl Message {
                 state.popup = None;
                 Task::none()
             }
+            Message::ExampleSaved(..) => Task::none(),
         }
     }
 }
@@ -0,0 +1,135 @@
+use std::borrow::Cow;
+
+use components::colors;
+use core::diff::LineTag;
+use iced::widget::{button, container, scrollable, text, Column, Row};
+use iced::{Element, Font, Length, Task};
+
+use crate::state::popups::{Popup, RequestConflictState};
+use crate::state::watch::load_request;
+use crate::state::{AppState, RequestDirtyState};
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    KeepMine,
+    LoadTheirs,
+    ToggleDiff,
+}
+
+impl Message {
+    pub fn update(self, state: &mut AppState) -> Task<Message> {
+        let Some(Popup::RequestConflict(data)) = state.popup.as_mut() else {
+            return Task::none();
+        };
+
+        match self {
+            Message::ToggleDiff => {
+                data.show_diff = !data.show_diff;
+                return Task::none();
+            }
+            Message::KeepMine | Message::LoadTheirs if state.conflicts.is_empty() => (),
+            Message::KeepMine => {
+                let conflict = state.conflicts.remove(0);
+                // Saving now overwrites the file on disk
+                conflict.theirs.track();
+                if let Some(tab) = state.tabs.get_mut(&conflict.tab) {
+                    tab.set_request_dirty_state(RequestDirtyState::Dirty);
+                }
+            }
+            Message::LoadTheirs => {
+                let conflict = state.conflicts.remove(0);
+                if let Some(tab) = state.tabs.get_mut(&conflict.tab) {
+                    load_request(tab, conflict.theirs);
+                }
+            }
+        }
+
+        if state.conflicts.is_empty() {
+            state.popup = None;
+        } else {
+            Popup::request_conflict(state);
+        }
+        Task::none()
+    }
+}
+
+pub fn title<'a>() -> Cow<'a, str> {
+    Cow::Borrowed("Request Changed on Disk")
+}
+
+pub fn done(_data: &RequestConflictState) -> Option<Message> {
+    // Unsaved changes are only dropped by choosing to load theirs
+    Some(Message::KeepMine)
+}
+
+pub(crate) fn view<'a>(
+    state: &'a AppState,
+    data: &'a RequestConflictState,
+) -> Element<'a, Message> {
+    let size = 14;
+    let Some(conflict) = state.conflicts.first() else {
+        return Column::new().into();
+    };
+
+    let remaining = match state.conflicts.len() {
+        1 => String::new(),
+        len => format!(" ({} more after this one)", len - 1),
+    };
+    let message = text(format!(
+        "{} was changed outside of the app while it has unsaved changes{}",
+        conflict.name, remaining
+    ))
+    .size(size);
+
+    let actions = Row::new()
+        .push(
+            button(text("Keep Mine").size(size))
+                .style(button::secondary)
+                .on_press(Message::KeepMine),
+        )
+        .push(
+            button(text("Load Theirs").size(size))
+                .style(button::secondary)
+                .on_press(Message::LoadTheirs),
+        )
+        .push(
+            button(text(if data.show_diff { "Hide Diff" } else { "Diff" }).size(size))
+                .style(button::secondary)
+                .on_press(Message::ToggleDiff),
+        )
+        .spacing(8);
+
+    let diff = data.show_diff.then(|| {
+        let lines = conflict
+            .diff
+            .iter()
+            .enumerate()
+            .fold(Column::new(), |col, (idx, hunk)| {
+                let col = col.push_maybe((idx > 0).then(|| text("...").color(colors::DARK_GREY)));
+                col.extend(hunk.iter().map(|line| {
+                    let (prefix, color) = match line.tag {
+                        LineTag::Equal => (" ", None),
+                        LineTag::Insert => ("+", Some(colors::LIME_GREEN)),
+                        LineTag::Delete => ("-", Some(colors::RED)),
+                    };
+                    text(format!("{} {}", prefix, line.text))
+                        .size(13)
+                        .font(Font::MONOSPACE)
+                        .style(move |_| text::Style { color })
+                        .into()
+                }))
+            });
+
+        scrollable(container(lines).padding([4, 8]))
+            .width(Length::Fill)
+            .height(300)
+    });
+
+    Column::new()
+        .push(message)
+        .push(actions)
+        .push_maybe(diff)
+        .spacing(8)
+        .width(500)
+        .into()
+}
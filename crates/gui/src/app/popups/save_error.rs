@@ -0,0 +1,38 @@
+use std::borrow::Cow;
+
+use iced::widget::{text, Column};
+use iced::{Element, Task};
+
+use crate::state::popups::{Popup, SaveErrorState};
+use crate::state::AppState;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Done,
+}
+
+impl Message {
+    pub fn update(self, state: &mut AppState) -> Task<Message> {
+        match self {
+            Message::Done => Popup::close(state),
+        }
+        Task::none()
+    }
+}
+
+pub fn title<'a>() -> Cow<'a, str> {
+    Cow::Borrowed("Failed to Save")
+}
+
+pub fn done(_data: &SaveErrorState) -> Option<Message> {
+    Some(Message::Done)
+}
+
+pub(crate) fn view<'a>(_state: &'a AppState, data: &'a SaveErrorState) -> Element<'a, Message> {
+    Column::new()
+        .push(text(format!("{} could not be saved", data.name)).size(14))
+        .push(text(&data.error).size(13))
+        .spacing(8)
+        .width(500)
+        .into()
+}
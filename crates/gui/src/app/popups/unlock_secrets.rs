@@ -0,0 +1,87 @@
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use components::colors;
+use core::persistence::secrets::SecretStore;
+use iced::widget::{text, text_input, Column};
+use iced::{Element, Task};
+
+use crate::commands::builders::unlock_secrets_cmd;
+use crate::state::popups::{Popup, UnlockSecretsState};
+use crate::state::AppState;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    PassphraseChanged(String),
+    Unlock,
+    Unlocked(Result<Box<SecretStore>, Arc<anyhow::Error>>),
+}
+
+impl Message {
+    pub fn update(self, state: &mut AppState) -> Task<Message> {
+        // The store is kept even if the popup was closed meanwhile
+        if let Message::Unlocked(Ok(store)) = self {
+            if let Some(Popup::UnlockSecrets(_)) = state.popup {
+                state.popup = None;
+            }
+            state.unlock_secrets(*store);
+            return Task::none();
+        }
+
+        let Some(Popup::UnlockSecrets(data)) = state.popup.as_mut() else {
+            return Task::none();
+        };
+
+        match self {
+            Message::PassphraseChanged(passphrase) => {
+                data.passphrase = passphrase;
+                data.error = None;
+            }
+            Message::Unlock => {
+                data.unlocking = true;
+                data.error = None;
+                return unlock_secrets_cmd(data.passphrase.clone(), Message::Unlocked);
+            }
+            Message::Unlocked(Ok(_)) => (),
+            Message::Unlocked(Err(e)) => {
+                log::error!("Error unlocking secrets: {:?}", e);
+                data.unlocking = false;
+                data.error = Some(e.to_string());
+            }
+        }
+        Task::none()
+    }
+}
+
+pub fn title<'a>() -> Cow<'a, str> {
+    Cow::Borrowed("Unlock Secrets")
+}
+
+pub fn done(data: &UnlockSecretsState) -> Option<Message> {
+    if data.passphrase.is_empty() || data.unlocking {
+        None
+    } else {
+        Some(Message::Unlock)
+    }
+}
+
+pub(crate) fn view<'a>(_state: &'a AppState, data: &'a UnlockSecretsState) -> Element<'a, Message> {
+    let size = 14;
+    let passphrase = text_input("Passphrase", &data.passphrase)
+        .secure(true)
+        .on_input_maybe((!data.unlocking).then_some(Message::PassphraseChanged))
+        .on_submit_maybe(done(data));
+
+    let hint = match (&data.error, data.unlocking) {
+        (Some(e), _) => text(e).size(size).color(colors::RED),
+        (None, true) => text("Unlocking...").size(size),
+        (None, false) => text("The store is created with this passphrase on first save").size(size),
+    };
+
+    Column::new()
+        .push(passphrase)
+        .push(hint)
+        .spacing(8)
+        .width(300)
+        .into()
+}
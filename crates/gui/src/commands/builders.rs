@@ -1,34 +1,76 @@
-use core::http::environment::EnvironmentKey;
+use core::http::environment::{Environment, EnvironmentKey, GLOBAL_ENV};
 use core::persistence::environment::{encode_environments, save_environments};
+use core::persistence::globals::{read_globals, save_globals};
+use core::persistence::history::{clear_history, read_history, save_history_entry, HistoryEntry};
 use core::persistence::{ENVIRONMENTS, HCL_EXTENSION, REQUESTS};
-use std::path::PathBuf;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use iced::advanced::graphics::futures::MaybeSend;
+use iced::futures::stream::{self, StreamExt};
 use iced::futures::TryFutureExt;
-use iced::Task;
+use iced::{clipboard, Task};
+use prost_reflect::DescriptorPool;
 use rfd::AsyncFileDialog;
 use tokio::fs;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
 
-use core::client::send_request;
+use core::client::{send_request, send_request_streamed, ResponseEvent, StreamHead};
+use core::curl;
+use core::graphql::{Schema, INTROSPECTION_QUERY};
+use core::grpc;
+use core::har::{save_har, Har, SentRequest};
 use core::http::collection::Collection;
+use core::http::grpc::GrpcRequest;
+use core::http::websocket::WebSocketRequest;
 use core::http::{
     collection::{Entry, FolderId, RequestId, RequestRef},
-    request::Request,
+    request::{Method, Request, RequestBody},
     CollectionKey, CollectionRequest,
 };
-use core::persistence::collections::{self, encode_collection, open_collection, save_collection};
-use core::persistence::request::{encode_request, read_request, save_req_to_file};
+use core::mock::{Mock, MockOptions};
+use core::persistence::collections::{
+    self, encode_collection, open_collection, save_collection, save_collection_file,
+};
+use core::persistence::examples::{
+    delete_example, examples_dir, read_example, save_example, ResponseExample,
+};
+use core::persistence::files::{read_tracked, FileConflict};
+use core::persistence::import::har::{self, HarOptions};
+use core::persistence::import::openapi::{self, OpenApiOptions};
+use core::persistence::import::{file_name, postman, write_collection, ImportReport};
+use core::persistence::request::{
+    encode_grpc, encode_request, encode_websocket, open_request_file, read_request,
+    read_request_file, read_request_on_disk, save_req_to_file, RequestFile, RequestOnDisk,
+};
+use core::persistence::secrets::SecretStore;
+use core::persistence::session::{read_session, Session};
+use core::persistence::watch::{read_update, snapshot, CollectionSnapshot, CollectionUpdate};
+use core::sse::SseEvent;
+use core::transformers::grpc::{transform_grpc, transform_url};
 use core::transformers::request::transform_request;
+use core::transformers::websocket::transform_websocket;
+use core::websocket::{self, WsEvent};
 
-use crate::commands::cancellable_task::{cancellable_task, TaskResult};
+use crate::commands::cancellable_task::{cancellable_stream, TaskResult};
+use crate::state::collection_tab::MockServer;
+use crate::state::grpc_tab::Descriptors;
+use crate::state::popups::{ImportFormat, Popup};
 use crate::state::request::RequestPane;
 use crate::state::response::ResponseState;
+use crate::state::ws_tab::ConnectionState;
 use crate::state::{AppState, RequestDirtyState, Tab, TabKey};
 
 #[derive(Debug, Clone)]
 pub enum ResponseResult {
+    /// The request as it is about to be sent, kept for HAR export
+    Sent(Box<SentRequest>),
     Completed(core::client::Response),
+    StreamOpened(StreamHead),
+    Event(SseEvent),
+    StreamClosed,
     Error(Arc<anyhow::Error>),
     Cancelled,
 }
@@ -42,38 +84,230 @@ pub fn send_request_cmd<M: 'static + MaybeSend>(
         return Task::none();
     };
 
-    let env = state
-        .collections
-        .get(sel_tab.collection_ref.0)
-        .and_then(|c| c.get_active_environment())
-        .cloned();
+    let env = state.environment(sel_tab.collection_ref.0);
 
     let client = state.client.clone();
-    let req_fut = transform_request(client.clone(), sel_tab.request().to_request(), env)
-        .and_then(move |req| send_request(client, req));
+    let root = collection_root(state, sel_tab.collection_ref);
+    let req_fut = transform_request(client.clone(), sel_tab.request().to_request(), env, root);
+    let events = stream::once(req_fut).flat_map(move |req| match req {
+        Ok(req) => {
+            let sent = ResponseResult::Sent(Box::new(SentRequest::new(&req)));
+            stream::once(async { Ok(sent) })
+                .chain(send_request_streamed(client.clone(), req).map(|e| e.map(response_event)))
+                .boxed()
+        }
+        Err(e) => stream::once(async { Err(e) }).boxed(),
+    });
 
-    let (cancel_tx, req_fut) = cancellable_task(req_fut);
+    let (cancel_tx, events) = cancellable_stream(events);
 
     let Some(Tab::Http(sel_tab)) = state.get_tab_mut(tab) else {
         return Task::none();
     };
 
+    // Saved examples are not runs to compare with
+    let previous = std::mem::replace(&mut sel_tab.response.state, ResponseState::Executing);
+    if let (ResponseState::Completed(res), None) = (previous, &sel_tab.response.example) {
+        sel_tab.response.previous = Some(res.result);
+    }
+    sel_tab.response.example = None;
+    sel_tab.add_task(cancel_tx);
+
+    Task::stream(events.map(move |r| match r {
+        TaskResult::Completed(Ok(result)) => on_result(result),
+        TaskResult::Cancelled => on_result(ResponseResult::Cancelled),
+        TaskResult::Completed(Err(e)) => on_result(ResponseResult::Error(Arc::new(e))),
+    }))
+}
+
+/// Runs the introspection query against the tab's URL, reusing its headers,
+/// auth and environment so the schema matches what the request would see.
+pub fn introspect_schema_cmd<M: 'static + MaybeSend>(
+    state: &mut AppState,
+    tab: TabKey,
+    on_result: impl Fn(Result<Arc<Schema>, Arc<anyhow::Error>>) -> M + 'static + MaybeSend,
+) -> Task<M> {
+    let Some(Tab::Http(sel_tab)) = state.get_tab(tab) else {
+        return Task::none();
+    };
+
+    let env = state.environment(sel_tab.collection_ref.0);
+
+    let mut req = sel_tab.request().to_request();
+    req.method = Method::POST;
+    req.body = RequestBody::GraphQL {
+        query: INTROSPECTION_QUERY.to_string(),
+        variables: String::new(),
+        operation_name: None,
+    };
+
+    let client = state.client.clone();
+    let root = collection_root(state, sel_tab.collection_ref);
+    let fut = async move {
+        let req = transform_request(client.clone(), req, env, root).await?;
+        let res = send_request(client, req).await?;
+        Schema::from_introspection(&res.body.data)
+    };
+
+    Task::perform(fut, move |r| on_result(r.map(Arc::new).map_err(Arc::new)))
+}
+
+#[derive(Debug, Clone)]
+pub enum WebSocketResult {
+    Event(WsEvent),
+    Error(Arc<anyhow::Error>),
+    Cancelled,
+}
+
+pub fn connect_websocket_cmd<M: 'static + MaybeSend>(
+    state: &mut AppState,
+    tab: TabKey,
+    on_result: impl Fn(WebSocketResult) -> M + 'static + MaybeSend,
+) -> Task<M> {
+    let Some(Tab::WebSocket(sel_tab)) = state.get_tab(tab) else {
+        return Task::none();
+    };
+
+    let env = state.environment(sel_tab.collection_key());
+
+    let request = transform_websocket(sel_tab.to_request(), env);
+
+    let Some(Tab::WebSocket(sel_tab)) = state.get_tab_mut(tab) else {
+        return Task::none();
+    };
+
+    let request = match request {
+        Ok(request) => request,
+        Err(e) => {
+            sel_tab.connection = ConnectionState::Failed(Arc::new(e));
+            return Task::none();
+        }
+    };
+
+    let (sender, outgoing) = mpsc::unbounded_channel();
+    let (cancel_tx, events) = cancellable_stream(websocket::connect(request, outgoing));
+
+    sel_tab.connection = ConnectionState::Connecting;
+    sel_tab.sender = Some(sender);
+    sel_tab.add_task(cancel_tx);
+
+    Task::stream(events.map(move |r| match r {
+        TaskResult::Completed(Ok(event)) => on_result(WebSocketResult::Event(event)),
+        TaskResult::Cancelled => on_result(WebSocketResult::Cancelled),
+        TaskResult::Completed(Err(e)) => on_result(WebSocketResult::Error(Arc::new(e))),
+    }))
+}
+
+fn response_event(event: ResponseEvent) -> ResponseResult {
+    match event {
+        ResponseEvent::Completed(res) => ResponseResult::Completed(res),
+        ResponseEvent::StreamOpened(head) => ResponseResult::StreamOpened(head),
+        ResponseEvent::Event(event) => ResponseResult::Event(event),
+        ResponseEvent::StreamClosed => ResponseResult::StreamClosed,
+    }
+}
+
+/// Directory relative file paths of a request body are resolved against.
+fn collection_root(state: &AppState, req: CollectionRequest) -> PathBuf {
+    state
+        .collections
+        .get(req.0)
+        .map(|c| c.path.clone())
+        .unwrap_or_default()
+}
+
+/// Directory relative proto file paths of a request are resolved against.
+fn request_dir(state: &AppState, req: CollectionRequest) -> PathBuf {
+    state
+        .collections
+        .get_ref(req)
+        .and_then(|r| r.path.parent())
+        .map(Path::to_path_buf)
+        .unwrap_or_default()
+}
+
+/// Loads service descriptors from the tab's proto files, or from the server
+/// using reflection when there are none.
+pub fn load_grpc_descriptors_cmd<M: 'static + MaybeSend>(
+    state: &mut AppState,
+    tab: TabKey,
+    on_result: impl Fn(Result<DescriptorPool, Arc<anyhow::Error>>) -> M + 'static + MaybeSend,
+) -> Task<M> {
+    let Some(Tab::Grpc(sel_tab)) = state.get_tab(tab) else {
+        return Task::none();
+    };
+
+    let env = state.environment(sel_tab.collection_key());
+
+    let req = sel_tab.to_request();
+    let url = transform_url(&req.url, env.as_ref());
+    let files = req.resolve_proto_files(&request_dir(state, sel_tab.collection_ref));
+
+    let Some(Tab::Grpc(sel_tab)) = state.get_tab_mut(tab) else {
+        return Task::none();
+    };
+    sel_tab.descriptors = Descriptors::Loading;
+
+    let fut = async move { grpc::load_descriptors(&url?, &files).await };
+    Task::perform(fut, move |r| on_result(r.map_err(Arc::new)))
+}
+
+pub fn send_grpc_cmd<M: 'static + MaybeSend>(
+    state: &mut AppState,
+    tab: TabKey,
+    on_result: impl Fn(ResponseResult) -> M + 'static + MaybeSend,
+) -> Task<M> {
+    let Some(Tab::Grpc(sel_tab)) = state.get_tab(tab) else {
+        return Task::none();
+    };
+
+    let env = state.environment(sel_tab.collection_key());
+
+    let req = sel_tab.to_request();
+    let files = req.resolve_proto_files(&request_dir(state, sel_tab.collection_ref));
+    let pool = sel_tab.descriptors.pool().cloned();
+
+    let call_fut = async move {
+        let pool = match pool {
+            Some(pool) => pool,
+            None => {
+                let url = transform_url(&req.url, env.as_ref())?;
+                grpc::load_descriptors(&url, &files).await?
+            }
+        };
+        transform_grpc(req, &pool, env)
+    };
+    let events = stream::once(call_fut).flat_map(|call| match call {
+        Ok(call) => {
+            let sent = ResponseResult::Sent(Box::new(call.sent()));
+            stream::once(async { Ok(sent) })
+                .chain(grpc::call(call).map(|e| e.map(response_event)))
+                .boxed()
+        }
+        Err(e) => stream::once(async { Err(e) }).boxed(),
+    });
+
+    let (cancel_tx, events) = cancellable_stream(events);
+
+    let Some(Tab::Grpc(sel_tab)) = state.get_tab_mut(tab) else {
+        return Task::none();
+    };
+
     sel_tab.response.state = ResponseState::Executing;
     sel_tab.add_task(cancel_tx);
 
-    Task::perform(req_fut, move |r| match r {
-        TaskResult::Completed(Ok(res)) => on_result(ResponseResult::Completed(res)),
+    Task::stream(events.map(move |r| match r {
+        TaskResult::Completed(Ok(result)) => on_result(result),
         TaskResult::Cancelled => on_result(ResponseResult::Cancelled),
         TaskResult::Completed(Err(e)) => on_result(ResponseResult::Error(Arc::new(e))),
-    })
+    }))
 }
 
-pub fn save_request_cmd<M: 'static + MaybeSend>(
-    req: &RequestPane,
+fn save_encoded_cmd<M: 'static + MaybeSend>(
     path: PathBuf,
+    encoded: hcl::Body,
     on_done: impl Fn(Option<Arc<anyhow::Error>>) -> M + 'static + MaybeSend,
 ) -> Task<M> {
-    let encoded = encode_request(req.to_request()).expect("Failed to encode request");
     Task::perform(save_req_to_file(path, encoded), move |r| match r {
         Ok(_) => on_done(None),
         Err(e) => {
@@ -83,6 +317,59 @@ pub fn save_request_cmd<M: 'static + MaybeSend>(
     })
 }
 
+pub fn save_request_cmd<M: 'static + MaybeSend>(
+    req: &RequestPane,
+    path: PathBuf,
+    on_done: impl Fn(Option<Arc<anyhow::Error>>) -> M + 'static + MaybeSend,
+) -> Task<M> {
+    let encoded = encode_request(req.to_request()).expect("Failed to encode request");
+    save_encoded_cmd(path, encoded, on_done)
+}
+
+pub fn save_websocket_cmd<M: 'static + MaybeSend>(
+    req: WebSocketRequest,
+    path: PathBuf,
+    on_done: impl Fn(Option<Arc<anyhow::Error>>) -> M + 'static + MaybeSend,
+) -> Task<M> {
+    let encoded = encode_websocket(req).expect("Failed to encode request");
+    save_encoded_cmd(path, encoded, on_done)
+}
+
+pub fn save_grpc_cmd<M: 'static + MaybeSend>(
+    req: GrpcRequest,
+    path: PathBuf,
+    on_done: impl Fn(Option<Arc<anyhow::Error>>) -> M + 'static + MaybeSend,
+) -> Task<M> {
+    let encoded = encode_grpc(req).expect("Failed to encode request");
+    save_encoded_cmd(path, encoded, on_done)
+}
+
+/// Reads the request a save of the tab refused to overwrite as it changed
+/// on disk, other errors are shown to the user
+pub fn save_failed_cmd<M: 'static + MaybeSend>(
+    state: &mut AppState,
+    tab: TabKey,
+    error: &anyhow::Error,
+    on_done: impl Fn(Option<RequestOnDisk>) -> M + 'static + MaybeSend,
+) -> Task<M> {
+    let Some(FileConflict { path }) = error.downcast_ref::<FileConflict>() else {
+        log::error!("Error saving request: {:?}", error);
+        let name = state.get_tab(tab).map(|tab| tab.name().to_owned());
+        Popup::save_error(state, name.unwrap_or_default(), error);
+        return Task::none();
+    };
+    let path = path.clone();
+
+    let fut = async move { read_request_on_disk(&path).await };
+    Task::perform(fut, move |res| match res {
+        Ok(req) => on_done(Some(req)),
+        Err(e) => {
+            log::error!("Error reading conflicting request: {:?}", e);
+            on_done(None)
+        }
+    })
+}
+
 pub fn save_tab_request_cmd<M: 'static + MaybeSend>(
     state: &mut AppState,
     name: String,
@@ -99,28 +386,23 @@ pub fn save_tab_request_cmd<M: 'static + MaybeSend>(
     create_new_request_cmd(state, col, fol, name, req, msg)
 }
 
-pub fn create_new_request_cmd<M: 'static + MaybeSend>(
+fn create_request_entry(
     state: &mut AppState,
     col: CollectionKey,
     fol: Option<FolderId>,
     name: String,
-    req: Request,
-    msg: impl Fn(Option<anyhow::Error>) -> M + 'static + MaybeSend,
-) -> Task<M> {
-    let Some(collection) = state.collections.get_mut(col) else {
-        return Task::none();
-    };
+) -> Option<PathBuf> {
+    let collection = state.collections.get_mut(col)?;
 
     let path = match fol {
         Some(fol) => {
-            let Some(folder) = collection.folder_mut(fol) else {
-                return Task::none();
-            };
+            let folder = collection.folder_mut(fol)?;
             let path = folder.path.join(format!("{}{}", &name, HCL_EXTENSION));
             folder.entries.push(Entry::Item(RequestRef {
                 name,
                 id: RequestId::new(),
                 path: path.clone(),
+                examples: Vec::new(),
             }));
             path
         }
@@ -133,13 +415,20 @@ pub fn create_new_request_cmd<M: 'static + MaybeSend>(
                 name,
                 id: RequestId::new(),
                 path: path.clone(),
+                examples: Vec::new(),
             }));
             path
         }
     };
 
-    let encoded = encode_request(req).expect("Failed to encode request");
+    Some(path)
+}
 
+fn create_new_file_cmd<M: 'static + MaybeSend>(
+    path: PathBuf,
+    encoded: hcl::Body,
+    msg: impl Fn(Option<anyhow::Error>) -> M + 'static + MaybeSend,
+) -> Task<M> {
     Task::perform(save_req_to_file(path, encoded), move |r| match r {
         Ok(_) => msg(None),
         Err(e) => {
@@ -149,6 +438,54 @@ pub fn create_new_request_cmd<M: 'static + MaybeSend>(
     })
 }
 
+pub fn create_new_request_cmd<M: 'static + MaybeSend>(
+    state: &mut AppState,
+    col: CollectionKey,
+    fol: Option<FolderId>,
+    name: String,
+    req: Request,
+    msg: impl Fn(Option<anyhow::Error>) -> M + 'static + MaybeSend,
+) -> Task<M> {
+    let Some(path) = create_request_entry(state, col, fol, name) else {
+        return Task::none();
+    };
+
+    let encoded = encode_request(req).expect("Failed to encode request");
+    create_new_file_cmd(path, encoded, msg)
+}
+
+pub fn create_new_websocket_cmd<M: 'static + MaybeSend>(
+    state: &mut AppState,
+    col: CollectionKey,
+    fol: Option<FolderId>,
+    name: String,
+    req: WebSocketRequest,
+    msg: impl Fn(Option<anyhow::Error>) -> M + 'static + MaybeSend,
+) -> Task<M> {
+    let Some(path) = create_request_entry(state, col, fol, name) else {
+        return Task::none();
+    };
+
+    let encoded = encode_websocket(req).expect("Failed to encode request");
+    create_new_file_cmd(path, encoded, msg)
+}
+
+pub fn create_new_grpc_cmd<M: 'static + MaybeSend>(
+    state: &mut AppState,
+    col: CollectionKey,
+    fol: Option<FolderId>,
+    name: String,
+    req: GrpcRequest,
+    msg: impl Fn(Option<anyhow::Error>) -> M + 'static + MaybeSend,
+) -> Task<M> {
+    let Some(path) = create_request_entry(state, col, fol, name) else {
+        return Task::none();
+    };
+
+    let encoded = encode_grpc(req).expect("Failed to encode request");
+    create_new_file_cmd(path, encoded, msg)
+}
+
 pub(crate) fn create_collection_cmd<Message: 'static + MaybeSend>(
     state: &mut AppState,
     name: String,
@@ -188,10 +525,42 @@ pub fn open_collection_cmd<M: 'static + MaybeSend>(
     Task::perform(fut, on_done)
 }
 
+pub fn import_collection_cmd<M: 'static + MaybeSend>(
+    format: ImportFormat,
+    source: PathBuf,
+    environments: Vec<PathBuf>,
+    openapi_options: OpenApiOptions,
+    har_options: HarOptions,
+    location: PathBuf,
+    on_done: impl Fn(Result<(Collection, ImportReport), Arc<anyhow::Error>>) -> M + 'static + MaybeSend,
+) -> Task<M> {
+    let fut = async move {
+        let data = fs::read_to_string(&source).await?;
+        let mut envs = Vec::new();
+        for path in environments {
+            envs.push(fs::read_to_string(path).await?);
+        }
+
+        let imported = match format {
+            ImportFormat::Postman => postman::import(&data, &envs)?,
+            ImportFormat::OpenApi => openapi::import(&data, openapi_options)?,
+            ImportFormat::Har => har::import(&data, har_options)?,
+        };
+
+        let path = location.join(file_name(&imported.name));
+        let report = write_collection(path.clone(), imported).await?;
+        let col = open_collection(path).await?;
+
+        Ok::<_, anyhow::Error>((col, report))
+    };
+
+    Task::perform(fut, move |r| on_done(r.map_err(Arc::new)))
+}
+
 pub fn open_request_cmd<M: 'static + MaybeSend>(
     state: &mut AppState,
     col: CollectionRequest,
-    on_done: impl Fn(Option<(Request, String)>) -> M + 'static + MaybeSend,
+    on_done: impl Fn(Option<(RequestFile, String)>) -> M + 'static + MaybeSend,
 ) -> Task<M> {
     let Some(req) = state.collections.get_ref(col) else {
         return Task::none();
@@ -200,7 +569,7 @@ pub fn open_request_cmd<M: 'static + MaybeSend>(
     let path = req.path.clone();
     let name = req.name.clone();
 
-    let fut = async move { read_request(&path).await };
+    let fut = async move { open_request_file(&path).await };
 
     Task::perform(fut, move |res| match res {
         Ok(req) => on_done(Some((req, name.clone()))),
@@ -211,6 +580,196 @@ pub fn open_request_cmd<M: 'static + MaybeSend>(
     })
 }
 
+/// Saves the response shown in the tab as an example of its request, the
+/// example is named after the file it is saved to
+pub fn save_example_cmd<M: 'static + MaybeSend>(
+    state: &mut AppState,
+    tab: TabKey,
+    name: String,
+    on_done: impl Fn(CollectionRequest, String, Option<Arc<anyhow::Error>>) -> M + 'static + MaybeSend,
+) -> Task<M> {
+    let Some(Tab::Http(tab)) = state.get_tab(tab) else {
+        return Task::none();
+    };
+    let ResponseState::Completed(ref res) = tab.response.state else {
+        return Task::none();
+    };
+    let col = tab.collection_ref;
+    let Some(request) = state.collections.get_ref(col) else {
+        return Task::none();
+    };
+
+    let path = request.path.clone();
+    let name = file_name(&name);
+    let example = ResponseExample::from_response(name.clone(), &res.result);
+
+    Task::perform(
+        async move { save_example(&path, example).await },
+        move |res| match res {
+            Ok(_) => on_done(col, name.clone(), None),
+            Err(e) => {
+                log::error!("Error saving example: {:?}", e);
+                on_done(col, name.clone(), Some(Arc::new(e)))
+            }
+        },
+    )
+}
+
+pub fn open_example_cmd<M: 'static + MaybeSend>(
+    state: &AppState,
+    col: CollectionRequest,
+    name: String,
+    on_done: impl Fn(Option<ResponseExample>) -> M + 'static + MaybeSend,
+) -> Task<M> {
+    let Some(request) = state.collections.get_ref(col) else {
+        return Task::none();
+    };
+
+    let path = request.path.clone();
+    let fut = async move { read_example(&path, &name).await };
+
+    Task::perform(fut, move |res| match res {
+        Ok(example) => on_done(Some(example)),
+        Err(e) => {
+            log::error!("Error opening example: {:?}", e);
+            on_done(None)
+        }
+    })
+}
+
+pub(crate) fn delete_example_cmd<M: 'static + MaybeSend>(
+    state: &mut AppState,
+    col: CollectionRequest,
+    name: String,
+    on_done: impl Fn() -> M + 'static + MaybeSend,
+) -> Task<M> {
+    let Some(request) = state.collections.get_ref(col) else {
+        return Task::none();
+    };
+    let path = request.path.clone();
+    state.collections.remove_example(col, &name);
+
+    Task::perform(
+        async move { delete_example(&path, &name).await },
+        move |res| {
+            if let Err(e) = res {
+                log::error!("Error deleting example: {:?}", e);
+            }
+            on_done()
+        },
+    )
+}
+
+pub fn export_har_cmd<M: 'static + MaybeSend>(
+    path: PathBuf,
+    har: Har,
+    on_done: impl Fn(Option<Arc<anyhow::Error>>) -> M + 'static + MaybeSend,
+) -> Task<M> {
+    Task::perform(
+        async move { save_har(path, &har).await },
+        move |r| match r {
+            Ok(_) => on_done(None),
+            Err(e) => {
+                log::error!("Error exporting HAR: {:?}", e);
+                on_done(Some(Arc::new(e)))
+            }
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub enum MockStatus {
+    Listening(SocketAddr),
+    Failed(Arc<anyhow::Error>),
+}
+
+/// Serves the collection's examples on the port until the server is removed
+/// from the app state, the active environment resolves variables in paths
+pub fn start_mock_cmd<M: 'static + MaybeSend>(
+    state: &mut AppState,
+    col: CollectionKey,
+    port: u16,
+    options: MockOptions,
+    on_status: impl Fn(MockStatus) -> M + 'static + MaybeSend,
+) -> Task<M> {
+    let Some(collection) = state.collections.get(col) else {
+        return Task::none();
+    };
+    let collection = collection.clone();
+    let env = state.environment(col);
+
+    let start = async move {
+        let (mock, skipped) = Mock::load(&collection, env.as_ref()).await?;
+        for file in skipped {
+            log::warn!("Mock server skipped {}", file);
+        }
+        let listener = TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], port))).await?;
+        let addr = listener.local_addr()?;
+        Ok::<_, anyhow::Error>((mock, listener, addr))
+    };
+
+    let events = stream::once(start).flat_map(move |started| match started {
+        Ok((mock, listener, addr)) => {
+            // Serving only ends with an error, stopping drops the stream
+            let serve = async move {
+                let result = mock.serve(listener, options, |_| ()).await;
+                MockStatus::Failed(Arc::new(
+                    result
+                        .err()
+                        .unwrap_or_else(|| anyhow::anyhow!("Mock server stopped")),
+                ))
+            };
+            stream::once(async move { MockStatus::Listening(addr) })
+                .chain(stream::once(serve))
+                .boxed()
+        }
+        Err(e) => stream::once(async move { MockStatus::Failed(Arc::new(e)) }).boxed(),
+    });
+
+    let (cancel_tx, events) = cancellable_stream(events);
+    state.mocks.insert(col, MockServer::Starting(cancel_tx));
+
+    Task::stream(events.filter_map(move |r| {
+        let msg = match r {
+            TaskResult::Completed(status) => Some(on_status(status)),
+            TaskResult::Cancelled => None,
+        };
+        async move { msg }
+    }))
+}
+
+/// Copies the request as a curl command with the active environment applied,
+/// unsaved changes of an open tab are included
+pub fn copy_curl_cmd<M: 'static + MaybeSend>(state: &AppState, col: CollectionRequest) -> Task<M> {
+    let Some(req) = state.collections.get_ref(col) else {
+        return Task::none();
+    };
+
+    let env = state.environment(col.0);
+
+    let open = state.tabs.values().find_map(|tab| match tab {
+        Tab::Http(tab) if tab.collection_ref == col => Some(tab.request().to_request()),
+        _ => None,
+    });
+
+    let path = req.path.clone();
+    let fut = async move {
+        let request = match open {
+            Some(request) => request,
+            None => read_request(&path).await?,
+        };
+        Ok::<_, anyhow::Error>(curl::to_curl(request, env.as_ref()))
+    };
+
+    Task::perform(fut, |res| res).then(|res| match res {
+        Ok(command) => clipboard::write(command),
+        Err(e) => {
+            log::error!("Error copying request as curl: {:?}", e);
+            Task::none()
+        }
+    })
+}
+
 pub(crate) fn delete_folder_cmd<M: 'static + MaybeSend>(
     state: &mut AppState,
     col: CollectionKey,
@@ -263,10 +822,23 @@ pub(crate) fn create_script_cmd<Message: 'static + MaybeSend>(
     Task::perform(fut(), move |_| done())
 }
 
+/// Writes the collection file with the name, default environment and
+/// variables of the collection
+pub(crate) fn save_collection_file_cmd<Message: 'static + MaybeSend>(
+    collection: &Collection,
+    done: impl Fn(Option<Arc<anyhow::Error>>) -> Message + 'static + MaybeSend,
+) -> Task<Message> {
+    let fut = save_collection_file(collection);
+    Task::perform(fut, move |r| done(r.err().map(Arc::new)))
+}
+
+/// Secret values are saved to the store when it is unlocked, callers keep
+/// them from being saved while it is locked
 pub(crate) fn save_environments_cmd<Message: 'static + MaybeSend>(
     collection: &mut Collection,
     deletions: &[EnvironmentKey],
-    done: impl Fn() -> Message + 'static + MaybeSend,
+    secrets: Option<&mut SecretStore>,
+    done: impl Fn(Option<Arc<anyhow::Error>>) -> Message + 'static + MaybeSend,
 ) -> Task<Message> {
     let encoded = encode_environments(&collection.environments);
     let mut delete_path = Vec::new();
@@ -283,15 +855,182 @@ pub(crate) fn save_environments_cmd<Message: 'static + MaybeSend>(
         }
     }
 
+    let store = secrets.map(|store| {
+        store.store(collection);
+        store.clone()
+    });
+
     let delete_fut = async {
         for path in delete_path {
             fs::remove_file(path).await?;
         }
         Ok(())
     };
-    let fut = save_environments(collection.path.clone(), encoded).and_then(|_| delete_fut);
+    let secrets_fut = async move {
+        match store {
+            Some(store) => store.save().await,
+            None => Ok(()),
+        }
+    };
+    let fut = save_environments(collection.path.clone(), encoded)
+        .and_then(|_| delete_fut)
+        .and_then(|_| secrets_fut);
 
-    Task::perform(fut, move |_| done())
+    Task::perform(fut, move |r| done(r.err().map(Arc::new)))
+}
+
+/// Key derivation is slow on purpose, the store is opened in the background
+pub fn unlock_secrets_cmd<M: 'static + MaybeSend>(
+    passphrase: String,
+    on_done: impl Fn(Result<Box<SecretStore>, Arc<anyhow::Error>>) -> M + 'static + MaybeSend,
+) -> Task<M> {
+    Task::perform(
+        async move { SecretStore::open(&passphrase).await },
+        move |r| on_done(r.map(Box::new).map_err(Arc::new)),
+    )
+}
+
+pub async fn load_globals_cmd() -> Environment {
+    read_globals().await.unwrap_or_else(|e| {
+        log::error!("Error loading global variables: {:?}", e);
+        Environment::new(GLOBAL_ENV.to_string())
+    })
+}
+
+pub fn save_globals_cmd<M: 'static + MaybeSend>(
+    globals: Environment,
+    on_done: impl Fn() -> M + 'static + MaybeSend,
+) -> Task<M> {
+    Task::perform(save_globals(globals), move |r| {
+        if let Err(e) = r {
+            log::error!("Error saving global variables: {:?}", e);
+        }
+        on_done()
+    })
+}
+
+pub async fn load_session_cmd() -> Option<Session> {
+    read_session().await.unwrap_or_else(|e| {
+        log::error!("Error loading session: {:?}", e);
+        None
+    })
+}
+
+/// Tracks the files of the requests restored from the session, saving them
+/// refuses to overwrite changes made on disk since
+pub fn track_open_requests_cmd<M: 'static + MaybeSend>(
+    state: &AppState,
+    on_done: impl Fn() -> M + 'static + MaybeSend,
+) -> Task<M> {
+    let paths = state
+        .tabs
+        .values()
+        .filter_map(Tab::collection_ref)
+        .filter_map(|req| state.collections.get_ref(req))
+        .map(|req| req.path.clone())
+        .collect::<Vec<_>>();
+
+    let fut = async move {
+        for path in paths {
+            if let Err(e) = read_tracked(&path).await {
+                log::error!("Error reading request {}: {:?}", path.display(), e);
+            }
+        }
+    };
+    Task::perform(fut, move |_| on_done())
+}
+
+pub async fn load_history_cmd() -> Vec<HistoryEntry> {
+    read_history().await.unwrap_or_else(|e| {
+        log::error!("Error loading history: {:?}", e);
+        vec![]
+    })
+}
+
+pub fn save_history_cmd<M: 'static + MaybeSend>(
+    entry: HistoryEntry,
+    on_done: impl Fn() -> M + 'static + MaybeSend,
+) -> Task<M> {
+    Task::perform(save_history_entry(entry), move |r| {
+        if let Err(e) = r {
+            log::error!("Error saving history entry: {:?}", e);
+        }
+        on_done()
+    })
+}
+
+pub fn clear_history_cmd<M: 'static + MaybeSend>(
+    on_done: impl Fn() -> M + 'static + MaybeSend,
+) -> Task<M> {
+    Task::perform(clear_history(), move |r| {
+        if let Err(e) = r {
+            log::error!("Error clearing history: {:?}", e);
+        }
+        on_done()
+    })
+}
+
+/// New state of the directory of a collection, with the parts read again
+/// when it changed since it was last seen
+#[derive(Debug, Clone)]
+pub struct CollectionWatch {
+    pub key: CollectionKey,
+    pub snapshot: CollectionSnapshot,
+    pub update: Option<CollectionUpdate>,
+}
+
+/// Reads the collections changed on disk and the ones not seen yet
+pub fn watch_collections_cmd<M: 'static + MaybeSend>(
+    state: &mut AppState,
+    on_done: impl Fn(Vec<CollectionWatch>) -> M + 'static + MaybeSend,
+) -> Task<M> {
+    let pending = std::mem::take(&mut state.watch_pending);
+    let collections = state
+        .collections
+        .iter()
+        .filter(|(key, _)| pending.contains(key) || !state.watched.contains_key(key))
+        .map(|(key, col)| (key, col.path.clone(), state.watched.get(&key).cloned()))
+        .collect::<Vec<_>>();
+
+    async fn exec(
+        collections: Vec<(CollectionKey, PathBuf, Option<CollectionSnapshot>)>,
+    ) -> Vec<CollectionWatch> {
+        let mut watches = Vec::new();
+        for (key, path, seen) in collections {
+            let snapshot = match snapshot(&path).await {
+                Ok(snapshot) => snapshot,
+                Err(e) => {
+                    log::error!("Error watching collection {}: {:?}", path.display(), e);
+                    continue;
+                }
+            };
+            let Some(seen) = seen else {
+                watches.push(CollectionWatch {
+                    key,
+                    snapshot,
+                    update: None,
+                });
+                continue;
+            };
+
+            let changes = seen.changes(&snapshot, &path);
+            if changes.is_empty() {
+                continue;
+            }
+            // Kept as last seen on errors, the next change reads them again
+            match read_update(&path, &changes).await {
+                Ok(update) => watches.push(CollectionWatch {
+                    key,
+                    snapshot,
+                    update: Some(update),
+                }),
+                Err(e) => log::error!("Error reading changes of {}: {:?}", path.display(), e),
+            }
+        }
+        watches
+    }
+
+    Task::perform(exec(collections), on_done)
 }
 
 pub async fn load_collections_cmd() -> Vec<Collection> {
@@ -307,30 +1046,27 @@ pub fn check_dirty_requests_cmd<M: 'static + MaybeSend>(
 ) -> Task<M> {
     let mut to_check = Vec::new();
     for (key, tab) in state.tabs.iter_mut() {
-        let Tab::Http(tab) = tab else {
-            continue;
-        };
-
-        if RequestDirtyState::CheckIfDirty != tab.request_dirty_state {
+        if Some(RequestDirtyState::CheckIfDirty) != tab.request_dirty_state() {
             continue;
         }
 
-        let Some(request_ref) = state.collections.get_ref(tab.collection_ref) else {
-            tab.request_dirty_state = RequestDirtyState::Clean;
+        let request_ref = tab
+            .collection_ref()
+            .and_then(|req_ref| state.collections.get_ref(req_ref));
+        let (Some(request_ref), Some(req)) = (request_ref, tab.to_request_file()) else {
+            tab.set_request_dirty_state(RequestDirtyState::Clean);
             continue;
         };
 
-        let req = tab.request().to_request();
-
         to_check.push((*key, req, request_ref.path.clone()));
     }
 
     async fn exec(
-        to_check: Vec<(TabKey, Request, PathBuf)>,
+        to_check: Vec<(TabKey, RequestFile, PathBuf)>,
     ) -> Result<Vec<(TabKey, RequestDirtyState)>, anyhow::Error> {
         let mut status = Vec::new();
         for (key, req, path) in to_check {
-            let file_request = read_request(&path).await?;
+            let file_request = read_request_file(&path).await?;
             if req != file_request {
                 status.push((key, RequestDirtyState::Dirty));
             } else {
@@ -379,7 +1115,17 @@ pub(crate) fn rename_request_cmd<Message: 'static + MaybeSend>(
         return Task::none();
     };
 
-    Task::perform(fs::rename(old, new), move |res| {
+    // Saved examples follow the request
+    let fut = async move {
+        fs::rename(&old, &new).await?;
+        let examples = examples_dir(&old);
+        if fs::try_exists(&examples).await? {
+            fs::rename(examples, examples_dir(&new)).await?;
+        }
+        anyhow::Ok(())
+    };
+
+    Task::perform(fut, move |res| {
         if let Err(e) = res {
             log::error!("Error renaming request: {:?}", e);
         }
@@ -397,5 +1143,14 @@ pub(crate) fn delete_request_cmd<M: 'static + MaybeSend>(
         return Task::none();
     };
 
-    Task::perform(fs::remove_file(path), move |_| action())
+    let fut = async move {
+        fs::remove_file(&path).await?;
+        let examples = examples_dir(&path);
+        if fs::try_exists(&examples).await? {
+            fs::remove_dir_all(examples).await?;
+        }
+        anyhow::Ok(())
+    };
+
+    Task::perform(fut, move |_| action())
 }
@@ -1,5 +1,5 @@
 use iced::advanced::graphics::futures::MaybeSend;
-use std::future::Future;
+use iced::futures::stream::{self, Stream, StreamExt};
 use tokio::sync::oneshot;
 
 pub enum TaskResult<A> {
@@ -7,19 +7,25 @@ pub enum TaskResult<A> {
     Cancelled,
 }
 
-pub fn cancellable_task<A>(
-    future: impl Future<Output = A> + 'static + MaybeSend,
+/// Wraps a stream so it can be stopped from outside, the returned stream
+/// yields [`TaskResult::Cancelled`] once and ends when the sender fires or
+/// is dropped.
+pub fn cancellable_stream<A: 'static + MaybeSend>(
+    stream: impl Stream<Item = A> + 'static + MaybeSend,
 ) -> (
     oneshot::Sender<()>,
-    impl Future<Output = TaskResult<A>> + 'static + MaybeSend,
+    impl Stream<Item = TaskResult<A>> + 'static + MaybeSend,
 ) {
     let (tx, rx) = oneshot::channel();
-    let fut = async {
+    let stream = stream::unfold(Some((Box::pin(stream), rx)), |state| async move {
+        let (mut stream, mut rx) = state?;
         tokio::select! {
-            _ = rx => TaskResult::Cancelled,
-            r = future => TaskResult::Completed(r),
+            _ = &mut rx => Some((TaskResult::Cancelled, None)),
+            item = stream.next() => {
+                item.map(|item| (TaskResult::Completed(item), Some((stream, rx))))
+            }
         }
-    };
+    });
 
-    (tx, fut)
+    (tx, stream)
 }
@@ -1,3 +1,4 @@
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use iced::advanced::graphics::futures::MaybeSend;
@@ -30,3 +31,42 @@ pub fn open_file_dialog<Message: MaybeSend + 'static>(
         on_done,
     )
 }
+
+pub fn open_files_dialog<Message: MaybeSend + 'static>(
+    title: &str,
+    filter: (&str, &[&str]),
+    on_done: impl Fn(Vec<PathBuf>) -> Message + MaybeSend + 'static,
+) -> Task<Message> {
+    let (name, extensions) = filter;
+    Task::perform(
+        AsyncFileDialog::new()
+            .set_title(title)
+            .add_filter(name, extensions)
+            .pick_files()
+            .map(|res| {
+                res.unwrap_or_default()
+                    .iter()
+                    .map(|handle| handle.path().to_path_buf())
+                    .collect()
+            }),
+        on_done,
+    )
+}
+
+pub fn save_file_dialog<Message: MaybeSend + 'static>(
+    title: &str,
+    file_name: &str,
+    filter: (&str, &[&str]),
+    on_done: impl Fn(Option<PathBuf>) -> Message + MaybeSend + 'static,
+) -> Task<Message> {
+    let (name, extensions) = filter;
+    Task::perform(
+        AsyncFileDialog::new()
+            .set_title(title)
+            .set_file_name(file_name)
+            .add_filter(name, extensions)
+            .save_file()
+            .map(|res| res.map(|handle| handle.path().to_path_buf())),
+        on_done,
+    )
+}
@@ -1,15 +1,24 @@
 use core::http::collection::Collection;
+use core::http::environment::Environment;
 use core::persistence::collections;
-use iced::Task;
+use core::persistence::history::HistoryEntry;
+use core::persistence::session::{save_session, Session};
+use core::persistence::watch::CollectionWatcher;
+use iced::futures::{SinkExt, Stream};
+use iced::{Subscription, Task};
+use std::path::PathBuf;
 use std::time::Instant;
 
 use crate::{
     app::AppMsg,
-    state::{collection_tab::CollectionTab, RequestDirtyState, Tab, TabKey},
+    state::{collection_tab::CollectionTab, session, watch, RequestDirtyState, Tab, TabKey},
     AppState,
 };
 
-use self::builders::{check_dirty_requests_cmd, load_collections_cmd};
+use self::builders::{
+    check_dirty_requests_cmd, load_collections_cmd, load_globals_cmd, load_history_cmd,
+    load_session_cmd, track_open_requests_cmd, watch_collections_cmd, CollectionWatch,
+};
 
 pub mod builders;
 mod cancellable_task;
@@ -26,6 +35,8 @@ pub struct JobState {
 pub enum BackgroundTask {
     SaveCollections,
     CheckDirtyRequests,
+    SaveSession,
+    WatchCollections,
 }
 
 fn remove_task(state: &mut AppState, task: BackgroundTask) {
@@ -38,6 +49,23 @@ fn task_done(state: &mut AppState, task: BackgroundTask) {
     }
 }
 
+/// Starts the task unless it is still running
+fn start_task(state: &mut AppState, task: BackgroundTask) -> bool {
+    let running = state
+        .background_tasks
+        .iter()
+        .any(|t| t.task == task && !t.done);
+    if !running {
+        remove_task(state, task);
+        state.background_tasks.push(JobState {
+            task,
+            done: false,
+            started: Instant::now(),
+        });
+    }
+    !running
+}
+
 fn schedule_task(state: &mut AppState, task: BackgroundTask, delay: u64) -> bool {
     let job = state.background_tasks.iter().find(|t| t.task == task);
 
@@ -59,8 +87,14 @@ fn schedule_task(state: &mut AppState, task: BackgroundTask, delay: u64) -> bool
 #[derive(Debug, Clone)]
 pub enum TaskMsg {
     CollectionsLoaded(Vec<Collection>),
+    GlobalsLoaded(Environment),
+    HistoryLoaded(Vec<HistoryEntry>),
+    SessionLoaded(Box<Option<Session>>),
     Completed(BackgroundTask),
     UpdateDirtyTabs(Vec<(TabKey, RequestDirtyState)>),
+    CollectionsChanged(Vec<CollectionWatch>),
+    RequestsTracked,
+    FilesChanged(Vec<PathBuf>),
 }
 
 impl TaskMsg {
@@ -68,11 +102,37 @@ impl TaskMsg {
         match self {
             TaskMsg::CollectionsLoaded(collection) => {
                 state.collections.insert_all(collection);
-                let last = state.collections.iter().last();
-                if let Some((key, col)) = last {
-                    state.open_tab(Tab::Collection(CollectionTab::new(key, col)));
-                }
                 task_done(state, BackgroundTask::SaveCollections);
+                return Task::perform(load_session_cmd(), |s| TaskMsg::SessionLoaded(Box::new(s)));
+            }
+            TaskMsg::SessionLoaded(saved) => {
+                match *saved {
+                    Some(saved) => session::restore(state, saved),
+                    None => {
+                        let last = state.collections.iter().last();
+                        if let Some((key, col)) = last {
+                            state.open_tab(Tab::Collection(CollectionTab::new(key, col)));
+                        }
+                    }
+                }
+                state.saved_session = Some(session::snapshot(state));
+                return track_open_requests_cmd(state, || TaskMsg::RequestsTracked);
+            }
+            TaskMsg::GlobalsLoaded(globals) => {
+                state.globals = globals;
+            }
+            TaskMsg::HistoryLoaded(entries) => {
+                state.history.entries = entries;
+            }
+            TaskMsg::RequestsTracked => (),
+            TaskMsg::FilesChanged(paths) => {
+                let changed = state
+                    .collections
+                    .iter()
+                    .filter(|(_, col)| paths.iter().any(|path| path.starts_with(&col.path)))
+                    .map(|(key, _)| key)
+                    .collect::<Vec<_>>();
+                state.watch_pending.extend(changed);
             }
             TaskMsg::Completed(task) => {
                 task_done(state, task);
@@ -80,11 +140,29 @@ impl TaskMsg {
             TaskMsg::UpdateDirtyTabs(status) => {
                 task_done(state, BackgroundTask::CheckDirtyRequests);
                 for (key, status) in status {
-                    if let Some(Tab::Http(tab)) = state.tabs.get_mut(&key) {
-                        tab.request_dirty_state = status;
+                    if let Some(tab) = state.tabs.get_mut(&key) {
+                        tab.set_request_dirty_state(status);
                     };
                 }
             }
+            TaskMsg::CollectionsChanged(watches) => {
+                task_done(state, BackgroundTask::WatchCollections);
+                for CollectionWatch {
+                    key,
+                    snapshot,
+                    update,
+                } in watches
+                {
+                    // Closed while being read
+                    if state.collections.get(key).is_none() {
+                        continue;
+                    }
+                    state.watched.insert(key, snapshot);
+                    if let Some(update) = update {
+                        watch::apply_update(state, key, update);
+                    }
+                }
+            }
         };
         Task::none()
     }
@@ -116,10 +194,99 @@ fn check_dirty_requests(state: &mut AppState) -> Task<TaskMsg> {
     check_dirty_requests_cmd(state, TaskMsg::UpdateDirtyTabs)
 }
 
+fn save_open_session(state: &mut AppState) -> Task<TaskMsg> {
+    if state.saved_session.is_none() {
+        return Task::none();
+    }
+    let task = BackgroundTask::SaveSession;
+    if !schedule_task(state, task, 2) {
+        return Task::none();
+    }
+
+    let current = session::snapshot(state);
+    if state.saved_session.as_ref() == Some(&current) {
+        task_done(state, task);
+        return Task::none();
+    }
+    state.saved_session = Some(current.clone());
+    Task::perform(save_session(current), |result| {
+        if let Err(e) = result {
+            log::error!("Error saving session: {:?}", e);
+        }
+        TaskMsg::Completed(BackgroundTask::SaveSession)
+    })
+}
+
+fn watch_collections(state: &mut AppState) -> Task<TaskMsg> {
+    let unseen = state
+        .collections
+        .iter()
+        .any(|(key, _)| !state.watched.contains_key(&key));
+    if !unseen && state.watch_pending.is_empty() {
+        return Task::none();
+    }
+    if !start_task(state, BackgroundTask::WatchCollections) {
+        return Task::none();
+    }
+
+    watch_collections_cmd(state, TaskMsg::CollectionsChanged)
+}
+
+/// Paths changed under the collection directories, as reported by the file
+/// system, collections are only read again once something changed
+fn watch_files(roots: Vec<PathBuf>) -> impl Stream<Item = TaskMsg> {
+    iced::stream::channel(100, move |mut output| async move {
+        let (mut watcher, mut changed) = match CollectionWatcher::new() {
+            Ok(watcher) => watcher,
+            Err(e) => {
+                log::error!("Error watching collections: {:?}", e);
+                return;
+            }
+        };
+        for root in &roots {
+            if let Err(e) = watcher.watch(root) {
+                log::error!("Error watching collection {}: {:?}", root.display(), e);
+            }
+        }
+
+        while let Some(path) = changed.recv().await {
+            // Saving a file is reported as several events
+            let mut paths = vec![path];
+            while let Ok(path) = changed.try_recv() {
+                paths.push(path);
+            }
+            if output.send(TaskMsg::FilesChanged(paths)).await.is_err() {
+                break;
+            }
+        }
+    })
+}
+
+pub fn subscription(state: &AppState) -> Subscription<TaskMsg> {
+    let mut roots = state
+        .collections
+        .iter()
+        .map(|(_, col)| col.path.clone())
+        .collect::<Vec<_>>();
+    roots.sort();
+    // Started again with a new watcher when collections are opened or closed
+    Subscription::run_with_id(roots.clone(), watch_files(roots))
+}
+
 pub fn background(state: &mut AppState) -> Task<TaskMsg> {
-    Task::batch([save_open_collections(state), check_dirty_requests(state)])
+    Task::batch([
+        save_open_collections(state),
+        check_dirty_requests(state),
+        save_open_session(state),
+        watch_collections(state),
+    ])
 }
 
 pub fn init_command() -> Task<AppMsg> {
-    Task::perform(load_collections_cmd(), TaskMsg::CollectionsLoaded).map(AppMsg::Command)
+    Task::batch([
+        Task::perform(load_collections_cmd(), TaskMsg::CollectionsLoaded),
+        Task::perform(load_globals_cmd(), TaskMsg::GlobalsLoaded),
+        Task::perform(load_history_cmd(), TaskMsg::HistoryLoaded),
+    ])
+    .map(AppMsg::Command)
 }
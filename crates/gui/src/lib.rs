@@ -17,6 +17,7 @@ const HACK_MONO_REG_BYTES: &[u8] = include_bytes!("../../../fonts/HackNerdFontMo
 
 pub fn main() -> Result<(), iced::Error> {
     iced::application("Sanchaar", app::update, app::view)
+        .subscription(app::subscription)
         .theme(|s| s.theme.clone())
         .antialiasing(true)
         .font(Cow::from(HACK_REG_BYTES))
@@ -1,7 +1,12 @@
-use core::http::{collection::Collection, environment::EnvironmentKey, CollectionKey};
+use components::{KeyValList, KeyValUpdateMsg};
+use core::http::environment::{EnvironmentKey, Environments};
+use core::http::{collection::Collection, CollectionKey};
 use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use tokio::sync::oneshot;
 
 use super::environment::{environment_keyvals, Env};
+use super::utils::from_core_kv_list;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum CollectionTabId {
@@ -18,6 +23,53 @@ pub struct EnvironmentEditor {
     pub edited: bool,
 }
 
+impl EnvironmentEditor {
+    /// Whether a secret variable has a value, which only an unlocked store
+    /// can keep
+    pub fn has_secret_values(&self) -> bool {
+        self.environments.values().any(|env| {
+            env.variables
+                .values()
+                .iter()
+                .any(|kv| kv.secret && !kv.is_value_empty())
+        })
+    }
+}
+
+#[derive(Debug)]
+pub enum MockServer {
+    Starting(oneshot::Sender<()>),
+    Running {
+        addr: SocketAddr,
+        cancel: oneshot::Sender<()>,
+    },
+    Failed(String),
+}
+
+impl MockServer {
+    pub fn is_active(&self) -> bool {
+        !matches!(self, MockServer::Failed(_))
+    }
+}
+
+/// Mock server settings as typed, parsed when the server is started
+#[derive(Debug)]
+pub struct MockSettings {
+    pub port: String,
+    pub latency: String,
+    pub error_rate: String,
+}
+
+impl Default for MockSettings {
+    fn default() -> Self {
+        Self {
+            port: "4010".to_string(),
+            latency: "0".to_string(),
+            error_rate: "0".to_string(),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct CollectionTab {
     pub name: String,
@@ -25,6 +77,10 @@ pub struct CollectionTab {
     pub collection_key: CollectionKey,
     pub tab: CollectionTabId,
     pub env_editor: EnvironmentEditor,
+    /// Variables of the collection as edited, environments override them
+    pub variables: KeyValList,
+    pub variables_edited: bool,
+    pub mock: MockSettings,
 }
 
 impl CollectionTab {
@@ -46,6 +102,9 @@ impl CollectionTab {
                 selected_env: col.active_environment,
                 edited: false,
             },
+            variables: from_core_kv_list(col.variables.clone(), false),
+            variables_edited: false,
+            mock: MockSettings::default(),
         }
     }
 
@@ -64,4 +123,53 @@ impl CollectionTab {
         self.env_editor.deleted.push(env_key);
         self.env_editor.environments.remove(&env_key)
     }
+
+    /// Shows the collection file read again, variables with unsaved edits
+    /// are kept
+    pub fn refresh_collection(&mut self, col: &Collection) {
+        self.name = col.name.clone();
+        self.default_env = col
+            .default_env
+            .and_then(|env| col.environments.get(env))
+            .map(|env| env.name.clone());
+        if !self.variables_edited {
+            self.variables = from_core_kv_list(col.variables.clone(), false);
+        }
+    }
+
+    /// Shows the environments of the collection again, unless the editor
+    /// has unsaved edits
+    pub fn refresh_environments(&mut self, envs: &Environments) {
+        let editor = &mut self.env_editor;
+        if editor.edited {
+            return;
+        }
+        editor.environments = environment_keyvals(envs);
+        editor.deleted.clear();
+        editor.selected_env = editor
+            .selected_env
+            .filter(|key| editor.environments.contains_key(key));
+    }
+
+    /// Sets the secret values which are still empty in the editor, edits are
+    /// kept
+    pub fn fill_secrets(&mut self, envs: &Environments) {
+        for (key, env) in self.env_editor.environments.iter_mut() {
+            let Some(source) = envs.get(*key) else {
+                continue;
+            };
+            let values: Vec<_> = env
+                .variables
+                .values()
+                .iter()
+                .enumerate()
+                .filter(|(_, kv)| kv.secret && kv.is_value_empty())
+                .filter_map(|(idx, kv)| Some((idx, source.get(kv.name())?.to_owned())))
+                .collect();
+            for (idx, value) in values {
+                env.variables
+                    .update(KeyValUpdateMsg::SecretChanged(idx, value));
+            }
+        }
+    }
 }
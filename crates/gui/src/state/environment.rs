@@ -1,9 +1,10 @@
-use core::http::environment::{Environment, EnvironmentKey, Environments};
+use core::http::environment::{Environment, EnvironmentKey, Environments, REDACTED};
+use core::template::{render_lenient, BUILTINS, ENV_PREFIX};
 use std::collections::BTreeMap;
 
-use components::KeyValList;
+use components::{KeyValList, Variables};
 
-use super::utils::{from_core_kv_list, to_core_kv_list};
+use super::utils::to_core_kv_list;
 
 #[derive(Debug)]
 pub struct Env {
@@ -21,18 +22,36 @@ impl Env {
 
 impl From<&Environment> for Env {
     fn from(env: &Environment) -> Self {
+        let variables = env
+            .variables
+            .iter()
+            .map(|kv| {
+                components::KeyValue::new(&kv.name, &kv.value, kv.disabled)
+                    .with_secret(env.is_secret(&kv.name))
+            })
+            .collect();
+
         Self {
             name: env.name.clone(),
-            variables: from_core_kv_list(env.variables.clone(), false),
+            variables: KeyValList::from(variables, false),
         }
     }
 }
 
 impl From<&Env> for Environment {
     fn from(value: &Env) -> Self {
+        let secrets = value
+            .variables
+            .values()
+            .iter()
+            .filter(|kv| kv.secret)
+            .map(|kv| kv.name().trim().to_owned())
+            .collect();
+
         Self {
             name: value.name.trim().to_owned(),
             variables: to_core_kv_list(&value.variables),
+            secrets,
         }
     }
 }
@@ -42,3 +61,37 @@ pub fn environment_keyvals(envs: &Environments) -> BTreeMap<EnvironmentKey, Env>
         .map(|(key, env)| (*key, Env::from(env)))
         .collect()
 }
+
+fn builtin_description(name: &str) -> &'static str {
+    match name {
+        "$uuid" => "random UUID",
+        "$timestamp" => "unix timestamp",
+        "$isoTimestamp" => "ISO 8601 timestamp",
+        "$randomInt" => "random integer",
+        _ => "built-in",
+    }
+}
+
+/// Variables the editors suggest and highlight, with their values resolved
+/// against the environment and secrets masked
+pub fn editor_variables(env: Option<&Environment>) -> Variables {
+    let builtins = BUILTINS
+        .iter()
+        .map(|name| (name.to_string(), builtin_description(name).to_owned()));
+
+    let values = env
+        .into_iter()
+        .flat_map(|env| {
+            env.variables.iter().map(move |kv| {
+                let value = match env.is_secret(&kv.name) {
+                    true => REDACTED.to_owned(),
+                    false => env.redact(&render_lenient(&kv.value, Some(env))),
+                };
+                (kv.name.clone(), value)
+            })
+        })
+        .chain(builtins)
+        .collect();
+
+    Variables::new(values).with_prefix(ENV_PREFIX)
+}
@@ -0,0 +1,159 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use iced::widget::pane_grid;
+use iced::widget::pane_grid::Configuration;
+use prost_reflect::DescriptorPool;
+use tokio::sync::oneshot;
+
+use components::{text_editor, KeyValList};
+use core::assertions::Assertions;
+use core::grpc::{list_methods, GrpcMethod};
+use core::http::grpc::GrpcRequest;
+use core::http::{CollectionKey, CollectionRequest};
+
+use super::http_tab::RequestDirtyState;
+use super::response::ResponsePane;
+use super::utils::{from_core_kv_list, to_core_kv_list};
+use super::SplitState;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum GrpcTabId {
+    #[default]
+    Message,
+    Metadata,
+    Proto,
+}
+
+#[derive(Debug, Default)]
+pub enum Descriptors {
+    #[default]
+    NotLoaded,
+    Loading,
+    Loaded {
+        pool: DescriptorPool,
+        methods: Vec<GrpcMethod>,
+    },
+    Failed(Arc<anyhow::Error>),
+}
+
+impl Descriptors {
+    pub fn loaded(pool: DescriptorPool) -> Self {
+        let methods = list_methods(&pool)
+            .into_iter()
+            .filter(GrpcMethod::is_supported)
+            .collect();
+        Self::Loaded { pool, methods }
+    }
+
+    pub fn pool(&self) -> Option<&DescriptorPool> {
+        match self {
+            Self::Loaded { pool, .. } => Some(pool),
+            _ => None,
+        }
+    }
+
+    pub fn methods(&self) -> &[GrpcMethod] {
+        match self {
+            Self::Loaded { methods, .. } => methods,
+            _ => &[],
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct GrpcTab {
+    pub name: String,
+    pub collection_ref: CollectionRequest,
+    pub description: String,
+    pub url_content: text_editor::Content,
+    pub proto_files: Vec<PathBuf>,
+    pub service: String,
+    pub method: String,
+    pub message: text_editor::Content,
+    pub metadata: KeyValList,
+    pub assertions: Assertions,
+    pub descriptors: Descriptors,
+    pub tab: GrpcTabId,
+    pub response: ResponsePane,
+    pub tasks: Vec<oneshot::Sender<()>>,
+    pub panes: pane_grid::State<SplitState>,
+    pub request_dirty_state: RequestDirtyState,
+}
+
+impl GrpcTab {
+    pub fn new(name: String, request: GrpcRequest, req_ref: CollectionRequest) -> Self {
+        Self {
+            name,
+            collection_ref: req_ref,
+            description: request.description,
+            url_content: text_editor::Content::with_text(&request.url),
+            proto_files: request.proto_files,
+            service: request.service,
+            method: request.method,
+            message: text_editor::Content::with_text(&request.message),
+            metadata: from_core_kv_list(request.metadata, false),
+            assertions: request.assertions,
+            descriptors: Descriptors::NotLoaded,
+            tab: GrpcTabId::Message,
+            response: ResponsePane::new(),
+            tasks: Vec::new(),
+            panes: pane_grid::State::with_configuration(Configuration::Split {
+                axis: pane_grid::Axis::Vertical,
+                ratio: 0.45,
+                a: Box::new(Configuration::Pane(SplitState::First)),
+                b: Box::new(Configuration::Pane(SplitState::Second)),
+            }),
+            request_dirty_state: RequestDirtyState::Clean,
+        }
+    }
+
+    pub fn to_request(&self) -> GrpcRequest {
+        GrpcRequest {
+            description: self.description.clone(),
+            url: self.url_content.text().trim().to_string(),
+            proto_files: self.proto_files.clone(),
+            service: self.service.clone(),
+            method: self.method.clone(),
+            message: self.message.text(),
+            metadata: to_core_kv_list(&self.metadata),
+            assertions: self.assertions.clone(),
+        }
+    }
+
+    pub fn is_request_dirty(&self) -> bool {
+        self.request_dirty_state == RequestDirtyState::Dirty
+    }
+
+    /// The request may differ from its file after an edit or a save
+    pub fn check_dirty(&mut self) {
+        self.request_dirty_state = RequestDirtyState::CheckIfDirty;
+    }
+
+    pub fn selected_method(&self) -> Option<&GrpcMethod> {
+        self.descriptors
+            .methods()
+            .iter()
+            .find(|m| m.service == self.service && m.method == self.method)
+    }
+
+    pub fn cancel_tasks(&mut self) {
+        for task in self.tasks.drain(..) {
+            let _ = task.send(());
+        }
+    }
+
+    pub fn add_task(&mut self, task: oneshot::Sender<()>) {
+        self.tasks.push(task);
+    }
+
+    pub fn collection_key(&self) -> CollectionKey {
+        self.collection_ref.0
+    }
+}
+
+impl Drop for GrpcTab {
+    fn drop(&mut self) {
+        self.cancel_tasks();
+    }
+}
@@ -0,0 +1,37 @@
+use core::persistence::history::{HistoryEntry, HistoryFilter, MAX_ENTRIES};
+
+#[derive(Debug, Default)]
+pub struct HistoryState {
+    /// Sent requests from the newest to the oldest
+    pub entries: Vec<HistoryEntry>,
+    pub filter: HistoryFilter,
+}
+
+impl HistoryState {
+    pub fn push(&mut self, entry: HistoryEntry) {
+        self.entries.insert(0, entry);
+        self.entries.truncate(MAX_ENTRIES);
+    }
+
+    pub fn get(&self, id: &str) -> Option<&HistoryEntry> {
+        self.entries.iter().find(|entry| entry.id == id)
+    }
+
+    pub fn filtered(&self) -> impl Iterator<Item = &HistoryEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.matches(&self.filter))
+    }
+
+    /// Hosts of the recorded requests to filter on
+    pub fn hosts(&self) -> Vec<String> {
+        let mut hosts = self
+            .entries
+            .iter()
+            .filter_map(HistoryEntry::host)
+            .collect::<Vec<_>>();
+        hosts.sort_unstable();
+        hosts.dedup();
+        hosts
+    }
+}
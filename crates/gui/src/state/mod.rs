@@ -1,24 +1,39 @@
-use collection_tab::CollectionTab;
+use collection_tab::{CollectionTab, MockServer};
 use iced::widget::pane_grid;
 use iced::widget::pane_grid::Configuration;
 use iced::Theme;
 use indexmap::IndexMap;
+use std::collections::{HashMap, HashSet};
 
 use core::client::create_client;
-use core::http::{CollectionRequest, Collections};
+use core::http::environment::{Environment, GLOBAL_ENV};
+use core::http::{CollectionKey, CollectionRequest, Collections, KeyValList};
+use core::persistence::request::RequestFile;
+use core::persistence::secrets::SecretStore;
+use core::persistence::session::Session;
+use core::persistence::watch::CollectionSnapshot;
+pub use grpc_tab::GrpcTab;
 pub use http_tab::*;
+pub use ws_tab::WebSocketTab;
 
 use crate::commands::JobState;
+use crate::state::history::HistoryState;
 use crate::state::popups::Popup;
-use crate::state::response::ResponseState;
+use crate::state::response::{ResponsePane, ResponseState};
+use crate::state::watch::RequestConflict;
 
 pub mod collection_tab;
 pub mod environment;
+pub mod grpc_tab;
+pub mod history;
 pub mod http_tab;
 pub mod popups;
 pub mod request;
 pub mod response;
+pub mod session;
 pub mod utils;
+pub mod watch;
+pub mod ws_tab;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum SplitState {
@@ -27,6 +42,13 @@ pub enum SplitState {
     Second, // Right or Bottom
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SideBarTab {
+    #[default]
+    Collections,
+    History,
+}
+
 core::new_id_type! {
     pub struct TabKey;
 }
@@ -34,9 +56,77 @@ core::new_id_type! {
 #[derive(Debug)]
 pub enum Tab {
     Http(HttpTab),
+    WebSocket(WebSocketTab),
+    Grpc(GrpcTab),
     Collection(CollectionTab),
 }
 
+impl Tab {
+    pub fn name(&self) -> &str {
+        match self {
+            Tab::Http(tab) => &tab.name,
+            Tab::WebSocket(tab) => &tab.name,
+            Tab::Grpc(tab) => &tab.name,
+            Tab::Collection(tab) => &tab.name,
+        }
+    }
+
+    pub fn collection_ref(&self) -> Option<CollectionRequest> {
+        match self {
+            Tab::Http(tab) => Some(tab.collection_ref),
+            Tab::WebSocket(tab) => Some(tab.collection_ref),
+            Tab::Grpc(tab) => Some(tab.collection_ref),
+            Tab::Collection(_) => None,
+        }
+    }
+
+    /// Request of the tab as it would be saved to its file
+    pub fn to_request_file(&self) -> Option<RequestFile> {
+        match self {
+            Tab::Http(tab) => Some(RequestFile::Http(tab.request().to_request())),
+            Tab::WebSocket(tab) => Some(RequestFile::WebSocket(tab.to_request())),
+            Tab::Grpc(tab) => Some(RequestFile::Grpc(tab.to_request())),
+            Tab::Collection(_) => None,
+        }
+    }
+
+    pub fn request_dirty_state(&self) -> Option<RequestDirtyState> {
+        match self {
+            Tab::Http(tab) => Some(tab.request_dirty_state),
+            Tab::WebSocket(tab) => Some(tab.request_dirty_state),
+            Tab::Grpc(tab) => Some(tab.request_dirty_state),
+            Tab::Collection(_) => None,
+        }
+    }
+
+    pub fn set_request_dirty_state(&mut self, status: RequestDirtyState) {
+        match self {
+            Tab::Http(tab) => tab.request_dirty_state = status,
+            Tab::WebSocket(tab) => tab.request_dirty_state = status,
+            Tab::Grpc(tab) => tab.request_dirty_state = status,
+            Tab::Collection(_) => (),
+        }
+    }
+
+    /// Response pane of the tabs that show results in the response view.
+    pub fn response_mut(&mut self) -> Option<&mut ResponsePane> {
+        match self {
+            Tab::Http(tab) => Some(&mut tab.response),
+            Tab::Grpc(tab) => Some(&mut tab.response),
+            Tab::WebSocket(_) | Tab::Collection(_) => None,
+        }
+    }
+
+    pub fn cancel_tasks(&mut self) {
+        match self {
+            Tab::Http(tab) => tab.cancel_tasks(),
+            Tab::WebSocket(tab) => tab.cancel_tasks(),
+            Tab::Grpc(tab) => tab.cancel_tasks(),
+            Tab::Collection(_) => (),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct AppState {
     pub active_tab: Option<TabKey>,
@@ -48,6 +138,23 @@ pub struct AppState {
     pub popup: Option<Popup>,
     pub theme: Theme,
     pub background_tasks: Vec<JobState>,
+    /// Mock servers of the collections, dropping one stops it
+    pub mocks: HashMap<CollectionKey, MockServer>,
+    /// Secret store once unlocked with the passphrase
+    pub secrets: Option<SecretStore>,
+    /// Variables shared by all collections
+    pub globals: Environment,
+    pub side_bar: SideBarTab,
+    pub history: HistoryState,
+    /// Last session written to disk, `None` until the previous one is
+    /// restored so it is not overwritten at startup
+    pub saved_session: Option<Session>,
+    /// Last seen state of the directories of the open collections
+    pub watched: HashMap<CollectionKey, CollectionSnapshot>,
+    /// Collections with files changed on disk since they were last seen
+    pub watch_pending: HashSet<CollectionKey>,
+    /// Requests changed on disk while edited in their tab
+    pub conflicts: Vec<RequestConflict>,
 }
 
 impl AppState {
@@ -67,9 +174,46 @@ impl AppState {
             popup: None,
             theme: Theme::GruvboxDark,
             background_tasks: Vec::new(),
+            mocks: HashMap::new(),
+            secrets: None,
+            globals: Environment::new(GLOBAL_ENV.to_string()),
+            side_bar: SideBarTab::default(),
+            history: HistoryState::default(),
+            saved_session: None,
+            watched: HashMap::new(),
+            watch_pending: HashSet::new(),
+            conflicts: Vec::new(),
+        }
+    }
+
+    /// Variables the requests of the collection are resolved with, from its
+    /// active environment, collection variables and the global variables
+    pub fn environment(&self, collection: CollectionKey) -> Option<Environment> {
+        match self.collections.get(collection) {
+            Some(col) => col.scoped_environment(&self.globals),
+            None => Environment::scoped(None, &KeyValList::new(), &self.globals),
         }
     }
 
+    /// Fills the secret values of the open collections, collections opened
+    /// later are filled from the kept store
+    pub fn unlock_secrets(&mut self, store: SecretStore) {
+        let keys: Vec<_> = self.collections.iter().map(|(key, _)| key).collect();
+        for key in keys {
+            self.collections
+                .with_collection_mut(key, |col| store.fill(col));
+        }
+
+        for tab in self.tabs.values_mut() {
+            if let Tab::Collection(tab) = tab {
+                if let Some(col) = self.collections.get(tab.collection_key) {
+                    tab.fill_secrets(&col.environments);
+                }
+            }
+        }
+        self.secrets = Some(store);
+    }
+
     pub fn switch_tab(&mut self, tab: TabKey) {
         self.active_tab = Some(tab);
         self.tab_history.shift_remove(&tab);
@@ -85,11 +229,7 @@ impl AppState {
     pub fn switch_to_tab(&mut self, req: CollectionRequest) -> bool {
         self.tabs
             .iter()
-            .filter_map(|(key, tab)| match tab {
-                Tab::Http(tab) => Some((key, tab)),
-                _ => None,
-            })
-            .find(|(_, tab)| tab.collection_ref == req)
+            .find(|(_, tab)| tab.collection_ref() == Some(req))
             .map(|(key, _)| *key)
             .inspect(|tab| {
                 self.switch_tab(*tab);
@@ -114,9 +254,11 @@ impl AppState {
     }
 
     pub fn cancel_tab_tasks(&mut self, tab: TabKey) {
-        if let Some(Tab::Http(tab)) = self.get_tab_mut(tab) {
+        if let Some(tab) = self.get_tab_mut(tab) {
             tab.cancel_tasks();
-            tab.response.state = ResponseState::Idle;
+            if let Some(response) = tab.response_mut() {
+                response.state = ResponseState::Idle;
+            }
         }
     }
 
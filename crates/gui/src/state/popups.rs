@@ -1,9 +1,13 @@
 use crate::state::TabKey;
+use core::codegen::Language;
 use core::http::collection::{FolderId, RequestId};
 use core::http::environment::EnvironmentKey;
 use core::http::CollectionKey;
+use core::persistence::import::ImportReport;
 use std::path::PathBuf;
+use strum::{Display, VariantArray};
 
+use super::environment::Env;
 use super::AppState;
 
 #[derive(Debug)]
@@ -12,6 +16,31 @@ pub struct CreateCollectionState {
     pub path: Option<PathBuf>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Display, VariantArray)]
+pub enum ImportFormat {
+    #[default]
+    #[strum(to_string = "Postman v2.1")]
+    Postman,
+    #[strum(to_string = "OpenAPI / Swagger")]
+    OpenApi,
+    #[strum(to_string = "HAR")]
+    Har,
+}
+
+#[derive(Debug)]
+pub struct ImportCollectionState {
+    pub format: ImportFormat,
+    pub source: Option<PathBuf>,
+    pub environments: Vec<PathBuf>,
+    pub status_assertions: bool,
+    pub deduplicate: bool,
+    pub path: Option<PathBuf>,
+    pub importing: bool,
+    pub error: Option<String>,
+    /// Set once the import finished with warnings to show
+    pub report: Option<ImportReport>,
+}
+
 #[derive(Debug)]
 pub struct SaveRequestState {
     pub tab: TabKey,
@@ -27,6 +56,10 @@ pub enum PopupNameAction {
     RenameRequest(CollectionKey, RequestId),
     CreateFolder(CollectionKey, Option<FolderId>),
     NewRequest(CollectionKey, Option<FolderId>),
+    NewWebSocket(CollectionKey, Option<FolderId>),
+    NewGrpc(CollectionKey, Option<FolderId>),
+    SaveWebSocketMessage(TabKey),
+    SaveExample(TabKey),
     NewScript(CollectionKey),
     CreateEnvironment(TabKey),
     RenameEnvironment(TabKey, EnvironmentKey),
@@ -38,22 +71,58 @@ pub struct PopupNameState {
     pub action: PopupNameAction,
 }
 
+#[derive(Debug)]
+pub struct GenerateCodeState {
+    pub tab: TabKey,
+    pub language: Language,
+    /// Leave `{{vars}}` in the snippet instead of the active environment values
+    pub keep_variables: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct UnlockSecretsState {
+    pub passphrase: String,
+    pub unlocking: bool,
+    pub error: Option<String>,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AppSettingTabs {
     General,
+    Globals,
 }
 
 #[derive(Debug)]
 pub struct AppSettingsState {
     pub active_tab: AppSettingTabs,
+    /// Global variables being edited, applied when done
+    pub globals: Env,
+}
+
+/// Resolves the conflicts of [`AppState::conflicts`] one at a time
+#[derive(Debug, Default)]
+pub struct RequestConflictState {
+    pub show_diff: bool,
+}
+
+/// A save which failed for another reason than a conflict
+#[derive(Debug)]
+pub struct SaveErrorState {
+    pub name: String,
+    pub error: String,
 }
 
 #[derive(Debug)]
 pub enum Popup {
     CreateCollection(CreateCollectionState),
+    ImportCollection(ImportCollectionState),
     SaveRequest(SaveRequestState),
     PopupName(PopupNameState),
     AppSettings(AppSettingsState),
+    GenerateCode(GenerateCodeState),
+    UnlockSecrets(UnlockSecretsState),
+    RequestConflict(RequestConflictState),
+    SaveError(SaveErrorState),
 }
 
 fn open_popup(state: &mut AppState, popup: Popup) {
@@ -62,7 +131,10 @@ fn open_popup(state: &mut AppState, popup: Popup) {
 
 impl Popup {
     pub fn close(state: &mut AppState) {
-        state.popup.take();
+        // Closing keeps the edits of every conflicting tab
+        if let Some(Popup::RequestConflict(_)) = state.popup.take() {
+            state.conflicts.clear();
+        }
     }
 
     pub fn save_request(state: &mut AppState, tab: TabKey) {
@@ -88,9 +160,52 @@ impl Popup {
         open_popup(state, popup);
     }
 
+    pub fn import_collection(state: &mut AppState) {
+        let popup = Self::ImportCollection(ImportCollectionState {
+            format: ImportFormat::default(),
+            source: None,
+            environments: Vec::new(),
+            status_assertions: false,
+            deduplicate: false,
+            path: None,
+            importing: false,
+            error: None,
+            report: None,
+        });
+        open_popup(state, popup);
+    }
+
+    pub fn generate_code(state: &mut AppState, tab: TabKey) {
+        let popup = Self::GenerateCode(GenerateCodeState {
+            tab,
+            language: Language::default(),
+            keep_variables: false,
+        });
+        open_popup(state, popup);
+    }
+
+    pub fn unlock_secrets(state: &mut AppState) {
+        let popup = Self::UnlockSecrets(UnlockSecretsState::default());
+        open_popup(state, popup);
+    }
+
+    pub fn request_conflict(state: &mut AppState) {
+        let popup = Self::RequestConflict(RequestConflictState::default());
+        open_popup(state, popup);
+    }
+
+    pub fn save_error(state: &mut AppState, name: String, error: &anyhow::Error) {
+        let popup = Self::SaveError(SaveErrorState {
+            name,
+            error: format!("{:#}", error),
+        });
+        open_popup(state, popup);
+    }
+
     pub fn app_settings(state: &mut AppState) {
         let popup = Self::AppSettings(AppSettingsState {
             active_tab: AppSettingTabs::General,
+            globals: Env::from(&state.globals),
         });
         open_popup(state, popup);
     }
@@ -1,9 +1,11 @@
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use body_types::*;
 use components::{self, KeyFileList};
 use components::{text_editor, KeyValList};
+use core::graphql::Schema;
 use core::http::request::{Auth, Method, Request, RequestBody};
 
 use super::utils::{from_core_kf_list, from_core_kv_list, to_core_kf_list, to_core_kv_list};
@@ -12,6 +14,7 @@ pub mod body_types {
     pub const FORM: &str = "URL Encoded";
     pub const MULTIPART: &str = "Multipart";
     pub const JSON: &str = "JSON";
+    pub const GRAPHQL: &str = "GraphQL";
     pub const XML: &str = "XML";
     pub const TEXT: &str = "Text";
     pub const FILE: &str = "File";
@@ -48,7 +51,7 @@ pub enum RawAuthType {
 }
 
 impl RawAuthType {
-    fn to_auth(&self) -> Auth {
+    pub fn to_auth(&self) -> Auth {
         match self {
             RawAuthType::None => Auth::None,
             RawAuthType::Basic { username, password } => Auth::Basic {
@@ -61,7 +64,7 @@ impl RawAuthType {
         }
     }
 
-    fn from_auth(auth: Auth) -> RawAuthType {
+    pub fn from_auth(auth: Auth) -> RawAuthType {
         match auth {
             Auth::None => RawAuthType::None,
             Auth::Basic { username, password } => RawAuthType::Basic {
@@ -74,6 +77,20 @@ impl RawAuthType {
         }
     }
 
+    pub fn change_type(&mut self, auth_type: &str) {
+        *self = match auth_type {
+            auth_types::NONE => RawAuthType::None,
+            auth_types::BASIC => RawAuthType::Basic {
+                username: text_editor::Content::new(),
+                password: text_editor::Content::new(),
+            },
+            auth_types::BEARER => RawAuthType::Bearer {
+                token: text_editor::Content::new(),
+            },
+            _ => RawAuthType::None,
+        };
+    }
+
     pub fn as_str(&self) -> &'static str {
         use auth_types::*;
         match self {
@@ -96,16 +113,41 @@ pub enum RawRequestBody {
     Form(KeyValList),
     Multipart(KeyValList, KeyFileList),
     Json(text_editor::Content),
+    GraphQL {
+        query: text_editor::Content,
+        variables: text_editor::Content,
+        operation_name: String,
+    },
     XML(text_editor::Content),
     Text(text_editor::Content),
     File(Option<PathBuf>),
 }
 
+#[derive(Debug, Default)]
+pub enum GraphQLSchema {
+    #[default]
+    None,
+    Loading,
+    Loaded(Arc<Schema>),
+    Failed(Arc<anyhow::Error>),
+}
+
 impl RawRequestBody {
     fn to_request_body(&self) -> RequestBody {
         match self {
             RawRequestBody::Form(form) => RequestBody::Form(to_core_kv_list(form)),
             RawRequestBody::Json(json) => RequestBody::Json(json.text()),
+            RawRequestBody::GraphQL {
+                query,
+                variables,
+                operation_name,
+            } => RequestBody::GraphQL {
+                query: query.text(),
+                variables: variables.text(),
+                operation_name: Some(operation_name.trim())
+                    .filter(|name| !name.is_empty())
+                    .map(str::to_owned),
+            },
             RawRequestBody::XML(xml) => RequestBody::XML(xml.text()),
             RawRequestBody::Text(text) => RequestBody::Text(text.text()),
             RawRequestBody::File(file) => RequestBody::File(file.clone()),
@@ -121,6 +163,15 @@ impl RawRequestBody {
         match body {
             RequestBody::Form(form) => RawRequestBody::Form(from_core_kv_list(form, false)),
             RequestBody::Json(json) => RawRequestBody::Json(text_editor::Content::with_text(&json)),
+            RequestBody::GraphQL {
+                query,
+                variables,
+                operation_name,
+            } => RawRequestBody::GraphQL {
+                query: text_editor::Content::with_text(&query),
+                variables: text_editor::Content::with_text(&variables),
+                operation_name: operation_name.unwrap_or_default(),
+            },
             RequestBody::XML(xml) => RawRequestBody::XML(text_editor::Content::with_text(&xml)),
             RequestBody::Text(text) => RawRequestBody::Text(text_editor::Content::with_text(&text)),
             RequestBody::File(file) => RawRequestBody::File(file.clone()),
@@ -138,6 +189,7 @@ impl RawRequestBody {
         match self {
             RawRequestBody::Form(_) => FORM,
             RawRequestBody::Json(_) => JSON,
+            RawRequestBody::GraphQL { .. } => GRAPHQL,
             RawRequestBody::XML(_) => XML,
             RawRequestBody::Text(_) => TEXT,
             RawRequestBody::File(_) => FILE,
@@ -147,7 +199,7 @@ impl RawRequestBody {
     }
 
     pub fn all_variants() -> &'static [&'static str] {
-        &[FORM, MULTIPART, JSON, XML, TEXT, FILE, NONE]
+        &[FORM, MULTIPART, JSON, GRAPHQL, XML, TEXT, FILE, NONE]
     }
 }
 
@@ -163,6 +215,7 @@ pub struct RequestPane {
     pub tab: ReqTabId,
     pub body_cache: HashMap<&'static str, RawRequestBody>,
     pub pre_request: Option<String>,
+    pub graphql_schema: GraphQLSchema,
 }
 
 impl RequestPane {
@@ -173,6 +226,11 @@ impl RequestPane {
             .unwrap_or_else(|| match content_type {
                 FORM => RawRequestBody::Form(KeyValList::new()),
                 JSON => RawRequestBody::Json(Default::default()),
+                GRAPHQL => RawRequestBody::GraphQL {
+                    query: Default::default(),
+                    variables: Default::default(),
+                    operation_name: String::new(),
+                },
                 XML => RawRequestBody::XML(Default::default()),
                 TEXT => RawRequestBody::Text(Default::default()),
                 FILE => RawRequestBody::File(Default::default()),
@@ -183,20 +241,6 @@ impl RequestPane {
         self.body_cache.insert(old_body.as_str(), old_body);
     }
 
-    pub fn change_auth_type(&mut self, auth_type: &str) {
-        self.auth = match auth_type {
-            auth_types::NONE => RawAuthType::None,
-            auth_types::BASIC => RawAuthType::Basic {
-                username: text_editor::Content::new(),
-                password: text_editor::Content::new(),
-            },
-            auth_types::BEARER => RawAuthType::Bearer {
-                token: text_editor::Content::new(),
-            },
-            _ => RawAuthType::None,
-        };
-    }
-
     pub fn to_request(&self) -> Request {
         Request {
             description: "Http request".to_string(),
@@ -224,6 +268,7 @@ impl RequestPane {
             tab: ReqTabId::Params,
             body_cache: HashMap::new(),
             pre_request: request.pre_request,
+            graphql_schema: GraphQLSchema::None,
         }
     }
 }
@@ -1,5 +1,13 @@
-use components::text_editor;
-use core::client;
+use components::text_editor::{self, Content};
+use core::client::{self, ContentType, ImageFormat, StreamHead};
+use core::diff::{diff_responses, DiffOptions, ResponseDiff};
+use core::graphql::{response_errors, GraphQLError};
+use core::har::SentRequest;
+use core::preview::{html_to_text, pdf_info};
+use core::sse::SseEvent;
+use iced::widget::{image, svg};
+use serde_json::Value;
+use std::collections::VecDeque;
 use std::sync::Arc;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
@@ -7,6 +15,35 @@ pub enum ResponseTabId {
     #[default]
     Body,
     Headers,
+    Diff,
+}
+
+/// What the response is compared with in the diff tab
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum DiffBase {
+    #[default]
+    Previous,
+    Example(String),
+}
+
+impl std::fmt::Display for DiffBase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiffBase::Previous => write!(f, "Previous response"),
+            DiffBase::Example(name) => write!(f, "Example: {}", name),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct DiffState {
+    pub base: DiffBase,
+    /// The saved example when comparing with one
+    pub example: Option<client::Response>,
+    /// Comma separated paths left out of the comparison
+    pub ignore: String,
+    /// Only computed while the diff tab is open
+    pub result: Option<ResponseDiff>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -15,15 +52,82 @@ pub enum BodyMode {
     Raw,
 }
 
+#[derive(Debug)]
+pub enum BodyPreview {
+    Text,
+    Image(image::Handle),
+    Svg(svg::Handle),
+    Pdf(Vec<(&'static str, String)>),
+    Binary,
+}
+
 #[derive(Debug)]
 pub struct CompletedResponse {
     pub result: client::Response,
     pub content: Option<text_editor::Content>,
     pub raw: text_editor::Content,
     pub mode: BodyMode,
+    pub preview: BodyPreview,
+    pub hex_page: usize,
+    pub graphql_errors: Vec<GraphQLError>,
+}
+
+fn pretty_body(content_type: ContentType, body: &[u8]) -> (String, Option<String>) {
+    let raw = String::from_utf8_lossy(body).to_string();
+
+    let pretty = match content_type {
+        ContentType::Html => Some(html_to_text(&raw)),
+        _ => serde_json::from_slice::<Value>(body)
+            .ok()
+            .and_then(|v| serde_json::to_string_pretty(&v).ok()),
+    };
+
+    (raw, pretty)
 }
 
 impl CompletedResponse {
+    pub fn new(result: client::Response) -> Self {
+        let body = &result.body;
+        let preview = match body.content_type {
+            ContentType::Image(ImageFormat::Svg) => {
+                BodyPreview::Svg(svg::Handle::from_memory(body.data.clone()))
+            }
+            ContentType::Image(_) => {
+                BodyPreview::Image(image::Handle::from_bytes(body.data.clone()))
+            }
+            ContentType::Pdf => BodyPreview::Pdf(pdf_info(&body.data).entries()),
+            ContentType::Buffer => BodyPreview::Binary,
+            ContentType::Json | ContentType::Xml | ContentType::Html | ContentType::Text => {
+                BodyPreview::Text
+            }
+        };
+
+        let (raw, content) = if body.content_type.is_text() {
+            let (raw, pretty) = pretty_body(body.content_type, &body.data);
+            (
+                Content::with_text(raw.as_str()),
+                pretty.map(|p| Content::with_text(p.as_str())),
+            )
+        } else {
+            (Content::new(), None)
+        };
+
+        let graphql_errors = match body.content_type {
+            ContentType::Json => response_errors(&body.data).unwrap_or_default(),
+            _ => Vec::new(),
+        };
+
+        Self {
+            result,
+            content,
+            raw,
+            mode: BodyMode::Pretty,
+            preview,
+            hex_page: 0,
+            graphql_errors,
+        }
+    }
+
     pub fn selected_content(&self) -> &text_editor::Content {
         match self.mode {
             BodyMode::Pretty => self.content.as_ref().unwrap_or(&self.raw),
@@ -38,12 +142,82 @@ impl CompletedResponse {
     }
 }
 
+/// Older events are dropped once a stream, or its buffer while paused, grows
+/// past this many events.
+pub const MAX_STREAM_EVENTS: usize = 1000;
+
+#[derive(Debug)]
+pub struct StreamingResponse {
+    pub head: StreamHead,
+    pub events: VecDeque<SseEvent>,
+    /// Events received while paused, shown once the stream is resumed.
+    pub buffered: VecDeque<SseEvent>,
+    /// Events dropped from the buffer while paused, past the same limit.
+    pub dropped: usize,
+    pub received: usize,
+    pub paused: bool,
+    pub closed: bool,
+    pub error: Option<Arc<anyhow::Error>>,
+}
+
+impl StreamingResponse {
+    pub fn new(head: StreamHead) -> Self {
+        Self {
+            head,
+            events: VecDeque::new(),
+            buffered: VecDeque::new(),
+            dropped: 0,
+            received: 0,
+            paused: false,
+            closed: false,
+            error: None,
+        }
+    }
+
+    pub fn push(&mut self, event: SseEvent) {
+        self.received += 1;
+        if self.paused {
+            if self.buffered.len() >= MAX_STREAM_EVENTS {
+                self.buffered.pop_front();
+                self.dropped += 1;
+            }
+            self.buffered.push_back(event);
+        } else {
+            self.append(event);
+        }
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+        if !paused {
+            for event in std::mem::take(&mut self.buffered) {
+                self.append(event);
+            }
+            self.dropped = 0;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+        self.buffered.clear();
+        self.dropped = 0;
+    }
+
+    fn append(&mut self, event: SseEvent) {
+        if self.events.len() >= MAX_STREAM_EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+    }
+}
+
 #[derive(Debug, Default)]
 pub enum ResponseState {
     #[default]
     Idle,
     Executing,
     Completed(CompletedResponse),
+    Streaming(StreamingResponse),
     Failed(Arc<anyhow::Error>),
 }
 
@@ -51,6 +225,12 @@ pub enum ResponseState {
 pub struct ResponsePane {
     pub state: ResponseState,
     pub active_tab: ResponseTabId,
+    pub sent: Option<SentRequest>,
+    /// Name of the saved example shown instead of a received response
+    pub example: Option<String>,
+    /// Last response received before the current one
+    pub previous: Option<client::Response>,
+    pub diff: DiffState,
 }
 
 impl Default for ResponsePane {
@@ -64,10 +244,37 @@ impl ResponsePane {
         Self {
             state: ResponseState::Idle,
             active_tab: ResponseTabId::Body,
+            sent: None,
+            example: None,
+            previous: None,
+            diff: DiffState::default(),
         }
     }
 
+    /// Compares the current response with the selected base
+    pub fn refresh_diff(&mut self) {
+        let base = match self.diff.base {
+            DiffBase::Previous => self.previous.as_ref(),
+            DiffBase::Example(_) => self.diff.example.as_ref(),
+        };
+        let current = match self.state {
+            ResponseState::Completed(ref res) if self.active_tab == ResponseTabId::Diff => {
+                Some(&res.result)
+            }
+            _ => None,
+        };
+
+        let options = DiffOptions::from_ignore_list(&self.diff.ignore);
+        self.diff.result = base
+            .zip(current)
+            .map(|(base, current)| diff_responses(base, current, &options));
+    }
+
     pub fn is_executing(&self) -> bool {
-        matches!(self.state, ResponseState::Executing)
+        match self.state {
+            ResponseState::Executing => true,
+            ResponseState::Streaming(ref stream) => !stream.closed,
+            _ => false,
+        }
     }
 }
@@ -0,0 +1,331 @@
+use std::path::Path;
+
+use iced::widget::pane_grid::{self, Node};
+use iced::Theme;
+
+use core::http::{CollectionKey, CollectionRequest};
+use core::persistence::request::RequestFile;
+use core::persistence::session::{Session, SessionCollection, SessionTab, SessionTabKind};
+
+use crate::state::collection_tab::CollectionTab;
+use crate::state::{AppState, GrpcTab, HttpTab, SplitState, Tab, TabKey, WebSocketTab};
+
+fn split_ratio(panes: &pane_grid::State<SplitState>) -> Option<f32> {
+    match panes.layout() {
+        Node::Split { ratio, .. } => Some(*ratio),
+        Node::Pane(_) => None,
+    }
+}
+
+fn set_split_ratio(panes: &mut pane_grid::State<SplitState>, ratio: Option<f32>) {
+    let (Node::Split { id, .. }, Some(ratio)) = (panes.layout(), ratio) else {
+        return;
+    };
+    let id = *id;
+    panes.resize(id, ratio);
+}
+
+fn find_collection(state: &AppState, path: &Path) -> Option<CollectionKey> {
+    state
+        .collections
+        .iter()
+        .find(|(_, col)| col.path == path)
+        .map(|(key, _)| key)
+}
+
+fn session_tab(state: &AppState, tab: &Tab) -> Option<SessionTab> {
+    let (kind, name, request, panes) = match tab {
+        Tab::Http(tab) => (
+            SessionTabKind::Http,
+            &tab.name,
+            RequestFile::Http(tab.request().to_request()),
+            &tab.panes,
+        ),
+        Tab::WebSocket(tab) => (
+            SessionTabKind::WebSocket,
+            &tab.name,
+            RequestFile::WebSocket(tab.to_request()),
+            &tab.panes,
+        ),
+        Tab::Grpc(tab) => (
+            SessionTabKind::Grpc,
+            &tab.name,
+            RequestFile::Grpc(tab.to_request()),
+            &tab.panes,
+        ),
+        Tab::Collection(tab) => {
+            let col = state.collections.get(tab.collection_key)?;
+            let mut session = SessionTab::new(SessionTabKind::Collection, tab.name.clone());
+            session.collection = Some(col.path.clone());
+            return Some(session);
+        }
+    };
+
+    let mut session = SessionTab::new(kind, name.clone())
+        .with_draft(request)
+        .inspect_err(|e| log::error!("Error keeping the draft of {}: {:?}", name, e))
+        .ok()?;
+    if let Some(col_ref) = tab.collection_ref() {
+        session.collection = state.collections.get(col_ref.0).map(|c| c.path.clone());
+        session.request = state.collections.get_ref(col_ref).map(|r| r.path.clone());
+    }
+    session.split_ratio = split_ratio(panes);
+    Some(session)
+}
+
+/// Open tabs and layout to restore on the next launch
+pub fn snapshot(state: &AppState) -> Session {
+    let tabs = state
+        .tabs
+        .iter()
+        .filter_map(|(key, tab)| Some((*key, session_tab(state, tab)?)))
+        .collect::<Vec<_>>();
+    let active_tab = state
+        .active_tab
+        .and_then(|active| tabs.iter().position(|(key, _)| *key == active));
+
+    let collections = state
+        .collections
+        .iter()
+        .map(|(_, col)| SessionCollection {
+            path: col.path.clone(),
+            expanded: col.expanded,
+            expanded_folders: col.expanded_folders(),
+            environment: col.get_active_environment().map(|env| env.name.clone()),
+        })
+        .collect();
+
+    Session {
+        theme: Some(state.theme.to_string()),
+        split_ratio: split_ratio(&state.panes),
+        active_tab,
+        tabs: tabs.into_iter().map(|(_, tab)| tab).collect(),
+        collections,
+    }
+}
+
+/// Tab of the session, requests which are no longer in their collection
+/// are opened as unsaved drafts
+fn restore_tab(state: &AppState, saved: SessionTab) -> Option<Tab> {
+    let col = saved
+        .collection
+        .as_deref()
+        .and_then(|path| find_collection(state, path));
+
+    if saved.kind == SessionTabKind::Collection {
+        let key = col?;
+        return Some(Tab::Collection(CollectionTab::new(
+            key,
+            state.collections.get(key)?,
+        )));
+    }
+
+    let req_ref = col
+        .zip(saved.request.as_deref())
+        .and_then(|(key, path)| {
+            let id = state.collections.get(key)?.find_request(path)?;
+            Some(CollectionRequest(key, id))
+        })
+        .unwrap_or(CollectionRequest(Default::default(), Default::default()));
+
+    let draft = match saved.draft() {
+        Ok(draft) => draft?,
+        Err(e) => {
+            log::error!("Error restoring the draft of {}: {:?}", saved.name, e);
+            return None;
+        }
+    };
+    // Drafts differing from the saved file are shown as edited
+    let mut tab = match draft {
+        RequestFile::Http(req) => {
+            let mut tab = HttpTab::new(saved.name, req, req_ref);
+            tab.check_dirty();
+            Tab::Http(tab)
+        }
+        RequestFile::WebSocket(req) => {
+            let mut tab = WebSocketTab::new(saved.name, req, req_ref);
+            tab.check_dirty();
+            Tab::WebSocket(tab)
+        }
+        RequestFile::Grpc(req) => {
+            let mut tab = GrpcTab::new(saved.name, req, req_ref);
+            tab.check_dirty();
+            Tab::Grpc(tab)
+        }
+    };
+
+    let panes = match &mut tab {
+        Tab::Http(tab) => &mut tab.panes,
+        Tab::WebSocket(tab) => &mut tab.panes,
+        Tab::Grpc(tab) => &mut tab.panes,
+        Tab::Collection(_) => return Some(tab),
+    };
+    set_split_ratio(panes, saved.split_ratio);
+    Some(tab)
+}
+
+/// Reopens the tabs and layout of the last run, once its collections are
+/// loaded
+pub fn restore(state: &mut AppState, session: Session) {
+    let theme = session
+        .theme
+        .as_ref()
+        .and_then(|name| Theme::ALL.iter().find(|theme| theme.to_string() == *name));
+    if let Some(theme) = theme {
+        state.theme = theme.clone();
+    }
+    set_split_ratio(&mut state.panes, session.split_ratio);
+
+    for saved in &session.collections {
+        let Some(key) = find_collection(state, &saved.path) else {
+            continue;
+        };
+        state.collections.with_collection_mut(key, |col| {
+            col.expanded = saved.expanded;
+            col.expand_folders(&saved.expanded_folders);
+            if let Some(env) = &saved.environment {
+                col.update_active_env_by_name(env);
+            }
+        });
+    }
+
+    let mut keys: Vec<Option<TabKey>> = Vec::new();
+    for saved in session.tabs {
+        let key = restore_tab(state, saved).map(|tab| {
+            state.open_tab(tab);
+            state.active_tab
+        });
+        keys.push(key.flatten());
+    }
+
+    let active = session.active_tab.and_then(|idx| *keys.get(idx)?);
+    if let Some(key) = active {
+        state.switch_tab(key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use core::http::collection::{Collection, Entry, Folder, FolderId, RequestId, RequestRef};
+    use core::http::environment::{Environment, Environments};
+    use core::http::grpc::GrpcRequest;
+    use core::http::request::Request;
+    use core::http::websocket::WebSocketRequest;
+
+    use super::*;
+
+    const ROOT: &str = "/work/api";
+
+    fn collection() -> Collection {
+        let root = PathBuf::from(ROOT);
+        let folder = root.join("requests").join("users");
+        let request = RequestRef {
+            id: RequestId::new(),
+            name: "Get user".to_string(),
+            path: folder.join("Get user.hcl"),
+            examples: Vec::new(),
+        };
+        let entries = vec![Entry::Folder(Folder {
+            id: FolderId::new(),
+            name: "users".to_string(),
+            entries: vec![Entry::Item(request)],
+            path: folder,
+            expanded: false,
+        })];
+        let mut environments = Environments::new();
+        environments.insert(Environment::new("dev".to_string()));
+        Collection::new(
+            "api".to_string(),
+            entries,
+            Vec::new(),
+            root,
+            environments,
+            None,
+        )
+    }
+
+    fn state() -> AppState {
+        let mut state = AppState::new();
+        state.collections.insert(collection());
+        state
+    }
+
+    fn unsaved() -> CollectionRequest {
+        CollectionRequest(Default::default(), Default::default())
+    }
+
+    #[test]
+    fn restores_tabs_and_layout() {
+        let mut state = state();
+        let root = PathBuf::from(ROOT);
+        let key = find_collection(&state, &root).unwrap();
+        let folder = root.join("requests").join("users");
+        let request = state.collections.with_collection_mut(key, |col| {
+            col.expanded = true;
+            col.expand_folders(&[folder.clone()]);
+            col.update_active_env_by_name("dev");
+            col.find_request(&folder.join("Get user.hcl"))
+        });
+        let req_ref = CollectionRequest(key, request.flatten().unwrap());
+
+        let edited = Request {
+            url: "{{host}}/users/1".to_string(),
+            ..Request::default()
+        };
+        let mut http = HttpTab::new("Get user".to_string(), edited, req_ref);
+        set_split_ratio(&mut http.panes, Some(0.3));
+        state.open_tab(Tab::Http(http));
+        let active = state.active_tab.unwrap();
+
+        let ws = WebSocketTab::new("Events".to_string(), WebSocketRequest::default(), unsaved());
+        state.open_tab(Tab::WebSocket(ws));
+        let grpc = GrpcTab::new("Echo".to_string(), GrpcRequest::default(), unsaved());
+        state.open_tab(Tab::Grpc(grpc));
+        let settings = CollectionTab::new(key, state.collections.get(key).unwrap());
+        state.open_tab(Tab::Collection(settings));
+
+        state.theme = Theme::Dracula;
+        set_split_ratio(&mut state.panes, Some(0.25));
+        state.switch_tab(active);
+
+        let session = snapshot(&state);
+        assert_eq!(session.tabs.len(), 4);
+        assert_eq!(session.active_tab, Some(0));
+
+        let read = hcl::from_str::<Session>(&hcl::to_string(&session).unwrap()).unwrap();
+        let mut restored = state();
+        restore(&mut restored, read);
+
+        assert_eq!(snapshot(&restored), session);
+        let Some(Tab::Http(tab)) = restored.active_tab.and_then(|key| restored.tabs.get(&key))
+        else {
+            panic!("expected the request tab to be active");
+        };
+        assert_eq!(tab.request().to_request().url, "{{host}}/users/1");
+    }
+
+    #[test]
+    fn skips_tabs_with_corrupt_drafts() {
+        let mut broken = SessionTab::new(SessionTabKind::Http, "Broken".to_string());
+        broken.draft = Some("method = \"GET\"\nurl = ".to_string());
+        let valid = SessionTab::new(SessionTabKind::Http, "Valid".to_string())
+            .with_draft(RequestFile::Http(Request::default()))
+            .unwrap();
+        let session = Session {
+            active_tab: Some(1),
+            tabs: vec![broken, valid],
+            ..Session::default()
+        };
+
+        let mut state = state();
+        restore(&mut state, session);
+
+        assert_eq!(state.tabs.len(), 1);
+        let Some(Tab::Http(tab)) = state.active_tab.and_then(|key| state.tabs.get(&key)) else {
+            panic!("expected the valid tab to be active");
+        };
+        assert_eq!(tab.name, "Valid");
+    }
+}
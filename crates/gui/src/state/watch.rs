@@ -0,0 +1,187 @@
+use core::diff::{diff_lines, DiffLine};
+use core::http::{CollectionKey, CollectionRequest, KeyValList};
+use core::persistence::request::{encode_request_file, RequestFile, RequestOnDisk};
+use core::persistence::watch::CollectionUpdate;
+use core::persistence::COLLECTION_ROOT_FILE;
+
+use crate::state::popups::Popup;
+use crate::state::request::RequestPane;
+use crate::state::{AppState, GrpcTab, HttpTab, RequestDirtyState, Tab, TabKey, WebSocketTab};
+
+/// A request changed on disk while its tab had unsaved changes
+#[derive(Debug)]
+pub struct RequestConflict {
+    pub tab: TabKey,
+    pub name: String,
+    pub theirs: RequestOnDisk,
+    /// From the edited request to the one on disk
+    pub diff: Vec<Vec<DiffLine>>,
+}
+
+/// Replaces the request of the tab with the one read from disk
+pub fn load_request(tab: &mut Tab, theirs: RequestOnDisk) {
+    theirs.track();
+    let request = theirs.request;
+    if let (Tab::Http(tab), RequestFile::Http(req)) = (&mut *tab, &request) {
+        let current = tab.request_mut();
+        let mut pane = RequestPane::from(req.clone());
+        pane.tab = current.tab;
+        *current = pane;
+        tab.request_dirty_state = RequestDirtyState::Clean;
+        return;
+    }
+
+    // Other tabs are opened again, a changed file may also change its type
+    let Some(req_ref) = tab.collection_ref() else {
+        return;
+    };
+    let name = tab.name().to_owned();
+    tab.cancel_tasks();
+    *tab = match request {
+        RequestFile::Http(req) => Tab::Http(HttpTab::new(name, req, req_ref)),
+        RequestFile::WebSocket(req) => Tab::WebSocket(WebSocketTab::new(name, req, req_ref)),
+        RequestFile::Grpc(req) => Tab::Grpc(GrpcTab::new(name, req, req_ref)),
+    };
+}
+
+/// Queues a conflict between the tab and the request on disk, unless they
+/// have the same content
+fn add_conflict(state: &mut AppState, key: TabKey, theirs: RequestOnDisk) {
+    let Some(tab) = state.tabs.get_mut(&key) else {
+        return;
+    };
+    let Some(mine) = tab.to_request_file() else {
+        return;
+    };
+
+    // Saved from this app or edited to the same content
+    if mine == theirs.request {
+        theirs.track();
+        tab.set_request_dirty_state(RequestDirtyState::Clean);
+        return;
+    }
+
+    let encode = |req: RequestFile| encode_request_file(req).unwrap_or_default();
+    let conflict = RequestConflict {
+        tab: key,
+        name: tab.name().to_owned(),
+        diff: diff_lines(&encode(mine), &encode(theirs.request.clone())),
+        theirs,
+    };
+    state.conflicts.retain(|c| c.tab != conflict.tab);
+    state.conflicts.push(conflict);
+}
+
+fn open_conflicts(state: &mut AppState) {
+    if state.popup.is_none() && !state.conflicts.is_empty() {
+        Popup::request_conflict(state);
+    }
+}
+
+/// Refreshes the tab of a request changed on disk, tabs with unsaved
+/// changes are left alone until the conflict is resolved
+fn refresh_tab(state: &mut AppState, col: CollectionKey, theirs: RequestOnDisk) {
+    let Some(id) = state
+        .collections
+        .get(col)
+        .and_then(|c| c.find_request(&theirs.path))
+    else {
+        return;
+    };
+    let req_ref = CollectionRequest(col, id);
+    let Some((key, tab)) = state
+        .tabs
+        .iter_mut()
+        .find(|(_, tab)| tab.collection_ref() == Some(req_ref))
+    else {
+        return;
+    };
+
+    let clean = tab.request_dirty_state() == Some(RequestDirtyState::Clean);
+    let same = tab.to_request_file().as_ref() == Some(&theirs.request);
+    if clean && !same {
+        load_request(tab, theirs);
+        return;
+    }
+    let key = *key;
+    add_conflict(state, key, theirs);
+}
+
+/// Prompts for the request a save of the tab refused to overwrite as it was
+/// changed on disk
+pub fn save_conflict(state: &mut AppState, key: TabKey, theirs: RequestOnDisk) {
+    add_conflict(state, key, theirs);
+    open_conflicts(state);
+}
+
+/// Applies the changes read from the directory of the collection
+pub fn apply_update(state: &mut AppState, col: CollectionKey, update: CollectionUpdate) {
+    let envs_changed = update.environments.is_some();
+    let file_changed = update.collection.is_some();
+    state.collections.with_collection_mut(col, |collection| {
+        if let Some(entries) = update.entries {
+            collection.merge_entries(entries);
+        }
+        if let Some(scripts) = update.scripts {
+            collection.scripts = scripts;
+        }
+        if let Some(envs) = update.environments {
+            collection.merge_environments(envs);
+        }
+        if let Some(file) = update.collection {
+            let default_env = file
+                .default_environment
+                .as_deref()
+                .and_then(|name| collection.environments.find_by_name(name));
+            collection.name = file.name;
+            collection.variables =
+                KeyValList::from(file.variables.into_iter().map(Into::into).collect());
+            collection.set_default_env(default_env);
+        }
+        let diagnostics = &mut collection.diagnostics;
+        diagnostics.retain(|d| !update.linted.contains(&d.path));
+        diagnostics.extend(update.diagnostics);
+        diagnostics.sort_by(|a, b| a.path.cmp(&b.path));
+
+        // The collection file is not saved over until it parses again
+        let root_file = collection.path.join(COLLECTION_ROOT_FILE);
+        if update.linted.contains(&root_file) {
+            collection.fallback = collection.diagnostics.iter().any(|d| d.path == root_file);
+        }
+    });
+
+    if file_changed {
+        if let Some(collection) = state.collections.get(col) {
+            for tab in state.tabs.values_mut() {
+                match tab {
+                    Tab::Collection(tab) if tab.collection_key == col => {
+                        tab.refresh_collection(collection);
+                    }
+                    _ => (),
+                }
+            }
+        }
+    }
+
+    if envs_changed {
+        if let (Some(secrets), Some(collection)) = (&state.secrets, state.collections.get_mut(col))
+        {
+            secrets.fill(collection);
+        }
+        if let Some(collection) = state.collections.get(col) {
+            for tab in state.tabs.values_mut() {
+                match tab {
+                    Tab::Collection(tab) if tab.collection_key == col => {
+                        tab.refresh_environments(&collection.environments);
+                    }
+                    _ => (),
+                }
+            }
+        }
+    }
+
+    for request in update.requests {
+        refresh_tab(state, col, request);
+    }
+    open_conflicts(state);
+}
@@ -0,0 +1,173 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use chrono::{DateTime, Local};
+use iced::widget::pane_grid;
+use iced::widget::pane_grid::Configuration;
+use tokio::sync::{mpsc, oneshot};
+
+use components::{text_editor, KeyValList};
+use core::http::websocket::{MessageKind, MessageTemplate, WebSocketRequest};
+use core::http::{CollectionKey, CollectionRequest};
+use core::websocket::{CloseReason, WsHandshake, WsMessage};
+
+use super::http_tab::RequestDirtyState;
+use super::request::RawAuthType;
+use super::utils::{from_core_kv_list, to_core_kv_list};
+use super::SplitState;
+
+/// Older messages are dropped once the log grows past this many entries.
+pub const MAX_LOG_MESSAGES: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum WsTabId {
+    #[default]
+    Message,
+    Saved,
+    Params,
+    Headers,
+    Auth,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageDirection {
+    Sent,
+    Received,
+}
+
+#[derive(Debug, Clone)]
+pub struct LoggedMessage {
+    pub direction: MessageDirection,
+    pub message: WsMessage,
+    pub time: DateTime<Local>,
+}
+
+#[derive(Debug, Default)]
+pub enum ConnectionState {
+    #[default]
+    Disconnected,
+    Connecting,
+    Connected(WsHandshake),
+    Closed(Option<CloseReason>),
+    Failed(Arc<anyhow::Error>),
+}
+
+#[derive(Debug)]
+pub struct WebSocketTab {
+    pub name: String,
+    pub collection_ref: CollectionRequest,
+    pub description: String,
+    pub url_content: text_editor::Content,
+    pub headers: KeyValList,
+    pub query_params: KeyValList,
+    pub auth: RawAuthType,
+    pub messages: Vec<MessageTemplate>,
+    pub composer: text_editor::Content,
+    pub composer_kind: MessageKind,
+    pub composer_error: Option<String>,
+    pub tab: WsTabId,
+    pub connection: ConnectionState,
+    pub sender: Option<mpsc::UnboundedSender<WsMessage>>,
+    pub log: VecDeque<LoggedMessage>,
+    pub tasks: Vec<oneshot::Sender<()>>,
+    pub panes: pane_grid::State<SplitState>,
+    pub request_dirty_state: RequestDirtyState,
+}
+
+impl WebSocketTab {
+    pub fn new(name: String, request: WebSocketRequest, req_ref: CollectionRequest) -> Self {
+        Self {
+            name,
+            collection_ref: req_ref,
+            description: request.description,
+            url_content: text_editor::Content::with_text(&request.url),
+            headers: from_core_kv_list(request.headers, false),
+            query_params: from_core_kv_list(request.query_params, false),
+            auth: RawAuthType::from_auth(request.auth),
+            messages: request.messages,
+            composer: text_editor::Content::new(),
+            composer_kind: MessageKind::Text,
+            composer_error: None,
+            tab: WsTabId::Message,
+            connection: ConnectionState::Disconnected,
+            sender: None,
+            log: VecDeque::new(),
+            tasks: Vec::new(),
+            panes: pane_grid::State::with_configuration(Configuration::Split {
+                axis: pane_grid::Axis::Vertical,
+                ratio: 0.45,
+                a: Box::new(Configuration::Pane(SplitState::First)),
+                b: Box::new(Configuration::Pane(SplitState::Second)),
+            }),
+            request_dirty_state: RequestDirtyState::Clean,
+        }
+    }
+
+    pub fn to_request(&self) -> WebSocketRequest {
+        WebSocketRequest {
+            description: self.description.clone(),
+            url: self.url_content.text().trim().to_string(),
+            headers: to_core_kv_list(&self.headers),
+            query_params: to_core_kv_list(&self.query_params),
+            auth: self.auth.to_auth(),
+            messages: self.messages.clone(),
+        }
+    }
+
+    pub fn is_request_dirty(&self) -> bool {
+        self.request_dirty_state == RequestDirtyState::Dirty
+    }
+
+    /// The request may differ from its file after an edit or a save
+    pub fn check_dirty(&mut self) {
+        self.request_dirty_state = RequestDirtyState::CheckIfDirty;
+    }
+
+    pub fn is_active(&self) -> bool {
+        matches!(
+            self.connection,
+            ConnectionState::Connecting | ConnectionState::Connected(_)
+        )
+    }
+
+    pub fn log_message(&mut self, direction: MessageDirection, message: WsMessage) {
+        if self.log.len() >= MAX_LOG_MESSAGES {
+            self.log.pop_front();
+        }
+        self.log.push_back(LoggedMessage {
+            direction,
+            message,
+            time: Local::now(),
+        });
+    }
+
+    /// Drops the outgoing sender, which closes an open connection gracefully,
+    /// and stops a connection attempt that is still in progress.
+    pub fn disconnect(&mut self) {
+        self.sender = None;
+        if let ConnectionState::Connecting = self.connection {
+            self.cancel_tasks();
+            self.connection = ConnectionState::Disconnected;
+        }
+    }
+
+    pub fn cancel_tasks(&mut self) {
+        for task in self.tasks.drain(..) {
+            let _ = task.send(());
+        }
+    }
+
+    pub fn add_task(&mut self, task: oneshot::Sender<()>) {
+        self.tasks.push(task);
+    }
+
+    pub fn collection_key(&self) -> CollectionKey {
+        self.collection_ref.0
+    }
+}
+
+impl Drop for WebSocketTab {
+    fn drop(&mut self) {
+        self.cancel_tasks();
+    }
+}